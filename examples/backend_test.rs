@@ -1,5 +1,5 @@
 // Test the backend functionality directly
-use taskwarrior_tui::backend::{BackendConfig, create_backend, TaskBackend};
+use taskwarrior_tui::backend::{BackendConfig, create_backend};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== Direct Backend Test ===");
@@ -33,10 +33,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("   ✅ SUCCESS: Found {} tasks for 'next' report", tasks.len());
         println!("   📋 First 5 tasks:");
         for (i, task) in tasks.iter().take(5).enumerate() {
-            println!("     {}. {} ({})", 
+            println!("     {}. {} ({:?})", 
                     i + 1, 
                     task.description(), 
-                    format!("{:?}", task.status()));
+                    task.status());
         }
         println!("   🎉 The TUI should now show these tasks!");
     }
@@ -9,9 +9,12 @@ pub enum Action {
   Modify,
   HelpPopup,
   ContextMenu,
-  Jump,
+  ReportMenu,
+  CommandPalette,
+  CommandLine,
   DeletePrompt,
   UndoPrompt,
+  Sync,
   DonePrompt,
   Error,
   TimewarriorInstallHook,
@@ -1,14 +1,13 @@
 use std::{
   collections::{HashMap, HashSet},
   convert::TryInto,
-  fs, io,
-  io::Read,
-  path::Path,
+  io,
+  path::{Path, PathBuf},
   time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{anyhow, Context as AnyhowContext, Result};
-use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Timelike, Utc};
 use crossterm::{
   event::{DisableMouseCapture, EnableMouseCapture},
   execute,
@@ -20,14 +19,15 @@ use ratatui::{
   backend::{Backend, CrosstermBackend},
   layout::{Constraint, Direction, Layout, Margin, Rect},
   style::{Color, Modifier, Style},
+  symbols,
   terminal::Frame,
   text::{Line, Span, Text},
-  widgets::{Block, BorderType, Borders, Clear, LineGauge, List, ListItem, Paragraph, Tabs, Wrap},
+  widgets::{Axis, Bar, BarChart, BarGroup, Block, BorderType, Borders, Chart, Clear, Dataset, GraphType, LegendPosition, LineGauge, List, ListItem, Paragraph, Tabs, Wrap},
   Terminal,
 };
-use regex::Regex;
 use rustyline::{history::SearchDirection as HistoryDirection, line_buffer::LineBuffer, At, Word};
-use task_hookrs::{date::Date, import::import, status::TaskStatus, task::Task};
+use task_hookrs::{date::Date, status::TaskStatus, task::Task};
+use tokio::sync::mpsc;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 use uuid::Uuid;
@@ -35,26 +35,108 @@ use versions::Versioning;
 
 use crate::{
   action::Action,
-  backend::{self, BackendConfig},
+  backend::{
+    self,
+    filter::{Filter, FilterContext},
+    worker::BackendWorker,
+    BackendConfig, Context, TaskBackend, TaskChanges,
+  },
   calendar::Calendar,
+  command_line::{self, ExCommand, SortKey, COMMANDS},
   completion::{get_start_word_under_cursor, CompletionList},
   config::Config,
   event::{Event, KeyCode},
   help::Help,
-  history::HistoryContext,
+  history::{self, HistoryContext},
   keyconfig::KeyConfig,
+  keymap::{notation, KeyInput, KeyScope, PendingKeys},
+  macros::{self, Macros, RegisterPrompt},
+  palette::{CommandPalette, PaletteAction, PaletteCommand},
   pane::{
+    agenda::{AgendaEntry, AgendaKind, AgendaState, AgendaView},
+    calendar::CalendarState,
+    graph::{GraphRow, GraphState},
+    board::BoardState,
     context::ContextsState,
-    project::ProjectsState,
+    project::{ProjectRow, ProjectsState},
+    statistics::{self, StatisticsPoint, StatisticsState},
     Pane,
   },
+  report_pane::{modification_for_filter, ReportPane},
   scrollbar::Scrollbar,
   table::{Row, Table, TableMode, TaskwarriorTuiTableState},
   task_report::TaskReportTable,
   timewarrior::TimewarriorIntegration, utils,
+  watcher::DataFingerprint,
 };
 
-const MAX_LINE: usize = 4096;
+pub(crate) const MAX_LINE: usize = 4096;
+
+/// Rows taken by a task card on the board: its borders, description and details
+const BOARD_CARD_HEIGHT: u16 = 4;
+/// Width of the list of the selected day's tasks next to the calendar
+const CALENDAR_DAY_WIDTH: u16 = 40;
+/// The day's tasks are only listed when the calendar keeps room for two months per row
+const CALENDAR_MIN_WIDTH_WITH_DAY: u16 = CALENDAR_DAY_WIDTH + 48;
+/// Hours the agenda's time grid always shows, so that a day without timed tasks still reads as a day
+const AGENDA_FIRST_HOUR: u32 = 8;
+const AGENDA_LAST_HOUR: u32 = 18;
+
+/// Frames of the spinner shown in the tab bar while backend jobs are running
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// The UI-thread half of a backend job, which stores the job's result in the app
+type Completion = Box<dyn FnOnce(&mut TaskwarriorTui) -> Result<()> + Send>;
+
+/// Longest time between full exports; in between, only modified tasks are exported again.
+/// A full export also refreshes what changes without a modification, like `+OVERDUE` and urgency.
+const FULL_EXPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What a refresh reads from the backend
+struct Refresh {
+  context: Context,
+  export: Export,
+  mark: ExportMark,
+  contexts: Vec<Context>,
+  /// The data files as they were once the export finished
+  fingerprint: DataFingerprint,
+  /// The reports of the panes without the focus
+  panes: Vec<PaneExport>,
+}
+
+/// The tasks of a report pane without the focus, always exported in full
+struct PaneExport {
+  pane: usize,
+  filter: String,
+  report: String,
+  tasks: Result<Vec<Task>>,
+}
+
+enum Export {
+  /// The whole report, and every task
+  Full {
+    tasks: Result<Vec<Task>>,
+    all_tasks: Result<Vec<Task>>,
+  },
+  /// Only the tasks modified since the previous export
+  Changes(TaskChanges),
+}
+
+/// When and what was last exported, so that the next refresh can export only the changes
+#[derive(Debug, Clone, PartialEq)]
+struct ExportMark {
+  started: DateTime<Utc>,
+  filter: String,
+  report: String,
+  context_filter: String,
+}
+
+impl ExportMark {
+  /// Whether an export described by `other` can be brought up to date from this one
+  fn covers(&self, other: &ExportMark) -> bool {
+    self.filter == other.filter && self.report == other.report && self.context_filter == other.context_filter
+  }
+}
 
 lazy_static! {
   static ref START_TIME: Instant = Instant::now();
@@ -125,6 +207,25 @@ fn get_formatted_datetime(date: &Date) -> String {
   )
 }
 
+/// Replace the tasks in `tasks` that were modified, keeping their place in the list
+///
+/// `keep` tells which modified tasks belong in the list; those that are new to it are appended.
+fn merge_tasks(tasks: &mut Vec<Task>, modified: &[Task], keep: impl Fn(&Task) -> bool) {
+  let positions: HashMap<Uuid, usize> = tasks.iter().enumerate().map(|(i, task)| (*task.uuid(), i)).collect();
+  let mut removed = HashSet::new();
+  for task in modified {
+    match (positions.get(task.uuid()), keep(task)) {
+      (Some(&i), true) => tasks[i] = task.clone(),
+      (Some(_), false) => {
+        removed.insert(*task.uuid());
+      }
+      (None, true) => tasks.push(task.clone()),
+      (None, false) => {}
+    }
+  }
+  tasks.retain(|task| !removed.contains(task.uuid()));
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
   let popup_layout = Layout::default()
     .direction(Direction::Vertical)
@@ -156,6 +257,10 @@ pub enum Mode {
   Tasks(Action),
   Projects,
   Calendar,
+  Agenda,
+  Board,
+  Graph,
+  Statistics,
 }
 
 pub struct TaskwarriorTui {
@@ -167,6 +272,9 @@ pub struct TaskwarriorTui {
   pub command: LineBuffer,
   pub filter: LineBuffer,
   pub modify: LineBuffer,
+  pub command_line: LineBuffer,
+  /// Sort order given with `:sort`, which comes before the report's own until the report changes
+  pub sort_keys: Vec<SortKey>,
   pub tasks: Vec<Task>,
   pub all_tasks: Vec<Task>,
   pub task_details: HashMap<Uuid, String>,
@@ -175,8 +283,10 @@ pub struct TaskwarriorTui {
   pub current_selection: usize,
   pub current_selection_uuid: Option<Uuid>,
   pub current_selection_id: Option<u64>,
+  /// Number of tasks from the selection on that the next action works on, given by a count
+  pub selection_count: usize,
   pub task_report_table: TaskReportTable,
-  pub calendar_year: i32,
+  pub calendar: CalendarState,
   pub mode: Mode,
   pub previous_mode: Option<Mode>,
   pub config: Config,
@@ -186,23 +296,55 @@ pub struct TaskwarriorTui {
   pub help_popup: Help,
   pub last_export: Option<SystemTime>,
   pub keyconfig: KeyConfig,
+  /// Keys typed towards a binding of the current keymap
+  pub pending_keys: PendingKeys,
   pub terminal_width: u16,
   pub terminal_height: u16,
   pub filter_history: HistoryContext,
   pub command_history: HistoryContext,
+  pub command_line_history: HistoryContext,
+  pub macros: Macros,
   pub history_status: Option<String>,
   pub completion_list: CompletionList,
   pub show_completion_pane: bool,
   pub report: String,
   pub projects: ProjectsState,
+  pub agenda: AgendaState,
+  pub board: BoardState,
+  pub graph: GraphState,
+  pub statistics: StatisticsState,
   pub contexts: ContextsState,
+  /// Selected row of the report picker
+  pub report_menu: TaskwarriorTuiTableState,
+  pub command_palette: CommandPalette,
+  /// Reports shown side by side, or none while a single report is shown
+  ///
+  /// The state of the focused pane lives in the fields above; its entry here only holds a placeholder.
+  pub report_panes: Vec<ReportPane>,
+  pub focused_report: usize,
   pub task_version: Versioning,
   pub error: Option<String>,
+  pub undo_preview: Option<String>,
   pub event_loop: crate::event::EventLoop,
   pub requires_redraw: bool,
   pub changes: utils::Changeset,
-  pub backend: Box<dyn crate::backend::TaskBackend>,
+  pub backend: BackendWorker,
+  /// Backend jobs whose results have not been applied yet; the tab bar shows a spinner meanwhile
+  pub backend_jobs: usize,
   pub timewarrior: TimewarriorIntegration,
+  completions: mpsc::UnboundedSender<Completion>,
+  completion_queue: mpsc::UnboundedReceiver<Completion>,
+  refresh_in_flight: bool,
+  task_details_in_flight: bool,
+  /// Whether `error` holds an export failure, which the next successful export clears
+  export_failed: bool,
+  export_mark: Option<ExportMark>,
+  last_full_export: Option<SystemTime>,
+  /// Set when tasks may have changed in ways the backend cannot report, e.g. by a shortcut script
+  needs_full_export: bool,
+  /// The task data directory, watched for changes made outside the app
+  data_dir: PathBuf,
+  data_fingerprint: Option<DataFingerprint>,
 }
 
 impl TaskwarriorTui {
@@ -229,7 +371,6 @@ impl TaskwarriorTui {
 
     let data = String::from_utf8_lossy(&output.stdout);
     let c = Config::new(&data, report)?;
-    let kc = KeyConfig::new(&data)?;
 
     let output = std::process::Command::new("task")
       .arg("--version")
@@ -238,15 +379,6 @@ impl TaskwarriorTui {
 
     let task_version = Versioning::new(String::from_utf8_lossy(&output.stdout).trim()).context("Unable to get version string")?;
 
-    let (w, h) = crossterm::terminal::size().unwrap_or((100, 30));
-
-    let tick_rate = if c.uda_tick_rate > 0 {
-      Some(std::time::Duration::from_millis(c.uda_tick_rate))
-    } else {
-      None
-    };
-    let event_loop = crate::event::EventLoop::new(tick_rate, init_event_loop);
-
     // Initialize backend based on configuration
     debug!("=== BACKEND INITIALIZATION ===");
     debug!("uda_backend config: '{}'", c.uda_backend);
@@ -255,10 +387,21 @@ impl TaskwarriorTui {
         #[cfg(feature = "taskchampion-backend")]
         {
           info!("Using TaskChampion backend");
-          BackendConfig::TaskChampion {
-            data_dir: c.uda_taskchampion_data_dir.as_ref().map(|s| std::path::PathBuf::from(s)),
+          BackendConfig::TaskChampion(backend::taskchampion::TaskChampionConfig {
+            data_dir: Some(backend::resolve_data_dir(
+              c.uda_taskchampion_data_dir.as_deref(),
+              Some(&c.data_location),
+            )),
             server_config: c.uda_taskchampion_server_config.clone(),
-          }
+            uda_types: c.uda.iter().map(|u| (u.name.clone(), u.kind.clone())).collect(),
+            report_filters: c.report_filters.clone(),
+            report_sorts: c.reports.iter().map(|report| (report.name.clone(), report.sort.clone())).collect(),
+            priority_values: c.uda_priority_values.clone(),
+            contexts: c.contexts.clone(),
+            urgency: c.urgency_coefficients.clone(),
+            editor: c.editor.clone(),
+            create_if_missing: false,
+          })
         }
         #[cfg(not(feature = "taskchampion-backend"))]
         {
@@ -272,9 +415,43 @@ impl TaskwarriorTui {
       }
     };
     debug!("Backend config: {:?}", backend_config);
-    let backend = backend::create_backend(backend_config)?;
+    let backend = BackendWorker::spawn(move || backend::create_backend(backend_config))?;
     debug!("=== BACKEND INITIALIZED ===");
 
+    Self::with_backend(report, &data, task_version, backend, init_event_loop, &history::data_dir()).await
+  }
+
+  /// Build the app from `task show` output and a backend, without running `task`
+  ///
+  /// `data` is what `task rc.color=off rc._forcecolor=off rc.defaultwidth=0 show` prints; it is
+  /// the source of the configuration, key bindings and report columns. Together with
+  /// `backend::memory::MemoryBackend` this runs the UI headless, e.g. in tests. Histories and
+  /// macros are kept in `history_dir`, which `new` takes from `history::data_dir`.
+  pub async fn with_backend(
+    report: &str,
+    data: &str,
+    task_version: Versioning,
+    backend: BackendWorker,
+    init_event_loop: bool,
+    history_dir: &Path,
+  ) -> Result<Self> {
+    let c = Config::new(data, report)?;
+    let kc = KeyConfig::new(data)?;
+
+    let (w, h) = crossterm::terminal::size().unwrap_or((100, 30));
+
+    let tick_rate = if c.uda_tick_rate > 0 {
+      Some(std::time::Duration::from_millis(c.uda_tick_rate))
+    } else {
+      None
+    };
+    let data_dir = backend::resolve_data_dir(
+      c.uda_taskchampion_data_dir.as_deref().filter(|_| c.uda_backend == "taskchampion"),
+      Some(&c.data_location),
+    );
+    let event_loop = crate::event::EventLoop::new(tick_rate, init_event_loop, Some(&data_dir));
+    let (completions, completion_queue) = mpsc::unbounded_channel();
+
     let mut app = Self {
       should_quit: false,
       dirty: true,
@@ -286,39 +463,65 @@ impl TaskwarriorTui {
       current_selection: 0,
       current_selection_uuid: None,
       current_selection_id: None,
+      selection_count: 1,
       current_context_filter: "".to_string(),
       current_context: "".to_string(),
       command: LineBuffer::with_capacity(MAX_LINE),
       filter: LineBuffer::with_capacity(MAX_LINE),
       modify: LineBuffer::with_capacity(MAX_LINE),
+      command_line: LineBuffer::with_capacity(MAX_LINE),
+      sort_keys: vec![],
       mode: Mode::Tasks(Action::Report),
       previous_mode: None,
       task_report_height: 0,
       task_details_scroll: 0,
       task_report_show_info: c.uda_task_report_show_info,
+      agenda: AgendaState::new(c.uda_agenda_days, c.weekstart),
+      statistics: StatisticsState::new(c.weekstart),
       config: c,
-      task_report_table: TaskReportTable::new(&data, report)?,
-      calendar_year: Local::now().year(),
+      task_report_table: TaskReportTable::new(data, report)?,
+      calendar: CalendarState::new(),
       help_popup: Help::new(),
       last_export: None,
       keyconfig: kc,
+      pending_keys: PendingKeys::default(),
       terminal_width: w,
       terminal_height: h,
-      filter_history: HistoryContext::new("filter.history"),
-      command_history: HistoryContext::new("command.history"),
+      filter_history: HistoryContext::with_path(history_dir.join("filter.history")),
+      command_history: HistoryContext::with_path(history_dir.join("command.history")),
+      command_line_history: HistoryContext::with_path(history_dir.join("command-line.history")),
+      macros: Macros::with_path(history_dir.join("macros")),
       history_status: None,
       completion_list: CompletionList::with_items(vec![]),
       show_completion_pane: false,
       report: report.to_string(),
       projects: ProjectsState::new(),
+      board: BoardState::new(),
+      graph: GraphState::new(),
       contexts: ContextsState::new(),
+      report_menu: TaskwarriorTuiTableState::default(),
+      command_palette: CommandPalette::new(),
+      report_panes: vec![],
+      focused_report: 0,
       task_version,
       error: None,
+      undo_preview: None,
       event_loop,
       requires_redraw: false,
       changes: utils::Changeset::default(),
       backend,
+      backend_jobs: 0,
       timewarrior: TimewarriorIntegration::default(),
+      completions,
+      completion_queue,
+      refresh_in_flight: false,
+      task_details_in_flight: false,
+      export_failed: false,
+      export_mark: None,
+      last_full_export: None,
+      needs_full_export: false,
+      data_dir,
+      data_fingerprint: None,
     };
 
     for c in app.config.filter.chars() {
@@ -328,11 +531,23 @@ impl TaskwarriorTui {
     app.task_report_table.date_time_vague_precise = app.config.uda_task_report_date_time_vague_more_precise;
     app.task_report_table.duration_human_readable = app.config.uda_task_report_duration_human_readable;
 
+    for report in app.config.uda_task_report_split.clone() {
+      let filter = app.config.report_filters.get(&report).map(|filter| format!("{} ", filter.trim())).unwrap_or_default();
+      let mut task_report_table = TaskReportTable::new(data, &report)?;
+      task_report_table.date_time_vague_precise = app.config.uda_task_report_date_time_vague_more_precise;
+      task_report_table.duration_human_readable = app.config.uda_task_report_duration_human_readable;
+      app.open_report_pane(ReportPane::new(&report, filter.trim_start(), task_report_table));
+    }
+    app.focus_report_pane(0);
+
     app.update(true).await?;
+    app.wait_for_backend().await?;
 
     app.filter_history.load()?;
     app.filter_history.add(app.filter.as_str());
     app.command_history.load()?;
+    app.command_line_history.load()?;
+    app.macros.load(app.keyconfig.leader)?;
     app.task_background();
 
     if app.task_version < *TASKWARRIOR_VERSION_SUPPORTED {
@@ -383,7 +598,7 @@ impl TaskwarriorTui {
     } else {
       None
     };
-    self.event_loop = crate::event::EventLoop::new(tick_rate, true);
+    self.event_loop = crate::event::EventLoop::new(tick_rate, true, Some(&self.data_dir));
     Ok(())
   }
 
@@ -408,21 +623,32 @@ impl TaskwarriorTui {
         self.requires_redraw = false;
       }
       terminal.draw(|f| self.draw(f))?;
-      // Handle input
-      if let Some(event) = self.next().await {
-        match event {
-          Event::Input(input) => {
-            debug!("Received input = {:?}", input);
-            self.handle_input(input).await?;
-          }
-          Event::Tick => {
-            debug!("Tick event");
-            self.update(false).await?;
-          }
-          Event::Closed => {
-            debug!("Event loop closed");
+      // Handle input, or the result of a backend job
+      tokio::select! {
+        Some(event) = self.event_loop.rx.recv() => {
+          match event {
+            Event::Input(input) => {
+              debug!("Received input = {:?}", input);
+              self.handle_input(input).await?;
+            }
+            Event::Tick => {
+              debug!("Tick event");
+              self.update(false).await?;
+            }
+            Event::DataChanged => {
+              if self.tasks_changed() {
+                debug!("Task data changed");
+                self.update(true).await?;
+              }
+            }
+            Event::Closed => {
+              debug!("Event loop closed");
+            }
           }
         }
+        Some(completion) = self.completion_queue.recv() => {
+          self.apply_completion(completion).await?;
+        }
       }
 
       if self.should_quit {
@@ -436,31 +662,72 @@ impl TaskwarriorTui {
     self.command.update("", 0, &mut self.changes)
   }
 
-  pub fn get_context(&mut self) -> Result<()> {
-    let output = std::process::Command::new("task").arg("_get").arg("rc.context").output()?;
-    self.current_context = String::from_utf8_lossy(&output.stdout).to_string();
-    self.current_context = self.current_context.strip_suffix('\n').unwrap_or("").to_string();
+  /// Run `job` on the backend thread, then `then` with its result on the UI thread
+  ///
+  /// Results are applied by `run` as they arrive, or by `wait_for_backend`.
+  pub fn spawn_backend<T, F, C>(&mut self, job: F, then: C)
+  where
+    T: Send + 'static,
+    F: FnOnce(&dyn TaskBackend) -> Result<T> + Send + 'static,
+    C: FnOnce(&mut TaskwarriorTui, Result<T>) -> Result<()> + Send + 'static,
+  {
+    self.backend_jobs += 1;
+    let completions = self.completions.clone();
+    self.backend.execute(move |backend| {
+      let result = job(backend);
+      let completion: Completion = Box::new(move |app| then(app, result));
+      completions.send(completion).ok();
+    });
+  }
 
-    // support new format for context
-    let output = std::process::Command::new("task")
-      .arg("_get")
-      .arg(format!("rc.context.{}.read", self.current_context))
-      .output()?;
-    self.current_context_filter = String::from_utf8_lossy(&output.stdout).to_string();
-    self.current_context_filter = self.current_context_filter.strip_suffix('\n').unwrap_or("").to_string();
-
-    // If new format is not used, check if old format is used
-    if self.current_context_filter.is_empty() {
-      let output = std::process::Command::new("task")
-        .arg("_get")
-        .arg(format!("rc.context.{}", self.current_context))
-        .output()?;
-      self.current_context_filter = String::from_utf8_lossy(&output.stdout).to_string();
-      self.current_context_filter = self.current_context_filter.strip_suffix('\n').unwrap_or("").to_string();
+  /// Run a task mutation on the backend thread and show `describe(err)` as an error if it fails
+  pub(crate) fn spawn_mutation<F, D>(&mut self, job: F, describe: D)
+  where
+    F: FnOnce(&dyn TaskBackend) -> Result<()> + Send + 'static,
+    D: FnOnce(anyhow::Error) -> String + Send + 'static,
+  {
+    self.spawn_backend(job, move |app, result| {
+      if let Err(err) = result {
+        app.show_backend_error(describe(err));
+      }
+      Ok(())
+    });
+  }
+
+  /// Show an error that arrived from the backend, returning to the current mode once dismissed
+  fn show_backend_error(&mut self, error: String) {
+    if self.mode != Mode::Tasks(Action::Error) {
+      self.previous_mode = Some(self.mode.clone());
+    }
+    self.error = Some(error);
+    self.mode = Mode::Tasks(Action::Error);
+  }
+
+  /// Wait for every queued backend job and apply its result
+  ///
+  /// The event loop applies results as they arrive; startup and tests use this to wait for them.
+  pub async fn wait_for_backend(&mut self) -> Result<()> {
+    while self.backend_jobs > 0 {
+      match self.completion_queue.recv().await {
+        Some(completion) => self.apply_completion(completion).await?,
+        None => break,
+      }
     }
     Ok(())
   }
 
+  async fn apply_completion(&mut self, completion: Completion) -> Result<()> {
+    self.backend_jobs -= 1;
+    completion(self)?;
+    // A refresh requested while another one was running starts once that one is done
+    if self.dirty {
+      self.update(true).await
+    } else {
+      self.update_view();
+      Ok(())
+    }
+  }
+
   pub fn draw(&mut self, f: &mut Frame) {
     let rect = f.size();
     self.terminal_width = rect.width;
@@ -479,19 +746,38 @@ impl TaskwarriorTui {
       Mode::Tasks(action) => self.draw_task(f, main_layout, action),
       Mode::Calendar => self.draw_calendar(f, main_layout),
       Mode::Projects => self.draw_projects(f, main_layout),
+      Mode::Agenda => self.draw_agenda(f, main_layout),
+      Mode::Board => self.draw_board(f, main_layout),
+      Mode::Graph => self.draw_graph(f, main_layout),
+      Mode::Statistics => self.draw_statistics(f, main_layout),
     }
   }
 
   fn draw_tabs(&self, f: &mut Frame, layout: Rect) {
-    let titles: Vec<&str> = vec!["Tasks", "Projects", "Calendar"];
+    let titles: Vec<&str> = vec!["Tasks", "Projects", "Calendar", "Agenda", "Board", "Graph", "Statistics"];
     let tab_names: Vec<_> = titles.into_iter().map(Line::from).collect();
     let selected_tab = match self.mode {
       Mode::Tasks(_) => 0,
       Mode::Projects => 1,
       Mode::Calendar => 2,
+      Mode::Agenda => 3,
+      Mode::Board => 4,
+      Mode::Graph => 5,
+      Mode::Statistics => 6,
     };
     let navbar_block = Block::default().style(self.config.uda_style_navbar);
-    let context = Line::from(vec![
+    let mut context = vec![];
+    if let Some(register) = self.macros.recording() {
+      context.push(Span::styled(format!("recording @{} ", register), Style::default().add_modifier(Modifier::BOLD)));
+    }
+    if !self.pending_keys.is_empty() {
+      context.push(Span::styled(format!("{} ", self.pending_keys), Style::default().add_modifier(Modifier::BOLD)));
+    }
+    if self.backend_jobs > 0 {
+      let frame = (START_TIME.elapsed().as_millis() / 100) as usize % SPINNER.len();
+      context.push(Span::from(format!("{} working… ", SPINNER[frame])));
+    }
+    context.extend(vec![
       Span::from("["),
       Span::from(if self.current_context.is_empty() {
         "none"
@@ -500,6 +786,7 @@ impl TaskwarriorTui {
       }),
       Span::from("]"),
     ]);
+    let context = Line::from(context);
     let tabs = Tabs::new(tab_names)
       .block(navbar_block.clone())
       .select(selected_tab)
@@ -523,12 +810,42 @@ impl TaskwarriorTui {
   }
 
   pub fn draw_projects(&mut self, f: &mut Frame, rect: Rect) {
-    let data = self.projects.data.clone();
-    let p = Paragraph::new(Text::from(&data[..]));
-    f.render_widget(p, rect);
+    let (projects, headers) = self.projects.simplified_view(&self.all_tasks);
+    let widths = self.calculate_widths(&projects, &headers, rect.width);
+    let mut rows = vec![];
+    let mut highlight_style = Style::default();
+    for (i, (project, row)) in projects.iter().zip(self.projects.rows.iter()).enumerate() {
+      let style = match row {
+        ProjectRow::Project { name, .. } => self.style_for_project(std::slice::from_ref(name)),
+        ProjectRow::Task { index, .. } => self.style_for_task(&self.all_tasks[*index]),
+      };
+      rows.push(Row::StyledData(project.iter(), style));
+      if i == self.projects.current_selection {
+        highlight_style = style;
+      }
+    }
+    let constraints: Vec<Constraint> = widths
+      .iter()
+      .map(|i| Constraint::Length((*i).try_into().unwrap_or(rect.width)))
+      .collect();
+    let t = Table::new(headers.iter(), rows.into_iter())
+      .header_style(
+        self
+          .config
+          .color
+          .get("color.label")
+          .copied()
+          .unwrap_or_default()
+          .add_modifier(Modifier::UNDERLINED),
+      )
+      .highlight_style(self.selection_style(highlight_style))
+      .highlight_symbol(&self.config.uda_selection_indicator)
+      .mark_symbol(&self.config.uda_mark_indicator)
+      .unmark_symbol(&self.config.uda_unmark_indicator)
+      .widths(&constraints);
+    f.render_stateful_widget(t, rect, &mut self.projects.table_state);
   }
 
-  #[allow(dead_code)]
   fn style_for_project(&self, project: &[String]) -> Style {
     let virtual_tag_names_in_precedence = &self.config.rule_precedence_color;
     let mut style = Style::default();
@@ -546,15 +863,367 @@ impl TaskwarriorTui {
     style
   }
 
+  pub fn draw_board(&mut self, f: &mut Frame, layout: Rect) {
+    if self.board.columns.is_empty() {
+      let p = Paragraph::new(Text::from("No tasks in this report")).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded));
+      f.render_widget(p, layout);
+      return;
+    }
+    let constraints = vec![Constraint::Ratio(1, self.board.columns.len() as u32); self.board.columns.len()];
+    let rects = Layout::default().direction(Direction::Horizontal).constraints(constraints).split(layout);
+    for (c, (column, rect)) in self.board.columns.iter().zip(rects.iter()).enumerate() {
+      let focused = c == self.board.current_column;
+      let title_style = if focused {
+        Style::default().add_modifier(Modifier::BOLD)
+      } else {
+        Style::default()
+      };
+      let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(format!("{} ({})", column.title(), column.tasks.len()), title_style));
+      let inner = block.inner(*rect);
+      f.render_widget(block, *rect);
+
+      // Scroll the focused column so that the selected card stays visible
+      let visible = (inner.height / BOARD_CARD_HEIGHT).max(1) as usize;
+      let offset = if focused { (self.board.current_row + 1).saturating_sub(visible) } else { 0 };
+      for (row, &i) in column.tasks.iter().enumerate().skip(offset).take(visible) {
+        let task = &self.tasks[i];
+        let style = self.style_for_task(task);
+        let area = Rect::new(inner.x, inner.y + ((row - offset) as u16) * BOARD_CARD_HEIGHT, inner.width, BOARD_CARD_HEIGHT);
+        let border_style = if focused && row == self.board.current_row {
+          self.selection_style(style).add_modifier(Modifier::BOLD)
+        } else {
+          style
+        };
+        let mut details = vec![];
+        if let Some(id) = task.id() {
+          details.push(format!("#{}", id));
+        }
+        if let Some(project) = task.project() {
+          details.push(project.clone());
+        }
+        let virtual_tags = &self.task_report_table.virtual_tags;
+        details.extend(task.tags().into_iter().flatten().filter(|tag| !virtual_tags.contains(tag)).map(|tag| format!("+{}", tag)));
+        let card = Paragraph::new(vec![Line::from(task.description().as_str()), Line::from(details.join(" "))])
+          .style(style)
+          .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(border_style));
+        f.render_widget(card, area);
+      }
+    }
+  }
+
+  pub fn draw_agenda(&mut self, f: &mut Frame, layout: Rect) {
+    let title = match self.agenda.view {
+      AgendaView::List => "Agenda",
+      AgendaView::Day => "Day",
+      AgendaView::Week => "Week",
+    };
+    let block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title);
+    let inner = block.inner(layout);
+    f.render_widget(block, layout);
+    match self.agenda.view {
+      AgendaView::List => self.draw_agenda_list(f, inner),
+      AgendaView::Day | AgendaView::Week => self.draw_agenda_grid(f, inner),
+    }
+  }
+
+  /// A task of the agenda or the calendar's day list, with the kind of its date when `kind` is given
+  fn dated_task_span(&self, task: usize, kind: Option<AgendaKind>, selected: bool) -> Span<'static> {
+    let task = &self.tasks[task];
+    let mut text = String::new();
+    if let Some(kind) = kind {
+      text.push_str(&format!("{:<9} ", kind.label()));
+    }
+    if let Some(id) = task.id() {
+      text.push_str(&format!("#{} ", id));
+    }
+    text.push_str(task.description());
+    let style = self.style_for_task(task);
+    if selected {
+      Span::styled(text, self.selection_style(style))
+    } else {
+      Span::styled(text, style)
+    }
+  }
+
+  fn draw_agenda_list(&self, f: &mut Frame, layout: Rect) {
+    let today = Local::now().date_naive();
+    let mut lines = vec![];
+    let mut selected_line = 0;
+    for day in self.agenda.days() {
+      let header_style = if day == today {
+        self.config.uda_style_calendar_today.add_modifier(Modifier::BOLD)
+      } else {
+        Style::default().add_modifier(Modifier::BOLD)
+      };
+      lines.push(Line::from(Span::styled(day.format("%a %Y-%m-%d").to_string(), header_style)));
+      for (i, entry) in self.agenda.entries.iter().enumerate().filter(|(_, entry)| entry.when.date() == day) {
+        if i == self.agenda.current {
+          selected_line = lines.len();
+        }
+        let time = if entry.is_timed() {
+          entry.when.format("%H:%M").to_string()
+        } else {
+          "     ".to_string()
+        };
+        lines.push(Line::from(vec![Span::raw(format!("  {} ", time)), self.dated_task_span(entry.task, Some(entry.kind), i == self.agenda.current)]));
+      }
+    }
+    // Scroll so that the selected entry stays visible
+    let offset = (selected_line + 1).saturating_sub(layout.height as usize);
+    f.render_widget(Paragraph::new(lines).scroll((offset as u16, 0)), layout);
+  }
+
+  fn draw_agenda_grid(&self, f: &mut Frame, layout: Rect) {
+    let days = self.agenda.days();
+    let timed = self.agenda.entries.iter().filter(|entry| entry.is_timed());
+    let first_hour = timed.clone().map(|entry| entry.when.hour()).min().unwrap_or(AGENDA_FIRST_HOUR).min(AGENDA_FIRST_HOUR);
+    let last_hour = timed.map(|entry| entry.when.hour()).max().unwrap_or(AGENDA_LAST_HOUR).max(AGENDA_LAST_HOUR);
+
+    let mut constraints = vec![Constraint::Length(6)];
+    constraints.extend(vec![Constraint::Ratio(1, days.len() as u32); days.len()]);
+    let rects = Layout::default().direction(Direction::Horizontal).constraints(constraints).split(layout);
+
+    let mut hours = vec![Line::from(""), Line::from("all")];
+    hours.extend((first_hour..=last_hour).map(|hour| Line::from(format!("{:02}:00", hour))));
+    f.render_widget(Paragraph::new(hours), rects[0]);
+
+    let today = Local::now().date_naive();
+    for (day, rect) in days.iter().zip(rects.iter().skip(1)) {
+      let header_style = if *day == today {
+        self.config.uda_style_calendar_today.add_modifier(Modifier::BOLD)
+      } else {
+        Style::default().add_modifier(Modifier::BOLD)
+      };
+      let entries: Vec<(usize, &AgendaEntry)> = self.agenda.entries.iter().enumerate().filter(|(_, entry)| entry.when.date() == *day).collect();
+      let row = |spans: Vec<Span<'static>>| {
+        let mut line = vec![];
+        for (j, span) in spans.into_iter().enumerate() {
+          if j > 0 {
+            line.push(Span::raw(", "));
+          }
+          line.push(span);
+        }
+        Line::from(line)
+      };
+      let mut lines = vec![Line::from(Span::styled(day.format("%a %d").to_string(), header_style))];
+      lines.push(row(
+        entries
+          .iter()
+          .filter(|(_, entry)| !entry.is_timed())
+          .map(|(i, entry)| self.dated_task_span(entry.task, None, *i == self.agenda.current))
+          .collect(),
+      ));
+      for hour in first_hour..=last_hour {
+        lines.push(row(
+          entries
+            .iter()
+            .filter(|(_, entry)| entry.is_timed() && entry.when.hour() == hour)
+            .map(|(i, entry)| self.dated_task_span(entry.task, None, *i == self.agenda.current))
+            .collect(),
+        ));
+      }
+      f.render_widget(Paragraph::new(lines), *rect);
+    }
+  }
+
+  pub fn draw_graph(&mut self, f: &mut Frame, layout: Rect) {
+    let mut title = "Dependencies".to_string();
+    if self.graph.unconnected > 0 {
+      title = format!("{} ({} tasks without dependencies)", title, self.graph.unconnected);
+    }
+    let block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title);
+    if self.graph.rows.is_empty() {
+      let p = Paragraph::new(Text::from("No dependencies between the tasks in this report")).block(block);
+      f.render_widget(p, layout);
+      return;
+    }
+    let cycle_style = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = self
+      .graph
+      .rows
+      .iter()
+      .enumerate()
+      .map(|(i, row)| {
+        let marker = if self.marked.contains(&row.uuid) {
+          &self.config.uda_mark_indicator
+        } else {
+          &self.config.uda_unmark_indicator
+        };
+        let prefix_style = if row.in_cycle { cycle_style } else { Style::default() };
+        let mut spans = vec![Span::raw(marker.clone()), Span::styled(row.prefix.clone(), prefix_style)];
+        spans.push(self.graph_node_span(row, i == self.graph.current));
+        if row.closes_cycle {
+          spans.push(Span::styled(" ↻ cycle", cycle_style));
+        } else if row.repeated {
+          spans.push(Span::raw(" …"));
+        }
+        Line::from(spans)
+      })
+      .collect();
+    // Scroll so that the selected row stays visible
+    let height = block.inner(layout).height as usize;
+    let offset = (self.graph.current + 1).saturating_sub(height);
+    f.render_widget(Paragraph::new(lines).block(block).scroll((offset as u16, 0)), layout);
+  }
+
+  fn graph_node_span(&self, row: &GraphRow, selected: bool) -> Span<'static> {
+    let (text, style) = match row.task {
+      Some(i) => {
+        let task = &self.tasks[i];
+        let text = match task.id() {
+          Some(id) => format!("#{} {}", id, task.description()),
+          None => task.description().to_string(),
+        };
+        (text, self.style_for_task(task))
+      }
+      // Dependencies outside the report are only known by their UUID, unless all tasks were exported
+      None => match self.all_tasks.iter().find(|task| *task.uuid() == row.uuid) {
+        Some(task) => (format!("{} (not in report)", task.description()), Style::default().add_modifier(Modifier::DIM)),
+        None => (format!("{} (not in report)", row.uuid), Style::default().add_modifier(Modifier::DIM)),
+      },
+    };
+    if selected {
+      Span::styled(text, self.selection_style(style))
+    } else {
+      Span::styled(text, style)
+    }
+  }
+
+  pub fn draw_statistics(&mut self, f: &mut Frame, layout: Rect) {
+    let points = if self.statistics.weekly { "weekly" } else { "daily" };
+    let title = format!("Burndown, last {} ({})", self.statistics.window.label(), points);
+    if let Some(error) = &self.statistics.error {
+      let block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title);
+      f.render_widget(Paragraph::new(Text::from(error.as_str())).block(block).wrap(Wrap { trim: true }), layout);
+      return;
+    }
+    let rects = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+      .split(layout);
+    let bottom = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+      .split(rects[1]);
+
+    let series = |count: fn(&StatisticsPoint) -> usize| -> Vec<(f64, f64)> {
+      self
+        .statistics
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| (i as f64, count(point) as f64))
+        .collect()
+    };
+    let (open, completed, overdue) = (series(|point| point.open), series(|point| point.completed), series(|point| point.overdue));
+    let dataset = |name: &'static str, color: Color, data| {
+      Dataset::default()
+        .name(name)
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(data)
+    };
+    let highest = |data: &[&[(f64, f64)]]| data.iter().flat_map(|data| data.iter()).map(|(_, count)| *count).fold(0.0, f64::max);
+
+    let burndown = Chart::new(vec![dataset("Open", Color::Yellow, &open), dataset("Completed", Color::Green, &completed)])
+      .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title))
+      .hidden_legend_constraints((Constraint::Ratio(1, 4), Constraint::Ratio(1, 2)))
+      .legend_position(Some(LegendPosition::TopLeft))
+      .x_axis(self.statistics_time_axis())
+      .y_axis(statistics::count_axis(highest(&[&open, &completed])));
+    f.render_widget(burndown, rects[0]);
+
+    let block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title("Completed per project");
+    if self.statistics.throughput.is_empty() {
+      f.render_widget(Paragraph::new(Text::from("No tasks completed")).block(block), bottom[0]);
+    } else {
+      let bars: Vec<Bar> = self
+        .statistics
+        .throughput
+        .iter()
+        .map(|(project, count)| Bar::default().value(*count as u64).label(Line::from(project.as_str())))
+        .collect();
+      let throughput = BarChart::default()
+        .block(block)
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(Color::Green))
+        .data(BarGroup::default().bars(&bars));
+      f.render_widget(throughput, bottom[0]);
+    }
+
+    let overdue_chart = Chart::new(vec![dataset("Overdue", Color::Red, &overdue)])
+      .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title("Overdue"))
+      .x_axis(self.statistics_time_axis())
+      .y_axis(statistics::count_axis(highest(&[&overdue])));
+    f.render_widget(overdue_chart, bottom[1]);
+  }
+
+  /// Axis over the points of the statistics, labelled with the first, middle and last day
+  fn statistics_time_axis(&self) -> Axis<'static> {
+    let points = &self.statistics.points;
+    let label = |i: usize| {
+      points
+        .get(i)
+        .map(|point| Local.from_utc_datetime(&point.end).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+    };
+    let last = points.len().saturating_sub(1);
+    Axis::default()
+      .bounds([0.0, last as f64])
+      .labels(vec![Span::raw(label(0)), Span::raw(label(last / 2)), Span::raw(label(last))])
+  }
+
   pub fn draw_calendar(&mut self, f: &mut Frame, layout: Rect) {
+    // The tasks of the selected day are listed on the right when there is room for them
+    let (calendar_layout, day_layout) = if layout.width >= CALENDAR_MIN_WIDTH_WITH_DAY {
+      let rects = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(CALENDAR_DAY_WIDTH)].as_ref())
+        .split(layout);
+      (rects[0], Some(rects[1]))
+    } else {
+      (layout, None)
+    };
     let mut c = Calendar::default()
       .today_style(self.config.uda_style_calendar_today)
-      .year(self.calendar_year)
+      .year(self.calendar.selected.year())
+      .selected(self.calendar.selected)
       .date_style(self.get_dates_with_styles())
       .months_per_row(self.config.uda_calendar_months_per_row)
       .start_on_monday(self.config.weekstart);
     c.title_background_color = self.config.uda_style_calendar_title.bg.unwrap_or(Color::Reset);
-    f.render_widget(c, layout);
+    f.render_widget(c, calendar_layout);
+
+    if let Some(day_layout) = day_layout {
+      let holiday_style = self.config.color_calendar_holiday.unwrap_or_default();
+      let mut lines: Vec<Line> = self
+        .config
+        .holidays
+        .iter()
+        .filter(|holiday| holiday.contains(self.calendar.selected))
+        .map(|holiday| Line::from(Span::styled(holiday.name.clone(), holiday_style)))
+        .collect();
+      lines.extend(
+        self
+          .calendar
+          .tasks_on_selected_day(&self.tasks)
+          .into_iter()
+          .map(|(kind, task)| Line::from(self.dated_task_span(task, Some(kind), false))),
+      );
+      let p = Paragraph::new(lines).block(
+        Block::default()
+          .borders(Borders::ALL)
+          .border_type(BorderType::Rounded)
+          .title(self.calendar.selected.format("%a %Y-%m-%d").to_string()),
+      );
+      f.render_widget(p, day_layout);
+    }
   }
 
   pub fn draw_task(&mut self, f: &mut Frame, layout: Rect, action: Action) {
@@ -563,19 +1232,10 @@ impl TaskwarriorTui {
       .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
       .split(layout);
 
-    // render task report and task details if required
-    if self.task_report_show_info {
-      let split_task_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(rects[0]);
-
-      self.task_report_height = split_task_layout[0].height;
-      self.draw_task_report(f, split_task_layout[0]);
-      self.draw_task_details(f, split_task_layout[1]);
+    if self.report_panes.is_empty() {
+      self.draw_focused_report(f, rects[0]);
     } else {
-      self.task_report_height = rects[0].height;
-      self.draw_task_report(f, rects[0]);
+      self.draw_report_panes(f, rects[0]);
     }
 
     // calculate selected tasks
@@ -584,7 +1244,10 @@ impl TaskwarriorTui {
       vec!["0".to_string()]
     } else {
       match self.task_table_state.mode() {
-        TableMode::SingleSelection => vec![self.tasks[selected].id().unwrap_or_default().to_string()],
+        TableMode::SingleSelection => self.tasks[selected..(selected + self.selection_count).min(self.tasks.len())]
+          .iter()
+          .map(|task| task.id().unwrap_or_default().to_string())
+          .collect(),
         TableMode::MultipleSelection => {
           let mut tids = vec![];
           for uuid in &self.marked {
@@ -601,6 +1264,54 @@ impl TaskwarriorTui {
     self.handle_task_mode_action(f, &rects, &task_ids, action);
   }
 
+  /// Draw the focused report, and the task details below it if required
+  fn draw_focused_report(&mut self, f: &mut Frame, layout: Rect) {
+    if self.task_report_show_info {
+      let split_task_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(layout);
+
+      self.task_report_height = split_task_layout[0].height;
+      self.draw_task_report(f, split_task_layout[0]);
+      self.draw_task_details(f, split_task_layout[1]);
+    } else {
+      self.task_report_height = layout.height;
+      self.draw_task_report(f, layout);
+    }
+  }
+
+  /// Draw the report panes side by side, each below a line with its report and filter
+  fn draw_report_panes(&mut self, f: &mut Frame, layout: Rect) {
+    let count = self.report_panes.len();
+    let rects = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints(vec![Constraint::Ratio(1, count as u32); count])
+      .split(layout);
+    for (i, rect) in rects.iter().enumerate() {
+      let focused = i == self.focused_report;
+      if !focused {
+        self.swap_report_pane(i);
+        self.update_task_table_state();
+      }
+      let title_style = if focused {
+        Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+      } else {
+        Style::default().add_modifier(Modifier::DIM)
+      };
+      let title = format!(" {} {} ", self.report, self.filter.as_str().trim());
+      let block = Block::default().borders(Borders::TOP).title(Span::styled(title, title_style));
+      let inner = block.inner(*rect);
+      f.render_widget(block, *rect);
+      if focused {
+        self.draw_focused_report(f, inner);
+      } else {
+        self.draw_task_report(f, inner);
+        self.swap_report_pane(i);
+      }
+    }
+  }
+
   fn handle_task_mode_action(&mut self, f: &mut Frame, rects: &[Rect], task_ids: &[String], action: Action) {
     match action {
       Action::Error => {
@@ -642,13 +1353,22 @@ impl TaskwarriorTui {
           self.error.clone(),
         );
       }
-      Action::Jump => {
-        let position = Self::get_position(&self.command);
+      Action::CommandLine => {
+        let position = Self::get_position(&self.command_line);
+        if self.show_completion_pane {
+          self.draw_completion_pop_up(f, rects[1], position);
+        }
         self.draw_command(
           f,
           rects[1],
-          self.command.as_str(),
-          (Span::styled("Jump to Task", Style::default().add_modifier(Modifier::BOLD)), None),
+          self.command_line.as_str(),
+          (
+            Span::styled("Command", Style::default().add_modifier(Modifier::BOLD)),
+            self
+              .history_status
+              .as_ref()
+              .map(|s| Span::styled(s, Style::default().add_modifier(Modifier::BOLD))),
+          ),
           position,
           true,
           self.error.clone(),
@@ -814,6 +1534,30 @@ impl TaskwarriorTui {
         );
         self.draw_context_menu(f, 80, 50);
       }
+      Action::ReportMenu => {
+        self.draw_command(
+          f,
+          rects[1],
+          self.filter.as_str(),
+          ("Filter Tasks".into(), None),
+          Self::get_position(&self.filter),
+          false,
+          self.error.clone(),
+        );
+        self.draw_report_menu(f, 80, 50);
+      }
+      Action::CommandPalette => {
+        self.draw_command(
+          f,
+          rects[1],
+          self.command_palette.query.as_str(),
+          (Span::styled("Command", Style::default().add_modifier(Modifier::BOLD)), None),
+          Self::get_position(&self.command_palette.query),
+          true,
+          self.error.clone(),
+        );
+        self.draw_command_palette(f, 80, 50);
+      }
       Action::DonePrompt => {
         let label = if task_ids.len() > 1 {
           format!("Done Tasks {}?", task_ids.join(","))
@@ -863,7 +1607,7 @@ impl TaskwarriorTui {
         );
       }
       Action::UndoPrompt => {
-        let label = "Run `task undo`?";
+        let label = "Undo these changes?";
         let k = match self.keyconfig.undo {
           KeyCode::Char(c) => c.to_string(),
           _ => "Enter".to_string(),
@@ -881,13 +1625,46 @@ impl TaskwarriorTui {
           false,
           self.error.clone(),
         );
+        // draw the changes that undo would revert
+        let text = self.undo_preview.clone().unwrap_or_default();
+        let title = vec![Span::styled("Undo", Style::default().add_modifier(Modifier::BOLD))];
+        let rect = centered_rect(90, 60, f.size());
+        f.render_widget(Clear, rect);
+        let lines: Vec<Line> = text
+          .lines()
+          .map(|line| {
+            let style = if line.starts_with("- ") {
+              Style::default().fg(Color::Red)
+            } else if line.starts_with("+ ") {
+              Style::default().fg(Color::Green)
+            } else {
+              Style::default().add_modifier(Modifier::BOLD)
+            };
+            Line::from(Span::styled(line.to_string(), style))
+          })
+          .collect();
+        let p = Paragraph::new(Text::from(lines))
+          .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(title))
+          .wrap(Wrap { trim: false });
+        f.render_widget(p, rect);
       }
-      Action::TimewarriorInstallHook => {
-        let label = "Install Timewarrior Hook";
+      Action::Sync => {
         self.draw_command(
           f,
           rects[1],
-          "Installing timewarrior hook...",
+          "Synchronizing with the sync server... Press any key to continue working meanwhile.",
+          (Span::styled("Sync", Style::default().add_modifier(Modifier::BOLD)), None),
+          0,
+          false,
+          self.error.clone(),
+        );
+      }
+      Action::TimewarriorInstallHook => {
+        let label = "Install Timewarrior Hook";
+        self.draw_command(
+          f,
+          rects[1],
+          "Installing timewarrior hook...",
           (Span::styled(label, Style::default().add_modifier(Modifier::BOLD)), None),
           0,
           false,
@@ -1003,19 +1780,23 @@ impl TaskwarriorTui {
           }
         }
         
-        // Note: Holiday detection would require external calendar data
-        // For now, we'll skip holiday colors as they would need additional configuration
-        
         date_styles.push((date, style));
       }
     }
     
+    // Holidays come after the tasks, so that a task due on a holiday keeps its own style
+    if let Some(holiday_style) = self.config.color_calendar_holiday {
+      for holiday in &self.config.holidays {
+        date_styles.extend(holiday.start.iter_days().take_while(|date| *date <= holiday.end).map(|date| (date, holiday_style)));
+      }
+    }
+    
     date_styles
   }
 
   pub fn get_position(lb: &LineBuffer) -> usize {
     let mut position = 0;
-    for (_i, (j, g)) in lb.as_str().grapheme_indices(true).enumerate() {
+    for (j, g) in lb.as_str().grapheme_indices(true) {
       if j == lb.pos() {
         break;
       }
@@ -1108,6 +1889,99 @@ impl TaskwarriorTui {
     f.render_stateful_widget(t, area, &mut self.contexts.table_state);
   }
 
+  fn draw_report_menu(&mut self, f: &mut Frame, percent_x: u16, percent_y: u16) {
+    let area = centered_rect(percent_x, percent_y, f.size());
+    f.render_widget(Clear, area);
+
+    let reports: Vec<Vec<String>> = self
+      .config
+      .reports
+      .iter()
+      .map(|report| vec![report.name.clone(), report.description.clone(), report.filter.clone()])
+      .collect();
+    let headers = vec!["Name".to_string(), "Description".to_string(), "Filter".to_string()];
+    let widths = self.calculate_widths(&reports, &headers, area.width);
+    let constraints: Vec<Constraint> = widths
+      .iter()
+      .map(|i| Constraint::Length((*i).try_into().unwrap_or(area.width)))
+      .collect();
+
+    let mut rows = vec![];
+    for (row, report) in reports.iter().zip(&self.config.reports) {
+      let style = if report.name == self.report {
+        self.config.uda_style_context_active
+      } else {
+        Style::default()
+      };
+      rows.push(Row::StyledData(row.iter(), style));
+    }
+    let t = Table::new(headers.iter(), rows.into_iter())
+      .block(
+        Block::default()
+          .borders(Borders::ALL)
+          .border_type(BorderType::Rounded)
+          .title(Line::from(vec![Span::styled("Report", Style::default().add_modifier(Modifier::BOLD))])),
+      )
+      .header_style(
+        self
+          .config
+          .color
+          .get("color.label")
+          .copied()
+          .unwrap_or_default()
+          .add_modifier(Modifier::UNDERLINED),
+      )
+      .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+      .highlight_symbol(&self.config.uda_selection_indicator)
+      .widths(&constraints);
+
+    f.render_stateful_widget(t, area, &mut self.report_menu);
+  }
+
+  fn draw_command_palette(&mut self, f: &mut Frame, percent_x: u16, percent_y: u16) {
+    let area = centered_rect(percent_x, percent_y, f.size());
+    f.render_widget(Clear, area);
+
+    let commands: Vec<Vec<String>> = self
+      .command_palette
+      .matches
+      .iter()
+      .map(|&i| {
+        let command = &self.command_palette.commands[i];
+        vec![command.name.clone(), command.keys.clone()]
+      })
+      .collect();
+    let headers = vec!["Command".to_string(), "Keys".to_string()];
+    let widths = self.calculate_widths(&commands, &headers, area.width);
+    let constraints: Vec<Constraint> = widths
+      .iter()
+      .map(|i| Constraint::Length((*i).try_into().unwrap_or(area.width)))
+      .collect();
+
+    let rows = commands.iter().map(|row| Row::StyledData(row.iter(), Style::default()));
+    let t = Table::new(headers.iter(), rows)
+      .block(
+        Block::default()
+          .borders(Borders::ALL)
+          .border_type(BorderType::Rounded)
+          .title(Line::from(vec![Span::styled("Commands", Style::default().add_modifier(Modifier::BOLD))])),
+      )
+      .header_style(
+        self
+          .config
+          .color
+          .get("color.label")
+          .copied()
+          .unwrap_or_default()
+          .add_modifier(Modifier::UNDERLINED),
+      )
+      .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+      .highlight_symbol(&self.config.uda_selection_indicator)
+      .widths(&constraints);
+
+    f.render_stateful_widget(t, area, &mut self.command_palette.state);
+  }
+
   fn draw_completion_pop_up(&mut self, f: &mut Frame, rect: Rect, cursor_position: usize) {
     if self.completion_list.candidates().is_empty() {
       self.show_completion_pane = false;
@@ -1154,6 +2028,7 @@ impl TaskwarriorTui {
     f.render_stateful_widget(items, rect, &mut self.completion_list.state);
   }
 
+  #[allow(clippy::too_many_arguments)]
   fn draw_command(&self, f: &mut Frame, rect: Rect, text: &str, title: (Span, Option<Span>), position: usize, cursor: bool, error: Option<String>) {
     // f.render_widget(Clear, rect);
     if cursor {
@@ -1291,6 +2166,27 @@ impl TaskwarriorTui {
     style
   }
 
+  /// `style` of a selected task, with the `uda.taskwarrior-tui.selection.*` modifiers
+  fn selection_style(&self, style: Style) -> Style {
+    let mut style = style.patch(self.config.uda_style_report_selection);
+    if self.config.uda_selection_bold {
+      style = style.add_modifier(Modifier::BOLD);
+    }
+    if self.config.uda_selection_italic {
+      style = style.add_modifier(Modifier::ITALIC);
+    }
+    if self.config.uda_selection_dim {
+      style = style.add_modifier(Modifier::DIM);
+    }
+    if self.config.uda_selection_blink {
+      style = style.add_modifier(Modifier::SLOW_BLINK);
+    }
+    if self.config.uda_selection_reverse {
+      style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+  }
+
   pub fn calculate_widths(&self, tasks: &[Vec<String>], headers: &[String], maximum_column_width: u16) -> Vec<usize> {
     // naive implementation of calculate widths
     let mut widths = headers.iter().map(String::len).collect::<Vec<usize>>();
@@ -1356,22 +2252,7 @@ impl TaskwarriorTui {
       let style = self.style_for_task(&self.tasks[i]);
       if i == selected {
         pos = i;
-        highlight_style = style.patch(self.config.uda_style_report_selection);
-        if self.config.uda_selection_bold {
-          highlight_style = highlight_style.add_modifier(Modifier::BOLD);
-        }
-        if self.config.uda_selection_italic {
-          highlight_style = highlight_style.add_modifier(Modifier::ITALIC);
-        }
-        if self.config.uda_selection_dim {
-          highlight_style = highlight_style.add_modifier(Modifier::DIM);
-        }
-        if self.config.uda_selection_blink {
-          highlight_style = highlight_style.add_modifier(Modifier::SLOW_BLINK);
-        }
-        if self.config.uda_selection_reverse {
-          highlight_style = highlight_style.add_modifier(Modifier::REVERSED);
-        }
+        highlight_style = self.selection_style(style);
       }
       rows.push(Row::StyledData(task.iter(), style));
     }
@@ -1430,8 +2311,10 @@ impl TaskwarriorTui {
 
   pub async fn update(&mut self, force: bool) -> Result<()> {
     trace!("self.update({:?});", force);
-    if force || self.dirty || self.tasks_changed_since(self.last_export).unwrap_or(true) {
-      self.get_context()?;
+    if self.refresh_in_flight {
+      // Refresh again once the running export has finished
+      self.dirty |= force;
+    } else if force || self.dirty || (self.event_loop.watcher.is_none() && self.tasks_changed()) || self.export_outdated() {
       let task_uuids = self.selected_task_uuids();
       if self.current_selection_uuid.is_none() && self.current_selection_id.is_none() && task_uuids.len() == 1 {
         if let Some(uuid) = task_uuids.first() {
@@ -1440,31 +2323,302 @@ impl TaskwarriorTui {
       }
 
       self.task_report_table.export_headers(None, &self.report)?;
-      self.export_tasks()?;
-      if self.config.uda_task_report_use_all_tasks_for_completion {
-        self.export_all_tasks()?;
-      }
-      self.contexts.update_data()?;
-      self.projects.update_data()?;
-      self.update_tags();
-      self.task_details.clear();
+      self.refresh();
       self.dirty = false;
-      self.save_history()?;
-
-      // Some operations like export or summary change the taskwarrior database.
-      // The export time therefore gets set at the end, to avoid an infinite update loop.
-      self.last_export = Some(std::time::SystemTime::now());
     }
+    self.update_view();
+
+    Ok(())
+  }
+
+  /// Bring the selection and task details in line with the current tasks
+  fn update_view(&mut self) {
+    self.agenda.update_data(&self.tasks);
+    self.board.update_data(&self.tasks, &self.config, &self.task_report_table.virtual_tags);
+    self.graph.update_data(&self.tasks);
     self.cursor_fix();
     self.update_task_table_state();
     if self.task_report_show_info {
-      self.update_task_details().await?;
+      self.update_task_details();
     }
     self.selection_fix();
+  }
 
+  /// Recompute the statistics tab from every exported task
+  pub fn update_statistics(&mut self) {
+    self.statistics.update_data(&self.all_tasks, self.filter.as_str(), &self.current_context_filter);
+  }
+
+  /// Show `pane` after the focused report pane and give it the focus
+  pub fn open_report_pane(&mut self, pane: ReportPane) {
+    if self.report_panes.is_empty() {
+      self.report_panes.push(ReportPane::new(&self.report, "", self.task_report_table.clone()));
+    }
+    self.report_panes.insert(self.focused_report + 1, pane);
+    self.focus_report_pane(self.focused_report + 1);
+    self.dirty = true;
+  }
+
+  /// Close the focused report pane and focus the one before it
+  pub fn close_report_pane(&mut self) {
+    if self.report_panes.is_empty() {
+      return;
+    }
+    let closed = self.focused_report;
+    self.focus_report_pane(if closed > 0 { closed - 1 } else { 1 });
+    self.report_panes.remove(closed);
+    if self.focused_report > closed {
+      self.focused_report -= 1;
+    }
+    if self.report_panes.len() == 1 {
+      self.report_panes.clear();
+      self.focused_report = 0;
+    }
+    self.dirty = true;
+  }
+
+  pub fn focus_report_pane(&mut self, i: usize) {
+    if i == self.focused_report || i >= self.report_panes.len() {
+      return;
+    }
+    self.swap_report_pane(self.focused_report);
+    self.swap_report_pane(i);
+    self.focused_report = i;
+    self.update_view();
+    self.update_statistics();
+  }
+
+  /// Trade the state of the focused report with the state kept by pane `i`
+  fn swap_report_pane(&mut self, i: usize) {
+    let pane = &mut self.report_panes[i];
+    std::mem::swap(&mut self.report, &mut pane.report);
+    std::mem::swap(&mut self.filter, &mut pane.filter);
+    std::mem::swap(&mut self.tasks, &mut pane.tasks);
+    std::mem::swap(&mut self.task_report_table, &mut pane.task_report_table);
+    std::mem::swap(&mut self.task_table_state, &mut pane.task_table_state);
+    std::mem::swap(&mut self.current_selection, &mut pane.current_selection);
+    std::mem::swap(&mut self.current_selection_uuid, &mut pane.current_selection_uuid);
+    std::mem::swap(&mut self.current_selection_id, &mut pane.current_selection_id);
+  }
+
+  /// Give the marked tasks, or the selected task, the attributes the filter of the next report pane asks for
+  pub fn task_move_to_next_report_pane(&mut self) -> Result<(), String> {
+    if self.report_panes.is_empty() {
+      return Err("There is no other report pane to move tasks to".to_string());
+    }
+    let next = &self.report_panes[(self.focused_report + 1) % self.report_panes.len()];
+    let Some(modification) = modification_for_filter(next.filter.as_str()) else {
+      return Err(format!(
+        "The filter `{}` of report `{}` does not name attributes or tags to give the tasks",
+        next.filter.as_str().trim(),
+        next.report
+      ));
+    };
+    let task_uuids: Vec<Uuid> = if self.marked.is_empty() {
+      self.selected_task_uuids()
+    } else {
+      self.marked.iter().copied().collect()
+    };
+    if task_uuids.is_empty() {
+      return Ok(());
+    }
+    let describe = modification.clone();
+    self.spawn_mutation(
+      move |backend| backend.modify_tasks(&task_uuids, &modification),
+      move |err| format!("Unable to move the tasks with `task modify {}`. Error: {}", describe, err),
+    );
+    Ok(())
+  }
+
+  /// Export the report, contexts and project summary on the backend thread
+  fn refresh(&mut self) {
+    let filter = self.filter.as_str().to_string();
+    let report = self.report.clone();
+    let full_export_due = self.needs_full_export
+      || self
+        .last_full_export
+        .is_none_or(|last| last.elapsed().map_or(true, |elapsed| elapsed > FULL_EXPORT_INTERVAL));
+    let previous = if full_export_due { None } else { self.export_mark.clone() };
+    let data_dir = self.data_dir.clone();
+    let panes: Vec<(usize, String, String)> = self
+      .report_panes
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| *i != self.focused_report)
+      .map(|(i, pane)| (i, pane.filter.as_str().to_string(), pane.report.clone()))
+      .collect();
+    self.refresh_in_flight = true;
+    self.spawn_backend(
+      move |backend| {
+        let started = Utc::now();
+        let context = backend.current_context()?.unwrap_or_default();
+        let mark = ExportMark {
+          started,
+          filter,
+          report,
+          context_filter: context.read_filter.clone(),
+        };
+        let changes = match previous.filter(|previous| previous.covers(&mark)) {
+          // Changes within the same second as the previous export are exported again, to not miss any
+          Some(previous) => backend
+            .export_changes(&mark.filter, &mark.report, &mark.context_filter, previous.started - chrono::Duration::seconds(1))
+            .unwrap_or_else(|err| {
+              warn!("Unable to export changed tasks, exporting all tasks instead: {}", err);
+              None
+            }),
+          None => None,
+        };
+        let export = match changes {
+          Some(changes) => Export::Changes(changes),
+          None => {
+            debug!("Exporting report '{}' with filter '{}' and context filter '{}'", mark.report, mark.filter, mark.context_filter);
+            Export::Full {
+              tasks: backend.export_tasks(&mark.filter, &mark.report, &mark.context_filter),
+              // Every task, for the project tree and completion
+              all_tasks: backend.export_tasks("", "all", ""),
+            }
+          }
+        };
+        let panes = panes
+          .into_iter()
+          .map(|(pane, filter, report)| PaneExport {
+            tasks: backend.export_tasks(&filter, &report, &mark.context_filter),
+            pane,
+            filter,
+            report,
+          })
+          .collect();
+        Ok(Refresh {
+          context,
+          export,
+          mark,
+          contexts: backend.list_contexts()?,
+          fingerprint: DataFingerprint::of(&data_dir),
+          panes,
+        })
+      },
+      |app, refresh| app.apply_refresh(refresh),
+    );
+  }
+
+  fn apply_refresh(&mut self, refresh: Result<Refresh>) -> Result<()> {
+    self.refresh_in_flight = false;
+    let refresh = refresh?;
+    self.current_context = refresh.context.name;
+    self.current_context_filter = refresh.context.read_filter;
+    match refresh.export {
+      Export::Full { tasks, all_tasks } => {
+        let exported = tasks.is_ok() && all_tasks.is_ok();
+        self.apply_export(tasks);
+        self.apply_export_all(all_tasks);
+        self.task_details.clear();
+        if exported {
+          self.export_mark = Some(refresh.mark);
+          self.last_full_export = Some(SystemTime::now());
+          self.needs_full_export = false;
+        } else {
+          self.export_mark = None;
+        }
+      }
+      Export::Changes(changes) => {
+        if self.changes_need_full_export(&changes) {
+          debug!("{} modified tasks affect other tasks, exporting all tasks", changes.modified.len());
+          self.needs_full_export = true;
+          self.dirty = true;
+        } else {
+          info!("Merged {} modified tasks", changes.modified.len());
+          merge_tasks(&mut self.tasks, &changes.modified, |task| changes.matching.contains(task.uuid()));
+          self.sort_report();
+          if let Some(limit) = changes.limit {
+            self.tasks.truncate(limit);
+          }
+          merge_tasks(&mut self.all_tasks, &changes.modified, |_| true);
+          for task in &changes.modified {
+            self.task_details.remove(task.uuid());
+          }
+          self.export_mark = Some(refresh.mark);
+          self.clear_export_error();
+        }
+      }
+    }
+    for export in refresh.panes {
+      match self.report_panes.get_mut(export.pane) {
+        Some(pane) if export.pane != self.focused_report && pane.report == export.report && pane.filter.as_str() == export.filter => {
+          match export.tasks {
+            Ok(tasks) => pane.set_tasks(tasks),
+            Err(err) => {
+              self.error = Some(format!("Unable to export report `{}`: {:?}", export.report, err));
+              self.mode = Mode::Tasks(Action::Error);
+              self.export_failed = true;
+            }
+          }
+        }
+        // The pane was focused, filtered or closed while it was exported
+        _ => self.dirty = true,
+      }
+    }
+    self.contexts.update_data(refresh.contexts);
+    self.projects.update_data(&self.all_tasks);
+    self.update_statistics();
+    self.data_fingerprint = Some(refresh.fingerprint);
+    self.update_tags();
+    self.save_history()?;
+
+    // Some operations like export or summary change the taskwarrior database.
+    // The export time therefore gets set at the end, to avoid an infinite update loop.
+    self.last_export = Some(std::time::SystemTime::now());
     Ok(())
   }
 
+  /// Whether merging `changes` would leave other tasks out of date, so that everything has to be exported again
+  ///
+  /// Changing dependencies blocks or unblocks other tasks, and completing or deleting a task renumbers
+  /// the others. With `limit:` the tasks that were cut off are unknown, so a listed task that leaves
+  /// the report or moves, or a new or re-sorted task that ends up in the last slot, may belong after one of them.
+  fn changes_need_full_export(&self, changes: &TaskChanges) -> bool {
+    let known: HashMap<Uuid, &Task> = self.all_tasks.iter().chain(self.tasks.iter()).map(|task| (*task.uuid(), task)).collect();
+    let affects_others = changes.modified.iter().any(|task| {
+      let previous = known.get(task.uuid());
+      let depends_changed = previous.map_or(task.depends().is_some_and(|depends| !depends.is_empty()), |previous| {
+        previous.depends() != task.depends()
+      });
+      let closed = matches!(task.status(), TaskStatus::Completed | TaskStatus::Deleted)
+        && previous.is_some_and(|previous| previous.status() != task.status());
+      depends_changed || closed
+    });
+    affects_others || self.changes_reorder_limited_report(changes)
+  }
+
+  /// Whether merging `changes` into a report cut off by `limit:` could put the wrong tasks in it
+  fn changes_reorder_limited_report(&self, changes: &TaskChanges) -> bool {
+    let Some(limit) = changes.limit.filter(|&limit| self.tasks.len() >= limit) else {
+      return false;
+    };
+    let keys = self.report_sort_keys();
+    let priorities = &self.config.uda_priority_values;
+    let listed: HashMap<Uuid, &Task> = self.tasks.iter().map(|task| (*task.uuid(), task)).collect();
+    let mut merged = self.tasks.clone();
+    merge_tasks(&mut merged, &changes.modified, |task| changes.matching.contains(task.uuid()));
+    command_line::sort_tasks(&mut merged, &keys, priorities);
+    // Order of the tasks that were listed before, to tell a task that moved from one that was pushed down
+    let order_before: Vec<Uuid> = self.tasks.iter().map(|task| *task.uuid()).collect();
+    let order_after: Vec<Uuid> = merged.iter().map(|task| *task.uuid()).filter(|uuid| listed.contains_key(uuid)).collect();
+    changes.modified.iter().any(|task| {
+      let uuid = task.uuid();
+      let after = merged.iter().position(|merged| merged.uuid() == uuid);
+      match (listed.get(uuid), after) {
+        (Some(_), None) => true,
+        (Some(previous), Some(after)) => {
+          let moved = order_before.iter().position(|listed| listed == uuid) != order_after.iter().position(|listed| listed == uuid);
+          let sorts_differently = command_line::compare_tasks(previous, task, &keys, priorities).is_ne();
+          moved || (sorts_differently && after + 1 >= limit)
+        }
+        (None, Some(after)) => after + 1 == limit,
+        (None, None) => false,
+      }
+    })
+  }
+
   pub fn selection_fix(&mut self) {
     if let (Some(t), Some(id)) = (self.task_current(), self.current_selection_id) {
       if t.id() != Some(id) {
@@ -1488,6 +2642,8 @@ impl TaskwarriorTui {
   pub fn save_history(&mut self) -> Result<()> {
     self.filter_history.write()?;
     self.command_history.write()?;
+    self.command_line_history.write()?;
+    self.macros.write()?;
     Ok(())
   }
 
@@ -1497,9 +2653,9 @@ impl TaskwarriorTui {
     }
   }
 
-  pub async fn update_task_details(&mut self) -> Result<()> {
-    if self.tasks.is_empty() {
-      return Ok(());
+  pub fn update_task_details(&mut self) {
+    if self.tasks.is_empty() || self.task_details_in_flight {
+      return;
     }
 
     // remove task_details of tasks not in task report
@@ -1515,9 +2671,8 @@ impl TaskwarriorTui {
 
     let selected = self.current_selection;
     if selected >= self.tasks.len() {
-      return Ok(());
+      return;
     }
-    let current_task_uuid = *self.tasks[selected].uuid();
 
     let mut l = vec![selected];
 
@@ -1528,37 +2683,31 @@ impl TaskwarriorTui {
 
     l.dedup();
 
-    let (tx, mut rx) = tokio::sync::mpsc::channel(100);
-    let tasks = self.tasks.clone();
     let defaultwidth = self.terminal_width.saturating_sub(2);
+    let mut task_uuids = vec![];
     for s in &l {
-      if tasks.is_empty() {
-        return Ok(());
-      }
-      if s >= &tasks.len() {
+      if s >= &self.tasks.len() {
         break;
       }
-      let task_uuid = *tasks[*s].uuid();
-      if !self.task_details.contains_key(&task_uuid) || task_uuid == current_task_uuid {
+      let task_uuid = *self.tasks[*s].uuid();
+      if !self.task_details.contains_key(&task_uuid) {
         debug!("Running task details for {}", task_uuid);
-        let _tx = tx.clone();
-        tokio::spawn(async move {
-          let output = tokio::process::Command::new("task")
-            .arg("rc.color=off")
-            .arg("rc._forcecolor=off")
-            .arg(format!("rc.defaultwidth={}", defaultwidth))
-            .arg(format!("{}", task_uuid))
-            .output()
-            .await;
-          if let Ok(output) = output {
-            let data = String::from_utf8_lossy(&output.stdout).to_string();
-            _tx.send(Some((task_uuid, data))).await.unwrap();
-          }
-        });
+        task_uuids.push(task_uuid);
       }
     }
-    drop(tx);
-    while let Some(Some((task_uuid, mut data))) = rx.recv().await {
+    if task_uuids.is_empty() {
+      return;
+    }
+    self.task_details_in_flight = true;
+    self.spawn_backend(
+      move |backend| backend.render_task_details(&task_uuids, defaultwidth),
+      |app, details| app.apply_task_details(details),
+    );
+  }
+
+  fn apply_task_details(&mut self, details: Result<HashMap<Uuid, String>>) -> Result<()> {
+    self.task_details_in_flight = false;
+    for (task_uuid, mut data) in details? {
       // Append timewarrior information if integration is enabled
       if self.timewarrior.get_config().enabled {
         let timewarrior_status = self.timewarrior.get_status();
@@ -1567,12 +2716,12 @@ impl TaskwarriorTui {
         data.push_str("──────────────────────────────────────────────────────\n");
         
         if timewarrior_status.timewarrior_available {
-          data.push_str(&format!("Status: Available\n"));
+          data.push_str("Status: Available\n");
           data.push_str(&format!("Hook installed: {}\n", if timewarrior_status.hook_installed { "Yes" } else { "No" }));
           data.push_str(&format!("Integration enabled: {}\n", if timewarrior_status.integration_enabled { "Yes" } else { "No" }));
           
           if let Some(active_info) = &timewarrior_status.active_tracking {
-            data.push_str(&format!("Active tracking: Yes\n"));
+            data.push_str("Active tracking: Yes\n");
             data.push_str(&format!("Tags: {}\n", active_info.tags));
             data.push_str(&format!("Duration: {}\n", active_info.duration));
           } else {
@@ -1593,7 +2742,13 @@ impl TaskwarriorTui {
     self.task_table_state.select(Some(self.current_selection));
 
     for uuid in self.marked.clone() {
-      if self.task_by_uuid(uuid).is_none() {
+      // Marks are shared by the report panes and kept while any of them lists the task
+      let listed = self.task_by_uuid(uuid).is_some()
+        || self
+          .report_panes
+          .iter()
+          .any(|pane| pane.tasks.iter().any(|task| *task.uuid() == uuid));
+      if !listed {
         self.marked.remove(&uuid);
       }
     }
@@ -1639,9 +2794,271 @@ impl TaskwarriorTui {
 
   pub fn context_select(&mut self) -> Result<()> {
     let i = self.contexts.table_state.current_selection().unwrap_or_default();
-    let mut command = std::process::Command::new("task");
-    command.arg("context").arg(&self.contexts.rows[i].name);
-    command.output()?;
+    let name = self.contexts.rows[i].name.clone();
+    self.spawn_mutation(move |backend| backend.set_context(&name), |err| err.to_string());
+    Ok(())
+  }
+
+  /// Show `report` in the focused pane, with its columns, labels, sort order and filter
+  pub fn switch_report(&mut self, report: &str) -> Result<()> {
+    let definition = self
+      .config
+      .reports
+      .iter()
+      .find(|definition| definition.name == report)
+      .ok_or_else(|| anyhow!("Unable to find report `{}` in `task show`", report))?;
+    let filter = if definition.filter.trim().is_empty() {
+      String::new()
+    } else {
+      format!("{} ", definition.filter.trim())
+    };
+    self.task_report_table.export_headers(None, report)?;
+    self.report = report.to_string();
+    self.sort_keys.clear();
+    self.filter.update(&filter, filter.len(), &mut self.changes);
+    self.current_selection = 0;
+    self.current_selection_id = None;
+    self.current_selection_uuid = None;
+    self.task_table_state.select(Some(0));
+    Ok(())
+  }
+
+  /// Switch to the next or previous favorite report, or of all reports when there are no favorites
+  pub fn cycle_report(&mut self, forward: bool) -> Result<()> {
+    let reports: Vec<String> = if self.config.uda_task_report_favorites.is_empty() {
+      self.config.reports.iter().map(|report| report.name.clone()).collect()
+    } else {
+      self.config.uda_task_report_favorites.clone()
+    };
+    if reports.is_empty() {
+      return Ok(());
+    }
+    let next = match reports.iter().position(|report| *report == self.report) {
+      Some(i) if forward => (i + 1) % reports.len(),
+      Some(i) => (i + reports.len() - 1) % reports.len(),
+      None if forward => 0,
+      None => reports.len() - 1,
+    };
+    let report = reports[next].clone();
+    self.switch_report(&report)
+  }
+
+  /// Every action of the report keymap, then the reports, named commands and Timewarrior actions
+  fn palette_commands(&self) -> Vec<PaletteCommand> {
+    let mut commands: Vec<PaletteCommand> = self
+      .keyconfig
+      .keymap(KeyScope::Report)
+      .bindings()
+      .iter()
+      .filter(|binding| binding.key != self.keyconfig.command_palette && self.keyconfig.command(binding.key).is_none())
+      .map(|binding| PaletteCommand {
+        name: binding.action.to_string(),
+        keys: notation(&binding.keys),
+        action: PaletteAction::Key(binding.key),
+      })
+      .collect();
+    for command in &self.config.uda_commands {
+      let action = format!("command.{}", command.name);
+      let binding = self.keyconfig.keymap(KeyScope::Report).bindings().iter().find(|binding| binding.action == action);
+      commands.push(PaletteCommand {
+        name: format!("command {}", command.name),
+        keys: binding.map(|binding| notation(&binding.keys)).unwrap_or_default(),
+        action: PaletteAction::CommandLine(command.line.clone()),
+      });
+    }
+    commands.extend(self.config.reports.iter().map(|report| PaletteCommand {
+      name: format!("report {}", report.name),
+      keys: String::new(),
+      action: PaletteAction::Report(report.name.clone()),
+    }));
+    for (name, action) in [
+      ("timewarrior-install-hook", Action::TimewarriorInstallHook),
+      ("timewarrior-uninstall-hook", Action::TimewarriorUninstallHook),
+      ("timewarrior-status", Action::TimewarriorStatus),
+    ] {
+      commands.push(PaletteCommand {
+        name: name.to_string(),
+        keys: String::new(),
+        action: PaletteAction::Timewarrior(action),
+      });
+    }
+    commands
+  }
+
+  async fn run_palette_command(&mut self, command: PaletteCommand) -> Result<()> {
+    match command.action {
+      PaletteAction::Key(key) => {
+        if !self.handle_macro_key(key, None) {
+          Box::pin(self.handle_key(key)).await?;
+        }
+      }
+      PaletteAction::Report(report) => match self.switch_report(&report) {
+        Ok(_) => self.update(true).await?,
+        Err(e) => {
+          self.error = Some(e.to_string());
+          self.mode = Mode::Tasks(Action::Error);
+        }
+      },
+      PaletteAction::CommandLine(line) => {
+        if let Err(e) = self.run_command_line(&line).await {
+          self.error = Some(e.to_string());
+          self.mode = Mode::Tasks(Action::Error);
+        }
+      }
+      PaletteAction::Timewarrior(Action::TimewarriorInstallHook) => self.timewarrior_hook(true).await?,
+      PaletteAction::Timewarrior(Action::TimewarriorUninstallHook) => self.timewarrior_hook(false).await?,
+      PaletteAction::Timewarrior(action) => self.mode = Mode::Tasks(action),
+    }
+    Ok(())
+  }
+
+  /// Run a line of the command line, such as `:done 12 15` or `:filter +work`
+  pub async fn run_command_line(&mut self, line: &str) -> Result<()> {
+    match command_line::parse(line)? {
+      ExCommand::Jump(id) => {
+        self.task_report_jump(id)?;
+        self.update(true).await?;
+      }
+      ExCommand::Modify { ids, modifications } => {
+        let task_uuids = self.task_uuids_by_id(&ids)?;
+        self.spawn_mutation(
+          move |backend| backend.modify_tasks(&task_uuids, &modifications),
+          |err| format!("Cannot modify task: {}", err),
+        );
+        self.update(true).await?;
+      }
+      ExCommand::Done(ids) => {
+        let task_uuids = self.task_uuids_by_id(&ids)?;
+        self.spawn_mutation(
+          move |backend| backend.mark_done(&task_uuids),
+          |err| format!("Cannot mark task as done: {}", err),
+        );
+        self.current_selection_uuid = None;
+        self.current_selection_id = None;
+        self.update(true).await?;
+      }
+      ExCommand::Delete(ids) => {
+        let task_uuids = self.task_uuids_by_id(&ids)?;
+        self.spawn_mutation(
+          move |backend| backend.delete_tasks(&task_uuids),
+          |err| format!("Cannot delete task: {}", err),
+        );
+        self.current_selection_uuid = None;
+        self.current_selection_id = None;
+        self.update(true).await?;
+      }
+      ExCommand::Filter(filter) => {
+        let filter = if filter.is_empty() { filter } else { format!("{} ", filter) };
+        self.filter.update(&filter, filter.len(), &mut self.changes);
+        self.filter_history.add(self.filter.as_str());
+        self.update(true).await?;
+      }
+      ExCommand::Sort(keys) => {
+        // Only a new export brings back the order of the report
+        self.needs_full_export |= keys.is_empty();
+        self.sort_keys = keys;
+        self.sort_report();
+        self.update(true).await?;
+      }
+      ExCommand::Report(report) => {
+        self.switch_report(&report)?;
+        self.update(true).await?;
+      }
+      ExCommand::Context(name) => {
+        if !self.contexts.rows.iter().any(|row| row.name == name) {
+          return Err(anyhow!("Unable to find context `{}`", name));
+        }
+        let context = name.clone();
+        self.spawn_mutation(
+          move |backend| backend.set_context(&context),
+          move |err| format!("Cannot switch to context `{}`: {}", name, err),
+        );
+        self.update(true).await?;
+      }
+      ExCommand::Mark(filter) => {
+        let task_uuids = self.task_uuids_matching(&filter)?;
+        self.marked.extend(task_uuids);
+        if !self.marked.is_empty() {
+          self.task_table_state.multiple_selection();
+        }
+      }
+      ExCommand::Unmark(filter) => {
+        for uuid in self.task_uuids_matching(&filter)? {
+          self.marked.remove(&uuid);
+        }
+      }
+      ExCommand::Write(path) => {
+        let path = shellexpand::tilde(&path).into_owned();
+        let tasks = self.tasks.clone();
+        // Serializing and writing a large report would stall the UI, so it happens on the backend thread
+        self.spawn_mutation(
+          move |_| {
+            let json = serde_json::to_string_pretty(&tasks)?;
+            std::fs::write(&path, json).with_context(|| format!("Unable to write tasks to `{}`", path))
+          },
+          |err| format!("{:#}", err),
+        );
+      }
+      ExCommand::Quit => self.should_quit = true,
+    }
+    Ok(())
+  }
+
+  /// Run the command line of `uda.taskwarrior-tui.commands.<name>`
+  async fn run_named_command(&mut self, name: &str) -> Result<()> {
+    let line = self
+      .config
+      .uda_commands
+      .iter()
+      .find(|command| command.name == name)
+      .map(|command| command.line.clone())
+      .ok_or_else(|| anyhow!("Unable to find `uda.taskwarrior-tui.commands.{}` in `task show`", name))?;
+    self.run_command_line(&line).await
+  }
+
+  /// The tasks of the report with these ids, or the selected tasks when no ids are given
+  fn task_uuids_by_id(&self, ids: &[u64]) -> Result<Vec<Uuid>> {
+    if ids.is_empty() {
+      return Ok(if self.tasks.is_empty() { vec![] } else { self.selected_task_uuids() });
+    }
+    ids
+      .iter()
+      .map(|id| {
+        self
+          .task_by_id(*id)
+          .map(|task| *task.uuid())
+          .ok_or_else(|| anyhow!("Cannot locate task id {} in report", id))
+      })
+      .collect()
+  }
+
+  /// The tasks of the report that match `filter`
+  fn task_uuids_matching(&self, filter: &str) -> Result<Vec<Uuid>> {
+    let filter = Filter::parse(filter)?;
+    let context = FilterContext::new(&self.all_tasks).weekstart_monday(self.config.weekstart);
+    Ok(
+      self
+        .tasks
+        .iter()
+        .filter(|task| filter.matches(task, &context))
+        .map(|task| *task.uuid())
+        .collect(),
+    )
+  }
+
+  /// Install or uninstall the Timewarrior hook, then go back to the report
+  async fn timewarrior_hook(&mut self, install: bool) -> Result<()> {
+    let result = if install { self.timewarrior.install_hook() } else { self.timewarrior.uninstall_hook() };
+    match result {
+      Ok(_) => {
+        self.mode = Mode::Tasks(Action::Report);
+        self.update(true).await?;
+      }
+      Err(e) => {
+        self.error = Some(e.to_string());
+        self.mode = Mode::Tasks(Action::Error);
+      }
+    }
     Ok(())
   }
 
@@ -1749,12 +3166,11 @@ impl TaskwarriorTui {
     self.current_selection_uuid = None;
   }
 
-  pub fn task_report_jump(&mut self) -> Result<()> {
+  pub fn task_report_jump(&mut self, i: u64) -> Result<()> {
     if self.tasks.is_empty() {
       return Ok(());
     }
-    let i = self.command.as_str().parse::<usize>()?;
-    if let Some(task) = self.task_by_id(i as u64) {
+    if let Some(task) = self.task_by_id(i) {
       let j = self.task_index_by_uuid(*task.uuid()).unwrap_or_default();
       self.current_selection = j;
       self.current_selection_id = None;
@@ -1765,89 +3181,55 @@ impl TaskwarriorTui {
     }
   }
 
-  fn get_task_database_mtime(&self) -> Result<SystemTime> {
-    let data_dir = shellexpand::tilde(&self.config.data_location);
-    let database_path = Path::new(data_dir.as_ref()).join("taskchampion.sqlite3");
-
-    let metadata = fs::metadata(database_path).context("Fetching the metadate of the task database failed")?;
-    let mtime = metadata
-      .modified()
-      .context("Could not get mtime of task database, but fetching metadata succeeded")?;
+  /// Whether the task data changed since the last export
+  ///
+  /// With a watcher running, `Event::DataChanged` triggers this check; otherwise every tick does.
+  /// Comparing the data files filters out the events caused by the export itself.
+  pub fn tasks_changed(&self) -> bool {
+    self.data_fingerprint.as_ref().is_none_or(|fingerprint| *fingerprint != DataFingerprint::of(&self.data_dir))
+  }
 
-    Ok(mtime)
+  /// Whether the last export is old enough for time-dependent values like urgency and `+OVERDUE` to be stale
+  fn export_outdated(&self) -> bool {
+    self
+      .last_export
+      .is_none_or(|last| last.elapsed().map_or(true, |elapsed| elapsed > FULL_EXPORT_INTERVAL))
   }
 
-  pub fn tasks_changed_since(&mut self, prev: Option<SystemTime>) -> Result<bool> {
-    if let Some(prev) = prev {
-      let mtime = self.get_task_database_mtime()?;
-      if mtime > prev {
-        Ok(true)
-      } else {
-        // Unfortunately, we can not use std::time::Instant which is guaranteed to be monotonic,
-        // because we need to compare it to a file mtime as SystemTime, so as a safety for unexpected
-        // time shifts, cap maximum wait to 1 min
-        let now = SystemTime::now();
-        let max_delta = Duration::from_secs(60);
-        Ok(now.duration_since(prev)? > max_delta)
+  fn apply_export_all(&mut self, tasks: Result<Vec<Task>>) {
+    match tasks {
+      Ok(tasks) => {
+        self.all_tasks = tasks;
+        info!("Imported {} tasks", self.all_tasks.len());
+        self.clear_export_error();
+      }
+      Err(err) => {
+        self.error = Some(format!("Unable to export all tasks:\n{}", err));
+        self.export_failed = true;
       }
-    } else {
-      Ok(true)
     }
   }
 
-  pub fn export_all_tasks(&mut self) -> Result<()> {
-    let mut task = std::process::Command::new("task");
-
-    task
-      .arg("rc.json.array=on")
-      .arg("rc.confirmation=off")
-      .arg("rc.json.depends.array=on")
-      .arg("rc.color=off")
-      .arg("rc._forcecolor=off");
-    // .arg("rc.verbose:override=false");
-
-    task.arg("export");
-
-    task.arg("all");
+  /// Sort the report's tasks by the `:sort` keys, then by `report.<name>.sort`
+  fn sort_report(&mut self) {
+    let keys = self.report_sort_keys();
+    command_line::sort_tasks(&mut self.tasks, &keys, &self.config.uda_priority_values);
+  }
 
-    info!("Running `{:?}`", task);
-    let output = task.output()?;
-    let data = String::from_utf8_lossy(&output.stdout);
-    let error = String::from_utf8_lossy(&output.stderr);
-
-    if output.status.success() {
-      let imported = import(data.as_bytes());
-      match imported {
-        Ok(imported) => {
-          self.all_tasks = imported;
-          info!("Imported {} tasks", self.tasks.len());
-          self.error = None;
-          if self.mode == Mode::Tasks(Action::Error) {
-            self.mode = self.previous_mode.clone().unwrap_or(Mode::Tasks(Action::Report));
-            self.previous_mode = None;
-          }
-        }
-        Err(err) => {
-          self.error = Some(format!("Unable to parse output of `{:?}`:\n`{:?}`\n\n{}", task, data, err));
-          self.mode = Mode::Tasks(Action::Error);
-          debug!("Unable to parse output: {:?}", data);
-          debug!("Error: {:?}", err);
-        }
+  /// The `:sort` keys, then the report's own `report.<name>.sort`
+  fn report_sort_keys(&self) -> Vec<SortKey> {
+    let mut keys = self.sort_keys.clone();
+    if let Some(report) = self.config.reports.iter().find(|report| report.name == self.report) {
+      match command_line::parse_sort_keys(&report.sort) {
+        Ok(report_keys) => keys.extend(report_keys),
+        Err(err) => warn!("Ignoring report.{}.sort: {}", report.name, err),
       }
-    } else {
-      self.error = Some(format!("Cannot run `{:?}` - ({}) error:\n{}", &task, output.status, error));
     }
-
-    Ok(())
+    keys
   }
 
-  pub fn export_tasks(&mut self) -> Result<()> {
-    debug!("=== EXPORT_TASKS DEBUG ===");
-    debug!("Filter: '{}'", self.filter.to_string());
-    debug!("Report: '{}'", self.report);
-    debug!("Context filter: '{}'", self.current_context_filter);
-    
-    match self.backend.export_tasks(&self.filter.to_string(), &self.report, &self.current_context_filter) {
+  fn apply_export(&mut self, tasks: Result<Vec<Task>>) {
+    match tasks {
       Ok(tasks) => {
         debug!("Backend returned {} tasks", tasks.len());
         if tasks.is_empty() {
@@ -1858,42 +3240,45 @@ impl TaskwarriorTui {
         }
         
         self.tasks = tasks;
+        self.sort_report();
         info!("Exported {} tasks", self.tasks.len());
-        debug!("App now has {} tasks in self.tasks", self.tasks.len());
-        
-        self.error = None;
-        if self.mode == Mode::Tasks(Action::Error) {
-          self.mode = self.previous_mode.clone().unwrap_or(Mode::Tasks(Action::Report));
-          self.previous_mode = None;
-        }
+        self.clear_export_error();
       }
       Err(err) => {
         error!("Backend export failed: {:?}", err);
         self.error = Some(format!("Unable to export tasks: {:?}", err));
         self.mode = Mode::Tasks(Action::Error);
-        debug!("Export error: {:?}", err);
+        self.export_failed = true;
       }
     }
-    
-    debug!("=== END EXPORT_TASKS DEBUG ===");
-    Ok(())
+  }
+
+  /// Dismiss the error of a failed export; errors of other backend jobs stay until the user dismisses them
+  fn clear_export_error(&mut self) {
+    if !self.export_failed {
+      return;
+    }
+    self.export_failed = false;
+    self.error = None;
+    if self.mode == Mode::Tasks(Action::Error) {
+      self.mode = self.previous_mode.clone().unwrap_or(Mode::Tasks(Action::Report));
+      self.previous_mode = None;
+    }
   }
 
   pub fn selected_task_uuids(&self) -> Vec<Uuid> {
     let selected = match self.task_table_state.mode() {
-      TableMode::SingleSelection => vec![self.current_selection],
+      TableMode::SingleSelection => (self.current_selection..self.current_selection + self.selection_count).collect(),
       TableMode::MultipleSelection => self.task_table_state.marked().copied().collect::<Vec<usize>>(),
     };
 
     let mut task_uuids = vec![];
 
     for s in selected {
-      if self.tasks.is_empty() {
+      let Some(task) = self.tasks.get(s) else {
         break;
-      }
-      let _task_id = self.tasks[s].id().unwrap_or_default();
-      let task_uuid = *self.tasks[s].uuid();
-      task_uuids.push(task_uuid);
+      };
+      task_uuids.push(*task.uuid());
     }
 
     task_uuids
@@ -1945,24 +3330,24 @@ impl TaskwarriorTui {
   }
 
   pub fn task_log(&mut self) -> Result<(), String> {
-    let mut command = std::process::Command::new("task");
-
-    command.arg("log");
-
     let shell = self.command.as_str();
 
     match shlex::split(shell) {
       Some(cmd) => {
-        for s in cmd {
-          command.arg(&s);
-        }
-        let output = command.output();
-        match output {
-          Ok(_) => Ok(()),
-          Err(_) => Err(format!("Cannot run `task log {}`. Check documentation for more information", shell)),
+        if cmd.is_empty() {
+          return Err("No task description provided".to_string());
         }
+
+        let description = cmd[0].clone();
+        let args = cmd[1..].to_vec();
+
+        self.spawn_mutation(
+          move |backend| backend.log_task(&description, &args.iter().map(String::as_str).collect::<Vec<_>>()),
+          |err| format!("Cannot log task: {}", err),
+        );
+        Ok(())
       }
-      None => Err(format!("Unable to run `{:?}`: shlex::split(`{}`) failed.", command, shell)),
+      None => Err(format!("Unable to parse command: shlex::split(`{}`) failed.", shell)),
     }
   }
 
@@ -2013,6 +3398,8 @@ impl TaskwarriorTui {
     );
 
     let shell = shellexpand::tilde(&shell).into_owned();
+    // A shortcut may run `task undo` or edit tasks in other ways that keep their modification date
+    self.needs_full_export = true;
     let r = match shlex::split(&shell) {
       Some(cmd) => {
         let mut command = std::process::Command::new(&cmd[0]);
@@ -2062,12 +3449,13 @@ impl TaskwarriorTui {
     }
 
     let task_uuids = self.selected_task_uuids();
-    let shell = self.modify.as_str();
+    let shell = self.modify.as_str().to_string();
 
-    let r = match self.backend.modify_tasks(&task_uuids, shell) {
-      Ok(()) => Ok(()),
-      Err(err) => Err(format!("Cannot modify task: {}", err)),
-    };
+    let uuids = task_uuids.clone();
+    self.spawn_mutation(
+      move |backend| backend.modify_tasks(&uuids, &shell),
+      |err| format!("Cannot modify task: {}", err),
+    );
 
     if task_uuids.len() == 1 {
       if let Some(uuid) = task_uuids.first() {
@@ -2075,7 +3463,7 @@ impl TaskwarriorTui {
       }
     }
 
-    r
+    Ok(())
   }
 
   pub fn task_annotate(&mut self) -> Result<(), String> {
@@ -2084,39 +3472,16 @@ impl TaskwarriorTui {
     }
 
     let task_uuids = self.selected_task_uuids();
-
-    let mut command = std::process::Command::new("task");
-    command.arg("rc.bulk=0");
-    command.arg("rc.confirmation=off");
-    command.arg("rc.dependency.confirmation=off");
-    command.arg("rc.recurrence.confirmation=off");
-    for task_uuid in &task_uuids {
-      command.arg(task_uuid.to_string());
-    }
-    command.arg("annotate");
-
     let shell = self.command.as_str();
 
     let r = match shlex::split(shell) {
       Some(cmd) => {
-        for s in cmd {
-          command.arg(&s);
-        }
-        let output = command.output();
-        match output {
-          Ok(o) => {
-            if o.status.success() {
-              Ok(())
-            } else {
-              Err(format!("Annotate failed. {}", String::from_utf8_lossy(&o.stdout)))
-            }
-          }
-          Err(_) => Err(format!(
-            "Cannot run `task {} annotate {}`. Check documentation for more information",
-            task_uuids.iter().map(ToString::to_string).collect::<Vec<String>>().join(" "),
-            shell
-          )),
-        }
+        let uuids = task_uuids.clone();
+        self.spawn_mutation(
+          move |backend| backend.annotate_tasks(&uuids, &cmd.join(" ")),
+          |err| format!("Annotate failed. {}", err),
+        );
+        Ok(())
       }
       None => Err(format!("Cannot shlex split `{}`", shell)),
     };
@@ -2138,46 +3503,21 @@ impl TaskwarriorTui {
           return Err("No task description provided".to_string());
         }
         
-        let description = &cmd[0];
-        let args: Vec<&str> = cmd.iter().skip(1).map(|s| s.as_str()).collect();
+        let description = cmd[0].clone();
+        let args = cmd[1..].to_vec();
         
-        match self.backend.add_task(description, &args) {
-          Ok(()) => {
-            // TODO: Implement task ID tracking for jump-to-task feature
-            self.current_selection_id = None;
-            Ok(())
-          }
-          Err(err) => Err(format!("Cannot add task: {}", err)),
-        }
+        self.spawn_mutation(
+          move |backend| backend.add_task(&description, &args.iter().map(String::as_str).collect::<Vec<_>>()),
+          |err| format!("Cannot add task: {}", err),
+        );
+        // TODO: Implement task ID tracking for jump-to-task feature
+        self.current_selection_id = None;
+        Ok(())
       }
       None => Err(format!("Unable to parse command: shlex::split(`{}`) failed.", shell)),
     }
   }
 
-  pub fn task_virtual_tags(task_uuid: Uuid) -> Result<String, String> {
-    let output = std::process::Command::new("task").arg(format!("{}", task_uuid)).output();
-
-    match output {
-      Ok(output) => {
-        let data = String::from_utf8_lossy(&output.stdout);
-        for line in data.split('\n') {
-          for prefix in &["Virtual tags", "Virtual"] {
-            if line.starts_with(prefix) {
-              let line = line.to_string();
-              let line = line.replace(prefix, "");
-              return Ok(line);
-            }
-          }
-        }
-        Err(format!(
-          "Cannot find any tags for `task {}`. Check documentation for more information",
-          task_uuid
-        ))
-      }
-      Err(_) => Err(format!("Cannot run `task {}`. Check documentation for more information", task_uuid)),
-    }
-  }
-
   pub fn task_start_stop(&mut self) -> Result<(), String> {
     if self.tasks.is_empty() {
       return Ok(());
@@ -2185,44 +3525,48 @@ impl TaskwarriorTui {
 
     let task_uuids = self.selected_task_uuids();
 
+    let timewarrior_enabled = self.timewarrior.get_config().enabled;
     for task_uuid in &task_uuids {
-      let mut command = "start";
-      let mut is_active = false;
-      for tag in TaskwarriorTui::task_virtual_tags(*task_uuid).unwrap_or_default().split(' ') {
-        if tag == "ACTIVE" {
-          command = "stop";
-          is_active = true;
-        }
-      }
-
-      let output = std::process::Command::new("task").arg(task_uuid.to_string()).arg(command).output();
-      if output.is_err() {
-        return Err(format!("Error running `task {}` for task `{}`.", command, task_uuid));
-      }
-
-      // Check timewarrior status after task operation
-      if self.timewarrior.get_config().enabled {
-        let timewarrior_status = self.timewarrior.get_status();
-        if timewarrior_status.timewarrior_available {
+      let is_active = self.task_by_uuid(*task_uuid).is_some_and(|task| task.start().is_some());
+      let task_uuid = *task_uuid;
+      let command = if is_active { "stop" } else { "start" };
+      self.spawn_backend(
+        move |backend| {
           if is_active {
-            // Task was stopped
-            if timewarrior_status.active_tracking.is_some() {
-              self.error = Some(format!("Task stopped. Timewarrior tracking automatically stopped."));
-            } else {
-              self.error = Some(format!("Task stopped. No active timewarrior tracking detected."));
-            }
+            backend.stop_tasks(&[task_uuid])?;
           } else {
-            // Task was started
-            if timewarrior_status.active_tracking.is_some() {
-              self.error = Some(format!("Task started. Timewarrior tracking automatically started."));
-            } else {
-              self.error = Some(format!("Task started. Check timewarrior integration if tracking expected."));
+            backend.start_tasks(&[task_uuid])?;
+          }
+          // Ask timewarrior only now, once the hook has had a chance to start or stop tracking
+          Ok(timewarrior_enabled.then(|| {
+            (
+              TimewarriorIntegration::check_timewarrior_available(),
+              TimewarriorIntegration::get_active_tracking_info().is_some(),
+            )
+          }))
+        },
+        move |app, result| {
+          match result {
+            Ok(Some((true, tracking))) => {
+              app.error = Some(
+                match (is_active, tracking) {
+                  (true, true) => "Task stopped. Timewarrior is still tracking; check timewarrior integration.",
+                  (true, false) => "Task stopped. Timewarrior tracking automatically stopped.",
+                  (false, true) => "Task started. Timewarrior tracking automatically started.",
+                  (false, false) => "Task started. Check timewarrior integration if tracking expected.",
+                }
+                .to_string(),
+              );
+            }
+            Ok(Some((false, _))) => {
+              app.error = Some(format!("Task {}, but timewarrior is not available.", if is_active { "stopped" } else { "started" }));
             }
+            Ok(None) => {}
+            Err(err) => app.show_backend_error(format!("Error running `task {}` for task `{}`. {}", command, task_uuid, err)),
           }
-        } else {
-          self.error = Some(format!("Task {}, but timewarrior is not available.", if is_active { "stopped" } else { "started" }));
-        }
-      }
+          Ok(())
+        },
+      );
     }
 
     if task_uuids.len() == 1 {
@@ -2235,7 +3579,7 @@ impl TaskwarriorTui {
   }
 
   pub fn task_quick_tag(&mut self) -> Result<(), String> {
-    let tag_name = &self.config.uda_quick_tag_name;
+    let tag_name = self.config.uda_quick_tag_name.clone();
     let ptag_name = format!("+{}", tag_name);
     let ntag_name = format!("-{}", tag_name);
     if self.tasks.is_empty() {
@@ -2247,21 +3591,18 @@ impl TaskwarriorTui {
     for task_uuid in &task_uuids {
       if let Some(task) = self.task_by_uuid(*task_uuid) {
         let mut tag_to_set = &ptag_name;
-        for tag in task.tags().unwrap() {
-          if tag == tag_name {
+        for tag in task.tags().into_iter().flatten() {
+          if *tag == tag_name {
             tag_to_set = &ntag_name;
           }
         }
 
-        let output = std::process::Command::new("task")
-          .arg(task_uuid.to_string())
-          .arg("modify")
-          .arg(tag_to_set)
-          .output();
-
-        if output.is_err() {
-          return Err(format!("Error running `task modify {}` for task `{}`.", tag_to_set, task_uuid,));
-        }
+        let (task_uuid, tag_to_set) = (*task_uuid, tag_to_set.clone());
+        let modification = tag_to_set.clone();
+        self.spawn_mutation(
+          move |backend| backend.modify_tasks(&[task_uuid], &modification),
+          move |err| format!("Error running `task modify {}` for task `{}`. {}", tag_to_set, task_uuid, err),
+        );
       }
     }
 
@@ -2281,16 +3622,13 @@ impl TaskwarriorTui {
 
     let task_uuids = self.selected_task_uuids();
 
-    let r = match self.backend.delete_tasks(&task_uuids) {
-      Ok(()) => Ok(()),
-      Err(err) => Err(format!(
-        "Cannot delete task: {}",
-        err
-      )),
-    };
+    self.spawn_mutation(
+      move |backend| backend.delete_tasks(&task_uuids),
+      |err| format!("Cannot delete task: {}", err),
+    );
     self.current_selection_uuid = None;
     self.current_selection_id = None;
-    r
+    Ok(())
   }
 
   pub fn task_done(&mut self) -> Result<(), String> {
@@ -2298,16 +3636,13 @@ impl TaskwarriorTui {
       return Ok(());
     }
     let task_uuids = self.selected_task_uuids();
-    let r = match self.backend.mark_done(&task_uuids) {
-      Ok(()) => Ok(()),
-      Err(err) => Err(format!(
-        "Cannot mark task as done: {}",
-        err
-      )),
-    };
+    self.spawn_mutation(
+      move |backend| backend.mark_done(&task_uuids),
+      |err| format!("Cannot mark task as done: {}", err),
+    );
     self.current_selection_uuid = None;
     self.current_selection_id = None;
-    r
+    Ok(())
   }
 
   pub fn task_priority(&mut self, priority: &str) -> Result<(), String> {
@@ -2317,46 +3652,68 @@ impl TaskwarriorTui {
     let mut priority_arg = String::from("priority:");
     priority_arg.push_str(priority);
     let task_uuids = self.selected_task_uuids();
-    let mut cmd = std::process::Command::new("task");
-    cmd
-      .arg("rc.bulk=0")
-      .arg("rc.confirmation=off")
-      .arg("rc.dependency.confirmation=off")
-      .arg("rc.recurrence.confirmation=off")
-      .arg("modify")
-      .arg(&priority_arg);
-    for task_uuid in &task_uuids {
-      cmd.arg(task_uuid.to_string());
-    }
-    let output = cmd.output();
-    let r = match output {
-      Ok(_) => Ok(()),
-      Err(_) => Err(format!(
-        "Cannot run `task modify priority` for task `{}`. Check documentation for more information",
-        task_uuids.iter().map(ToString::to_string).collect::<Vec<String>>().join(" ")
-      )),
-    };
+    self.spawn_mutation(
+      move |backend| backend.modify_tasks(&task_uuids, &priority_arg),
+      |err| format!("Cannot modify priority: {}", err),
+    );
     self.current_selection_uuid = None;
     self.current_selection_id = None;
-    r
+    Ok(())
   }
 
   pub fn task_undo(&mut self) -> Result<(), String> {
-    let output = std::process::Command::new("task").arg("rc.confirmation=off").arg("undo").output();
-
-    match output {
-      Ok(output) => {
-        let data = String::from_utf8_lossy(&output.stdout);
-        let re = Regex::new(r"(?P<task_uuid>[a-fA-F0-9]{8}-[a-fA-F0-9]{4}-[a-fA-F0-9]{4}-[a-fA-F0-9]{4}-[a-fA-F0-9]{12})").unwrap();
-        if let Some(caps) = re.captures(&data) {
-          if let Ok(uuid) = Uuid::parse_str(&caps["task_uuid"]) {
-            self.current_selection_uuid = Some(uuid);
+    self.undo_preview = None;
+    self.spawn_backend(
+      |backend| backend.undo(),
+      |app, uuid| {
+        match uuid {
+          Ok(uuid) => {
+            if let Some(uuid) = uuid {
+              app.current_selection_uuid = Some(uuid);
+            }
           }
+          Err(err) => app.show_backend_error(format!("Cannot undo: {}", err)),
         }
         Ok(())
-      }
-      Err(_) => Err("Cannot run `task undo`. Check documentation for more information".to_string()),
-    }
+      },
+    );
+    Ok(())
+  }
+
+  /// Ask the backend for the changes `undo` would revert, then prompt for confirmation
+  pub fn task_undo_preview(&mut self) {
+    self.spawn_backend(
+      |backend| backend.undo_preview(),
+      |app, preview| {
+        match preview {
+          Ok(Some(preview)) => {
+            app.undo_preview = Some(preview);
+            app.mode = Mode::Tasks(Action::UndoPrompt);
+          }
+          Ok(None) => app.show_backend_error("Nothing to undo.".to_string()),
+          Err(e) => app.show_backend_error(format!("Cannot preview undo: {}", e)),
+        }
+        Ok(())
+      },
+    );
+  }
+
+  /// Sync on the backend thread, showing the sync message until it is done
+  pub fn task_sync(&mut self) {
+    self.mode = Mode::Tasks(Action::Sync);
+    self.spawn_backend(
+      |backend| backend.sync(),
+      |app, result| {
+        if app.mode == Mode::Tasks(Action::Sync) {
+          app.mode = Mode::Tasks(Action::Report);
+        }
+        match result {
+          Ok(()) => app.dirty = true,
+          Err(err) => app.show_backend_error(format!("Cannot sync tasks: {:#}", err)),
+        }
+        Ok(())
+      },
+    );
   }
 
   pub fn task_duplicate(&mut self) -> Result<(), String> {
@@ -2366,30 +3723,11 @@ impl TaskwarriorTui {
 
     let task_uuids = self.selected_task_uuids();
 
-    let mut command = std::process::Command::new("task");
-    command.arg("rc.bulk=0");
-    command.arg("rc.confirmation=off");
-    command.arg("rc.dependency.confirmation=off");
-    command.arg("rc.recurrence.confirmation=off");
-    for task_uuid in &task_uuids {
-      command.arg(task_uuid.to_string());
-    }
-    command.arg("duplicate");
-
-    let output = command.output();
-    let r = match output {
-      Ok(o) => {
-        if o.status.success() {
-          Ok(())
-        } else {
-          Err(format!("Modify failed. {}", String::from_utf8_lossy(&o.stdout)))
-        }
-      }
-      Err(_) => Err(format!(
-        "Cannot run `task {:?} duplicate`. Check documentation for more information",
-        task_uuids,
-      )),
-    };
+    let uuids = task_uuids.clone();
+    self.spawn_mutation(
+      move |backend| backend.duplicate_tasks(&uuids),
+      |err| format!("Duplicate failed. {}", err),
+    );
 
     if task_uuids.len() == 1 {
       if let Some(uuid) = task_uuids.first() {
@@ -2397,7 +3735,7 @@ impl TaskwarriorTui {
       }
     }
 
-    r
+    Ok(())
   }
 
   pub async fn task_edit(&mut self) -> Result<(), String> {
@@ -2411,31 +3749,10 @@ impl TaskwarriorTui {
     let _task_id = self.tasks[selected].id().unwrap_or_default();
     let task_uuid = *self.tasks[selected].uuid();
 
-    let r = std::process::Command::new("task").arg(format!("{}", task_uuid)).arg("edit").spawn();
-
-    let r = match r {
-      Ok(child) => {
-        let output = child.wait_with_output();
-        match output {
-          Ok(output) => {
-            if output.status.success() {
-              Ok(())
-            } else {
-              Err(format!(
-                "`task edit` for task `{}` failed. {}{}",
-                task_uuid,
-                String::from_utf8_lossy(&output.stdout),
-                String::from_utf8_lossy(&output.stderr),
-              ))
-            }
-          }
-          Err(err) => Err(format!("Cannot run `task edit` for task `{}`. {}", task_uuid, err)),
-        }
-      }
-      _ => Err(format!(
-        "Cannot start `task edit` for task `{}`. Check documentation for more information",
-        task_uuid
-      )),
+    // The editor needs the terminal, so wait for it instead of returning to the event loop
+    let r = match self.backend.run(move |backend| backend.edit_task(task_uuid)).await {
+      Ok(()) => Ok(()),
+      Err(err) => Err(format!("`task edit` for task `{}` failed. {}", task_uuid, err)),
     };
 
     self.current_selection_uuid = Some(task_uuid);
@@ -2573,43 +3890,205 @@ impl TaskwarriorTui {
     }
   }
 
-  pub fn toggle_mark(&mut self) {
-    if !self.tasks.is_empty() {
-      let selected = self.current_selection;
-      let _task_id = self.tasks[selected].id().unwrap_or_default();
-      let task_uuid = *self.tasks[selected].uuid();
+  pub fn toggle_mark(&mut self) {
+    if !self.tasks.is_empty() {
+      let selected = self.current_selection;
+      let _task_id = self.tasks[selected].id().unwrap_or_default();
+      let task_uuid = *self.tasks[selected].uuid();
+
+      if !self.marked.insert(task_uuid) {
+        self.marked.remove(&task_uuid);
+      }
+    }
+  }
+
+  pub fn toggle_mark_all(&mut self) {
+    for task in &self.tasks {
+      if !self.marked.insert(*task.uuid()) {
+        self.marked.remove(task.uuid());
+      }
+    }
+  }
+
+  pub fn escape(s: &str) -> String {
+    let mut es = String::with_capacity(s.len() + 2);
+    es.push('"');
+    for ch in s.chars() {
+      match ch {
+        '"' => {
+          es.push('\\');
+          es.push(ch);
+        }
+        _ => es.push(ch),
+      }
+    }
+    es.push('"');
+    es
+  }
+
+  /// The keymap the current mode reads keys with
+  fn key_scope(&self) -> KeyScope {
+    match self.mode {
+      Mode::Tasks(Action::Report) => KeyScope::Report,
+      Mode::Tasks(Action::Filter | Action::Add | Action::Annotate | Action::Subprocess | Action::Log | Action::Modify | Action::CommandLine | Action::CommandPalette) => {
+        KeyScope::Prompt
+      }
+      Mode::Tasks(_) => KeyScope::Popup,
+      Mode::Projects => KeyScope::Projects,
+      Mode::Calendar => KeyScope::Calendar,
+      Mode::Agenda => KeyScope::Agenda,
+      Mode::Board => KeyScope::Board,
+      Mode::Graph => KeyScope::Graph,
+      Mode::Statistics => KeyScope::Statistics,
+    }
+  }
+
+  /// Handle a key read from the terminal, once it completes a binding of the current keymap
+  ///
+  /// A count before a binding moves that many times. Other actions of the report work on that
+  /// many tasks from the selection on, and the go to top and bottom keys go to that task.
+  pub async fn handle_input(&mut self, input: KeyCode) -> Result<()> {
+    if let Some(prompt) = self.macros.prompt.take() {
+      if let RegisterPrompt::Replay(_) = prompt {
+        self.macros.record(&[input]);
+      }
+      if let Err(e) = self.handle_register(prompt, input).await {
+        self.previous_mode = Some(self.mode.clone());
+        self.error = Some(e.to_string());
+        self.mode = Mode::Tasks(Action::Error);
+      }
+      return Ok(());
+    }
+    let scope = self.key_scope();
+    self.macros.typed.push(input);
+    let result = self.pending_keys.push(self.keyconfig.keymap(scope), input);
+    if result != KeyInput::Pending {
+      // The key that stops a recording is left out of it
+      let typed = std::mem::take(&mut self.macros.typed);
+      let stop = matches!(result, KeyInput::Key { key, .. } if key == self.keyconfig.record_macro) && self.is_tab(scope);
+      if !stop {
+        self.macros.record(&typed);
+      }
+    }
+    let (key, count) = match result {
+      KeyInput::Key { key, count } => (key, count),
+      KeyInput::Text(keys) => {
+        for key in keys {
+          self.handle_key(key).await?;
+        }
+        return Ok(());
+      }
+      KeyInput::Pending | KeyInput::Discarded => return Ok(()),
+    };
+    if self.is_tab(scope) && self.handle_macro_key(key, count) {
+      return Ok(());
+    }
+    let Some(count) = count else {
+      return self.handle_key(key).await;
+    };
+    let moves = [
+      self.keyconfig.down,
+      self.keyconfig.up,
+      self.keyconfig.page_down,
+      self.keyconfig.page_up,
+      KeyCode::Down,
+      KeyCode::Up,
+      KeyCode::PageDown,
+      KeyCode::PageUp,
+    ];
+    if scope == KeyScope::Report && (key == self.keyconfig.go_to_top || key == self.keyconfig.go_to_bottom) {
+      if !self.tasks.is_empty() {
+        self.current_selection = count.clamp(1, self.tasks.len()) - 1;
+        self.current_selection_id = None;
+        self.current_selection_uuid = None;
+      }
+      self.update_task_table_state();
+    } else if scope == KeyScope::Report && !moves.contains(&key) {
+      self.selection_count = count;
+      self.handle_key(key).await?;
+    } else {
+      let mode = self.mode.clone();
+      for _ in 0..count {
+        if self.mode != mode {
+          break;
+        }
+        self.handle_key(key).await?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Keys are commands in the tabs, and text or answers in popups and prompts
+  fn is_tab(&self, scope: KeyScope) -> bool {
+    !matches!(scope, KeyScope::Popup | KeyScope::Prompt)
+  }
 
-      if !self.marked.insert(task_uuid) {
-        self.marked.remove(&task_uuid);
+  /// Start or stop recording a macro, or wait for the register to replay `count` times; false
+  /// for any other key
+  fn handle_macro_key(&mut self, key: KeyCode, count: Option<usize>) -> bool {
+    if key == self.keyconfig.record_macro {
+      if self.macros.recording().is_some() {
+        self.macros.stop();
+      } else {
+        self.macros.prompt = Some(RegisterPrompt::Record);
       }
+    } else if key == self.keyconfig.replay_macro {
+      self.macros.prompt = Some(RegisterPrompt::Replay(count.unwrap_or(1)));
+    } else {
+      return false;
     }
+    true
   }
 
-  pub fn toggle_mark_all(&mut self) {
-    for task in &self.tasks {
-      if !self.marked.insert(*task.uuid()) {
-        self.marked.remove(task.uuid());
-      }
+  /// Record into or replay the register named by `input`; `@` replays the register replayed last
+  async fn handle_register(&mut self, prompt: RegisterPrompt, input: KeyCode) -> Result<()> {
+    let register = match input {
+      KeyCode::Esc => return Ok(()),
+      KeyCode::Char(c) if macros::is_register(c) => c,
+      KeyCode::Char('@') if prompt != RegisterPrompt::Record => self.macros.last.ok_or_else(|| anyhow!("No macro was replayed yet"))?,
+      key => return Err(anyhow!("`{}` does not name a register", notation(&[key]))),
+    };
+    match prompt {
+      RegisterPrompt::Record => self.macros.start(register),
+      RegisterPrompt::Replay(count) => self.replay_macro(register, count).await?,
     }
+    Ok(())
   }
 
-  pub fn escape(s: &str) -> String {
-    let mut es = String::with_capacity(s.len() + 2);
-    es.push('"');
-    for ch in s.chars() {
-      match ch {
-        '"' => {
-          es.push('\\');
-          es.push(ch);
+  /// Feed the keys of `register` through `handle_input`, `count` times
+  ///
+  /// Every key waits for the backend jobs of the keys before it, as if it was typed once they
+  /// were done. Replaying stops at the first error, and a macro cannot replay itself.
+  async fn replay_macro(&mut self, register: char, count: usize) -> Result<()> {
+    let keys = self
+      .macros
+      .get(register)
+      .ok_or_else(|| anyhow!("Register `{}` holds no macro", register))?
+      .to_vec();
+    if self.macros.replaying.contains(&register) {
+      return Err(anyhow!("Macro `{}` cannot replay itself", register));
+    }
+    self.macros.last = Some(register);
+    self.macros.replaying.push(register);
+    let result = self.replay_keys(&keys, count).await;
+    self.macros.replaying.pop();
+    result
+  }
+
+  async fn replay_keys(&mut self, keys: &[KeyCode], count: usize) -> Result<()> {
+    for _ in 0..count {
+      for key in keys {
+        if self.should_quit || self.mode == Mode::Tasks(Action::Error) {
+          return Ok(());
         }
-        _ => es.push(ch),
+        Box::pin(self.handle_input(*key)).await?;
+        self.wait_for_backend().await?;
       }
     }
-    es.push('"');
-    es
+    Ok(())
   }
 
-  pub async fn handle_input(&mut self, input: KeyCode) -> Result<()> {
+  async fn handle_key(&mut self, input: KeyCode) -> Result<()> {
     match self.mode {
       Mode::Tasks(_) => {
         self.handle_input_by_task_mode(input).await?;
@@ -2618,50 +4097,31 @@ impl TaskwarriorTui {
         ProjectsState::handle_input(self, input)?;
         self.update(false).await?;
       }
+      Mode::Agenda => {
+        AgendaState::handle_input(self, input)?;
+        self.update(false).await?;
+      }
+      Mode::Board => {
+        BoardState::handle_input(self, input)?;
+        self.update(false).await?;
+      }
+      Mode::Graph => {
+        GraphState::handle_input(self, input)?;
+        self.update(false).await?;
+      }
+      Mode::Statistics => {
+        StatisticsState::handle_input(self, input)?;
+        self.update(false).await?;
+      }
       Mode::Calendar => {
-        if input == self.keyconfig.quit || input == KeyCode::Ctrl('c') {
-          self.should_quit = true;
-        } else if input == self.keyconfig.next_tab {
-          if self.config.uda_change_focus_rotate {
-            self.mode = Mode::Tasks(Action::Report);
-          }
-        } else if input == self.keyconfig.previous_tab {
-          self.mode = Mode::Projects;
-        } else if input == KeyCode::Up || input == self.keyconfig.up {
-          if self.calendar_year > 0 {
-            self.calendar_year -= 1;
-          }
-        } else if input == KeyCode::Down || input == self.keyconfig.down {
-          self.calendar_year += 1;
-        } else if input == KeyCode::PageUp || input == self.keyconfig.page_up {
-          self.task_report_previous_page();
-        } else if input == KeyCode::PageDown || input == self.keyconfig.page_down {
-          self.calendar_year += 10;
-        } else if input == KeyCode::Ctrl('e') {
-          self.task_details_scroll_down();
-        } else if input == KeyCode::Ctrl('y') {
-          self.task_details_scroll_up();
-        } else if input == self.keyconfig.done {
-          if self.config.uda_task_report_prompt_on_done {
-            self.mode = Mode::Tasks(Action::DonePrompt);
-            if self.task_current().is_none() {
-              self.mode = Mode::Tasks(Action::Report);
-            }
-          } else {
-            match self.task_done() {
-              Ok(_) => self.update(true).await?,
-              Err(e) => {
-                self.error = Some(e);
-                self.mode = Mode::Tasks(Action::Error);
-              }
-            }
-            if self.calendar_year > 0 {
-              self.calendar_year -= 10;
-            }
-          }
-        }
+        CalendarState::handle_input(self, input)?;
+        self.update(false).await?;
       }
     }
+    // A count lasts until the prompt it opened is closed
+    if self.mode == Mode::Tasks(Action::Report) {
+      self.selection_count = 1;
+    }
     self.update_task_table_state();
     Ok(())
   }
@@ -2677,6 +4137,26 @@ impl TaskwarriorTui {
           } else if input == self.keyconfig.select {
             self.task_table_state.multiple_selection();
             self.toggle_mark();
+          } else if input == KeyCode::Tab && !self.report_panes.is_empty() {
+            self.focus_report_pane((self.focused_report + 1) % self.report_panes.len());
+          } else if input == KeyCode::BackTab && !self.report_panes.is_empty() {
+            self.focus_report_pane((self.focused_report + self.report_panes.len() - 1) % self.report_panes.len());
+          } else if input == self.keyconfig.report_split {
+            let pane = ReportPane::new(&self.report, self.filter.as_str(), self.task_report_table.clone());
+            self.open_report_pane(pane);
+            self.update(true).await?;
+          } else if input == self.keyconfig.report_close {
+            self.close_report_pane();
+            self.update(true).await?;
+          } else if input == self.keyconfig.report_move {
+            match self.task_move_to_next_report_pane() {
+              Ok(_) => self.update(true).await?,
+              Err(e) => {
+                self.update(true).await?;
+                self.error = Some(e);
+                self.mode = Mode::Tasks(Action::Error);
+              }
+            }
           } else if input == self.keyconfig.select_all {
             self.task_table_state.multiple_selection();
             self.toggle_mark_all();
@@ -2760,12 +4240,11 @@ impl TaskwarriorTui {
                 self.mode = Mode::Tasks(Action::Error);
               }
             }
+          } else if input == self.keyconfig.sync {
+            self.task_sync();
           } else if input == self.keyconfig.undo {
             if self.config.uda_task_report_prompt_on_undo {
-              self.mode = Mode::Tasks(Action::UndoPrompt);
-              if self.task_current().is_none() {
-                self.mode = Mode::Tasks(Action::Report);
-              }
+              self.task_undo_preview();
             } else {
               match self.task_undo() {
                 Ok(_) => self.update(true).await?,
@@ -2875,7 +4354,23 @@ impl TaskwarriorTui {
             ));
             self.update_completion_list();
           } else if input == KeyCode::Char(':') {
-            self.mode = Mode::Tasks(Action::Jump);
+            self.mode = Mode::Tasks(Action::CommandLine);
+            self.command_line_history.reset();
+            self.history_status = Some(format!(
+              "{} / {}",
+              self
+                .command_line_history
+                .history_index()
+                .unwrap_or_else(|| self.command_line_history.history_len().saturating_sub(1))
+                .saturating_add(1),
+              self.command_line_history.history_len()
+            ));
+            self.update_completion_list();
+          } else if let Some(name) = self.keyconfig.command(input).map(str::to_string) {
+            if let Err(e) = self.run_named_command(&name).await {
+              self.error = Some(e.to_string());
+              self.mode = Mode::Tasks(Action::Error);
+            }
           } else if input == self.keyconfig.shortcut1 {
             match self.task_shortcut(1).await {
               Ok(_) => self.update(true).await?,
@@ -2993,9 +4488,25 @@ impl TaskwarriorTui {
             self.task_report_show_info = !self.task_report_show_info;
           } else if input == self.keyconfig.context_menu {
             self.mode = Mode::Tasks(Action::ContextMenu);
+          } else if input == self.keyconfig.report_menu {
+            let current = self.config.reports.iter().position(|report| report.name == self.report);
+            self.report_menu.select(Some(current.unwrap_or_default()));
+            self.mode = Mode::Tasks(Action::ReportMenu);
+          } else if input == self.keyconfig.command_palette {
+            let commands = self.palette_commands();
+            self.command_palette.open(commands);
+            self.mode = Mode::Tasks(Action::CommandPalette);
+          } else if input == self.keyconfig.report_next || input == self.keyconfig.report_previous {
+            match self.cycle_report(input == self.keyconfig.report_next) {
+              Ok(_) => self.update(true).await?,
+              Err(e) => {
+                self.error = Some(e.to_string());
+                self.mode = Mode::Tasks(Action::Error);
+              }
+            }
           } else if input == self.keyconfig.previous_tab {
             if self.config.uda_change_focus_rotate {
-              self.mode = Mode::Calendar;
+              self.mode = Mode::Statistics;
             }
           } else if input == self.keyconfig.next_tab {
             self.mode = Mode::Projects;
@@ -3051,6 +4562,49 @@ impl TaskwarriorTui {
             }
           }
         }
+        Action::ReportMenu => {
+          let count = self.config.reports.len().max(1);
+          let current = self.report_menu.current_selection().unwrap_or_default();
+          if input == self.keyconfig.quit || input == KeyCode::Esc {
+            self.mode = Mode::Tasks(Action::Report);
+          } else if input == KeyCode::Down || input == self.keyconfig.down {
+            self.report_menu.select(Some((current + 1) % count));
+          } else if input == KeyCode::Up || input == self.keyconfig.up {
+            self.report_menu.select(Some((current + count - 1) % count));
+          } else if input == KeyCode::Char('\n') {
+            self.mode = Mode::Tasks(Action::Report);
+            if let Some(report) = self.config.reports.get(current).map(|report| report.name.clone()) {
+              match self.switch_report(&report) {
+                Ok(_) => self.update(true).await?,
+                Err(e) => {
+                  self.error = Some(e.to_string());
+                  self.mode = Mode::Tasks(Action::Error);
+                }
+              }
+            }
+          }
+        }
+        Action::CommandPalette => match input {
+          KeyCode::Esc => {
+            self.mode = Mode::Tasks(Action::Report);
+          }
+          KeyCode::Char('\n') => {
+            self.mode = Mode::Tasks(Action::Report);
+            if let Some(command) = self.command_palette.selected().cloned() {
+              self.run_palette_command(command).await?;
+            }
+          }
+          KeyCode::Down | KeyCode::Tab | KeyCode::Ctrl('n') => {
+            self.command_palette.move_selection(true);
+          }
+          KeyCode::Up | KeyCode::BackTab | KeyCode::Ctrl('p') => {
+            self.command_palette.move_selection(false);
+          }
+          _ => {
+            handle_movement(&mut self.command_palette.query, input, &mut self.changes);
+            self.command_palette.update_matches();
+          }
+        },
         Action::HelpPopup => {
           if input == self.keyconfig.quit || input == KeyCode::Esc {
             self.mode = Mode::Tasks(Action::Report);
@@ -3393,31 +4947,99 @@ impl TaskwarriorTui {
             self.update_input_for_completion();
           }
         },
-        Action::Jump => match input {
+        Action::CommandLine => match input {
+          KeyCode::Esc => {
+            if self.show_completion_pane {
+              self.show_completion_pane = false;
+              self.completion_list.unselect();
+            } else {
+              self.command_line.update("", 0, &mut self.changes);
+              self.history_status = None;
+              self.mode = Mode::Tasks(Action::Report);
+            }
+          }
           KeyCode::Char('\n') => {
-            if self.error.is_some() {
-              self.previous_mode = Some(self.mode.clone());
-              self.mode = Mode::Tasks(Action::Error);
+            if self.show_completion_pane {
+              self.show_completion_pane = false;
+              if let Some((_i, (r, _m, o, _, _))) = self.completion_list.selected() {
+                let (before, after) = self.command_line.as_str().split_at(self.command_line.pos());
+                let fs = format!("{}{}{}", before.trim_end_matches(&o), r, after);
+                self.command_line.update(&fs, self.command_line.pos() + r.len() - o.len(), &mut self.changes);
+              }
+              self.completion_list.unselect();
             } else {
-              match self.task_report_jump() {
-                Ok(_) => {
-                  self.mode = Mode::Tasks(Action::Report);
-                  self.reset_command();
-                  self.update(true).await?;
-                }
-                Err(e) => {
-                  self.reset_command();
-                  self.error = Some(e.to_string());
-                  self.mode = Mode::Tasks(Action::Error);
-                }
+              let line = self.command_line.as_str().to_string();
+              self.command_line_history.add(&line);
+              self.command_line.update("", 0, &mut self.changes);
+              self.history_status = None;
+              self.mode = Mode::Tasks(Action::Report);
+              if let Err(e) = self.run_command_line(&line).await {
+                self.error = Some(e.to_string());
+                self.mode = Mode::Tasks(Action::Error);
               }
             }
           }
-          KeyCode::Esc => {
-            self.reset_command();
-            self.mode = Mode::Tasks(Action::Report);
+          KeyCode::Tab | KeyCode::Ctrl('n') => {
+            if !self.completion_list.is_empty() {
+              self.update_input_for_completion();
+              if !self.show_completion_pane {
+                self.show_completion_pane = true;
+              }
+              self.completion_list.next();
+            }
+          }
+          KeyCode::BackTab | KeyCode::Ctrl('p') => {
+            if self.show_completion_pane && !self.completion_list.is_empty() {
+              self.completion_list.previous();
+            }
+          }
+          KeyCode::Up => {
+            if self.show_completion_pane && !self.completion_list.is_empty() {
+              self.completion_list.previous();
+            } else if let Some(s) = self
+              .command_line_history
+              .history_search(&self.command_line.as_str()[..self.command_line.pos()], HistoryDirection::Reverse)
+            {
+              let p = self.command_line.pos();
+              self.command_line.update("", 0, &mut self.changes);
+              self.command_line.update(&s, std::cmp::min(s.len(), p), &mut self.changes);
+              self.history_status = Some(format!(
+                "{} / {}",
+                self
+                  .command_line_history
+                  .history_index()
+                  .unwrap_or_else(|| self.command_line_history.history_len().saturating_sub(1))
+                  .saturating_add(1),
+                self.command_line_history.history_len()
+              ));
+            }
+          }
+          KeyCode::Down => {
+            if self.show_completion_pane && !self.completion_list.is_empty() {
+              self.completion_list.next();
+            } else if let Some(s) = self
+              .command_line_history
+              .history_search(&self.command_line.as_str()[..self.command_line.pos()], HistoryDirection::Forward)
+            {
+              let p = self.command_line.pos();
+              self.command_line.update("", 0, &mut self.changes);
+              self.command_line.update(&s, std::cmp::min(s.len(), p), &mut self.changes);
+              self.history_status = Some(format!(
+                "{} / {}",
+                self
+                  .command_line_history
+                  .history_index()
+                  .unwrap_or_else(|| self.command_line_history.history_len().saturating_sub(1))
+                  .saturating_add(1),
+                self.command_line_history.history_len()
+              ));
+            }
+          }
+          _ => {
+            self.command_line_history.reset();
+            handle_movement(&mut self.command_line, input, &mut self.changes);
+            self.update_input_for_completion();
           }
-          _ => handle_movement(&mut self.command, input, &mut self.changes),
         },
         Action::Add => match input {
           KeyCode::Esc => {
@@ -3680,6 +5302,10 @@ impl TaskwarriorTui {
             handle_movement(&mut self.command, input, &mut self.changes);
           }
         }
+        Action::Sync => {
+          // Hide the sync message; the sync carries on and the tab bar shows it is still running
+          self.mode = Mode::Tasks(Action::Report);
+        }
         Action::UndoPrompt => {
           if input == self.keyconfig.undo || input == KeyCode::Char('\n') {
             if self.error.is_some() {
@@ -3698,34 +5324,17 @@ impl TaskwarriorTui {
               }
             }
           } else if input == self.keyconfig.quit || input == KeyCode::Esc {
+            self.undo_preview = None;
             self.mode = Mode::Tasks(Action::Report);
           } else {
             handle_movement(&mut self.command, input, &mut self.changes);
           }
         }
         Action::TimewarriorInstallHook => {
-          match self.timewarrior.install_hook() {
-            Ok(_) => {
-              self.mode = Mode::Tasks(Action::Report);
-              self.update(true).await?;
-            }
-            Err(e) => {
-              self.error = Some(e.to_string());
-              self.mode = Mode::Tasks(Action::Error);
-            }
-          }
+          self.timewarrior_hook(true).await?;
         }
         Action::TimewarriorUninstallHook => {
-          match self.timewarrior.uninstall_hook() {
-            Ok(_) => {
-              self.mode = Mode::Tasks(Action::Report);
-              self.update(true).await?;
-            }
-            Err(e) => {
-              self.error = Some(e.to_string());
-              self.mode = Mode::Tasks(Action::Error);
-            }
-          }
+          self.timewarrior_hook(false).await?;
         }
         Action::TimewarriorStatus => {
           self.mode = Mode::Tasks(Action::Report);
@@ -3758,7 +5367,7 @@ impl TaskwarriorTui {
       &self.tasks
     };
 
-    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log) = self.mode {
+    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log | Action::CommandLine) = self.mode {
       for s in [
         "project:".to_string(),
         "priority:".to_string(),
@@ -3772,7 +5381,7 @@ impl TaskwarriorTui {
       }
     }
 
-    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log) = self.mode {
+    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log | Action::CommandLine) = self.mode {
       for s in [
         ".before:",
         ".under:",
@@ -3801,7 +5410,7 @@ impl TaskwarriorTui {
       }
     }
 
-    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log) = self.mode {
+    if let Mode::Tasks(Action::Modify | Action::Filter | Action::Annotate | Action::Add | Action::Log | Action::CommandLine) = self.mode {
       for priority in &self.config.uda_priority_values {
         let p = priority.to_string();
         self.completion_list.insert(("priority".to_string(), p));
@@ -3857,6 +5466,18 @@ impl TaskwarriorTui {
       }
     }
 
+    if self.mode == Mode::Tasks(Action::CommandLine) {
+      for command in COMMANDS {
+        self.completion_list.insert(("command".to_string(), command.to_string()));
+      }
+      for report in &self.config.reports {
+        self.completion_list.insert(("report".to_string(), report.name.clone()));
+      }
+      for context in &self.contexts.rows {
+        self.completion_list.insert(("context".to_string(), context.name.clone()));
+      }
+    }
+
     if self.mode == Mode::Tasks(Action::Filter) {
       self.completion_list.insert(("status".to_string(), "pending".into()));
       self.completion_list.insert(("status".to_string(), "completed".into()));
@@ -3882,6 +5503,21 @@ impl TaskwarriorTui {
         let input = self.filter.as_str()[i..self.filter.pos()].to_string();
         self.completion_list.input(input, "".to_string());
       }
+      Mode::Tasks(Action::CommandLine) => {
+        let line = self.command_line.as_str();
+        let i = get_start_word_under_cursor(line, self.command_line.pos());
+        let input = line[i..self.command_line.pos()].to_string();
+        // The first word names a command, and the word after `report` or `context` one of those
+        let before: Vec<&str> = line[..i].trim_start().trim_start_matches(':').split_whitespace().collect();
+        match before.as_slice() {
+          [] => self.completion_list.input_in_context(input.trim_start_matches(':').to_string(), "command"),
+          [name] => match command_line::command_name(name) {
+            Ok(command @ ("report" | "context")) => self.completion_list.input_in_context(input, command),
+            _ => self.completion_list.input(input, "".to_string()),
+          },
+          _ => self.completion_list.input(input, "".to_string()),
+        }
+      }
       _ => {}
     }
   }
@@ -3957,10 +5593,12 @@ mod tests {
   use std::{
     fmt::Write,
     fs::File,
+    io::Read,
     path::{Path, PathBuf},
   };
 
   use ratatui::{backend::TestBackend, buffer::Buffer};
+  use regex::Regex;
 
   use super::*;
 
@@ -3972,6 +5610,7 @@ mod tests {
   }
 
   /// Returns a string representation of the given buffer for debugging purpose.
+  #[allow(dead_code)]
   fn buffer_view(buffer: &Buffer) -> String {
     let mut view = String::with_capacity(buffer.content.len() + buffer.area.height as usize * 3);
     for cells in buffer.content.chunks(buffer.area.width as usize) {
@@ -4056,6 +5695,7 @@ mod tests {
       app.handle_input(KeyCode::Char(c)).await.unwrap();
     }
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
     app.handle_input(KeyCode::Down).await.unwrap();
 
     assert_eq!("\"Buy groceries\" +test", app.command.as_str());
@@ -4081,12 +5721,15 @@ mod tests {
     app.handle_input(KeyCode::Right).await.unwrap();
     app.handle_input(KeyCode::Backspace).await.unwrap();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
     app.handle_input(KeyCode::Down).await.unwrap();
 
     assert_eq!("\"Buy groceries", app.command.as_str());
 
     app.update(true).await.unwrap();
 
+    app.wait_for_backend().await.unwrap();
+
     app.handle_input(KeyCode::Up).await.unwrap();
 
     assert_eq!("\"Buy groceries\" +test", app.command.as_str());
@@ -4168,6 +5811,7 @@ mod tests {
 
     app.task_quick_tag().unwrap();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     let task = app.task_by_id(11).unwrap();
     let tags = ["next", "finance", "UNBLOCKED", "PENDING", "TAGGED", "UDA"]
@@ -4180,6 +5824,7 @@ mod tests {
 
     app.task_quick_tag().unwrap();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     let task = app.task_by_id(11).unwrap();
     let tags = ["finance", "UNBLOCKED", "PENDING", "TAGGED", "UDA"]
@@ -4218,7 +5863,7 @@ mod tests {
     assert_eq!(style, Style::default().fg(Color::Indexed(2)));
 
     let task = app.task_by_id(11).unwrap();
-    let style = app.style_for_task(&task);
+    let _style = app.style_for_task(&task);
   }
 
   async fn test_task_context() {
@@ -4226,6 +5871,8 @@ mod tests {
 
     assert!(app.update(true).await.is_ok());
 
+    assert!(app.wait_for_backend().await.is_ok());
+
     app.context_select().unwrap();
 
     assert_eq!(app.tasks.len(), 26);
@@ -4239,6 +5886,8 @@ mod tests {
 
     assert!(app.update(true).await.is_ok());
 
+    assert!(app.wait_for_backend().await.is_ok());
+
     assert_eq!(app.tasks.len(), 1);
     assert_eq!(app.current_context_filter, "+finance -private");
 
@@ -4250,6 +5899,8 @@ mod tests {
 
     assert!(app.update(true).await.is_ok());
 
+    assert!(app.wait_for_backend().await.is_ok());
+
     assert_eq!(app.tasks.len(), 26);
     assert_eq!(app.current_context_filter, "");
   }
@@ -4259,6 +5910,7 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
@@ -4289,6 +5941,8 @@ mod tests {
     assert_eq!(task_id, total_tasks + 1);
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), (total_tasks + 1) as usize);
     assert_eq!(app.current_context_filter, "");
 
@@ -4300,7 +5954,7 @@ mod tests {
       }
     }
 
-    let output = std::process::Command::new("task")
+    let _output = std::process::Command::new("task")
       .arg("rc.confirmation=off")
       .arg("undo")
       .output()
@@ -4308,6 +5962,7 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
   }
@@ -4317,12 +5972,10 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
-    let now = Local::now();
-    let now = TimeZone::from_utc_datetime(now.offset(), &now.naive_utc());
-
     let mut command = std::process::Command::new("task");
     command.arg("add");
     let message = "'new task for testing earlier today' due:now";
@@ -4340,6 +5993,8 @@ mod tests {
     assert_eq!(task_id, total_tasks + 1);
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), (total_tasks + 1) as usize);
     assert_eq!(app.current_context_filter, "");
 
@@ -4359,7 +6014,7 @@ mod tests {
       assert!(task.tags().unwrap().contains(&s.to_string()));
     }
 
-    let output = std::process::Command::new("task")
+    let _output = std::process::Command::new("task")
       .arg("rc.confirmation=off")
       .arg("undo")
       .output()
@@ -4367,6 +6022,7 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
   }
@@ -4376,6 +6032,7 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
@@ -4407,6 +6064,8 @@ mod tests {
     assert_eq!(task_id, total_tasks + 1);
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), (total_tasks + 1) as usize);
     assert_eq!(app.current_context_filter, "");
 
@@ -4415,7 +6074,7 @@ mod tests {
       assert!(task.tags().unwrap().contains(&s.to_string()));
     }
 
-    let output = std::process::Command::new("task")
+    let _output = std::process::Command::new("task")
       .arg("rc.confirmation=off")
       .arg("undo")
       .output()
@@ -4423,13 +6082,14 @@ mod tests {
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     assert!(app.update(true).await.is_ok());
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
   }
 
   async fn test_draw_empty_task_report() {
     let mut expected = Buffer::with_lines(vec![
-      " Tasks   Projects   Calendar                [none]",
+      " Tasks   Projects   Calendar   Agenda   Boar[none]",
       "                                                  ",
       "                                                  ",
       "                                                  ",
@@ -4469,14 +6129,15 @@ mod tests {
     let total_tasks: u64 = 0;
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
-    let now = Local::now();
-    let now = TimeZone::from_utc_datetime(now.offset(), &now.naive_utc());
-
     app.update(true).await.unwrap();
 
+    app.wait_for_backend().await.unwrap();
+
     let backend = TestBackend::new(50, 15);
     let mut terminal = Terminal::new(backend).unwrap();
     terminal
@@ -4518,12 +6179,11 @@ mod tests {
     let total_tasks: u64 = 26;
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
-    let now = Local::now();
-    let now = TimeZone::from_utc_datetime(now.offset(), &now.naive_utc());
-
     app.mode = Mode::Tasks(Action::Modify);
     match app.task_table_state.mode() {
       TableMode::SingleSelection => match app.task_current() {
@@ -4538,6 +6198,8 @@ mod tests {
 
     app.update(true).await.unwrap();
 
+    app.wait_for_backend().await.unwrap();
+
     let backend = TestBackend::new(25, 3);
     let mut terminal = Terminal::new(backend).unwrap();
     terminal
@@ -4647,6 +6309,7 @@ mod tests {
     terminal.backend().assert_buffer(&expected2);
   }
 
+  #[allow(dead_code)]
   async fn test_draw_task_report() {
     let mut expected = Buffer::with_lines(vec![
       "╭Task|Calendar───────────────────────────────────╮",
@@ -4711,12 +6374,11 @@ mod tests {
     let total_tasks: u64 = 26;
 
     assert!(app.update(true).await.is_ok());
+
+    assert!(app.wait_for_backend().await.is_ok());
     assert_eq!(app.tasks.len(), total_tasks as usize);
     assert_eq!(app.current_context_filter, "");
 
-    let now = Local::now();
-    let now = TimeZone::from_utc_datetime(now.offset(), &now.naive_utc());
-
     let mut command = std::process::Command::new("task");
     command.arg("add");
     let message = "'new task 1 for testing draw' priority:U";
@@ -4756,6 +6418,7 @@ mod tests {
     app.task_report_bottom();
     app.task_report_top();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     let backend = TestBackend::new(50, 15);
     let mut terminal = Terminal::new(backend).unwrap();
@@ -4774,12 +6437,12 @@ mod tests {
       })
       .unwrap();
 
-    let output = std::process::Command::new("task")
+    let _output = std::process::Command::new("task")
       .arg("rc.confirmation=off")
       .arg("undo")
       .output()
       .unwrap();
-    let output = std::process::Command::new("task")
+    let _output = std::process::Command::new("task")
       .arg("rc.confirmation=off")
       .arg("undo")
       .output()
@@ -4791,7 +6454,7 @@ mod tests {
 
   async fn test_draw_calendar() {
     let mut expected = Buffer::with_lines(vec![
-      " Tasks   Projects   Calendar                [none]",
+      " Tasks   Projects   Calendar   Agenda   Boar[none]",
       "                                                  ",
       "                       2020                       ",
       "                                                  ",
@@ -4843,17 +6506,25 @@ mod tests {
         .set_style(Style::default().bg(Color::Reset).add_modifier(Modifier::UNDERLINED));
     }
 
+    for i in 12..=13 {
+      // Selected day
+      expected.get_mut(i, 6).set_style(Style::default().add_modifier(Modifier::REVERSED));
+    }
+
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
 
     app.task_report_next();
     app.context_next();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
-    app.calendar_year = 2020;
+    app.calendar.selected = chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
     app.mode = Mode::Calendar;
 
     app.update(true).await.unwrap();
 
+    app.wait_for_backend().await.unwrap();
+
     let backend = TestBackend::new(50, 15);
     let mut terminal = Terminal::new(backend).unwrap();
     terminal
@@ -4880,7 +6551,7 @@ mod tests {
       "│                                      │",
       "│    [: Previous view                  │",
       "╰──────────────────────────────────────╯",
-      "9% ─────────────────────────────────────",
+      "5% ─────────────────────────────────────",
     ]);
 
     for i in 1..=4 {
@@ -4888,8 +6559,6 @@ mod tests {
       expected.get_mut(i, 0).set_style(Style::default().add_modifier(Modifier::BOLD));
     }
     expected.get_mut(3, 11).set_style(Style::default().fg(Color::Gray));
-    expected.get_mut(4, 11).set_style(Style::default().fg(Color::Gray));
-    expected.get_mut(5, 11).set_style(Style::default().fg(Color::Gray));
 
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
 
@@ -4897,6 +6566,7 @@ mod tests {
     app.task_report_next();
     app.context_next();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     let backend = TestBackend::new(40, 12);
     let mut terminal = Terminal::new(backend).unwrap();
@@ -4911,6 +6581,7 @@ mod tests {
   }
 
   // #[test]
+  #[allow(dead_code)]
   async fn test_draw_context_menu() {
     let mut expected = Buffer::with_lines(vec![
       "╭Context───────────────────────────────────────────────────────────────────────╮",
@@ -4955,6 +6626,7 @@ mod tests {
     app.mode = Mode::Tasks(Action::ContextMenu);
     app.task_report_next();
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     let backend = TestBackend::new(80, 10);
     let mut terminal = Terminal::new(backend).unwrap();
@@ -4970,6 +6642,7 @@ mod tests {
   }
 
   // #[test]
+  #[allow(dead_code)]
   async fn test_graphemes() {
     dbg!("写作业".graphemes(true).count());
     dbg!(UnicodeWidthStr::width("写作业"));
@@ -4984,6 +6657,7 @@ mod tests {
       app.current_selection_uuid = None;
     }
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
     app.mode = Mode::Tasks(Action::Modify);
     match app.task_current() {
       Some(t) => {
@@ -4993,6 +6667,7 @@ mod tests {
       None => app.modify.update("", 0, &mut app.changes),
     }
     app.update(true).await.unwrap();
+    app.wait_for_backend().await.unwrap();
 
     dbg!(app.modify.as_str());
     dbg!(app.modify.as_str().len());
@@ -5003,6 +6678,7 @@ mod tests {
   }
 
   // #[test]
+  #[allow(dead_code)]
   async fn test_taskwarrior_tui_completion() {
     let mut app = TaskwarriorTui::new("next", false).await.unwrap();
     app.handle_input(KeyCode::Char('z')).await.unwrap();
@@ -1,13 +1,21 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use task_hookrs::{import::import, task::Task};
 use uuid::Uuid;
 use versions::Versioning;
 
-use super::TaskBackend;
+use super::{Context, TaskBackend, TaskChanges};
 
 /// CLI-based task backend that shells out to the task command
 pub struct CliBackend {
     task_version: Versioning,
+    /// Set by undo and sync, whose changes cannot be found through the `modified` dates
+    untracked_changes: AtomicBool,
 }
 
 impl CliBackend {
@@ -16,10 +24,43 @@ impl CliBackend {
         let task_version = get_taskwarrior_version()?;
         log::debug!("Detected TaskWarrior version: {}", task_version);
         
-        Ok(Self { task_version })
+        Ok(Self {
+            task_version,
+            untracked_changes: AtomicBool::new(false),
+        })
     }
 }
 
+/// `task <uuids>` without confirmation prompts, ready for a command such as `start`
+fn bulk_command(task_uuids: &[Uuid]) -> Command {
+    let mut cmd = Command::new("task");
+    cmd
+        .arg("rc.bulk=0")
+        .arg("rc.confirmation=off")
+        .arg("rc.dependency.confirmation=off")
+        .arg("rc.recurrence.confirmation=off");
+    for task_uuid in task_uuids {
+        cmd.arg(task_uuid.to_string());
+    }
+    cmd
+}
+
+/// Run `cmd`, turning a non-zero exit status into an error naming `command`
+fn run(mut cmd: Command, command: &str) -> Result<()> {
+    let output = cmd.output()?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("Task {} failed: {}", command, error));
+    }
+    Ok(())
+}
+
+/// Value of a configuration setting, as printed by `task _get rc.<name>`
+fn get_rc(name: &str) -> Result<String> {
+    let output = Command::new("task").arg("_get").arg(format!("rc.{}", name)).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string())
+}
+
 impl TaskBackend for CliBackend {
     fn export_tasks(&self, filter: &str, report: &str, context_filter: &str) -> Result<Vec<Task>> {
         let mut task = std::process::Command::new("task");
@@ -77,6 +118,29 @@ impl TaskBackend for CliBackend {
         }
     }
 
+    fn export_changes(&self, filter: &str, report: &str, context_filter: &str, since: DateTime<Utc>) -> Result<Option<TaskChanges>> {
+        if self.untracked_changes.swap(false, Ordering::SeqCst) {
+            return Ok(None);
+        }
+
+        let modified_filter = format!("modified.after:{}", since.format("%Y-%m-%dT%H:%M:%SZ"));
+        let modified = self.export_tasks("", "all", &modified_filter)?;
+        if modified.is_empty() {
+            return Ok(Some(TaskChanges::default()));
+        }
+        // The report's own filter, e.g. `limit:page`, is left to `task`
+        let matching = self
+            .export_tasks(filter, report, &format!("{} {}", context_filter, modified_filter))?
+            .iter()
+            .map(|task| *task.uuid())
+            .collect();
+        Ok(Some(TaskChanges {
+            modified,
+            matching,
+            limit: None,
+        }))
+    }
+
     fn add_task(&self, description: &str, args: &[&str]) -> Result<()> {
         let mut cmd = std::process::Command::new("task");
         cmd.arg("add").arg(description);
@@ -195,8 +259,194 @@ impl TaskBackend for CliBackend {
             return Err(anyhow::anyhow!("Task sync failed: {}", error));
         }
         
+        self.untracked_changes.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn undo_preview(&self) -> Result<Option<String>> {
+        // `task undo` prints the changes it would revert before asking for confirmation
+        let mut child = std::process::Command::new("task")
+            .arg("rc.color=off")
+            .arg("rc._forcecolor=off")
+            .arg("rc.confirmation=on")
+            .arg("undo")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(b"no\n")?;
+        }
+        let output = child.wait_with_output()?;
+        
+        let data = String::from_utf8_lossy(&output.stdout);
+        let preview = data
+            .lines()
+            .take_while(|line| !line.contains("(yes/no)"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let lowercase = format!("{} {}", preview, String::from_utf8_lossy(&output.stderr)).to_lowercase();
+        if preview.trim().is_empty() || lowercase.contains("no operations to undo") || lowercase.contains("no undo transactions") {
+            Ok(None)
+        } else {
+            Ok(Some(preview.trim_end().to_string()))
+        }
+    }
+
+    fn undo(&self) -> Result<Option<Uuid>> {
+        let output = std::process::Command::new("task").arg("rc.confirmation=off").arg("undo").output()?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("Task undo failed: {}", error));
+        }
+        self.untracked_changes.store(true, Ordering::SeqCst);
+        
+        let data = String::from_utf8_lossy(&output.stdout);
+        let re = Regex::new(r"(?P<task_uuid>[a-fA-F0-9]{8}-[a-fA-F0-9]{4}-[a-fA-F0-9]{4}-[a-fA-F0-9]{4}-[a-fA-F0-9]{12})").unwrap();
+        Ok(re.captures(&data).and_then(|caps| Uuid::parse_str(&caps["task_uuid"]).ok()))
+    }
+
+    fn start_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let mut cmd = bulk_command(task_uuids);
+        cmd.arg("start");
+        run(cmd, "start")
+    }
+
+    fn stop_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let mut cmd = bulk_command(task_uuids);
+        cmd.arg("stop");
+        run(cmd, "stop")
+    }
+
+    fn annotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        let mut cmd = bulk_command(task_uuids);
+        cmd.arg("annotate").arg(annotation);
+        run(cmd, "annotate")
+    }
+
+    fn denotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        let mut cmd = bulk_command(task_uuids);
+        cmd.arg("denotate").arg(annotation);
+        run(cmd, "denotate")
+    }
+
+    fn log_task(&self, description: &str, args: &[&str]) -> Result<()> {
+        let mut cmd = Command::new("task");
+        cmd.arg("log").arg(description).args(args);
+        run(cmd, "log")
+    }
+
+    fn duplicate_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let mut cmd = bulk_command(task_uuids);
+        cmd.arg("duplicate");
+        run(cmd, "duplicate")
+    }
+
+    fn edit_task(&self, task_uuid: Uuid) -> Result<()> {
+        // The editor needs the terminal, so stdio is inherited rather than captured
+        let status = Command::new("task").arg(task_uuid.to_string()).arg("edit").status()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("`task edit` for task `{}` failed with {}", task_uuid, status));
+        }
         Ok(())
     }
+
+    fn render_task_details(&self, task_uuids: &[Uuid], width: u16) -> Result<HashMap<Uuid, String>> {
+        // `task info` is slow, so render all tasks in parallel
+        let handles: Vec<_> = task_uuids
+            .iter()
+            .map(|&task_uuid| {
+                std::thread::spawn(move || {
+                    Command::new("task")
+                        .arg("rc.color=off")
+                        .arg("rc._forcecolor=off")
+                        .arg(format!("rc.defaultwidth={}", width))
+                        .arg(task_uuid.to_string())
+                        .output()
+                        .map(|output| (task_uuid, String::from_utf8_lossy(&output.stdout).to_string()))
+                })
+            })
+            .collect();
+
+        let mut details = HashMap::new();
+        for handle in handles {
+            let (task_uuid, data) = handle.join().map_err(|_| anyhow::anyhow!("Rendering task details panicked"))??;
+            details.insert(task_uuid, data);
+        }
+        Ok(details)
+    }
+
+    fn list_contexts(&self) -> Result<Vec<Context>> {
+        let output = Command::new("task").arg("context").output()?;
+        let data = String::from_utf8_lossy(&output.stdout);
+
+        let mut contexts: Vec<Context> = vec![];
+        for (i, line) in data.trim().split('\n').enumerate() {
+            if line.starts_with("  ") && line.trim().starts_with("write") {
+                if let Some(c) = contexts.last_mut() {
+                    c.write_filter = line.trim().trim_start_matches("write").trim().to_string();
+                }
+                continue;
+            }
+            if line.starts_with("  ") && !(line.trim().ends_with("yes") || line.trim().ends_with("no")) {
+                let definition = line.trim();
+                if let Some(c) = contexts.last_mut() {
+                    c.read_filter = format!("{} {}", c.read_filter, definition);
+                }
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line == "Use 'task context none' to unset the current context." {
+                continue;
+            }
+            if i == 0 || i == 1 {
+                continue;
+            }
+            let mut s = line.split_whitespace();
+            let name = s.next().unwrap_or_default();
+            let typ = s.next().unwrap_or_default();
+            let active = s.last().unwrap_or_default();
+            let definition = line.replacen(name, "", 1);
+            let definition = definition.replacen(typ, "", 1);
+            let definition = definition.strip_suffix(active).unwrap_or_default();
+            contexts.push(Context {
+                name: name.to_string(),
+                read_filter: definition.trim().to_string(),
+                write_filter: String::new(),
+                active: active == "yes",
+            });
+        }
+        Ok(contexts)
+    }
+
+    fn current_context(&self) -> Result<Option<Context>> {
+        let name = get_rc("context")?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        // support new format for context
+        let mut read_filter = get_rc(&format!("context.{}.read", name))?;
+        let mut write_filter = get_rc(&format!("context.{}.write", name))?;
+
+        // If new format is not used, check if old format is used
+        if read_filter.is_empty() {
+            read_filter = get_rc(&format!("context.{}", name))?;
+            write_filter = read_filter.clone();
+        }
+        Ok(Some(Context {
+            name,
+            read_filter,
+            write_filter,
+            active: true,
+        }))
+    }
+
+    fn set_context(&self, name: &str) -> Result<()> {
+        let mut cmd = Command::new("task");
+        cmd.arg("context").arg(name);
+        run(cmd, "context")
+    }
 }
 
 fn get_taskwarrior_version() -> Result<Versioning> {
@@ -0,0 +1,1485 @@
+//! Taskwarrior filter language for backends that evaluate filters in-process
+//!
+//! The grammar follows `man task`: terms are joined by an implicit `and`, and
+//! `and`, `or`, `xor`, `not`/`!` and parentheses are supported with Taskwarrior's
+//! precedence (`not` > `and` > `xor` > `or`). A term is one of
+//!
+//! * `+tag` / `-tag`, including virtual tags such as `+OVERDUE` or `-WAITING`
+//! * `attribute[.modifier]:value`, e.g. `project:Home` or `due.before:eow+1d`
+//! * `attribute <op> value`, e.g. `urgency > 5` or `description ~ ^fix`
+//! * an ID list (`1,3-5`) or a UUID prefix
+//! * `/regex/` or a bare word, matched against description and annotations
+//!
+//! `limit:` terms are not part of the expression and are exposed via [`Filter::limit`].
+
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use lazy_static::lazy_static;
+use regex::Regex;
+use task_hookrs::{status::TaskStatus, task::Task, uda::UDAValue};
+use uuid::Uuid;
+
+/// Number of tasks returned for `limit:page`
+const PAGE_SIZE: usize = 25;
+
+/// Tags Taskwarrior computes from other attributes instead of storing them
+const VIRTUAL_TAGS: &[&str] = &[
+    "ACTIVE",
+    "ANNOTATED",
+    "BLOCKED",
+    "BLOCKING",
+    "CHILD",
+    "COMPLETED",
+    "DELETED",
+    "DUE",
+    "DUETODAY",
+    "INSTANCE",
+    "MONTH",
+    "ORPHAN",
+    "OVERDUE",
+    "PARENT",
+    "PENDING",
+    "PRIORITY",
+    "PROJECT",
+    "QUARTER",
+    "READY",
+    "SCHEDULED",
+    "TAGGED",
+    "TEMPLATE",
+    "TODAY",
+    "TOMORROW",
+    "UDA",
+    "UNBLOCKED",
+    "UNTIL",
+    "WAITING",
+    "WEEK",
+    "YEAR",
+    "YESTERDAY",
+];
+
+/// Core attribute names, used to resolve abbreviations such as `pro:` or `desc.has:`
+const ATTRIBUTES: &[(&str, Attr)] = &[
+    ("depends", Attr::Depends),
+    ("description", Attr::Description),
+    ("due", Attr::Date(DateAttr::Due)),
+    ("end", Attr::Date(DateAttr::End)),
+    ("entry", Attr::Date(DateAttr::Entry)),
+    ("id", Attr::Id),
+    ("imask", Attr::Imask),
+    ("mask", Attr::Mask),
+    ("modified", Attr::Date(DateAttr::Modified)),
+    ("parent", Attr::Parent),
+    ("priority", Attr::Priority),
+    ("project", Attr::Project),
+    ("recur", Attr::Recur),
+    ("scheduled", Attr::Date(DateAttr::Scheduled)),
+    ("start", Attr::Date(DateAttr::Start)),
+    ("status", Attr::Status),
+    ("tags", Attr::Tags),
+    ("until", Attr::Date(DateAttr::Until)),
+    ("urgency", Attr::Urgency),
+    ("uuid", Attr::Uuid),
+    ("wait", Attr::Date(DateAttr::Wait)),
+];
+
+/// Attribute modifiers and their aliases
+const MODIFIERS: &[(&str, Cmp)] = &[
+    ("above", Cmp::Gt),
+    ("after", Cmp::Gt),
+    ("any", Cmp::Any),
+    ("before", Cmp::Lt),
+    ("below", Cmp::Lt),
+    ("by", Cmp::Le),
+    ("contains", Cmp::Has),
+    ("endswith", Cmp::Ends),
+    ("equals", Cmp::Exact),
+    ("has", Cmp::Has),
+    ("hasnt", Cmp::Hasnt),
+    ("is", Cmp::Exact),
+    ("isnt", Cmp::NotExact),
+    ("left", Cmp::Starts),
+    ("none", Cmp::None),
+    ("not", Cmp::NotPartial),
+    ("noword", Cmp::NoWord),
+    ("over", Cmp::Gt),
+    ("right", Cmp::Ends),
+    ("startswith", Cmp::Starts),
+    ("under", Cmp::Lt),
+    ("word", Cmp::Word),
+];
+
+lazy_static! {
+    static ref ID_LIST: Regex = Regex::new(r"^\d+(-\d+)?(,\d+(-\d+)?)*$").unwrap();
+    static ref UUID_PREFIX: Regex = Regex::new(r"^[0-9a-fA-F]{8}(-[0-9a-fA-F]{1,4}){0,3}(-[0-9a-fA-F]{1,12})?$").unwrap();
+    static ref ATTRIBUTE_NAME: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*(\.[A-Za-z]+)?$").unwrap();
+    static ref DATE_EXPR: Regex = Regex::new(r"^(.+?)((?:[+-]\d*[A-Za-z]+)*)$").unwrap();
+    static ref DATE_OFFSET: Regex = Regex::new(r"([+-])(\d*)([A-Za-z]+)").unwrap();
+    static ref BOUNDARY: Regex = Regex::new(r"^(so|eo)([cpn]?)(d|ww|w|m|q|y)$").unwrap();
+    static ref ORDINAL: Regex = Regex::new(r"^(\d{1,2})(st|nd|rd|th)$").unwrap();
+}
+
+/// A parsed Taskwarrior filter
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    expr: Option<Expr>,
+    limit: Option<usize>,
+}
+
+impl Filter {
+    /// Parse a filter string such as `status:pending -WAITING (project:Home or +work)`
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut limit = None;
+        let mut tokens = Vec::new();
+        for token in tokenize(input)? {
+            match &token {
+                Token::Word(word) if word.starts_with("limit:") => {
+                    let value = &word["limit:".len()..];
+                    limit = Some(if value == "page" {
+                        PAGE_SIZE
+                    } else {
+                        value.parse().map_err(|_| anyhow!("Invalid limit '{}'", value))?
+                    });
+                }
+                // Configuration overrides are not filter terms
+                Token::Word(word) if word.starts_with("rc.") || word.starts_with("rc:") => {}
+                _ => tokens.push(token),
+            }
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse()?;
+        Ok(Self { expr, limit })
+    }
+
+    /// Combine two filters so that a task has to match both, e.g. a report filter and a context filter
+    pub fn and(self, other: Filter) -> Filter {
+        let expr = match (self.expr, other.expr) {
+            (Some(lhs), Some(rhs)) => Some(Expr::And(Box::new(lhs), Box::new(rhs))),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+        Filter {
+            expr,
+            limit: self.limit.or(other.limit),
+        }
+    }
+
+    /// Maximum number of tasks requested with `limit:`
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn matches(&self, task: &Task, context: &FilterContext) -> bool {
+        self.expr.as_ref().is_none_or(|expr| expr.eval(task, context))
+    }
+
+    /// Keep the tasks matching this filter, honouring `limit:`
+    pub fn apply(&self, tasks: Vec<Task>, context: &FilterContext) -> Vec<Task> {
+        let matching = tasks.into_iter().filter(|task| self.matches(task, context));
+        match self.limit {
+            Some(limit) => matching.take(limit).collect(),
+            None => matching.collect(),
+        }
+    }
+}
+
+/// Information about the whole task list and the current time needed to evaluate a filter
+#[derive(Debug, Clone)]
+pub struct FilterContext {
+    now: NaiveDateTime,
+    offset: FixedOffset,
+    weekstart_monday: bool,
+    due_days: i64,
+    pending: HashSet<Uuid>,
+    blocking: HashSet<Uuid>,
+}
+
+impl FilterContext {
+    pub fn new(tasks: &[Task]) -> Self {
+        let pending: HashSet<Uuid> = tasks.iter().filter(|task| is_pending(task)).map(|task| *task.uuid()).collect();
+        let blocking = tasks
+            .iter()
+            .filter(|task| is_pending(task))
+            .filter_map(|task| task.depends())
+            .flatten()
+            .filter(|uuid| pending.contains(uuid))
+            .copied()
+            .collect();
+        Self::from_dependencies(pending, blocking)
+    }
+
+    /// A context for a task list summarised by its pending tasks and the pending tasks that block another pending task
+    ///
+    /// This evaluates `+BLOCKED` and `+BLOCKING` without converting every task, e.g. when only a few changed tasks are filtered.
+    pub fn from_dependencies(pending: HashSet<Uuid>, blocking: HashSet<Uuid>) -> Self {
+        let now = Local::now().fixed_offset();
+        Self {
+            now: now.naive_local(),
+            offset: *now.offset(),
+            weekstart_monday: false,
+            due_days: 7,
+            pending,
+            blocking,
+        }
+    }
+
+    /// Evaluate relative dates against `now` instead of the current time
+    pub fn with_now(mut self, now: DateTime<FixedOffset>) -> Self {
+        self.now = now.naive_local();
+        self.offset = *now.offset();
+        self
+    }
+
+    /// Equivalent of `rc.weekstart=monday`
+    pub fn weekstart_monday(mut self, weekstart_monday: bool) -> Self {
+        self.weekstart_monday = weekstart_monday;
+        self
+    }
+
+    /// Equivalent of `rc.due`, the number of days in which a task counts as `+DUE`
+    pub fn due_days(mut self, due_days: i64) -> Self {
+        self.due_days = due_days;
+        self
+    }
+
+    fn to_local(&self, date: &NaiveDateTime) -> NaiveDateTime {
+        self.offset.from_utc_datetime(date).naive_local()
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.now.date()
+    }
+}
+
+/// Resolve a date as written in a modification (`due:eow`, `wait:today+3d`, `2024-05-01`) to UTC
+pub fn parse_date(input: &str, context: &FilterContext) -> Result<DateTime<Utc>> {
+    let local = DateExpr::parse(input)?.resolve(context);
+    let date = context
+        .offset
+        .from_local_datetime(&local)
+        .earliest()
+        .ok_or_else(|| anyhow!("'{}' is not a valid local time", input))?;
+    Ok(date.with_timezone(&Utc))
+}
+
+fn is_pending(task: &Task) -> bool {
+    matches!(task.status(), TaskStatus::Pending | TaskStatus::Waiting)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Xor,
+    Not,
+    Op(Cmp),
+    Word(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' { Token::LParen } else { Token::RParen });
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut quote = None;
+        let mut in_regex = false;
+        let starts_quoted = c == '\'' || c == '"';
+        while let Some(&c) = chars.peek() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => word.push(c),
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if c == '\\' => {
+                    chars.next();
+                    match chars.peek() {
+                        Some(&escaped) => word.push(escaped),
+                        None => word.push(c),
+                    }
+                }
+                None if c == '/' && (word.is_empty() || in_regex) => {
+                    in_regex = word.is_empty();
+                    word.push(c);
+                }
+                None if in_regex => word.push(c),
+                None if c.is_whitespace() || c == '(' || c == ')' => break,
+                None => word.push(c),
+            }
+            chars.next();
+        }
+        if let Some(q) = quote {
+            return Err(anyhow!("Unterminated {} quote in filter", q));
+        }
+        // `task` parses a quoted expression such as `'(project:Home or project:Work)'` as if it
+        // was not quoted
+        if starts_quoted && word.starts_with('(') && word.ends_with(')') {
+            tokens.extend(tokenize(&word)?);
+            continue;
+        }
+
+        match word.as_str() {
+            "and" => tokens.push(Token::And),
+            "or" => tokens.push(Token::Or),
+            "xor" => tokens.push(Token::Xor),
+            "not" | "!" => tokens.push(Token::Not),
+            _ => {
+                if let Some(op) = Cmp::from_operator(&word) {
+                    tokens.push(Token::Op(op));
+                } else if word.len() > 1 && word.starts_with('!') {
+                    tokens.push(Token::Not);
+                    tokens.push(Token::Word(word[1..].to_string()));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<Option<Expr>> {
+        if self.tokens.is_empty() {
+            return Ok(None);
+        }
+        let expr = self.parse_or()?;
+        match self.peek() {
+            None => Ok(Some(expr)),
+            Some(token) => Err(anyhow!("Unexpected {} in filter", token.describe())),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_xor()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.parse_xor()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_xor(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Xor) {
+            self.next();
+            lhs = Expr::Xor(Box::new(lhs), Box::new(self.parse_and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                }
+                // Adjacent terms are joined by an implicit `and`
+                Some(Token::LParen) | Some(Token::Not) | Some(Token::Word(_)) => {}
+                _ => break,
+            }
+            lhs = Expr::And(Box::new(lhs), Box::new(self.parse_unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(anyhow!("Missing closing parenthesis in filter")),
+                }
+            }
+            Some(Token::Word(word)) => {
+                if let Some(Token::Op(cmp)) = self.peek().cloned() {
+                    self.next();
+                    let value = match self.next() {
+                        Some(Token::Word(value)) => value,
+                        _ => return Err(anyhow!("Missing value after operator in filter")),
+                    };
+                    Ok(Expr::Term(Term::comparison(&word, cmp, &value)?))
+                } else {
+                    Ok(Expr::Term(Term::from_word(&word)?))
+                }
+            }
+            Some(token) => Err(anyhow!("Unexpected {} in filter", token.describe())),
+            None => Err(anyhow!("Unexpected end of filter")),
+        }
+    }
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::And => "'and'".to_string(),
+            Token::Or => "'or'".to_string(),
+            Token::Xor => "'xor'".to_string(),
+            Token::Not => "'not'".to_string(),
+            Token::Op(cmp) => format!("operator {:?}", cmp),
+            Token::Word(word) => format!("'{}'", word),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+impl Expr {
+    fn eval(&self, task: &Task, context: &FilterContext) -> bool {
+        match self {
+            Expr::And(lhs, rhs) => lhs.eval(task, context) && rhs.eval(task, context),
+            Expr::Or(lhs, rhs) => lhs.eval(task, context) || rhs.eval(task, context),
+            Expr::Xor(lhs, rhs) => lhs.eval(task, context) != rhs.eval(task, context),
+            Expr::Not(expr) => !expr.eval(task, context),
+            Expr::Term(term) => term.eval(task, context),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateAttr {
+    Due,
+    End,
+    Entry,
+    Modified,
+    Scheduled,
+    Start,
+    Until,
+    Wait,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Attr {
+    Depends,
+    Description,
+    Date(DateAttr),
+    Id,
+    Imask,
+    Mask,
+    Parent,
+    Priority,
+    Project,
+    Recur,
+    Status,
+    Tags,
+    Urgency,
+    Uuid,
+    Uda(String),
+}
+
+impl Attr {
+    fn resolve(name: &str) -> Attr {
+        match resolve_abbreviation(name, ATTRIBUTES) {
+            Some(attr) => attr,
+            None if name == "tag" => Attr::Tags,
+            None => Attr::Uda(name.to_string()),
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            Attr::Date(_) => Kind::Date,
+            Attr::Id | Attr::Imask | Attr::Urgency => Kind::Number,
+            Attr::Depends | Attr::Tags => Kind::List,
+            Attr::Uda(_) => Kind::Any,
+            _ => Kind::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Number,
+    Date,
+    List,
+    Any,
+}
+
+/// Find the unique entry `name` abbreviates (at least two characters), preferring exact matches
+pub(super) fn resolve_abbreviation<T: Clone>(name: &str, candidates: &[(&str, T)]) -> Option<T> {
+    if let Some((_, value)) = candidates.iter().find(|(candidate, _)| *candidate == name) {
+        return Some(value.clone());
+    }
+    if name.len() < 2 {
+        return None;
+    }
+    let mut matching = candidates.iter().filter(|(candidate, _)| candidate.starts_with(name));
+    match (matching.next(), matching.next()) {
+        (Some((_, value)), None) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// How an attribute is compared with a value, from either a modifier or an operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    /// `attr:value` or `=`; left match for strings, same day for dates
+    Partial,
+    /// `.is` or `==`
+    Exact,
+    /// `.not` or `!=`
+    NotPartial,
+    /// `.isnt` or `!==`
+    NotExact,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Has,
+    Hasnt,
+    Starts,
+    Ends,
+    Word,
+    NoWord,
+    Match,
+    NoMatch,
+    None,
+    Any,
+}
+
+impl Cmp {
+    fn from_operator(operator: &str) -> Option<Cmp> {
+        let cmp = match operator {
+            "=" => Cmp::Partial,
+            "==" => Cmp::Exact,
+            "!=" => Cmp::NotPartial,
+            "!==" => Cmp::NotExact,
+            "<" => Cmp::Lt,
+            "<=" => Cmp::Le,
+            ">" => Cmp::Gt,
+            ">=" => Cmp::Ge,
+            "~" => Cmp::Match,
+            "!~" => Cmp::NoMatch,
+            _ => return None,
+        };
+        Some(cmp)
+    }
+
+    /// Whether a task without the attribute satisfies the comparison
+    fn matches_missing(self) -> bool {
+        matches!(self, Cmp::NotPartial | Cmp::NotExact | Cmp::Hasnt | Cmp::NoWord | Cmp::NoMatch | Cmp::None)
+    }
+
+    fn supports(self, kind: Kind) -> bool {
+        match kind {
+            Kind::Number | Kind::Date => matches!(
+                self,
+                Cmp::Partial | Cmp::Exact | Cmp::NotPartial | Cmp::NotExact | Cmp::Lt | Cmp::Le | Cmp::Gt | Cmp::Ge | Cmp::None | Cmp::Any
+            ),
+            Kind::List => !matches!(self, Cmp::Lt | Cmp::Le | Cmp::Gt | Cmp::Ge | Cmp::Starts | Cmp::Ends),
+            Kind::Text | Kind::Any => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Empty,
+    Text(String),
+    Number(f64),
+    Date(DateExpr),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Tag { name: String, present: bool },
+    Attribute { attr: Attr, cmp: Cmp, value: Value },
+    Ids(Vec<(u64, u64)>),
+    Uuid(String),
+    Pattern(String),
+    Regex(Regex),
+}
+
+impl Term {
+    fn from_word(word: &str) -> Result<Term> {
+        if word.len() > 1 && (word.starts_with('+') || word.starts_with('-')) && !word.contains(':') {
+            return Ok(Term::Tag {
+                name: word[1..].to_string(),
+                present: word.starts_with('+'),
+            });
+        }
+        if word.len() > 2 && word.starts_with('/') && word.ends_with('/') {
+            let pattern = &word[1..word.len() - 1];
+            return Ok(Term::Regex(Regex::new(pattern).map_err(|e| anyhow!("Invalid regex '{}': {}", pattern, e))?));
+        }
+        if ID_LIST.is_match(word) {
+            return Term::ids(word);
+        }
+        if UUID_PREFIX.is_match(word) {
+            return Ok(Term::Uuid(word.to_lowercase()));
+        }
+        if let Some((name, value)) = word.split_once(':') {
+            if ATTRIBUTE_NAME.is_match(name) && !value.starts_with("//") {
+                let (name, modifier) = match name.split_once('.') {
+                    Some((name, modifier)) => {
+                        let cmp = resolve_abbreviation(modifier, MODIFIERS).ok_or_else(|| anyhow!("Unknown modifier '{}' in '{}'", modifier, word))?;
+                        (name, cmp)
+                    }
+                    None => (name, Cmp::Partial),
+                };
+                return Term::attribute(Attr::resolve(name), modifier, value);
+            }
+        }
+        Ok(Term::Pattern(word.to_string()))
+    }
+
+    fn comparison(name: &str, cmp: Cmp, value: &str) -> Result<Term> {
+        if name.contains('.') {
+            return Err(anyhow!("Modifiers cannot be combined with operators in '{}'", name));
+        }
+        Term::attribute(Attr::resolve(name), cmp, value)
+    }
+
+    fn attribute(attr: Attr, cmp: Cmp, value: &str) -> Result<Term> {
+        let kind = attr.kind();
+        if !cmp.supports(kind) {
+            return Err(anyhow!("{:?} comparison is not supported for attribute {:?}", cmp, attr));
+        }
+        let value = if value.is_empty() || matches!(cmp, Cmp::None | Cmp::Any) {
+            Value::Empty
+        } else if matches!(cmp, Cmp::Match | Cmp::NoMatch) {
+            Value::Regex(Regex::new(value).map_err(|e| anyhow!("Invalid regex '{}': {}", value, e))?)
+        } else {
+            match kind {
+                Kind::Date => Value::Date(DateExpr::parse(value)?),
+                Kind::Number => Value::Number(value.parse().map_err(|_| anyhow!("'{}' is not a valid number", value))?),
+                Kind::Text | Kind::List | Kind::Any => Value::Text(value.to_string()),
+            }
+        };
+        Ok(Term::Attribute { attr, cmp, value })
+    }
+
+    fn ids(word: &str) -> Result<Term> {
+        let mut ranges = Vec::new();
+        for part in word.split(',') {
+            let range = match part.split_once('-') {
+                Some((start, end)) => (start.parse()?, end.parse()?),
+                None => {
+                    let id = part.parse()?;
+                    (id, id)
+                }
+            };
+            ranges.push(range);
+        }
+        Ok(Term::Ids(ranges))
+    }
+
+    fn eval(&self, task: &Task, context: &FilterContext) -> bool {
+        match self {
+            Term::Tag { name, present } => has_tag(task, name, context) == *present,
+            Term::Attribute { attr, cmp, value } => eval_attribute(task, attr, *cmp, value, context),
+            Term::Ids(ranges) => task.id().is_some_and(|id| ranges.iter().any(|(start, end)| (*start..=*end).contains(&id))),
+            Term::Uuid(prefix) => task.uuid().to_string().starts_with(prefix.as_str()),
+            Term::Pattern(pattern) => searchable_text(task).any(|text| text.contains(pattern.as_str())),
+            Term::Regex(regex) => searchable_text(task).any(|text| regex.is_match(text)),
+        }
+    }
+}
+
+/// Description and annotations, which bare words and `/regex/` terms are matched against
+fn searchable_text(task: &Task) -> impl Iterator<Item = &str> {
+    std::iter::once(task.description().as_str()).chain(task.annotations().into_iter().flatten().map(|a| a.description().as_str()))
+}
+
+fn has_tag(task: &Task, name: &str, context: &FilterContext) -> bool {
+    if VIRTUAL_TAGS.contains(&name) {
+        return has_virtual_tag(task, name, context);
+    }
+    task.tags().is_some_and(|tags| tags.iter().any(|tag| tag == name))
+}
+
+fn has_virtual_tag(task: &Task, name: &str, context: &FilterContext) -> bool {
+    let open = is_pending(task);
+    let due = task.due().map(|due| context.to_local(due));
+    let due_in = |start: NaiveDateTime, end: NaiveDateTime| open && due.is_some_and(|due| due >= start && due <= end);
+    let period = |unit: Period| {
+        let start = unit.start(context.today(), context.weekstart_monday);
+        due_in(start.and_time(NaiveTime::MIN), unit.end(start))
+    };
+
+    match name {
+        "ACTIVE" => open && task.start().is_some(),
+        "ANNOTATED" => task.annotations().is_some_and(|a| !a.is_empty()),
+        "BLOCKED" => open && is_blocked(task, context),
+        "UNBLOCKED" => !is_blocked(task, context),
+        "BLOCKING" => context.blocking.contains(task.uuid()),
+        "CHILD" | "INSTANCE" => task.parent().is_some(),
+        "PARENT" | "TEMPLATE" => matches!(task.status(), TaskStatus::Recurring),
+        "COMPLETED" => matches!(task.status(), TaskStatus::Completed),
+        "DELETED" => matches!(task.status(), TaskStatus::Deleted),
+        "PENDING" => open,
+        "WAITING" => is_waiting(task, context),
+        "READY" => {
+            open && !is_waiting(task, context)
+                && !is_blocked(task, context)
+                && task.scheduled().is_none_or(|s| context.to_local(s) <= context.now)
+        }
+        "DUE" => {
+            let today = context.today().and_time(NaiveTime::MIN);
+            due_in(today, today + Duration::days(context.due_days) - Duration::seconds(1))
+        }
+        "DUETODAY" | "TODAY" => period(Period::Day),
+        "TOMORROW" => {
+            let start = (context.today() + Duration::days(1)).and_time(NaiveTime::MIN);
+            due_in(start, Period::Day.end(start.date()))
+        }
+        "YESTERDAY" => {
+            let start = (context.today() - Duration::days(1)).and_time(NaiveTime::MIN);
+            due_in(start, Period::Day.end(start.date()))
+        }
+        "OVERDUE" => open && due.is_some_and(|due| due < context.now),
+        "WEEK" => period(Period::Week),
+        "MONTH" => period(Period::Month),
+        "QUARTER" => period(Period::Quarter),
+        "YEAR" => period(Period::Year),
+        "PRIORITY" => task.priority().is_some(),
+        "PROJECT" => task.project().is_some(),
+        "SCHEDULED" => task.scheduled().is_some(),
+        "TAGGED" => task.tags().is_some_and(|tags| !tags.is_empty()),
+        "UDA" => !task.uda().is_empty(),
+        "UNTIL" => task.until().is_some(),
+        _ => false,
+    }
+}
+
+fn is_blocked(task: &Task, context: &FilterContext) -> bool {
+    task.depends().is_some_and(|depends| depends.iter().any(|uuid| context.pending.contains(uuid)))
+}
+
+fn is_waiting(task: &Task, context: &FilterContext) -> bool {
+    match task.status() {
+        TaskStatus::Waiting => true,
+        TaskStatus::Pending => task.wait().is_some_and(|wait| context.to_local(wait) > context.now),
+        _ => false,
+    }
+}
+
+/// The value of an attribute on a task, in a form that can be compared
+enum Field {
+    Missing,
+    Text(String),
+    Number(f64),
+    Date(NaiveDateTime),
+    List(Vec<String>),
+}
+
+fn field(task: &Task, attr: &Attr, context: &FilterContext) -> Field {
+    let text = |value: Option<&String>| value.map_or(Field::Missing, |v| Field::Text(v.clone()));
+    let date = |value: Option<&task_hookrs::date::Date>| value.map_or(Field::Missing, |d| Field::Date(context.to_local(d)));
+
+    match attr {
+        Attr::Depends => Field::List(task.depends().into_iter().flatten().map(ToString::to_string).collect()),
+        Attr::Description => Field::Text(task.description().clone()),
+        Attr::Date(DateAttr::Due) => date(task.due()),
+        Attr::Date(DateAttr::End) => date(task.end()),
+        Attr::Date(DateAttr::Entry) => date(Some(task.entry())),
+        Attr::Date(DateAttr::Modified) => date(task.modified()),
+        Attr::Date(DateAttr::Scheduled) => date(task.scheduled()),
+        Attr::Date(DateAttr::Start) => date(task.start()),
+        Attr::Date(DateAttr::Until) => date(task.until()),
+        Attr::Date(DateAttr::Wait) => date(task.wait()),
+        Attr::Id => task.id().map_or(Field::Missing, |id| Field::Number(id as f64)),
+        Attr::Imask => task.imask().map_or(Field::Missing, |imask| Field::Number(*imask)),
+        Attr::Mask => text(task.mask()),
+        Attr::Parent => task.parent().map_or(Field::Missing, |uuid| Field::Text(uuid.to_string())),
+        Attr::Priority => text(task.priority()),
+        Attr::Project => text(task.project()),
+        Attr::Recur => text(task.recur()),
+        Attr::Status => Field::Text(status_name(task.status()).to_string()),
+        Attr::Tags => Field::List(task.tags().cloned().unwrap_or_default()),
+        Attr::Urgency => task.urgency().map_or(Field::Missing, |urgency| Field::Number(*urgency)),
+        Attr::Uuid => Field::Text(task.uuid().to_string()),
+        Attr::Uda(name) => match task.uda().get(name) {
+            None => Field::Missing,
+            Some(UDAValue::U64(n)) => Field::Number(*n as f64),
+            Some(UDAValue::F64(n)) => Field::Number(*n),
+            Some(UDAValue::Str(s)) if s.is_empty() => Field::Missing,
+            Some(UDAValue::Str(s)) => match NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ") {
+                Ok(d) => Field::Date(context.to_local(&d)),
+                Err(_) => Field::Text(s.clone()),
+            },
+        },
+    }
+}
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::Deleted => "deleted",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Waiting => "waiting",
+        TaskStatus::Recurring => "recurring",
+    }
+}
+
+/// Rank of a priority for ordering comparisons, with no priority ranking lowest
+fn priority_rank(priority: &str) -> u8 {
+    match priority {
+        "H" => 3,
+        "M" => 2,
+        "L" => 1,
+        _ => 0,
+    }
+}
+
+fn eval_attribute(task: &Task, attr: &Attr, cmp: Cmp, value: &Value, context: &FilterContext) -> bool {
+    // Waiting tasks are stored as pending in Taskwarrior 2.6+, but `status:waiting` still finds them
+    if let (Attr::Status, Value::Text(status)) = (attr, value) {
+        let status = status.to_lowercase();
+        let matches = match status.as_str() {
+            "pending" => is_pending(task),
+            "waiting" => is_waiting(task, context),
+            _ => status_name(task.status()).starts_with(status.as_str()),
+        };
+        match cmp {
+            Cmp::Partial | Cmp::Exact => return matches,
+            Cmp::NotPartial | Cmp::NotExact => return !matches,
+            _ => {}
+        }
+    }
+    if let (Attr::Priority, Value::Text(priority), Cmp::Lt | Cmp::Le | Cmp::Gt | Cmp::Ge) = (attr, value, cmp) {
+        let rank = priority_rank(task.priority().map_or("", String::as_str));
+        return compare_ordering(rank.cmp(&priority_rank(priority)), cmp);
+    }
+
+    let field = match field(task, attr, context) {
+        Field::List(items) if items.is_empty() => Field::Missing,
+        field => field,
+    };
+    // `attr:` with an empty value is the same as `attr.none:`
+    if let Value::Empty = value {
+        let missing = matches!(field, Field::Missing);
+        return match cmp {
+            Cmp::None | Cmp::Partial | Cmp::Exact => missing,
+            _ => !missing,
+        };
+    }
+    match (field, value) {
+        (Field::Missing, _) => cmp.matches_missing(),
+        (Field::List(items), Value::Regex(regex)) => (cmp == Cmp::Match) == items.iter().any(|item| regex.is_match(item)),
+        (Field::List(items), Value::Text(value)) => {
+            let found = items.iter().any(|item| item == value || (matches!(attr, Attr::Depends) && item.starts_with(value.as_str())));
+            match cmp {
+                Cmp::Partial | Cmp::Exact | Cmp::Has | Cmp::Word => found,
+                _ => !found,
+            }
+        }
+        (Field::Number(n), Value::Number(v)) => compare_numbers(n, *v, cmp),
+        (Field::Number(n), Value::Text(v)) => match v.parse::<f64>() {
+            Ok(v) => compare_numbers(n, v, cmp),
+            Err(_) => compare_text(&n.to_string(), v, cmp),
+        },
+        (Field::Date(d), Value::Date(expr)) => compare_dates(d, expr.resolve(context), cmp),
+        (Field::Date(d), Value::Text(v)) => match DateExpr::parse(v) {
+            Ok(expr) => compare_dates(d, expr.resolve(context), cmp),
+            Err(_) => false,
+        },
+        (Field::Text(text), Value::Text(v)) => compare_text(&text, v, cmp),
+        (Field::Text(text), Value::Regex(regex)) => (cmp == Cmp::Match) == regex.is_match(&text),
+        (Field::Number(n), Value::Regex(regex)) => (cmp == Cmp::Match) == regex.is_match(&n.to_string()),
+        _ => false,
+    }
+}
+
+fn compare_ordering(ordering: std::cmp::Ordering, cmp: Cmp) -> bool {
+    use std::cmp::Ordering::*;
+    match cmp {
+        Cmp::Partial | Cmp::Exact => ordering == Equal,
+        Cmp::NotPartial | Cmp::NotExact => ordering != Equal,
+        Cmp::Lt => ordering == Less,
+        Cmp::Le => ordering != Greater,
+        Cmp::Gt => ordering == Greater,
+        Cmp::Ge => ordering != Less,
+        Cmp::Any => true,
+        _ => false,
+    }
+}
+
+fn compare_numbers(field: f64, value: f64, cmp: Cmp) -> bool {
+    field.partial_cmp(&value).is_some_and(|ordering| compare_ordering(ordering, cmp))
+}
+
+fn compare_dates(field: NaiveDateTime, value: NaiveDateTime, cmp: Cmp) -> bool {
+    match cmp {
+        Cmp::Partial => field.date() == value.date(),
+        Cmp::NotPartial => field.date() != value.date(),
+        _ => compare_ordering(field.cmp(&value), cmp),
+    }
+}
+
+fn compare_text(field: &str, value: &str, cmp: Cmp) -> bool {
+    match cmp {
+        Cmp::Partial => field.starts_with(value),
+        Cmp::NotPartial => !field.starts_with(value),
+        Cmp::Has => field.contains(value),
+        Cmp::Hasnt => !field.contains(value),
+        Cmp::Starts => field.starts_with(value),
+        Cmp::Ends => field.ends_with(value),
+        Cmp::Word | Cmp::NoWord => {
+            let found = field.split(|c: char| !c.is_alphanumeric()).any(|word| word == value);
+            found == (cmp == Cmp::Word)
+        }
+        // Numeric UDAs are often stored as strings
+        Cmp::Lt | Cmp::Le | Cmp::Gt | Cmp::Ge => match (field.parse::<f64>(), value.parse::<f64>()) {
+            (Ok(field), Ok(value)) => compare_numbers(field, value, cmp),
+            _ => compare_ordering(field.cmp(value), cmp),
+        },
+        _ => compare_ordering(field.cmp(value), cmp),
+    }
+}
+
+/// A calendar period used by named dates such as `sow` or `eoq`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Day,
+    Week,
+    WorkWeek,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    /// First day of the period containing `date`
+    fn start(self, date: NaiveDate, weekstart_monday: bool) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week if weekstart_monday => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Week => date - Duration::days(date.weekday().num_days_from_sunday() as i64),
+            Period::WorkWeek => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap(),
+            Period::Quarter => NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1).unwrap(),
+            Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+
+    /// Move a period start by `count` periods
+    fn shift(self, start: NaiveDate, count: i32) -> NaiveDate {
+        let months = |n: i32| {
+            let months = Months::new(n.unsigned_abs());
+            if n >= 0 {
+                start.checked_add_months(months)
+            } else {
+                start.checked_sub_months(months)
+            }
+            .unwrap_or(start)
+        };
+        match self {
+            Period::Day => start + Duration::days(count as i64),
+            Period::Week | Period::WorkWeek => start + Duration::weeks(count as i64),
+            Period::Month => months(count),
+            Period::Quarter => months(count * 3),
+            Period::Year => months(count * 12),
+        }
+    }
+
+    /// Last second of the period starting at `start`
+    fn end(self, start: NaiveDate) -> NaiveDateTime {
+        let next = match self {
+            Period::WorkWeek => start + Duration::days(5),
+            _ => self.shift(start, 1),
+        };
+        next.and_time(NaiveTime::MIN) - Duration::seconds(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NamedDate {
+    Now,
+    Today,
+    EndOfDay,
+    Yesterday,
+    Tomorrow,
+    Later,
+    Weekday(Weekday),
+    Month(u32),
+    Ordinal(u32),
+    Boundary { end: bool, relative: i32, period: Period },
+}
+
+impl NamedDate {
+    fn parse(name: &str) -> Option<NamedDate> {
+        let named = match name {
+            "now" => NamedDate::Now,
+            "today" | "sod" => NamedDate::Today,
+            "eod" => NamedDate::EndOfDay,
+            "yesterday" => NamedDate::Yesterday,
+            "tomorrow" => NamedDate::Tomorrow,
+            "later" | "someday" => NamedDate::Later,
+            "monday" | "mon" => NamedDate::Weekday(Weekday::Mon),
+            "tuesday" | "tue" => NamedDate::Weekday(Weekday::Tue),
+            "wednesday" | "wed" => NamedDate::Weekday(Weekday::Wed),
+            "thursday" | "thu" => NamedDate::Weekday(Weekday::Thu),
+            "friday" | "fri" => NamedDate::Weekday(Weekday::Fri),
+            "saturday" | "sat" => NamedDate::Weekday(Weekday::Sat),
+            "sunday" | "sun" => NamedDate::Weekday(Weekday::Sun),
+            "january" | "jan" => NamedDate::Month(1),
+            "february" | "feb" => NamedDate::Month(2),
+            "march" | "mar" => NamedDate::Month(3),
+            "april" | "apr" => NamedDate::Month(4),
+            "may" => NamedDate::Month(5),
+            "june" | "jun" => NamedDate::Month(6),
+            "july" | "jul" => NamedDate::Month(7),
+            "august" | "aug" => NamedDate::Month(8),
+            "september" | "sep" | "sept" => NamedDate::Month(9),
+            "october" | "oct" => NamedDate::Month(10),
+            "november" | "nov" => NamedDate::Month(11),
+            "december" | "dec" => NamedDate::Month(12),
+            _ => {
+                if let Some(captures) = ORDINAL.captures(name) {
+                    let day = captures[1].parse().ok()?;
+                    return (1..=31).contains(&day).then_some(NamedDate::Ordinal(day));
+                }
+                let captures = BOUNDARY.captures(name)?;
+                let relative = match &captures[2] {
+                    "p" => -1,
+                    "n" => 1,
+                    _ => 0,
+                };
+                let period = match &captures[3] {
+                    "d" => Period::Day,
+                    "w" => Period::Week,
+                    "ww" => Period::WorkWeek,
+                    "m" => Period::Month,
+                    "q" => Period::Quarter,
+                    _ => Period::Year,
+                };
+                NamedDate::Boundary {
+                    end: &captures[1] == "eo",
+                    relative,
+                    period,
+                }
+            }
+        };
+        Some(named)
+    }
+
+    fn resolve(self, context: &FilterContext) -> NaiveDateTime {
+        let today = context.today();
+        let midnight = |date: NaiveDate| date.and_time(NaiveTime::MIN);
+        match self {
+            NamedDate::Now => context.now,
+            NamedDate::Today => midnight(today),
+            NamedDate::EndOfDay => Period::Day.end(today),
+            NamedDate::Yesterday => midnight(today - Duration::days(1)),
+            NamedDate::Tomorrow => midnight(today + Duration::days(1)),
+            NamedDate::Later => midnight(NaiveDate::from_ymd_opt(9999, 12, 30).unwrap()),
+            NamedDate::Weekday(weekday) => {
+                let days = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64 + 6).rem_euclid(7) + 1;
+                midnight(today + Duration::days(days))
+            }
+            NamedDate::Month(month) => {
+                let year = if month > today.month() { today.year() } else { today.year() + 1 };
+                midnight(NaiveDate::from_ymd_opt(year, month, 1).unwrap())
+            }
+            NamedDate::Ordinal(day) => {
+                let mut month_start = Period::Month.start(today, false);
+                if day <= today.day() {
+                    month_start = Period::Month.shift(month_start, 1);
+                }
+                // Skip months that are too short, e.g. `31st` in April
+                loop {
+                    if let Some(date) = month_start.with_day(day) {
+                        return midnight(date);
+                    }
+                    month_start = Period::Month.shift(month_start, 1);
+                }
+            }
+            NamedDate::Boundary { end, relative, period } => {
+                let start = period.shift(period.start(today, context.weekstart_monday), relative);
+                if end {
+                    period.end(start)
+                } else {
+                    midnight(start)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DurationUnit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Quarters,
+    Years,
+}
+
+impl DurationUnit {
+    fn parse(unit: &str) -> Option<DurationUnit> {
+        let unit = match unit.to_lowercase().as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => DurationUnit::Seconds,
+            "min" | "mins" | "minute" | "minutes" => DurationUnit::Minutes,
+            "h" | "hr" | "hrs" | "hour" | "hours" => DurationUnit::Hours,
+            "d" | "day" | "days" => DurationUnit::Days,
+            "w" | "wk" | "wks" | "week" | "weeks" => DurationUnit::Weeks,
+            "mo" | "mos" | "mth" | "mths" | "month" | "months" => DurationUnit::Months,
+            "q" | "qtr" | "qtrs" | "quarter" | "quarters" => DurationUnit::Quarters,
+            "y" | "yr" | "yrs" | "year" | "years" => DurationUnit::Years,
+            _ => return None,
+        };
+        Some(unit)
+    }
+
+    fn add(self, date: NaiveDateTime, amount: i64) -> NaiveDateTime {
+        let months = |n: i64| {
+            let months = Months::new(n.unsigned_abs() as u32);
+            if n >= 0 {
+                date.checked_add_months(months)
+            } else {
+                date.checked_sub_months(months)
+            }
+            .unwrap_or(date)
+        };
+        match self {
+            DurationUnit::Seconds => date + Duration::seconds(amount),
+            DurationUnit::Minutes => date + Duration::minutes(amount),
+            DurationUnit::Hours => date + Duration::hours(amount),
+            DurationUnit::Days => date + Duration::days(amount),
+            DurationUnit::Weeks => date + Duration::weeks(amount),
+            DurationUnit::Months => months(amount),
+            DurationUnit::Quarters => months(amount * 3),
+            DurationUnit::Years => months(amount * 12),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateBase {
+    /// An absolute date in local time
+    Local(NaiveDateTime),
+    /// An absolute date in UTC, e.g. `20240101T120000Z`
+    Utc(NaiveDateTime),
+    Named(NamedDate),
+}
+
+/// A date value such as `2024-05-01`, `eow` or `today+3d`, resolved when a filter is evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DateExpr {
+    base: DateBase,
+    offsets: Vec<(i64, DurationUnit)>,
+}
+
+impl DateExpr {
+    fn parse(input: &str) -> Result<DateExpr> {
+        let invalid = || anyhow!("'{}' is not a valid date", input);
+        let captures = DATE_EXPR.captures(input).ok_or_else(invalid)?;
+        let base = parse_date_base(&captures[1]).ok_or_else(invalid)?;
+
+        let mut offsets = Vec::new();
+        for offset in DATE_OFFSET.captures_iter(&captures[2]) {
+            let amount: i64 = if offset[2].is_empty() { 1 } else { offset[2].parse().map_err(|_| invalid())? };
+            let unit = DurationUnit::parse(&offset[3]).ok_or_else(invalid)?;
+            offsets.push((if &offset[1] == "-" { -amount } else { amount }, unit));
+        }
+
+        Ok(DateExpr { base, offsets })
+    }
+
+    fn resolve(&self, context: &FilterContext) -> NaiveDateTime {
+        let base = match self.base {
+            DateBase::Local(date) => date,
+            DateBase::Utc(date) => context.to_local(&date),
+            DateBase::Named(named) => named.resolve(context),
+        };
+        self.offsets.iter().fold(base, |date, (amount, unit)| unit.add(date, *amount))
+    }
+}
+
+fn parse_date_base(input: &str) -> Option<DateBase> {
+    if let Some(named) = NamedDate::parse(&input.to_lowercase()) {
+        return Some(DateBase::Named(named));
+    }
+    for format in ["%Y%m%dT%H%M%SZ", "%Y-%m-%dT%H:%M:%SZ"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(DateBase::Utc(date));
+        }
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(DateBase::Local(date));
+        }
+    }
+    for format in ["%Y-%m-%d", "%Y%m%d"] {
+        if let Ok(date) = NaiveDate::parse_from_str(input, format) {
+            return Some(DateBase::Local(date.and_time(NaiveTime::MIN)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use task_hookrs::import::import;
+
+    use super::*;
+
+    /// Wednesday 2024-05-15 12:00 UTC
+    fn context(tasks: &[Task]) -> FilterContext {
+        let now = FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+        FilterContext::new(tasks).with_now(now)
+    }
+
+    fn tasks() -> Vec<Task> {
+        import(
+            r#"[
+              {"id":1,"uuid":"aaaaaaaa-0000-4000-8000-000000000001","status":"pending","entry":"20240501T100000Z","description":"Fix kitchen sink","project":"Home.Kitchen","priority":"H","tags":["chore"],"due":"20240514T090000Z","urgency":9.5},
+              {"id":2,"uuid":"bbbbbbbb-0000-4000-8000-000000000002","status":"pending","entry":"20240502T100000Z","description":"Write report","project":"Work","tags":["work","next"],"due":"20240517T120000Z","urgency":4.2,"annotations":[{"entry":"20240503T100000Z","description":"ask about budget"}]},
+              {"id":3,"uuid":"cccccccc-0000-4000-8000-000000000003","status":"pending","entry":"20240503T100000Z","description":"Plan holiday","project":"Homework","wait":"20240601T000000Z","urgency":1.0,"estimate":"3"},
+              {"id":4,"uuid":"dddddddd-0000-4000-8000-000000000004","status":"pending","entry":"20240504T100000Z","description":"Review draft","depends":["bbbbbbbb-0000-4000-8000-000000000002"],"start":"20240515T080000Z","priority":"L","urgency":2.0},
+              {"uuid":"eeeeeeee-0000-4000-8000-000000000005","status":"completed","entry":"20240401T100000Z","end":"20240410T100000Z","description":"Buy groceries","project":"Home","tags":["chore"]}
+            ]"#
+            .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn ids(filter: &str) -> Vec<u64> {
+        let tasks = tasks();
+        let context = context(&tasks);
+        let filter = Filter::parse(filter).unwrap();
+        filter.apply(tasks, &context).iter().map(|t| t.id().unwrap_or(0)).collect()
+    }
+
+    fn resolve(date: &str) -> String {
+        let context = context(&[]);
+        DateExpr::parse(date).unwrap().resolve(&context).format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize(r#"(project:Home or +work) !+next description:"a (b) c" /x(y)/"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::LParen,
+                Token::Word("project:Home".to_string()),
+                Token::Or,
+                Token::Word("+work".to_string()),
+                Token::RParen,
+                Token::Not,
+                Token::Word("+next".to_string()),
+                Token::Word("description:a (b) c".to_string()),
+                Token::Word("/x(y)/".to_string()),
+            ]
+        );
+        assert!(tokenize("description:'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        assert_eq!(ids(""), vec![1, 2, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_boolean_precedence() {
+        assert_eq!(ids("project:Work or +chore status:pending"), vec![1, 2]);
+        assert_eq!(ids("(project:Work or +chore) status:completed"), vec![0]);
+        assert_eq!(ids("'(project:Work or +chore)' status:completed"), vec![0]);
+        assert_eq!(ids("+chore and not status:completed"), vec![1]);
+        assert_eq!(ids("!+chore !+work"), vec![3, 4]);
+        assert_eq!(ids("+chore xor project:Home"), vec![3]);
+        assert_eq!(ids("+chore xor project:Home or +work"), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Filter::parse("(project:Home").is_err());
+        assert!(Filter::parse("project:Home)").is_err());
+        assert!(Filter::parse("due.before:notadate").is_err());
+        assert!(Filter::parse("project.frobnicate:x").is_err());
+        assert!(Filter::parse("urgency.over:high").is_err());
+        assert!(Filter::parse("due.has:x").is_err());
+        assert!(Filter::parse("/(/").is_err());
+        assert!(Filter::parse("+work or").is_err());
+    }
+
+    #[test]
+    fn test_status_and_virtual_tags() {
+        assert_eq!(ids("status:pending"), vec![1, 2, 3, 4]);
+        assert_eq!(ids("status:pending -WAITING"), vec![1, 2, 4]);
+        assert_eq!(ids("status:waiting"), vec![3]);
+        assert_eq!(ids("status.not:pending"), vec![0]);
+        assert_eq!(ids("+OVERDUE"), vec![1]);
+        assert_eq!(ids("+DUE"), vec![2]);
+        assert_eq!(ids("+WEEK"), vec![1, 2]);
+        assert_eq!(ids("+ACTIVE"), vec![4]);
+        assert_eq!(ids("+BLOCKED"), vec![4]);
+        assert_eq!(ids("+BLOCKING"), vec![2]);
+        assert_eq!(ids("+UNBLOCKED status:pending"), vec![1, 2, 3]);
+        assert_eq!(ids("+READY"), vec![1, 2]);
+        assert_eq!(ids("+ANNOTATED"), vec![2]);
+        assert_eq!(ids("+COMPLETED"), vec![0]);
+        assert_eq!(ids("+UDA"), vec![3]);
+        assert_eq!(ids("-PROJECT"), vec![4]);
+    }
+
+    #[test]
+    fn test_attribute_modifiers() {
+        assert_eq!(ids("project:Home"), vec![1, 3, 0]);
+        assert_eq!(ids("project.is:Home"), vec![0]);
+        assert_eq!(ids("project.not:Home"), vec![2, 4]);
+        assert_eq!(ids("project.isnt:Home"), vec![1, 2, 3, 4]);
+        assert_eq!(ids("project:"), vec![4]);
+        assert_eq!(ids("project.none:"), vec![4]);
+        assert_eq!(ids("project.any:"), vec![1, 2, 3, 0]);
+        assert_eq!(ids("project.endswith:Kitchen"), vec![1]);
+        assert_eq!(ids("description.has:re"), vec![2]);
+        assert_eq!(ids("description.hasnt:re status:pending"), vec![1, 3, 4]);
+        assert_eq!(ids("description.startswith:Plan"), vec![3]);
+        assert_eq!(ids("description.word:sink"), vec![1]);
+        assert_eq!(ids("description.noword:sin status:pending"), vec![1, 2, 3, 4]);
+        assert_eq!(ids("tags:chore"), vec![1, 0]);
+        assert_eq!(ids("tags.hasnt:chore"), vec![2, 3, 4]);
+        assert_eq!(ids("tags.none:"), vec![3, 4]);
+        assert_eq!(ids("depends:bbbbbbbb"), vec![4]);
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        assert_eq!(ids("pro:Work"), vec![2]);
+        assert_eq!(ids("desc.star:Write"), vec![2]);
+        assert_eq!(ids("due.bef:today"), vec![1]);
+    }
+
+    #[test]
+    fn test_numeric_attributes() {
+        assert_eq!(ids("urgency.over:4"), vec![1, 2]);
+        assert_eq!(ids("urgency.under:2"), vec![3]);
+        assert_eq!(ids("urgency >= 2"), vec![1, 2, 4]);
+        assert_eq!(ids("id.below:3"), vec![1, 2]);
+        assert_eq!(ids("priority.over:L"), vec![1]);
+        assert_eq!(ids("priority.below:M status:pending"), vec![2, 3, 4]);
+        assert_eq!(ids("estimate.over:2"), vec![3]);
+        assert_eq!(ids("estimate:3"), vec![3]);
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(ids("due.before:eow"), vec![1, 2]);
+        assert_eq!(ids("due.before:tomorrow"), vec![1]);
+        assert_eq!(ids("due.after:now"), vec![2]);
+        assert_eq!(ids("due.before:today+3d"), vec![1, 2]);
+        assert_eq!(ids("due:2024-05-17"), vec![2]);
+        assert_eq!(ids("due.by:2024-05-17T12:00"), vec![1, 2]);
+        assert_eq!(ids("due < 20240517T000000Z"), vec![1]);
+        assert_eq!(ids("due.any:"), vec![1, 2]);
+        assert_eq!(ids("end.after:som-2mo"), vec![0]);
+        assert_eq!(ids("entry.after:2024-05-02T12:00 entry.before:later"), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_date_math() {
+        assert_eq!(resolve("now"), "2024-05-15 12:00:00");
+        assert_eq!(resolve("today"), "2024-05-15 00:00:00");
+        assert_eq!(resolve("eod"), "2024-05-15 23:59:59");
+        assert_eq!(resolve("today+3d"), "2024-05-18 00:00:00");
+        assert_eq!(resolve("now-2wk+1h"), "2024-05-01 13:00:00");
+        assert_eq!(resolve("sow"), "2024-05-12 00:00:00");
+        assert_eq!(resolve("eow"), "2024-05-18 23:59:59");
+        assert_eq!(resolve("soww"), "2024-05-13 00:00:00");
+        assert_eq!(resolve("eoww"), "2024-05-17 23:59:59");
+        assert_eq!(resolve("sonw"), "2024-05-19 00:00:00");
+        assert_eq!(resolve("eom"), "2024-05-31 23:59:59");
+        assert_eq!(resolve("sopm"), "2024-04-01 00:00:00");
+        assert_eq!(resolve("soq"), "2024-04-01 00:00:00");
+        assert_eq!(resolve("eoq"), "2024-06-30 23:59:59");
+        assert_eq!(resolve("eoy"), "2024-12-31 23:59:59");
+        assert_eq!(resolve("wednesday"), "2024-05-22 00:00:00");
+        assert_eq!(resolve("fri"), "2024-05-17 00:00:00");
+        assert_eq!(resolve("march"), "2025-03-01 00:00:00");
+        assert_eq!(resolve("june+1d"), "2024-06-02 00:00:00");
+        assert_eq!(resolve("20th"), "2024-05-20 00:00:00");
+        assert_eq!(resolve("1st"), "2024-06-01 00:00:00");
+        assert_eq!(resolve("2024-01-31+1mo"), "2024-02-29 00:00:00");
+
+        let monday = context(&[]).weekstart_monday(true);
+        assert_eq!(DateExpr::parse("sow").unwrap().resolve(&monday).to_string(), "2024-05-13 00:00:00");
+
+        assert_eq!(parse_date("today+3d", &context(&[])).unwrap().to_rfc3339(), "2024-05-18T00:00:00+00:00");
+        assert!(parse_date("eventually", &context(&[])).is_err());
+    }
+
+    #[test]
+    fn test_patterns_ids_and_uuids() {
+        assert_eq!(ids("report"), vec![2]);
+        assert_eq!(ids("budget"), vec![2]);
+        assert_eq!(ids("/^(Fix|Plan) /"), vec![1, 3]);
+        assert_eq!(ids("description ~ ^R"), vec![4]);
+        assert_eq!(ids("description !~ e"), vec![3]);
+        assert_eq!(ids("2,4"), vec![2, 4]);
+        assert_eq!(ids("1-3 +chore"), vec![1]);
+        assert_eq!(ids("cccccccc"), vec![3]);
+        assert_eq!(ids("eeeeeeee-0000"), vec![0]);
+    }
+
+    #[test]
+    fn test_limit_and_combination() {
+        let filter = Filter::parse("status:pending limit:2").unwrap();
+        assert_eq!(filter.limit(), Some(2));
+        assert_eq!(Filter::parse("limit:page").unwrap().limit(), Some(PAGE_SIZE));
+        assert!(Filter::parse("limit:lots").is_err());
+        assert_eq!(ids("status:pending limit:2"), vec![1, 2]);
+        assert_eq!(ids("rc.search.case.sensitive=no +chore"), vec![1, 0]);
+
+        let tasks = tasks();
+        let context = context(&tasks);
+        let combined = Filter::parse("status:pending limit:3").unwrap().and(Filter::parse("+chore or +work").unwrap());
+        let matched: Vec<u64> = combined.apply(tasks, &context).iter().filter_map(Task::id).collect();
+        assert_eq!(matched, vec![1, 2]);
+        assert_eq!(combined.limit(), Some(3));
+    }
+}
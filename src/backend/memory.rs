@@ -0,0 +1,244 @@
+//! In-memory task backend for headless tests
+//!
+//! `MemoryBackend` keeps its tasks in an in-memory TaskChampion replica, so filters, modifications
+//! and undo behave like the TaskChampion backend without touching disk or running `task`. Every
+//! call is recorded, and failures can be scripted per method to exercise error handling.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use task_hookrs::task::Task;
+use uuid::Uuid;
+
+use super::taskchampion::{TaskChampionBackend, TaskChampionConfig};
+use super::worker::BackendWorker;
+use super::{Context, TaskBackend, TaskChanges};
+
+/// A recorded `TaskBackend` call: the method name and its arguments rendered as strings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub method: &'static str,
+    pub args: Vec<String>,
+}
+
+struct Shared {
+    /// The replica is not `Send`, so it lives on a worker thread of its own
+    tasks: BackendWorker<TaskChampionBackend>,
+    calls: Mutex<Vec<Call>>,
+    /// Method name to the error messages its next calls fail with
+    failures: Mutex<HashMap<&'static str, VecDeque<String>>>,
+}
+
+/// In-memory `TaskBackend`; clones share the same tasks, call log and scripted failures
+///
+/// Keep a clone around after handing the backend to `TaskwarriorTui::with_backend` to inspect
+/// the calls the UI made.
+#[derive(Clone)]
+pub struct MemoryBackend {
+    shared: Arc<Shared>,
+}
+
+impl MemoryBackend {
+    /// An empty backend with no report filters, contexts or UDAs
+    pub fn new() -> Self {
+        Self::with_config(TaskChampionConfig::default()).expect("An in-memory replica without a sync server cannot fail to open")
+    }
+
+    /// An empty backend using the report filters, contexts and UDA types of `config`
+    pub fn with_config(config: TaskChampionConfig) -> Result<Self> {
+        Ok(Self {
+            shared: Arc::new(Shared {
+                tasks: BackendWorker::spawn(move || Ok(Box::new(TaskChampionBackend::in_memory(config)?)))?,
+                calls: Mutex::new(Vec::new()),
+                failures: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// A worker thread running this backend, as `TaskwarriorTui::with_backend` expects
+    pub fn worker(&self) -> Result<BackendWorker> {
+        let backend = self.clone();
+        BackendWorker::spawn(move || Ok(Box::new(backend) as Box<dyn TaskBackend>))
+    }
+
+    /// Store tasks as they were exported by `task export`; this is not recorded as a call
+    pub fn import_tasks(&self, tasks: &[Task]) -> Result<()> {
+        let tasks = tasks.to_vec();
+        self.shared.tasks.run_blocking(move |backend| backend.import_tasks(&tasks))
+    }
+
+    /// Make the next call to `method` (a `TaskBackend` method name such as `"mark_done"`) fail with `message`
+    ///
+    /// Failures queue up, so calling this twice makes the next two calls fail.
+    pub fn fail_next(&self, method: &'static str, message: &str) {
+        self.shared.failures.lock().unwrap().entry(method).or_default().push_back(message.to_string());
+    }
+
+    /// All calls made so far, oldest first
+    pub fn calls(&self) -> Vec<Call> {
+        self.shared.calls.lock().unwrap().clone()
+    }
+
+    /// The arguments of every call made to `method`, oldest first
+    pub fn calls_to(&self, method: &str) -> Vec<Vec<String>> {
+        self.calls().into_iter().filter(|call| call.method == method).map(|call| call.args).collect()
+    }
+
+    /// Forget the calls recorded so far
+    pub fn clear_calls(&self) {
+        self.shared.calls.lock().unwrap().clear();
+    }
+
+    /// Record a call, then fail it if a failure was scripted for `method` or else run `job` on the replica
+    fn record<T, F>(&self, method: &'static str, args: Vec<String>, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&TaskChampionBackend) -> Result<T> + Send + 'static,
+    {
+        self.shared.calls.lock().unwrap().push(Call { method, args });
+        let failure = self.shared.failures.lock().unwrap().get_mut(method).and_then(VecDeque::pop_front);
+        match failure {
+            Some(message) => Err(anyhow::anyhow!(message)),
+            None => self.shared.tasks.run_blocking(job),
+        }
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn strings<T: ToString>(items: &[T]) -> Vec<String> {
+    items.iter().map(ToString::to_string).collect()
+}
+
+impl TaskBackend for MemoryBackend {
+    fn export_tasks(&self, filter: &str, report: &str, context_filter: &str) -> Result<Vec<Task>> {
+        let (filter, report, context_filter) = (filter.to_string(), report.to_string(), context_filter.to_string());
+        self.record("export_tasks", vec![filter.clone(), report.clone(), context_filter.clone()], move |tasks| {
+            tasks.export_tasks(&filter, &report, &context_filter)
+        })
+    }
+
+    fn export_changes(&self, filter: &str, report: &str, context_filter: &str, since: DateTime<Utc>) -> Result<Option<TaskChanges>> {
+        let (filter, report, context_filter) = (filter.to_string(), report.to_string(), context_filter.to_string());
+        let recorded = vec![filter.clone(), report.clone(), context_filter.clone(), since.to_rfc3339()];
+        self.record("export_changes", recorded, move |tasks| {
+            tasks.export_changes(&filter, &report, &context_filter, since)
+        })
+    }
+
+    fn add_task(&self, description: &str, args: &[&str]) -> Result<()> {
+        let description = description.to_string();
+        let args = strings(args);
+        let mut recorded = vec![description.clone()];
+        recorded.extend(args.iter().cloned());
+        self.record("add_task", recorded, move |tasks| {
+            tasks.add_task(&description, &args.iter().map(String::as_str).collect::<Vec<_>>())
+        })
+    }
+
+    fn mark_done(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        self.record("mark_done", strings(&task_uuids), move |tasks| tasks.mark_done(&task_uuids))
+    }
+
+    fn delete_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        self.record("delete_tasks", strings(&task_uuids), move |tasks| tasks.delete_tasks(&task_uuids))
+    }
+
+    fn modify_tasks(&self, task_uuids: &[Uuid], modifications: &str) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        let modifications = modifications.to_string();
+        let mut recorded = strings(&task_uuids);
+        recorded.push(modifications.clone());
+        self.record("modify_tasks", recorded, move |tasks| tasks.modify_tasks(&task_uuids, &modifications))
+    }
+
+    fn get_task_details(&self, task_uuid: Uuid) -> Result<Option<String>> {
+        self.record("get_task_details", strings(&[task_uuid]), move |tasks| tasks.get_task_details(task_uuid))
+    }
+
+    fn sync(&self) -> Result<()> {
+        // There is no server to sync with, so only a scripted failure can make this fail
+        self.record("sync", vec![], |_| Ok(()))
+    }
+
+    fn undo_preview(&self) -> Result<Option<String>> {
+        self.record("undo_preview", vec![], |tasks| tasks.undo_preview())
+    }
+
+    fn undo(&self) -> Result<Option<Uuid>> {
+        self.record("undo", vec![], |tasks| tasks.undo())
+    }
+
+    fn start_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        self.record("start_tasks", strings(&task_uuids), move |tasks| tasks.start_tasks(&task_uuids))
+    }
+
+    fn stop_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        self.record("stop_tasks", strings(&task_uuids), move |tasks| tasks.stop_tasks(&task_uuids))
+    }
+
+    fn annotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        let annotation = annotation.to_string();
+        let mut recorded = strings(&task_uuids);
+        recorded.push(annotation.clone());
+        self.record("annotate_tasks", recorded, move |tasks| tasks.annotate_tasks(&task_uuids, &annotation))
+    }
+
+    fn denotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        let annotation = annotation.to_string();
+        let mut recorded = strings(&task_uuids);
+        recorded.push(annotation.clone());
+        self.record("denotate_tasks", recorded, move |tasks| tasks.denotate_tasks(&task_uuids, &annotation))
+    }
+
+    fn log_task(&self, description: &str, args: &[&str]) -> Result<()> {
+        let description = description.to_string();
+        let args = strings(args);
+        let mut recorded = vec![description.clone()];
+        recorded.extend(args.iter().cloned());
+        self.record("log_task", recorded, move |tasks| {
+            tasks.log_task(&description, &args.iter().map(String::as_str).collect::<Vec<_>>())
+        })
+    }
+
+    fn duplicate_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let task_uuids = task_uuids.to_vec();
+        self.record("duplicate_tasks", strings(&task_uuids), move |tasks| tasks.duplicate_tasks(&task_uuids))
+    }
+
+    fn edit_task(&self, task_uuid: Uuid) -> Result<()> {
+        // Opening an editor would hang a headless test, so editing is only recorded
+        self.record("edit_task", strings(&[task_uuid]), |_| Ok(()))
+    }
+
+    fn render_task_details(&self, task_uuids: &[Uuid], width: u16) -> Result<HashMap<Uuid, String>> {
+        let task_uuids = task_uuids.to_vec();
+        let mut recorded = strings(&task_uuids);
+        recorded.push(width.to_string());
+        self.record("render_task_details", recorded, move |tasks| tasks.render_task_details(&task_uuids, width))
+    }
+
+    fn list_contexts(&self) -> Result<Vec<Context>> {
+        self.record("list_contexts", vec![], |tasks| tasks.list_contexts())
+    }
+
+    fn current_context(&self) -> Result<Option<Context>> {
+        self.record("current_context", vec![], |tasks| tasks.current_context())
+    }
+
+    fn set_context(&self, name: &str) -> Result<()> {
+        let name = name.to_string();
+        self.record("set_context", vec![name.clone()], move |tasks| tasks.set_context(&name))
+    }
+}
@@ -1,10 +1,21 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use task_hookrs::task::Task;
 use uuid::Uuid;
 
 pub mod cli;
+pub mod filter;
+#[cfg(feature = "taskchampion-backend")]
+pub mod memory;
+#[cfg(feature = "taskchampion-backend")]
+pub mod modification;
 #[cfg(feature = "taskchampion-backend")]
 pub mod taskchampion;
+#[cfg(feature = "taskchampion-backend")]
+pub mod urgency;
+pub mod worker;
 
 #[cfg(test)]
 mod tests;
@@ -14,6 +25,12 @@ pub trait TaskBackend {
     /// Export tasks based on filter and report
     fn export_tasks(&self, filter: &str, report: &str, context_filter: &str) -> Result<Vec<Task>>;
     
+    /// Tasks modified after `since`, to bring an earlier export with the same filter, report and context up to date
+    ///
+    /// Returns `None` when the backend cannot tell what changed, e.g. after an undo or a sync, which
+    /// change tasks without updating their `modified` date; everything has to be exported again then.
+    fn export_changes(&self, filter: &str, report: &str, context_filter: &str, since: DateTime<Utc>) -> Result<Option<TaskChanges>>;
+    
     /// Add a new task with the given description and args
     fn add_task(&self, description: &str, args: &[&str]) -> Result<()>;
     
@@ -31,31 +48,83 @@ pub trait TaskBackend {
     
     /// Sync tasks (for backends that support it)
     fn sync(&self) -> Result<()>;
+    
+    /// Describe the changes the next `undo` would revert, or `None` if there is nothing to undo
+    fn undo_preview(&self) -> Result<Option<String>>;
+    
+    /// Revert the most recent change, returning the UUID of a task it affected
+    fn undo(&self) -> Result<Option<Uuid>>;
+    
+    /// Start task(s)
+    fn start_tasks(&self, task_uuids: &[Uuid]) -> Result<()>;
+    
+    /// Stop task(s)
+    fn stop_tasks(&self, task_uuids: &[Uuid]) -> Result<()>;
+    
+    /// Add an annotation to task(s)
+    fn annotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()>;
+    
+    /// Remove the annotation matching `annotation` from task(s)
+    fn denotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()>;
+    
+    /// Record an already completed task with the given description and args
+    fn log_task(&self, description: &str, args: &[&str]) -> Result<()>;
+    
+    /// Duplicate task(s)
+    fn duplicate_tasks(&self, task_uuids: &[Uuid]) -> Result<()>;
+    
+    /// Edit a task in the user's editor; the terminal must be released by the caller
+    fn edit_task(&self, task_uuid: Uuid) -> Result<()>;
+    
+    /// Render task(s) the way `task info` does, wrapped to `width` columns
+    fn render_task_details(&self, task_uuids: &[Uuid], width: u16) -> Result<HashMap<Uuid, String>>;
+    
+    /// All contexts defined in the configuration
+    fn list_contexts(&self) -> Result<Vec<Context>>;
+    
+    /// The active context, if any
+    fn current_context(&self) -> Result<Option<Context>>;
+    
+    /// Activate the context called `name`, or clear the context for `none`
+    fn set_context(&self, name: &str) -> Result<()>;
+}
+
+/// Tasks modified since an earlier export, see `TaskBackend::export_changes`
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    /// Every modified task, whatever its status
+    pub modified: Vec<Task>,
+    /// The modified tasks that match the export's filter
+    pub matching: HashSet<Uuid>,
+    /// The filter's `limit:`, when the backend applies it
+    pub limit: Option<usize>,
+}
+
+/// A Taskwarrior context, as defined by `context.<name>.read` and `context.<name>.write`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    pub name: String,
+    pub read_filter: String,
+    pub write_filter: String,
+    pub active: bool,
 }
 
 /// Backend configuration enum
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // Built once at startup
 pub enum BackendConfig {
     /// Use the traditional CLI backend
     Cli,
     /// Use the TaskChampion backend
     #[cfg(feature = "taskchampion-backend")]
-    TaskChampion {
-        /// Directory for TaskChampion database
-        data_dir: Option<std::path::PathBuf>,
-        /// Server configuration for sync
-        server_config: Option<String>,
-    },
+    TaskChampion(taskchampion::TaskChampionConfig),
 }
 
 impl Default for BackendConfig {
     fn default() -> Self {
         #[cfg(feature = "taskchampion-backend")]
         {
-            Self::TaskChampion {
-                data_dir: None,
-                server_config: None,
-            }
+            Self::TaskChampion(taskchampion::TaskChampionConfig::default())
         }
         #[cfg(not(feature = "taskchampion-backend"))]
         {
@@ -64,6 +133,29 @@ impl Default for BackendConfig {
     }
 }
 
+/// Resolve the Taskwarrior data directory the same way the `task` CLI does
+///
+/// `configured` is `uda.taskwarrior-tui.taskchampion.data-dir` and takes precedence. Otherwise
+/// `TASKDATA` (also set by `--taskdata`) overrides `data_location`, which is `data.location`
+/// from `task show`. Without either, Taskwarrior's default `~/.task` is used. Relative paths
+/// are resolved against the working directory, like `task` does.
+pub fn resolve_data_dir(configured: Option<&str>, data_location: Option<&str>) -> PathBuf {
+    let taskdata = std::env::var("TASKDATA").ok();
+    let location = [configured, taskdata.as_deref(), data_location]
+        .iter()
+        .flatten()
+        .map(|location| location.trim())
+        .find(|location| !location.is_empty())
+        .unwrap_or("~/.task");
+    let path = PathBuf::from(shellexpand::tilde(location).into_owned());
+    if path.is_relative() {
+        if let Ok(cwd) = std::env::current_dir() {
+            return cwd.join(path);
+        }
+    }
+    path
+}
+
 /// Create a backend instance from configuration
 pub fn create_backend(config: BackendConfig) -> Result<Box<dyn TaskBackend>> {
     match config {
@@ -71,8 +163,6 @@ pub fn create_backend(config: BackendConfig) -> Result<Box<dyn TaskBackend>> {
             Ok(Box::new(cli::CliBackend::new()?))
         }
         #[cfg(feature = "taskchampion-backend")]
-        BackendConfig::TaskChampion { data_dir, server_config } => {
-            Ok(Box::new(taskchampion::TaskChampionBackend::new(data_dir, server_config)?))
-        }
+        BackendConfig::TaskChampion(config) => Ok(Box::new(taskchampion::TaskChampionBackend::new(config)?)),
     }
 }
\ No newline at end of file
@@ -0,0 +1,309 @@
+//! Taskwarrior modification arguments for the TaskChampion backend
+//!
+//! The words after `task add`, `task log` or `task <filter> modify`, for example
+//! `Call mom project:Home +phone due:eow`, are split into description text,
+//! tag changes and attribute assignments, then applied to a task's properties.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use taskchampion::{Operations, TaskData, WorkingSet};
+use uuid::Uuid;
+
+use super::filter::{parse_date, resolve_abbreviation, FilterContext};
+
+/// Attributes that can be assigned with `name:value`
+const ATTRIBUTES: &[&str] = &[
+    "description",
+    "status",
+    "project",
+    "priority",
+    "due",
+    "wait",
+    "scheduled",
+    "until",
+    "start",
+    "end",
+    "entry",
+    "recur",
+    "depends",
+];
+
+/// Values `status` can be set to
+const STATUSES: &[&str] = &["pending", "completed", "deleted", "waiting", "recurring"];
+
+/// Attributes holding a date
+const DATE_ATTRIBUTES: &[&str] = &["due", "wait", "scheduled", "until", "start", "end", "entry"];
+
+/// A single change requested by a modification word
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    /// `+tag`
+    AddTag(String),
+    /// `-tag`
+    RemoveTag(String),
+    /// `name:value`, where an empty value removes the attribute
+    Set(String, String),
+}
+
+/// Parsed modification arguments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modifications {
+    /// Words that are not tags or attributes, which together replace the description
+    description: Vec<String>,
+    changes: Vec<Change>,
+}
+
+impl Modifications {
+    /// Parse modification words; `uda_types` lists the UDAs that may be assigned besides the core attributes
+    ///
+    /// A value with no attribute name, such as `:Home`, is an error.
+    pub fn parse<S: AsRef<str>>(words: &[S], uda_types: &HashMap<String, String>) -> Result<Self> {
+        let mut modifications = Self::default();
+        for word in words {
+            let word = word.as_ref();
+            if let Some(tag) = word.strip_prefix('+').filter(|tag| is_tag(tag)) {
+                modifications.changes.push(Change::AddTag(tag.to_string()));
+            } else if let Some(tag) = word.strip_prefix('-').filter(|tag| is_tag(tag)) {
+                modifications.changes.push(Change::RemoveTag(tag.to_string()));
+            } else if let Some(value) = word.strip_prefix(':').filter(|value| !value.is_empty()) {
+                return Err(anyhow!("Missing attribute name before ':{}'", value));
+            } else if let Some((name, value)) = word.split_once(':').and_then(|(name, value)| Some((resolve_attribute(name, uda_types)?, value))) {
+                if name == "description" {
+                    modifications.description.push(value.to_string());
+                } else {
+                    modifications.changes.push(Change::Set(name, value.to_string()));
+                }
+            } else if !word.is_empty() {
+                modifications.description.push(word.to_string());
+            }
+        }
+        Ok(modifications)
+    }
+
+    /// The new description, if the words contained one
+    pub fn description(&self) -> Option<String> {
+        if self.description.is_empty() {
+            None
+        } else {
+            Some(self.description.join(" "))
+        }
+    }
+
+    /// Whether the modifications leave the task unchanged
+    pub fn is_empty(&self) -> bool {
+        self.description.is_empty() && self.changes.is_empty()
+    }
+
+    /// Apply the modifications to `task`, recording the operations in `ops`
+    ///
+    /// Dates are resolved against `context`, and dependencies given by ID are looked up in `working_set`.
+    pub fn apply(
+        &self,
+        task: &mut TaskData,
+        uda_types: &HashMap<String, String>,
+        working_set: &WorkingSet,
+        context: &FilterContext,
+        ops: &mut Operations,
+    ) -> Result<()> {
+        if let Some(description) = self.description() {
+            task.update("description", Some(description), ops);
+        }
+
+        for change in &self.changes {
+            match change {
+                Change::AddTag(tag) => task.update(format!("tag_{}", tag), Some(String::new()), ops),
+                Change::RemoveTag(tag) => task.update(format!("tag_{}", tag), None, ops),
+                Change::Set(name, value) if name == "depends" => set_depends(task, value, working_set, ops)?,
+                Change::Set(name, value) if name == "status" => {
+                    if !STATUSES.contains(&value.as_str()) {
+                        return Err(anyhow!("'{}' is not a valid status", value));
+                    }
+                    task.update("status", Some(value.clone()), ops);
+                    // Like `task done` and `task delete`, unless the end date is given explicitly
+                    if !self.sets("end") {
+                        match value.as_str() {
+                            "completed" | "deleted" => task.update("end", Some(Utc::now().timestamp().to_string()), ops),
+                            "pending" => task.update("end", None, ops),
+                            _ => {}
+                        }
+                    }
+                }
+                Change::Set(name, value) if value.is_empty() => task.update(name.as_str(), None, ops),
+                Change::Set(name, value) => {
+                    let kind = uda_types.get(name).map(String::as_str);
+                    let value = if DATE_ATTRIBUTES.contains(&name.as_str()) || kind == Some("date") {
+                        parse_date(value, context)?.timestamp().to_string()
+                    } else if kind == Some("numeric") {
+                        value
+                            .parse::<f64>()
+                            .map_err(|_| anyhow!("'{}' is not a numeric value for {}", value, name))?;
+                        value.clone()
+                    } else {
+                        value.clone()
+                    };
+                    task.update(name.as_str(), Some(value), ops);
+                }
+            }
+        }
+
+        task.update("modified", Some(Utc::now().timestamp().to_string()), ops);
+        Ok(())
+    }
+
+    /// Whether the modifications assign attribute `name`
+    fn sets(&self, name: &str) -> bool {
+        self.changes.iter().any(|change| matches!(change, Change::Set(set, _) if set == name))
+    }
+}
+
+/// `depends:1,2,-3` adds dependencies on tasks 1 and 2 and removes the one on task 3
+fn set_depends(task: &mut TaskData, value: &str, working_set: &WorkingSet, ops: &mut Operations) -> Result<()> {
+    if value.is_empty() {
+        let dependencies: Vec<String> = task.properties().filter(|p| p.starts_with("dep_")).cloned().collect();
+        for dependency in dependencies {
+            task.update(dependency, None, ops);
+        }
+        return Ok(());
+    }
+
+    for item in value.split(',') {
+        let (remove, reference) = match item.strip_prefix('-') {
+            Some(reference) => (true, reference),
+            None => (false, item),
+        };
+        let uuid = match reference.parse::<usize>() {
+            Ok(id) => working_set.by_index(id).ok_or_else(|| anyhow!("No task with ID {}", id))?,
+            Err(_) => Uuid::parse_str(reference).map_err(|_| anyhow!("'{}' is not a task ID or UUID", reference))?,
+        };
+        if uuid == task.get_uuid() {
+            return Err(anyhow!("A task cannot depend on itself"));
+        }
+        let value = if remove { None } else { Some(String::new()) };
+        task.update(format!("dep_{}", uuid), value, ops);
+    }
+    Ok(())
+}
+
+fn is_tag(tag: &str) -> bool {
+    taskchampion::Tag::from_str(tag).is_ok_and(|tag| tag.is_user())
+}
+
+/// Resolve an attribute or UDA name, or an unambiguous abbreviation of one
+fn resolve_attribute(name: &str, uda_types: &HashMap<String, String>) -> Option<String> {
+    let candidates: Vec<(&str, &str)> = ATTRIBUTES
+        .iter()
+        .copied()
+        .chain(uda_types.keys().map(String::as_str))
+        .map(|candidate| (candidate, candidate))
+        .collect();
+    resolve_abbreviation(name, &candidates).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+
+    fn udas() -> HashMap<String, String> {
+        [("estimate", "numeric"), ("reviewed", "date"), ("area", "string")]
+            .iter()
+            .map(|(name, kind)| (name.to_string(), kind.to_string()))
+            .collect()
+    }
+
+    fn parse(words: &str) -> Modifications {
+        let words: Vec<&str> = words.split_whitespace().collect();
+        Modifications::parse(&words, &udas()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let modifications = parse("Call mom pro:Home +phone -work due:eow url:http://example.com area:");
+        assert_eq!(modifications.description(), Some("Call mom url:http://example.com".to_string()));
+        assert_eq!(
+            modifications.changes,
+            vec![
+                Change::Set("project".to_string(), "Home".to_string()),
+                Change::AddTag("phone".to_string()),
+                Change::RemoveTag("work".to_string()),
+                Change::Set("due".to_string(), "eow".to_string()),
+                Change::Set("area".to_string(), String::new()),
+            ]
+        );
+
+        assert_eq!(parse("description:Renamed").description(), Some("Renamed".to_string()));
+        // Virtual tags and bare signs are not tag changes
+        assert_eq!(parse("+ -ACTIVE").description(), Some("+ -ACTIVE".to_string()));
+        assert!(parse("").is_empty());
+        assert!(Modifications::parse(&["Renamed", ":Home"], &udas()).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut replica = taskchampion::Replica::new(taskchampion::StorageConfig::InMemory.into_storage().unwrap());
+        let mut ops = Operations::new();
+        let blocker = Uuid::new_v4();
+        let mut blocking = TaskData::create(blocker, &mut ops);
+        blocking.update("status", Some("pending".to_string()), &mut ops);
+        let uuid = Uuid::new_v4();
+        let mut task = TaskData::create(uuid, &mut ops);
+        task.update("status", Some("pending".to_string()), &mut ops);
+        task.update("tag_work", Some(String::new()), &mut ops);
+        replica.commit_operations(ops).unwrap();
+        let working_set = replica.working_set().unwrap();
+        let blocker_id = working_set.by_uuid(blocker).unwrap();
+
+        let now = FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+        let context = FilterContext::new(&[]).with_now(now);
+        let mut ops = Operations::new();
+        parse(&format!("Renamed +phone -work due:tomorrow estimate:2.5 dep:{}", blocker_id))
+            .apply(&mut task, &udas(), &working_set, &context, &mut ops)
+            .unwrap();
+
+        assert_eq!(task.get("description"), Some("Renamed"));
+        assert!(task.has("tag_phone"));
+        assert!(!task.has("tag_work"));
+        assert_eq!(task.get("due"), Some("1715817600"));
+        assert_eq!(task.get("estimate"), Some("2.5"));
+        assert!(task.has(format!("dep_{}", blocker)));
+        assert!(task.has("modified"));
+
+        let mut ops = Operations::new();
+        parse("status:completed").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert_eq!(task.get("status"), Some("completed"));
+        assert!(parse("status:finished").apply(&mut task, &udas(), &working_set, &context, &mut ops).is_err());
+        assert!(parse("estimate:lots").apply(&mut task, &udas(), &working_set, &context, &mut ops).is_err());
+        assert!(parse("due:eventually").apply(&mut task, &udas(), &working_set, &context, &mut ops).is_err());
+        parse(&format!("due: dep:-{}", blocker)).apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert!(!task.has("due"));
+        assert!(!task.has(format!("dep_{}", blocker)));
+    }
+
+    #[test]
+    fn test_apply_status_end() {
+        let mut replica = taskchampion::Replica::new(taskchampion::StorageConfig::InMemory.into_storage().unwrap());
+        let working_set = replica.working_set().unwrap();
+        let context = FilterContext::new(&[]);
+        let mut ops = Operations::new();
+        let mut task = TaskData::create(Uuid::new_v4(), &mut ops);
+        task.update("status", Some("pending".to_string()), &mut ops);
+        let recently = |task: &TaskData| task.get("end").and_then(|end| end.parse::<i64>().ok()).is_some_and(|end| Utc::now().timestamp() - end < 60);
+
+        parse("status:completed").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert!(recently(&task), "{:?}", task.get("end"));
+        parse("status:pending").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert!(!task.has("end"));
+        parse("status:deleted").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert!(recently(&task), "{:?}", task.get("end"));
+
+        // An explicit end date wins, whichever order the words come in
+        parse("end:2024-05-01 status:completed").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert_eq!(task.get("end").and_then(|end| end.parse::<i64>().ok()), Some(parse_date("2024-05-01", &context).unwrap().timestamp()));
+        parse("status:pending end:2024-05-02").apply(&mut task, &udas(), &working_set, &context, &mut ops).unwrap();
+        assert_eq!(task.get("end").and_then(|end| end.parse::<i64>().ok()), Some(parse_date("2024-05-02", &context).unwrap().timestamp()));
+    }
+}
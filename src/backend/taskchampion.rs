@@ -1,140 +1,348 @@
 use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Mutex;
-use task_hookrs::task::{Task, TaskBuilder};
+use task_hookrs::annotation::Annotation;
+use task_hookrs::date::Date;
 use task_hookrs::status::TaskStatus;
+use task_hookrs::task::Task;
+use task_hookrs::uda::{UDAValue, UDA};
+use taskchampion::{Operation, Operations, ServerConfig, TaskData};
 use uuid::Uuid;
 
-use super::TaskBackend;
+use super::filter::{parse_date, Filter, FilterContext};
+use super::modification::Modifications;
+use super::urgency::UrgencyCoefficients;
+use crate::command_line::{parse_sort_keys, sort_tasks, SortKey};
+use super::{Context, TaskBackend, TaskChanges};
+
+/// Date format used by `task export`
+const EXPORT_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Task properties that are stored as timestamps
+const DATE_PROPERTIES: &[&str] = &["due", "end", "entry", "modified", "scheduled", "start", "until", "wait"];
+
+/// File name of the Taskwarrior 3 database inside the data directory
+const DATABASE_FILE: &str = "taskchampion.sqlite3";
+
+/// Properties `task duplicate` does not copy to the new task
+const DUPLICATE_SKIPPED_PROPERTIES: &[&str] = &["status", "entry", "modified", "start", "end", "mask", "imask", "parent"];
+
+/// Format of dates shown to the user in `task info` and `task edit`
+const DISPLAY_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Settings for `TaskChampionBackend`, mostly taken from `task show`
+#[derive(Debug, Clone, Default)]
+pub struct TaskChampionConfig {
+    /// Directory holding the database, see `super::resolve_data_dir`
+    pub data_dir: Option<PathBuf>,
+    /// Value of `uda.taskwarrior-tui.taskchampion.server-config`, see `parse_server_config`
+    pub server_config: Option<String>,
+    /// UDA name to `uda.<name>.type`
+    pub uda_types: HashMap<String, String>,
+    /// Report name to `report.<name>.filter`
+    pub report_filters: HashMap<String, String>,
+    /// Report name to `report.<name>.sort`, which decides the tasks `limit:` keeps
+    pub report_sorts: HashMap<String, String>,
+    /// `uda.priority.values`, highest first
+    pub priority_values: Vec<String>,
+    /// Contexts defined by `context.<name>.read` and `context.<name>.write`
+    pub contexts: Vec<Context>,
+    /// The `urgency.*` settings, e.g. `urgency.due.coefficient`; Taskwarrior's defaults fill in the rest
+    pub urgency: HashMap<String, f64>,
+    /// The taskrc's `editor`, used by `edit_task` before `$VISUAL` and `$EDITOR`
+    pub editor: Option<String>,
+    /// Create an empty database when `data_dir` does not have one yet
+    pub create_if_missing: bool,
+}
 
 /// TaskChampion-based backend for direct database access
 pub struct TaskChampionBackend {
     replica: Mutex<taskchampion::Replica>,
+    /// UDA name to `uda.<name>.type`, needed to export UDA values with the right JSON type
+    uda_types: HashMap<String, String>,
+    /// Value of `uda.taskwarrior-tui.taskchampion.server-config`, see `parse_server_config`
+    server_config: Option<String>,
+    /// Sync server, connected on the first sync
+    server: Mutex<Option<Box<dyn taskchampion::Server>>>,
+    /// Report name to its filter, used when exporting without an explicit filter
+    report_filters: HashMap<String, String>,
+    /// Report name to its sort order
+    report_sorts: HashMap<String, Vec<SortKey>>,
+    /// `uda.priority.values`, for sorting by priority
+    priority_values: Vec<String>,
+    /// Contexts from the configuration. Switching context only changes which one is active
+    /// for this session; the taskrc is left untouched.
+    contexts: Mutex<Vec<Context>>,
+    /// Coefficients for the urgency of exported tasks
+    urgency: UrgencyCoefficients,
+    /// Editor for `edit_task`, see `run_editor`
+    editor: Option<String>,
+    /// Number of local operations at the last export, or `None` after an undo or sync, whose
+    /// changes cannot be found through the `modified` dates
+    exported_operations: Mutex<Option<usize>>,
 }
 
 impl TaskChampionBackend {
-    pub fn new(data_dir: Option<PathBuf>, _server_config: Option<String>) -> Result<Self> {
-        let storage_dir = data_dir.unwrap_or_else(|| {
-            dirs::data_dir()
-                .unwrap_or_else(|| std::env::current_dir().unwrap())
-                .join("taskwarrior-tui")
-                .join("taskchampion")
-        });
+    /// Open the replica in `config.data_dir`, or in the directory `super::resolve_data_dir` finds
+    ///
+    /// Unless `create_if_missing` is set, a directory without a Taskwarrior 3 database is an error:
+    /// a fresh, empty database would otherwise hide the user's tasks.
+    pub fn new(config: TaskChampionConfig) -> Result<Self> {
+        let storage_dir = config.data_dir.clone().unwrap_or_else(|| super::resolve_data_dir(None, None));
+        let create_if_missing = config.create_if_missing;
 
-        // Ensure the directory exists
-        std::fs::create_dir_all(&storage_dir)?;
+        if create_if_missing {
+            std::fs::create_dir_all(&storage_dir)?;
+        } else if !storage_dir.join(DATABASE_FILE).exists() {
+            let hint = if storage_dir.join("pending.data").exists() {
+                "This looks like a Taskwarrior 2.x data directory; run `task import-v2` with Taskwarrior 3 to convert it."
+            } else {
+                "Run `task` once to create it, or set uda.taskwarrior-tui.taskchampion.data-dir."
+            };
+            return Err(anyhow::anyhow!(
+                "No Taskwarrior database found at {}. {}",
+                storage_dir.join(DATABASE_FILE).display(),
+                hint
+            ));
+        }
+        log::info!("TaskChampion backend: Opening database in {}", storage_dir.display());
 
         let storage = taskchampion::StorageConfig::OnDisk {
             taskdb_dir: storage_dir,
+            create_if_missing,
         }
         .into_storage()?;
 
-        let replica = taskchampion::Replica::new(storage);
+        Self::with_replica(taskchampion::Replica::new(storage), config)
+    }
+
+    /// Keep the tasks in memory only, ignoring `config.data_dir`
+    pub fn in_memory(config: TaskChampionConfig) -> Result<Self> {
+        let storage = taskchampion::StorageConfig::InMemory.into_storage()?;
+        Self::with_replica(taskchampion::Replica::new(storage), config)
+    }
+
+    fn with_replica(replica: taskchampion::Replica, config: TaskChampionConfig) -> Result<Self> {
+        // Reject a malformed server configuration at startup rather than on the first sync
+        let server_config = config.server_config.filter(|server_config| !server_config.trim().is_empty());
+        if let Some(server_config) = &server_config {
+            parse_server_config(server_config)?;
+        }
+
+        let report_sorts = config
+            .report_sorts
+            .into_iter()
+            .filter_map(|(report, sort)| match parse_sort_keys(&sort) {
+                Ok(keys) => Some((report, keys)),
+                Err(err) => {
+                    log::warn!("Ignoring report.{}.sort: {}", report, err);
+                    None
+                }
+            })
+            .collect();
 
         Ok(Self {
             replica: Mutex::new(replica),
+            uda_types: config.uda_types,
+            server_config,
+            server: Mutex::new(None),
+            report_filters: config.report_filters,
+            report_sorts,
+            priority_values: config.priority_values,
+            contexts: Mutex::new(config.contexts),
+            urgency: UrgencyCoefficients::new(config.urgency),
+            editor: config.editor,
+            exported_operations: Mutex::new(None),
         })
     }
+
+    /// Convert a task like `convert_taskchampion_to_hookrs`, with the urgency `task export` would give it
+    ///
+    /// `dependencies` are the pending and blocking tasks, see `dependency_sets`.
+    fn export_task(&self, tc_task: &TaskData, id: Option<usize>, dependencies: &(HashSet<Uuid>, HashSet<Uuid>)) -> Result<Task> {
+        let (pending, blocking) = dependencies;
+        let mut task = convert_taskchampion_to_hookrs(tc_task, id, &self.uda_types)?;
+        let blocked = task.depends().is_some_and(|depends| depends.iter().any(|uuid| pending.contains(uuid)));
+        let urgency = self.urgency.urgency(&task, blocked, blocking.contains(task.uuid()), Utc::now().naive_utc());
+        if let Some(value) = task.urgency_mut() {
+            *value = urgency;
+        }
+        Ok(task)
+    }
+
+    /// The filter `task export` would apply: `filter`, or else the report's own filter, and the context on top
+    fn export_filter(&self, filter: &str, report: &str, context_filter: &str) -> Result<Filter> {
+        let effective_filter = if filter.is_empty() {
+            // Like `task <report>`, use the report's own filter
+            self.report_filters.get(report).cloned().unwrap_or_else(|| {
+                log::warn!("No filter configured for report '{}', using no filter", report);
+                String::new()
+            })
+        } else {
+            filter.to_string()
+        };
+        
+        log::debug!("TaskChampion backend: Using filter: '{}' with context '{}'", effective_filter, context_filter);
+        
+        // Like `task export`, the context filter is applied on top of the report filter
+        Ok(Filter::parse(&effective_filter)?.and(Filter::parse(context_filter)?))
+    }
+
+    /// Store tasks as they were exported by `task export`, replacing any existing task with the same UUID
+    pub fn import_tasks(&self, tasks: &[Task]) -> Result<()> {
+        let mut replica = self.replica.lock().unwrap();
+        let mut ops = vec![Operation::UndoPoint];
+
+        for task in tasks {
+            let mut data = match replica.get_task_data(*task.uuid())? {
+                Some(data) => data,
+                None => TaskData::create(*task.uuid(), &mut ops),
+            };
+            let taskmap = convert_hookrs_to_taskmap(task);
+            let stale: Vec<String> = data.properties().filter(|property| !taskmap.contains_key(*property)).cloned().collect();
+            for property in stale {
+                data.update(property, None, &mut ops);
+            }
+            for (property, value) in taskmap {
+                data.update(property, Some(value), &mut ops);
+            }
+        }
+
+        replica.commit_operations(ops)?;
+        log::info!("TaskChampion backend: Imported {} tasks", tasks.len());
+        Ok(())
+    }
+
+    /// Create a task from `task add`-style arguments with the given status
+    fn create_task(&self, description: &str, args: &[&str], status: &str) -> Result<Uuid> {
+        let words: Vec<&str> = std::iter::once(description).chain(args.iter().copied()).collect();
+        let modifications = Modifications::parse(&words, &self.uda_types)?;
+        if modifications.description().is_none() {
+            return Err(anyhow::anyhow!("Additional text must be provided."));
+        }
+        
+        let mut replica = self.replica.lock().unwrap();
+        let working_set = replica.working_set()?;
+        let now = Utc::now().timestamp().to_string();
+        
+        let uuid = Uuid::new_v4();
+        let mut ops = vec![Operation::UndoPoint];
+        let mut task = TaskData::create(uuid, &mut ops);
+        task.update("status", Some(status.to_string()), &mut ops);
+        task.update("entry", Some(now.clone()), &mut ops);
+        if status == "completed" {
+            task.update("end", Some(now), &mut ops);
+        }
+        modifications.apply(&mut task, &self.uda_types, &working_set, &FilterContext::new(&[]), &mut ops)?;
+        replica.commit_operations(ops)?;
+        Ok(uuid)
+    }
+    
+    /// Run `update` on each of the tasks, committing all changes as one undo step
+    fn update_tasks<F>(&self, task_uuids: &[Uuid], mut update: F) -> Result<()>
+    where
+        F: FnMut(&mut taskchampion::Task, &mut Operations) -> Result<()>,
+    {
+        let mut replica = self.replica.lock().unwrap();
+        let mut ops = vec![Operation::UndoPoint];
+        for &task_uuid in task_uuids {
+            if let Some(mut task) = replica.get_task(task_uuid)? {
+                update(&mut task, &mut ops)?;
+            }
+        }
+        replica.commit_operations(ops)?;
+        Ok(())
+    }
 }
 
 impl TaskBackend for TaskChampionBackend {
-    fn export_tasks(&self, filter: &str, report: &str, _context_filter: &str) -> Result<Vec<Task>> {
+    fn export_tasks(&self, filter: &str, report: &str, context_filter: &str) -> Result<Vec<Task>> {
         let mut replica = self.replica.lock().unwrap();
+        *self.exported_operations.lock().unwrap() = Some(replica.num_local_operations()?);
         
         // Get all tasks from TaskChampion
-        let tc_tasks = replica.all_tasks()?;
+        let working_set = replica.working_set()?;
+        let tc_tasks = replica.all_task_data()?;
         let total_tasks = tc_tasks.len();
         
-        let mut converted_tasks = Vec::new();
+        let dependencies = dependency_sets(&tc_tasks);
+        let mut converted_tasks = Vec::with_capacity(total_tasks);
         
-        for (tc_uuid, tc_task) in tc_tasks {
+        for (tc_uuid, tc_task) in &tc_tasks {
             // Convert TaskChampion task to task-hookrs format
-            if let Some(task) = convert_taskchampion_to_hookrs(tc_uuid, tc_task)? {
-                converted_tasks.push(task);
-            }
+            let id = working_set.by_uuid(*tc_uuid);
+            converted_tasks.push(self.export_task(tc_task, id, &dependencies)?);
         }
         
-        // Determine the actual filter to apply
-        let effective_filter = if filter.is_empty() {
-            // Get the default filter for this report
-            get_report_filter(report).unwrap_or_else(|| {
-                log::warn!("Could not get filter for report '{}', using no filter", report);
-                String::new()
-            })
-        } else {
-            filter.to_string()
-        };
-        
-        log::debug!("TaskChampion backend: Using filter: '{}'", effective_filter);
-        
-        // Apply filtering
-        let mut filtered_tasks = if effective_filter.is_empty() {
-            converted_tasks
-        } else {
-            converted_tasks.into_iter()
-                .filter(|task| task_matches_taskwarrior_filter(task, &effective_filter))
-                .collect()
-        };
-        
-        // Apply limit if specified in filter
-        if let Some(limit) = extract_limit_from_filter(&effective_filter) {
-            filtered_tasks.truncate(limit);
-            log::debug!("Applied limit of {} tasks", limit);
+        converted_tasks.sort_by_key(export_order);
+        // Like `task <report>`, `limit:` keeps the first tasks in the report's order
+        if let Some(keys) = self.report_sorts.get(report) {
+            sort_tasks(&mut converted_tasks, keys, &self.priority_values);
         }
         
+        let parsed_filter = self.export_filter(filter, report, context_filter)?;
+        let filter_context = FilterContext::new(&converted_tasks);
+        let filtered_tasks = parsed_filter.apply(converted_tasks, &filter_context);
+        
         log::info!("TaskChampion backend: Exported {} tasks (filtered from {} total)", 
                   filtered_tasks.len(), total_tasks);
         Ok(filtered_tasks)
     }
 
-    fn add_task(&self, description: &str, args: &[&str]) -> Result<()> {
+    fn export_changes(&self, filter: &str, report: &str, context_filter: &str, since: DateTime<Utc>) -> Result<Option<TaskChanges>> {
         let mut replica = self.replica.lock().unwrap();
-        
-        // Create a new task with TaskChampion
-        let new_task = replica.new_task(taskchampion::Status::Pending, description.to_string())?;
-        let mut task_mut = new_task.into_mut(&mut replica);
-        
-        // Parse and apply additional arguments
-        for arg in args {
-            if let Some((key, value)) = parse_task_arg(arg) {
-                match key.as_str() {
-                    "project" => {
-                        // TODO: TaskChampion doesn't seem to have project support in 0.4.1
-                        // We'll implement this when we find the right API
-                        log::debug!("Project setting not yet implemented: {}", value);
-                    }
-                    "priority" => {
-                        // TODO: TaskChampion doesn't seem to have priority support in 0.4.1
-                        // We'll implement this when we find the right API
-                        log::debug!("Priority setting not yet implemented: {}", value);
-                    }
-                    _ => {
-                        // Treat as tag
-                        let tag = taskchampion::Tag::from_str(&key)?;
-                        task_mut.add_tag(&tag)?;
-                    }
-                }
+        let operations = replica.num_local_operations()?;
+        let exported = self.exported_operations.lock().unwrap().replace(operations);
+        // Undoing or syncing in another process also leaves fewer local operations behind
+        if exported.is_none_or(|exported| operations < exported) {
+            return Ok(None);
+        }
+
+        let working_set = replica.working_set()?;
+        let tc_tasks = replica.all_task_data()?;
+        let since = since.timestamp();
+        let dependencies = dependency_sets(&tc_tasks);
+        let mut modified = Vec::new();
+        for (tc_uuid, tc_task) in &tc_tasks {
+            if tc_task.get("modified").and_then(|m| m.parse::<i64>().ok()).is_some_and(|m| m >= since) {
+                modified.push(self.export_task(tc_task, working_set.by_uuid(*tc_uuid), &dependencies)?);
             }
         }
-        
-        log::info!("TaskChampion backend: Added task '{}'", description);
+        modified.sort_by_key(export_order);
+
+        let parsed_filter = self.export_filter(filter, report, context_filter)?;
+        let (pending, blocking) = dependencies;
+        let filter_context = FilterContext::from_dependencies(pending, blocking);
+        let matching = modified.iter().filter(|task| parsed_filter.matches(task, &filter_context)).map(|task| *task.uuid()).collect();
+
+        log::info!("TaskChampion backend: Exported {} tasks modified since {}", modified.len(), since);
+        Ok(Some(TaskChanges {
+            modified,
+            matching,
+            limit: parsed_filter.limit(),
+        }))
+    }
+
+    fn add_task(&self, description: &str, args: &[&str]) -> Result<()> {
+        let uuid = self.create_task(description, args, "pending")?;
+        log::info!("TaskChampion backend: Added task {}", uuid);
         Ok(())
     }
 
     fn mark_done(&self, task_uuids: &[Uuid]) -> Result<()> {
         let mut replica = self.replica.lock().unwrap();
         
+        let mut ops = vec![Operation::UndoPoint];
         for &task_uuid in task_uuids {
-            // Convert UUID format
-            let tc_uuid = taskchampion::Uuid::from_bytes(task_uuid.as_bytes().clone());
-            
             // Get the task and mark it as done
-            if let Some(task) = replica.get_task(tc_uuid)? {
-                let mut task_mut = task.into_mut(&mut replica);
-                task_mut.done()?;
+            if let Some(mut task) = replica.get_task(task_uuid)? {
+                task.done(&mut ops)?;
             }
         }
+        replica.commit_operations(ops)?;
         
         log::info!("TaskChampion backend: Marked {} tasks as done", task_uuids.len());
         Ok(())
@@ -143,52 +351,53 @@ impl TaskBackend for TaskChampionBackend {
     fn delete_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
         let mut replica = self.replica.lock().unwrap();
         
+        let mut ops = vec![Operation::UndoPoint];
         for &task_uuid in task_uuids {
-            // Convert UUID format
-            let tc_uuid = taskchampion::Uuid::from_bytes(task_uuid.as_bytes().clone());
-            
             // Get the task and delete it
-            if let Some(task) = replica.get_task(tc_uuid)? {
-                let mut task_mut = task.into_mut(&mut replica);
-                task_mut.set_status(taskchampion::Status::Deleted)?;
+            if let Some(mut task) = replica.get_task(task_uuid)? {
+                task.set_status(taskchampion::Status::Deleted, &mut ops)?;
             }
         }
+        replica.commit_operations(ops)?;
         
         log::info!("TaskChampion backend: Deleted {} tasks", task_uuids.len());
         Ok(())
     }
 
     fn modify_tasks(&self, task_uuids: &[Uuid], modifications: &str) -> Result<()> {
+        let words = shlex::split(modifications).ok_or_else(|| anyhow::anyhow!("Unable to parse modifications `{}`", modifications))?;
+        let modifications = Modifications::parse(&words, &self.uda_types)?;
+        if modifications.is_empty() {
+            return Err(anyhow::anyhow!("Additional text must be provided."));
+        }
+        
         let mut replica = self.replica.lock().unwrap();
+        let working_set = replica.working_set()?;
+        let context = FilterContext::new(&[]);
         
+        let mut ops = vec![Operation::UndoPoint];
         for &task_uuid in task_uuids {
-            // Convert UUID format
-            let tc_uuid = taskchampion::Uuid::from_bytes(task_uuid.as_bytes().clone());
-            
-            // Get the task and modify it
-            if let Some(task) = replica.get_task(tc_uuid)? {
-                let mut task_mut = task.into_mut(&mut replica);
-                apply_modifications(&mut task_mut, modifications)?;
+            if let Some(mut task) = replica.get_task_data(task_uuid)? {
+                modifications.apply(&mut task, &self.uda_types, &working_set, &context, &mut ops)?;
             }
         }
+        replica.commit_operations(ops)?;
         
-        log::info!("TaskChampion backend: Modified {} tasks with '{}'", task_uuids.len(), modifications);
+        log::info!("TaskChampion backend: Modified {} tasks", task_uuids.len());
         Ok(())
     }
 
     fn get_task_details(&self, task_uuid: Uuid) -> Result<Option<String>> {
         let mut replica = self.replica.lock().unwrap();
         
-        // Convert UUID format
-        let tc_uuid = taskchampion::Uuid::from_bytes(task_uuid.as_bytes().clone());
-        
-        if let Some(task) = replica.get_task(tc_uuid)? {
+        if let Some(task) = replica.get_task_data(task_uuid)? {
             // Convert task to task-hookrs format and then to JSON
-            if let Some(hookrs_task) = convert_taskchampion_to_hookrs(tc_uuid, task)? {
-                let json = serde_json::to_string_pretty(&hookrs_task)?;
-                log::info!("TaskChampion backend: Got details for task {}", task_uuid);
-                return Ok(Some(json));
-            }
+            let id = replica.working_set()?.by_uuid(task_uuid);
+            let dependencies = dependency_sets(&replica.all_task_data()?);
+            let hookrs_task = self.export_task(&task, id, &dependencies)?;
+            let json = serde_json::to_string_pretty(&hookrs_task)?;
+            log::info!("TaskChampion backend: Got details for task {}", task_uuid);
+            return Ok(Some(json));
         }
         
         log::info!("TaskChampion backend: No details found for task {}", task_uuid);
@@ -196,231 +405,834 @@ impl TaskBackend for TaskChampionBackend {
     }
 
     fn sync(&self) -> Result<()> {
-        // Placeholder implementation
-        // TODO: Implement actual synchronization with TaskChampion
+        let config = self.server_config.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("No sync server configured. Set uda.taskwarrior-tui.taskchampion.server-config in your taskrc")
+        })?;
+        
+        let mut server = self.server.lock().unwrap();
+        if server.is_none() {
+            *server = Some(parse_server_config(config)?.into_server()?);
+        }
+        
         log::info!("TaskChampion backend: Syncing tasks");
+        let mut replica = self.replica.lock().unwrap();
+        replica.sync(server.as_mut().unwrap(), false)?;
+        *self.exported_operations.lock().unwrap() = None;
+        log::info!("TaskChampion backend: Sync complete");
         Ok(())
     }
-}
 
-/// Convert a TaskChampion task to task-hookrs format
-fn convert_taskchampion_to_hookrs(
-    tc_uuid: taskchampion::Uuid,
-    tc_task: taskchampion::Task,
-) -> Result<Option<Task>> {
-    // Skip deleted tasks
-    if tc_task.get_status() == taskchampion::Status::Deleted {
-        return Ok(None);
+    fn undo_preview(&self) -> Result<Option<String>> {
+        let mut replica = self.replica.lock().unwrap();
+        let ops = replica.get_undo_operations()?;
+        if !ops.iter().any(|op| !matches!(op, Operation::UndoPoint)) {
+            return Ok(None);
+        }
+        
+        let mut changes: Vec<(Uuid, TaskChange)> = Vec::new();
+        for op in &ops {
+            let uuid = match op {
+                Operation::Create { uuid } | Operation::Delete { uuid, .. } | Operation::Update { uuid, .. } => *uuid,
+                Operation::UndoPoint => continue,
+            };
+            let index = match changes.iter().position(|(u, _)| *u == uuid) {
+                Some(index) => index,
+                None => {
+                    changes.push((uuid, TaskChange::default()));
+                    changes.len() - 1
+                }
+            };
+            changes[index].1.apply(op);
+        }
+        
+        let mut preview = Vec::new();
+        for (uuid, change) in changes {
+            let description = match replica.get_task_data(uuid)? {
+                Some(task) => task.get("description").map(String::from),
+                None => None,
+            }
+            .or_else(|| change.description());
+            let verb = if change.created {
+                "created"
+            } else if change.deleted {
+                "deleted"
+            } else {
+                "modified"
+            };
+            preview.push(format!("Task {} \"{}\" ({})", uuid, description.unwrap_or_default(), verb));
+            preview.extend(change.diff());
+        }
+        Ok(Some(preview.join("\n")))
     }
     
-    // Convert UUID - TaskChampion uses uuid::Uuid, task-hookrs uses uuid::Uuid
-    let uuid = Uuid::from_bytes(tc_uuid.as_bytes().clone());
+    fn undo(&self) -> Result<Option<Uuid>> {
+        let mut replica = self.replica.lock().unwrap();
+        let ops = replica.get_undo_operations()?;
+        let uuid = ops.iter().find_map(|op| match op {
+            Operation::Create { uuid } | Operation::Delete { uuid, .. } | Operation::Update { uuid, .. } => Some(*uuid),
+            Operation::UndoPoint => None,
+        });
+        if uuid.is_none() {
+            return Err(anyhow::anyhow!("Nothing to undo"));
+        }
+        
+        if !replica.commit_reversed_operations(ops)? {
+            return Err(anyhow::anyhow!("The task database changed while undoing, nothing was undone"));
+        }
+        *self.exported_operations.lock().unwrap() = None;
+        log::info!("TaskChampion backend: Undid last change");
+        Ok(uuid)
+    }
+
+    fn start_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        self.update_tasks(task_uuids, |task, ops| Ok(task.start(ops)?))?;
+        log::info!("TaskChampion backend: Started {} tasks", task_uuids.len());
+        Ok(())
+    }
     
-    // Convert status
-    let status = match tc_task.get_status() {
-        taskchampion::Status::Pending => TaskStatus::Pending,
-        taskchampion::Status::Completed => TaskStatus::Completed,
-        taskchampion::Status::Deleted => return Ok(None), // Skip deleted tasks
-    };
+    fn stop_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        self.update_tasks(task_uuids, |task, ops| Ok(task.stop(ops)?))?;
+        log::info!("TaskChampion backend: Stopped {} tasks", task_uuids.len());
+        Ok(())
+    }
     
-    // Build the task using TaskBuilder
-    let mut builder = TaskBuilder::default();
-    builder
-        .uuid(uuid)
-        .status(status)
-        .description(tc_task.get_description().to_string());
+    fn annotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        if annotation.trim().is_empty() {
+            return Err(anyhow::anyhow!("Additional text must be provided."));
+        }
+        self.update_tasks(task_uuids, |task, ops| {
+            let annotation = taskchampion::Annotation {
+                entry: Utc::now(),
+                description: annotation.to_string(),
+            };
+            Ok(task.add_annotation(annotation, ops)?)
+        })?;
+        log::info!("TaskChampion backend: Annotated {} tasks", task_uuids.len());
+        Ok(())
+    }
     
-    // Add tags if present
-    let tags: Vec<String> = tc_task.get_tags().map(|tag| tag.to_string()).collect();
-    if !tags.is_empty() {
-        // TODO: task-hookrs doesn't seem to have a simple way to set tags via builder
-        // We'll need to set them after building or find another approach
+    fn denotate_tasks(&self, task_uuids: &[Uuid], annotation: &str) -> Result<()> {
+        self.update_tasks(task_uuids, |task, ops| {
+            // Like `task denotate`, an exact match wins over a partial one
+            let annotations: Vec<_> = task.get_annotations().collect();
+            let found = annotations
+                .iter()
+                .find(|a| a.description == annotation)
+                .or_else(|| annotations.iter().find(|a| a.description.contains(annotation)))
+                .ok_or_else(|| anyhow::anyhow!("Did not find any matching annotation to be deleted for '{}'.", annotation))?;
+            Ok(task.remove_annotation(found.entry, ops)?)
+        })?;
+        log::info!("TaskChampion backend: Denotated {} tasks", task_uuids.len());
+        Ok(())
     }
     
-    // Build the task
-    let task = builder.build().map_err(|e| anyhow::anyhow!("Failed to build task: {}", e))?;
+    fn log_task(&self, description: &str, args: &[&str]) -> Result<()> {
+        let uuid = self.create_task(description, args, "completed")?;
+        log::info!("TaskChampion backend: Logged task {}", uuid);
+        Ok(())
+    }
     
-    Ok(Some(task))
-}
-
-/// Get the filter for a specific TaskWarrior report
-fn get_report_filter(report: &str) -> Option<String> {
-    // Execute `task show report.{report}.filter` to get the filter
-    let output = std::process::Command::new("task")
-        .arg("show")
-        .arg(format!("report.{}.filter", report))
-        .output()
-        .ok()?;
+    fn duplicate_tasks(&self, task_uuids: &[Uuid]) -> Result<()> {
+        let mut replica = self.replica.lock().unwrap();
+        let now = Utc::now().timestamp().to_string();
+        
+        let mut ops = vec![Operation::UndoPoint];
+        for &task_uuid in task_uuids {
+            if let Some(original) = replica.get_task_data(task_uuid)? {
+                let mut task = TaskData::create(Uuid::new_v4(), &mut ops);
+                for (property, value) in original.iter() {
+                    if !DUPLICATE_SKIPPED_PROPERTIES.contains(&property.as_str()) {
+                        task.update(property.as_str(), Some(value.clone()), &mut ops);
+                    }
+                }
+                task.update("status", Some("pending".to_string()), &mut ops);
+                task.update("entry", Some(now.clone()), &mut ops);
+                task.update("modified", Some(now.clone()), &mut ops);
+            }
+        }
+        replica.commit_operations(ops)?;
+        
+        log::info!("TaskChampion backend: Duplicated {} tasks", task_uuids.len());
+        Ok(())
+    }
     
-    if !output.status.success() {
-        return None;
+    fn edit_task(&self, task_uuid: Uuid) -> Result<()> {
+        let original = {
+            let mut replica = self.replica.lock().unwrap();
+            let task = replica.get_task_data(task_uuid)?.ok_or_else(|| anyhow::anyhow!("Task {} not found", task_uuid))?;
+            render_edit_file(&task, &self.uda_types)
+        };
+        
+        // Like `task edit`, hand the task to the user's editor as plain text
+        let path = std::env::temp_dir().join(format!("taskwarrior-tui-{}.task", task_uuid));
+        std::fs::write(&path, &original)?;
+        let edited = run_editor(self.editor.as_deref(), &path).and_then(|()| Ok(std::fs::read_to_string(&path)?));
+        let _ = std::fs::remove_file(&path);
+        let edited = edited?;
+        if edited == original {
+            log::info!("TaskChampion backend: Task {} not changed", task_uuid);
+            return Ok(());
+        }
+        
+        let mut replica = self.replica.lock().unwrap();
+        let mut task = replica.get_task_data(task_uuid)?.ok_or_else(|| anyhow::anyhow!("Task {} not found", task_uuid))?;
+        let mut ops = vec![Operation::UndoPoint];
+        apply_edit_file(&mut task, &edited, &self.uda_types, &mut ops)?;
+        replica.commit_operations(ops)?;
+        
+        log::info!("TaskChampion backend: Edited task {}", task_uuid);
+        Ok(())
     }
     
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    fn render_task_details(&self, task_uuids: &[Uuid], width: u16) -> Result<HashMap<Uuid, String>> {
+        let mut replica = self.replica.lock().unwrap();
+        let working_set = replica.working_set()?;
+        
+        let mut details = HashMap::new();
+        for &task_uuid in task_uuids {
+            let task = match replica.get_task_data(task_uuid)? {
+                Some(task) => task,
+                None => continue,
+            };
+            
+            let mut dependencies = Vec::new();
+            for property in task.properties() {
+                if let Some(uuid) = property.strip_prefix("dep_").and_then(|uuid| Uuid::parse_str(uuid).ok()) {
+                    let description = replica.get_task_data(uuid)?.and_then(|dep| dep.get("description").map(String::from));
+                    let id = working_set.by_uuid(uuid).map(|id| id.to_string()).unwrap_or_else(|| uuid.to_string());
+                    dependencies.push(format!("{} {}", id, description.unwrap_or_default()));
+                }
+            }
+            dependencies.sort();
+            
+            let rows = task_info_rows(&task, working_set.by_uuid(task_uuid), &dependencies, &self.uda_types);
+            details.insert(task_uuid, render_table(&rows, width));
+        }
+        Ok(details)
+    }
     
-    // Parse the output to extract the filter value
-    // Format is typically:
-    // Config Variable    Value
-    // ------------------ ----------------------------------
-    // report.next.filter status:pending -WAITING limit:page
-    let mut in_data_section = false;
+    fn list_contexts(&self) -> Result<Vec<Context>> {
+        Ok(self.contexts.lock().unwrap().clone())
+    }
     
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        
-        // Skip until we find the data section (after the header)
-        if trimmed.starts_with("---") {
-            in_data_section = true;
-            continue;
+    fn current_context(&self) -> Result<Option<Context>> {
+        Ok(self.contexts.lock().unwrap().iter().find(|context| context.active).cloned())
+    }
+    
+    fn set_context(&self, name: &str) -> Result<()> {
+        let mut contexts = self.contexts.lock().unwrap();
+        if name != "none" && !contexts.iter().any(|context| context.name == name) {
+            return Err(anyhow::anyhow!("Context '{}' not found.", name));
         }
-        
-        if in_data_section && trimmed.contains(&format!("report.{}.filter", report)) {
-            // Split by whitespace and take everything after the variable name
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 2 {
-                // Join all parts after the first (variable name) to get the full filter
-                let filter = parts[1..].join(" ");
-                if !filter.is_empty() {
-                    return Some(filter);
+        for context in contexts.iter_mut() {
+            context.active = context.name == name;
+        }
+        log::info!("TaskChampion backend: Context set to '{}'", name);
+        Ok(())
+    }
+}
+
+/// Net effect of the operations in an undo set on a single task
+#[derive(Default)]
+struct TaskChange {
+    created: bool,
+    deleted: bool,
+    /// Property to value before and after the change, in the order the properties were first changed
+    properties: Vec<(String, Option<String>, Option<String>)>,
+}
+
+impl TaskChange {
+    fn apply(&mut self, op: &Operation) {
+        match op {
+            Operation::Create { .. } => self.created = true,
+            Operation::Delete { old_task, .. } => {
+                self.deleted = true;
+                let mut old_task: Vec<_> = old_task.iter().collect();
+                old_task.sort();
+                for (property, value) in old_task {
+                    self.update(property, Some(value.clone()), None);
                 }
             }
+            Operation::Update {
+                property, old_value, value, ..
+            } => self.update(property, old_value.clone(), value.clone()),
+            Operation::UndoPoint => {}
+        }
+    }
+
+    fn update(&mut self, property: &str, old_value: Option<String>, value: Option<String>) {
+        match self.properties.iter_mut().find(|(p, _, _)| p == property) {
+            Some((_, _, new)) => *new = value,
+            None => self.properties.push((property.to_string(), old_value, value)),
+        }
+    }
+
+    fn description(&self) -> Option<String> {
+        self.properties
+            .iter()
+            .find(|(property, _, _)| property == "description")
+            .and_then(|(_, old, new)| new.clone().or_else(|| old.clone()))
+    }
+
+    /// Render the change as diff lines from the current state to the state after undo
+    /// (`-` for values undo removes, `+` for values it restores)
+    fn diff(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (property, old, new) in &self.properties {
+            // TaskChampion updates `modified` on every change, which is just noise here
+            if old == new || property == "modified" {
+                continue;
+            }
+            if let Some(new) = new {
+                lines.push(format!("- {}", display_property(property, new)));
+            }
+            if let Some(old) = old {
+                lines.push(format!("+ {}", display_property(property, old)));
+            }
+        }
+        lines
+    }
+}
+
+/// Render a TaskChampion property the way `task info` shows it
+fn display_property(property: &str, value: &str) -> String {
+    let date = |value: &str| match parse_timestamp(value) {
+        Some(date) => DateTime::<Utc>::from_naive_utc_and_offset(*date, Utc)
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => value.to_string(),
+    };
+    if let Some(tag) = property.strip_prefix("tag_") {
+        format!("tag: {}", tag)
+    } else if let Some(uuid) = property.strip_prefix("dep_") {
+        format!("depends: {}", uuid)
+    } else if let Some(entry) = property.strip_prefix("annotation_") {
+        format!("annotation: {} {}", date(entry), value)
+    } else if DATE_PROPERTIES.contains(&property) {
+        format!("{}: {}", property, date(value))
+    } else {
+        format!("{}: {}", property, value)
+    }
+}
+
+/// Format a timestamp property in local time, as `task info` shows dates
+fn display_date(value: &str) -> Option<String> {
+    let date = parse_timestamp(value)?;
+    Some(
+        DateTime::<Utc>::from_naive_utc_and_offset(*date, Utc)
+            .with_timezone(&Local)
+            .format(DISPLAY_DATE_FORMAT)
+            .to_string(),
+    )
+}
+
+/// The name/value rows `task info` shows for a task
+fn task_info_rows(task: &TaskData, id: Option<usize>, dependencies: &[String], uda_types: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut rows = Vec::new();
+    let mut push = |name: &str, value: String| rows.push((name.to_string(), value));
+    
+    if let Some(id) = id {
+        push("ID", id.to_string());
+    }
+    let mut description = vec![task.get("description").unwrap_or_default().to_string()];
+    let mut annotations: Vec<_> = task
+        .iter()
+        .filter_map(|(property, value)| Some((property.strip_prefix("annotation_")?, value)))
+        .collect();
+    annotations.sort();
+    for (entry, annotation) in annotations {
+        description.push(format!("  {} {}", display_date(entry).unwrap_or_default(), annotation));
+    }
+    push("Description", description.join("\n"));
+    
+    let mut status = task.get("status").unwrap_or("pending").to_string();
+    if let Some(first) = status.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    push("Status", status);
+    for (property, name) in &[("project", "Project"), ("priority", "Priority"), ("recur", "Recurrence"), ("parent", "Parent task")] {
+        if let Some(value) = task.get(property).filter(|value| !value.is_empty()) {
+            push(name, value.to_string());
         }
     }
+    if !dependencies.is_empty() {
+        push("This task is blocked by", dependencies.join("\n"));
+    }
+    
+    let dates = [
+        ("entry", "Entered"),
+        ("wait", "Waiting until"),
+        ("scheduled", "Scheduled"),
+        ("start", "Start"),
+        ("due", "Due"),
+        ("end", "End"),
+        ("until", "Until"),
+        ("modified", "Last modified"),
+    ];
+    for (property, name) in &dates {
+        if let Some(date) = task.get(property).and_then(display_date) {
+            push(name, date);
+        }
+    }
+    
+    let tags: Vec<&str> = task.properties().filter_map(|property| property.strip_prefix("tag_")).collect::<BTreeSet<_>>().into_iter().collect();
+    if !tags.is_empty() {
+        push("Tags", tags.join(" "));
+    }
+    push("UUID", task.get_uuid().to_string());
+    
+    let mut udas: Vec<_> = task
+        .iter()
+        .filter(|(property, value)| !is_core_property(property) && !property.contains('_') && !value.is_empty())
+        .collect();
+    udas.sort();
+    for (property, value) in udas {
+        let value = match uda_types.get(property).map(String::as_str) {
+            Some("date") => display_date(value).unwrap_or_else(|| value.clone()),
+            _ => value.clone(),
+        };
+        push(property, value);
+    }
+    rows
+}
+
+/// Lay out name/value rows as a two column table no wider than `width`
+fn render_table(rows: &[(String, String)], width: u16) -> String {
+    let name_width = rows.iter().map(|(name, _)| name.chars().count()).chain(std::iter::once("Name".len())).max().unwrap_or_default();
+    let value_width = (width as usize).saturating_sub(name_width + 1).max(10);
     
-    None
+    let mut lines = vec![
+        format!("{:<name_width$} Value", "Name", name_width = name_width),
+        format!("{} {}", "-".repeat(name_width), "-".repeat(value_width)),
+    ];
+    for (name, value) in rows {
+        let wrapped: Vec<String> = value.lines().flat_map(|line| wrap(line, value_width)).collect();
+        for (i, line) in wrapped.iter().enumerate() {
+            let name = if i == 0 { name.as_str() } else { "" };
+            lines.push(format!("{:<name_width$} {}", name, line, name_width = name_width).trim_end().to_string());
+        }
+    }
+    lines.join("\n")
 }
 
-/// Parse and apply TaskWarrior filter syntax
-fn task_matches_taskwarrior_filter(task: &Task, filter: &str) -> bool {
-    if filter.is_empty() {
-        return true;
+/// Wrap `line` at whitespace so that no piece is longer than `width` characters, unless a single word is
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+    let mut pieces = Vec::new();
+    let mut current = indent.clone();
+    for word in line.split_whitespace() {
+        let length = current.chars().count();
+        if length > indent.len() && length + 1 + word.chars().count() > width {
+            pieces.push(std::mem::replace(&mut current, indent.clone()));
+        }
+        if current.chars().count() > indent.len() {
+            current.push(' ');
+        }
+        current.push_str(word);
     }
+    pieces.push(current);
+    pieces
+}
+
+/// Render a task as the text file `edit_task` opens in the editor
+fn render_edit_file(task: &TaskData, uda_types: &HashMap<String, String>) -> String {
+    let value = |property: &str| task.get(property).unwrap_or_default().to_string();
+    let date = |property: &str| task.get(property).and_then(display_date).unwrap_or_default();
     
-    log::debug!("Applying filter '{}' to task: {}", filter, task.description());
+    let mut lines = vec![
+        "# Edit the task below, then save and quit the editor.".to_string(),
+        "# Lines starting with '#' are ignored. An empty value removes the attribute.".to_string(),
+        format!("# Dates are in local time ({}), or any date Taskwarrior understands.", DISPLAY_DATE_FORMAT),
+        format!("# UUID: {}", task.get_uuid()),
+        format!("Description: {}", value("description")),
+        format!("Project: {}", value("project")),
+        format!("Priority: {}", value("priority")),
+    ];
+    let tags: BTreeSet<&str> = task.properties().filter_map(|property| property.strip_prefix("tag_")).collect();
+    lines.push(format!("Tags: {}", tags.into_iter().collect::<Vec<_>>().join(" ")));
+    for property in &["due", "wait", "scheduled", "until", "start", "end"] {
+        lines.push(format!("{}: {}", capitalize(property), date(property)));
+    }
+    lines.push(format!("Recur: {}", value("recur")));
+    let dependencies: BTreeSet<&str> = task.properties().filter_map(|property| property.strip_prefix("dep_")).collect();
+    lines.push(format!("Depends: {}", dependencies.into_iter().collect::<Vec<_>>().join(" ")));
     
-    // Split filter into individual terms
-    let terms: Vec<&str> = filter.split_whitespace().collect();
+    let mut udas: Vec<(&String, &String)> = uda_types.iter().collect();
+    udas.sort();
+    for (name, kind) in udas {
+        let value = if kind == "date" { date(name) } else { value(name) };
+        lines.push(format!("UDA {}: {}", name, value));
+    }
     
-    for term in terms {
-        if term.starts_with("status:") {
-            let status_filter = &term[7..]; // Remove "status:"
-            let task_status = format!("{:?}", task.status()).to_lowercase();
-            if !task_status.contains(&status_filter.to_lowercase()) {
-                log::debug!("Task status '{}' doesn't match filter '{}'", task_status, status_filter);
-                return false;
-            }
-        } else if term.starts_with("-") && term.len() > 1 {
-            // Negative filter (exclude)
-            let exclude_term = &term[1..];
-            if exclude_term.to_uppercase() == "WAITING" {
-                // Check if task is waiting (has wait date)
-                if task.wait().is_some() {
-                    log::debug!("Task is waiting, excluded by '-WAITING' filter");
-                    return false;
-                }
-            }
-            // TODO: Add more negative filters
-        } else if term.starts_with("project:") {
-            let project_filter = &term[8..]; // Remove "project:"
-            if let Some(task_project) = task.project() {
-                if !task_project.to_lowercase().contains(&project_filter.to_lowercase()) {
-                    log::debug!("Task project '{}' doesn't match filter '{}'", task_project, project_filter);
-                    return false;
+    lines.push("# Annotations look like 'Annotation: <date> -- <text>'; add new ones without a date.".to_string());
+    let mut annotations: Vec<_> = task
+        .iter()
+        .filter_map(|(property, value)| Some((property.strip_prefix("annotation_")?, value)))
+        .collect();
+    annotations.sort();
+    for (entry, annotation) in annotations {
+        lines.push(format!("Annotation: {} -- {}", display_date(entry).unwrap_or_default(), annotation));
+    }
+    lines.push("Annotation: ".to_string());
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Apply the edited text of `render_edit_file` to `task`
+fn apply_edit_file(task: &mut TaskData, text: &str, uda_types: &HashMap<String, String>, ops: &mut Operations) -> Result<()> {
+    let context = FilterContext::new(&[]);
+    let parse_edited_date = |value: &str| -> Result<String> {
+        let local = NaiveDateTime::parse_from_str(value, DISPLAY_DATE_FORMAT)
+            .ok()
+            .and_then(|date| Local.from_local_datetime(&date).earliest());
+        match local {
+            Some(date) => Ok(date.timestamp().to_string()),
+            None => Ok(parse_date(value, &context)?.timestamp().to_string()),
+        }
+    };
+    
+    let mut updates: Vec<(String, Option<String>)> = Vec::new();
+    let mut tags = BTreeSet::new();
+    let mut dependencies = BTreeSet::new();
+    let mut annotations = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or_else(|| anyhow::anyhow!("Unrecognized line `{}`", line))?;
+        let (key, value) = (key.trim(), value.trim());
+        let optional = |value: &str| Some(value.to_string()).filter(|value| !value.is_empty());
+        
+        if let Some(name) = key.strip_prefix("UDA ") {
+            let value = match uda_types.get(name).map(String::as_str) {
+                Some("date") if !value.is_empty() => Some(parse_edited_date(value)?),
+                Some("numeric") if !value.is_empty() => {
+                    value.parse::<f64>().map_err(|_| anyhow::anyhow!("'{}' is not a numeric value for {}", value, name))?;
+                    Some(value.to_string())
                 }
-            } else {
-                // Task has no project but filter requires one
-                log::debug!("Task has no project but filter requires '{}'", project_filter);
-                return false;
-            }
-        } else if term.starts_with("limit:") {
-            // Limit filters are handled at a higher level, ignore here
+                _ => optional(value),
+            };
+            updates.push((name.to_string(), value));
             continue;
-        } else if term.starts_with("+") {
-            // Tag filter
-            let tag_filter = &term[1..];
-            if let Some(tags) = task.tags() {
-                let has_tag = tags.iter().any(|tag| tag.to_lowercase() == tag_filter.to_lowercase());
-                if !has_tag {
-                    log::debug!("Task doesn't have required tag '{}'", tag_filter);
-                    return false;
+        }
+        match key.to_lowercase().as_str() {
+            "description" if value.is_empty() => return Err(anyhow::anyhow!("A task must have a description.")),
+            property @ ("description" | "project" | "priority" | "recur") => updates.push((property.to_string(), optional(value))),
+            property @ ("due" | "wait" | "scheduled" | "until" | "start" | "end") => {
+                let value = if value.is_empty() { None } else { Some(parse_edited_date(value)?) };
+                updates.push((property.to_string(), value));
+            }
+            "tags" => tags.extend(value.split_whitespace().map(String::from)),
+            "depends" => {
+                for uuid in value.split_whitespace() {
+                    dependencies.insert(Uuid::parse_str(uuid).map_err(|_| anyhow::anyhow!("'{}' is not a task UUID", uuid))?);
                 }
-            } else {
-                log::debug!("Task has no tags but filter requires '{}'", tag_filter);
-                return false;
             }
+            "annotation" if value.is_empty() => {}
+            "annotation" => annotations.push(match value.split_once(" -- ") {
+                Some((entry, description)) => (Some(parse_edited_date(entry.trim())?), description.to_string()),
+                None => (None, value.to_string()),
+            }),
+            _ => return Err(anyhow::anyhow!("Unrecognized line `{}`", line)),
+        }
+    }
+    
+    // Collections are replaced wholesale, so drop whatever is no longer listed
+    let stale: Vec<String> = task
+        .properties()
+        .filter(|property| {
+            property.strip_prefix("tag_").is_some_and(|tag| !tags.contains(tag))
+                || property.strip_prefix("dep_").is_some_and(|dep| !dependencies.iter().any(|uuid| uuid.to_string() == dep))
+                || property.starts_with("annotation_")
+        })
+        .cloned()
+        .collect();
+    for property in stale {
+        updates.push((property, None));
+    }
+    for tag in tags {
+        taskchampion::Tag::from_str(&tag)?;
+        updates.push((format!("tag_{}", tag), Some(String::new())));
+    }
+    for uuid in dependencies {
+        updates.push((format!("dep_{}", uuid), Some(String::new())));
+    }
+    let mut now = Utc::now().timestamp();
+    for (entry, description) in annotations {
+        let entry = entry.unwrap_or_else(|| {
+            // New annotations need distinct timestamps, as the timestamp is part of the property name
+            now += 1;
+            now.to_string()
+        });
+        updates.retain(|(property, _)| property != &format!("annotation_{}", entry));
+        updates.push((format!("annotation_{}", entry), Some(description)));
+    }
+    
+    for (property, value) in updates {
+        if task.get(&property) != value.as_deref() {
+            task.update(property, value, ops);
         }
-        // TODO: Add more filter types as needed
     }
+    task.update("modified", Some(Utc::now().timestamp().to_string()), ops);
+    Ok(())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Run the editor `task edit` would use on `path`: the configured `editor`, `$VISUAL`, `$EDITOR` or `vi`
+fn run_editor(editor: Option<&str>, path: &std::path::Path) -> Result<()> {
+    let editor = editor
+        .map(String::from)
+        .into_iter()
+        .chain(["VISUAL", "EDITOR"].iter().filter_map(|var| std::env::var(var).ok()))
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = shlex::split(&editor).filter(|words| !words.is_empty()).ok_or_else(|| anyhow::anyhow!("Unable to parse editor `{}`", editor))?;
+    let program = words.remove(0);
     
-    true
+    let status = std::process::Command::new(&program).args(&words).arg(path).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Editor `{}` exited with {}", editor, status));
+    }
+    Ok(())
 }
 
-/// Extract limit value from filter (e.g., "limit:page" or "limit:10")
-fn extract_limit_from_filter(filter: &str) -> Option<usize> {
-    let terms: Vec<&str> = filter.split_whitespace().collect();
+/// Parse `uda.taskwarrior-tui.taskchampion.server-config`
+///
+/// Accepted forms are a local server directory, either as `local:<dir>` or as a plain path, and
+/// a taskchampion-sync-server URL carrying the replica credentials as query parameters:
+/// `https://sync.example.com?client_id=<uuid>&encryption_secret=<secret>`.
+fn parse_server_config(config: &str) -> Result<ServerConfig> {
+    let config = config.trim();
     
-    for term in terms {
-        if term.starts_with("limit:") {
-            let limit_value = &term[6..]; // Remove "limit:"
-            
-            // Handle special values
-            if limit_value == "page" {
-                // TaskWarrior's default page size is typically 25
-                return Some(25);
-            } else if let Ok(num) = limit_value.parse::<usize>() {
-                return Some(num);
+    if config.starts_with("http://") || config.starts_with("https://") {
+        let (url, query) = config.split_once('?').ok_or_else(|| {
+            anyhow::anyhow!("Sync server URL `{}` is missing `?client_id=<uuid>&encryption_secret=<secret>`", config)
+        })?;
+        let mut client_id = None;
+        let mut encryption_secret = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("client_id", value)) => client_id = Some(Uuid::parse_str(value)?),
+                Some(("encryption_secret", value)) => encryption_secret = Some(value.as_bytes().to_vec()),
+                _ => return Err(anyhow::anyhow!("Unknown sync server parameter `{}`", pair)),
             }
         }
+        return Ok(ServerConfig::Remote {
+            url: url.to_string(),
+            client_id: client_id.ok_or_else(|| anyhow::anyhow!("Sync server URL is missing `client_id`"))?,
+            encryption_secret: encryption_secret.ok_or_else(|| anyhow::anyhow!("Sync server URL is missing `encryption_secret`"))?,
+        });
     }
     
-    None
+    let server_dir = config.strip_prefix("local:").unwrap_or(config);
+    if server_dir.is_empty() {
+        return Err(anyhow::anyhow!("Local sync server directory is empty"));
+    }
+    Ok(ServerConfig::Local {
+        server_dir: PathBuf::from(shellexpand::tilde(server_dir).into_owned()),
+    })
 }
 
-/// Parse a task argument in the format "key:value" or just "key" for tags
-fn parse_task_arg(arg: &str) -> Option<(String, String)> {
-    if let Some(pos) = arg.find(':') {
-        let key = arg[..pos].to_string();
-        let value = arg[pos + 1..].to_string();
-        Some((key, value))
-    } else {
-        // Treat as tag
-        Some((arg.to_string(), String::new()))
-    }
+/// Order tasks like `task export`: tasks in the working set by ID, then everything else by entry date
+fn export_order(task: &Task) -> (bool, Option<u64>, NaiveDateTime) {
+    (task.id().unwrap_or(0) == 0, task.id(), *task.entry().clone())
+}
+
+/// The pending tasks, and the pending tasks another pending task depends on
+fn dependency_sets(tasks: &HashMap<Uuid, TaskData>) -> (HashSet<Uuid>, HashSet<Uuid>) {
+    let is_pending = |task: &TaskData| matches!(task.get("status"), Some("pending") | Some("waiting"));
+    let pending: HashSet<Uuid> = tasks.iter().filter(|(_, task)| is_pending(task)).map(|(uuid, _)| *uuid).collect();
+    let blocking = tasks
+        .iter()
+        .filter(|(_, task)| is_pending(task))
+        .flat_map(|(_, task)| task.properties().filter_map(|p| p.strip_prefix("dep_")).filter_map(|uuid| Uuid::parse_str(uuid).ok()))
+        .filter(|uuid| pending.contains(uuid))
+        .collect();
+    (pending, blocking)
 }
 
-/// Apply modifications to a TaskChampion task
-fn apply_modifications(task: &mut taskchampion::TaskMut, modifications: &str) -> Result<()> {
-    let args: Vec<&str> = modifications.split_whitespace().collect();
+/// Convert a TaskChampion task to task-hookrs format, as `task export` would render it
+fn convert_taskchampion_to_hookrs(tc_task: &TaskData, id: Option<usize>, uda_types: &HashMap<String, String>) -> Result<Task> {
+    let date = |property: &str| tc_task.get(property).and_then(parse_timestamp);
+    let string = |property: &str| tc_task.get(property).filter(|value| !value.is_empty()).map(String::from);
     
-    for arg in args {
-        if let Some((key, value)) = parse_task_arg(arg) {
-            match key.as_str() {
-                "description" => {
-                    task.set_description(value)?;
-                }
-                "project" => {
-                    // TODO: TaskChampion doesn't seem to have project support in 0.4.1
-                    log::debug!("Project modification not yet implemented: {}", value);
-                }
-                "priority" => {
-                    // TODO: TaskChampion doesn't seem to have priority support in 0.4.1
-                    log::debug!("Priority modification not yet implemented: {}", value);
-                }
-                _ => {
-                    // Treat as tag
-                    if value.is_empty() {
-                        let tag = taskchampion::Tag::from_str(&key)?;
-                        task.add_tag(&tag)?;
-                    } else {
-                        // For now, just add as tag since we don't have UDA support
-                        let tag = taskchampion::Tag::from_str(&format!("{}:{}", key, value))?;
-                        task.add_tag(&tag)?;
-                    }
-                }
+    // Waiting is not a stored status since Taskwarrior 2.6, but `task export` still reports it
+    let is_waiting = date("wait").is_some_and(|wait| *wait > Utc::now().naive_utc());
+    let status = match tc_task.get("status").unwrap_or("pending") {
+        "pending" if is_waiting => TaskStatus::Waiting,
+        "pending" => TaskStatus::Pending,
+        "completed" => TaskStatus::Completed,
+        "deleted" => TaskStatus::Deleted,
+        "recurring" => TaskStatus::Recurring,
+        status => {
+            log::warn!("Task {} has unknown status '{}', treating it as pending", tc_task.get_uuid(), status);
+            TaskStatus::Pending
+        }
+    };
+    
+    let mut tags = Vec::new();
+    let mut annotations = Vec::new();
+    let mut depends = Vec::new();
+    let mut uda = UDA::default();
+    
+    for (property, value) in tc_task.iter() {
+        if let Some(tag) = property.strip_prefix("tag_") {
+            tags.push(tag.to_string());
+        } else if let Some(entry) = property.strip_prefix("annotation_") {
+            if let Some(entry) = parse_timestamp(entry) {
+                annotations.push(Annotation::new(entry, value.clone()));
             }
+        } else if let Some(uuid) = property.strip_prefix("dep_") {
+            depends.push(Uuid::parse_str(uuid)?);
+        } else if !is_core_property(property) && !value.is_empty() {
+            uda.insert(property.clone(), convert_uda_value(value, uda_types.get(property).map(String::as_str)));
         }
     }
+    tags.sort();
+    annotations.sort_by_key(|annotation| **annotation.entry());
+    depends.sort();
     
-    Ok(())
+    // Tasks outside the working set are exported with ID 0
+    let id = Some(id.unwrap_or(0) as u64);
+    let entry = date("entry").or_else(|| date("modified")).unwrap_or_else(|| Date::from(Utc::now().naive_utc()));
+    
+    Ok(Task::new(
+        id,
+        status,
+        tc_task.get_uuid(),
+        entry,
+        tc_task.get("description").unwrap_or_default().to_string(),
+        Some(annotations).filter(|a| !a.is_empty()),
+        Some(depends).filter(|d| !d.is_empty()),
+        date("due"),
+        date("end"),
+        tc_task.get("imask").and_then(|imask| imask.parse().ok()),
+        string("mask"),
+        date("modified"),
+        tc_task.get("parent").and_then(|parent| Uuid::parse_str(parent).ok()),
+        string("priority"),
+        string("project"),
+        string("recur"),
+        date("scheduled"),
+        date("start"),
+        Some(tags).filter(|t| !t.is_empty()),
+        date("until"),
+        date("wait"),
+        // Filled in by `TaskChampionBackend::export_task`, which knows the other tasks
+        Some(0.0),
+        uda,
+    ))
+}
+
+/// Convert a task-hookrs task into TaskChampion properties, the inverse of `convert_taskchampion_to_hookrs`
+fn convert_hookrs_to_taskmap(task: &Task) -> HashMap<String, String> {
+    let mut taskmap = HashMap::new();
+    let timestamp = |date: &Date| date.and_utc().timestamp().to_string();
+    
+    let status = match task.status() {
+        TaskStatus::Pending | TaskStatus::Waiting => "pending",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Deleted => "deleted",
+        TaskStatus::Recurring => "recurring",
+    };
+    taskmap.insert("status".to_string(), status.to_string());
+    taskmap.insert("description".to_string(), task.description().clone());
+    taskmap.insert("entry".to_string(), timestamp(task.entry()));
+    
+    let dates = [
+        ("due", task.due()),
+        ("end", task.end()),
+        ("modified", task.modified()),
+        ("scheduled", task.scheduled()),
+        ("start", task.start()),
+        ("until", task.until()),
+        ("wait", task.wait()),
+    ];
+    for (property, date) in dates {
+        if let Some(date) = date {
+            taskmap.insert(property.to_string(), timestamp(date));
+        }
+    }
+    
+    let strings = [
+        ("mask", task.mask()),
+        ("priority", task.priority()),
+        ("project", task.project()),
+        ("recur", task.recur()),
+    ];
+    for (property, value) in strings {
+        if let Some(value) = value {
+            taskmap.insert(property.to_string(), value.clone());
+        }
+    }
+    if let Some(imask) = task.imask() {
+        taskmap.insert("imask".to_string(), imask.to_string());
+    }
+    if let Some(parent) = task.parent() {
+        taskmap.insert("parent".to_string(), parent.to_string());
+    }
+    
+    for tag in task.tags().into_iter().flatten() {
+        taskmap.insert(format!("tag_{}", tag), String::new());
+    }
+    for annotation in task.annotations().into_iter().flatten() {
+        taskmap.insert(format!("annotation_{}", timestamp(annotation.entry())), annotation.description().clone());
+    }
+    for uuid in task.depends().into_iter().flatten() {
+        taskmap.insert(format!("dep_{}", uuid), String::new());
+    }
+    
+    for (name, value) in task.uda() {
+        let value = match value {
+            // Date UDAs are stored as timestamps, like the core date properties
+            UDAValue::Str(s) => match NaiveDateTime::parse_from_str(s, EXPORT_DATE_FORMAT) {
+                Ok(date) => date.and_utc().timestamp().to_string(),
+                Err(_) => s.clone(),
+            },
+            UDAValue::U64(n) => n.to_string(),
+            UDAValue::F64(n) => n.to_string(),
+        };
+        taskmap.insert(name.clone(), value);
+    }
+    
+    taskmap
+}
+
+/// Whether a TaskChampion property is mapped to a dedicated task-hookrs field
+fn is_core_property(property: &str) -> bool {
+    DATE_PROPERTIES.contains(&property)
+        || matches!(
+            property,
+            "status" | "description" | "imask" | "mask" | "parent" | "priority" | "project" | "recur" | "depends" | "tags"
+        )
 }
 
+/// Parse a TaskChampion timestamp (seconds since the epoch)
+fn parse_timestamp(value: &str) -> Option<Date> {
+    let seconds = value.parse::<i64>().ok()?;
+    DateTime::from_timestamp(seconds, 0).map(|date| Date::from(date.naive_utc()))
+}
+
+/// Convert a stored UDA value to the JSON type `task export` uses for `uda.<name>.type`
+fn convert_uda_value(value: &str, kind: Option<&str>) -> UDAValue {
+    match kind {
+        Some("numeric") => match value.parse::<u64>() {
+            Ok(n) => UDAValue::U64(n),
+            Err(_) => value.parse::<f64>().map(UDAValue::F64).unwrap_or_else(|_| UDAValue::Str(value.to_string())),
+        },
+        Some("date") => match parse_timestamp(value) {
+            Some(date) => UDAValue::Str(date.format(EXPORT_DATE_FORMAT).to_string()),
+            None => UDAValue::Str(value.to_string()),
+        },
+        _ => UDAValue::Str(value.to_string()),
+    }
+}
@@ -4,6 +4,7 @@ use super::*;
 use tempfile::TempDir;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use super::*;
 
@@ -17,10 +18,12 @@ mod tests {
     #[test]
     fn test_taskchampion_backend_creation() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
-        let backend = create_backend(BackendConfig::TaskChampion {
+        let backend = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
             data_dir: Some(temp_dir.path().to_path_buf()),
             server_config: None,
-        });
+            create_if_missing: true,
+            ..Default::default()
+        }));
         assert!(backend.is_ok(), "TaskChampion backend creation should succeed");
     }
 
@@ -49,7 +52,7 @@ mod tests {
     fn test_command_building_debug() {
         println!("=== Command Building Debug ===");
         
-        let backend = create_backend(BackendConfig::Cli).expect("Failed to create CLI backend");
+        let _backend = create_backend(BackendConfig::Cli).expect("Failed to create CLI backend");
         
         // Manually test the command building logic
         let mut cmd = std::process::Command::new("task");
@@ -133,17 +136,19 @@ mod tests {
     #[test]
     fn test_taskchampion_backend_basic_operations() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
-        let backend = create_backend(BackendConfig::TaskChampion {
+        let backend = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
             data_dir: Some(temp_dir.path().to_path_buf()),
             server_config: None,
-        }).expect("Failed to create TaskChampion backend");
+            create_if_missing: true,
+            ..Default::default()
+        })).expect("Failed to create TaskChampion backend");
 
         // Test export (should be empty initially)
         let initial_tasks = backend.export_tasks("", "next", "").expect("Export should succeed");
         println!("TaskChampion backend initial tasks: {}", initial_tasks.len());
 
         // Test adding a task
-        let add_result = backend.add_task("Test task from unit test", &["test", "unit"]);
+        let add_result = backend.add_task("Test task from unit test", &["+test", "+unit"]);
         assert!(add_result.is_ok(), "Add task should succeed: {:?}", add_result);
 
         // Test export again (should now have 1 task)
@@ -175,7 +180,7 @@ mod tests {
                 println!("Default backend is CLI");
             }
             #[cfg(feature = "taskchampion-backend")]
-            BackendConfig::TaskChampion { .. } => {
+            BackendConfig::TaskChampion(_) => {
                 println!("Default backend is TaskChampion (feature enabled)");
             }
         }
@@ -199,10 +204,12 @@ mod tests {
                 {
                     let data_dir = Some(std::path::PathBuf::from("/Users/emiller/Library/Mobile Documents/iCloud~com~mav~taskchamp/Documents/task"));
                     println!("   Would use TaskChampion backend with data_dir: {:?}", data_dir);
-                    BackendConfig::TaskChampion {
+                    BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
                         data_dir,
                         server_config: None,
-                    }
+                        create_if_missing: true,
+                        ..Default::default()
+                    })
                 }
                 #[cfg(not(feature = "taskchampion-backend"))]
                 {
@@ -327,8 +334,8 @@ mod tests {
             Ok(tasks) => {
                 println!("📋 'all' report returned {} tasks", tasks.len());
                 if !tasks.is_empty() {
-                    println!("   First task: {} ({})", tasks[0].description(), 
-                            format!("{:?}", tasks[0].status()));
+                    println!("   First task: {} ({:?})", tasks[0].description(), 
+                            tasks[0].status());
                 }
             }
             Err(e) => {
@@ -351,10 +358,12 @@ mod tests {
         
         // Test TaskChampion backend with correct data directory
         println!("2. Testing TaskChampion backend...");
-        let tc_backend = create_backend(BackendConfig::TaskChampion {
+        let tc_backend = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
             data_dir: Some(std::path::PathBuf::from("/Users/emiller/Library/Mobile Documents/iCloud~com~mav~taskchamp/Documents/task")),
             server_config: None,
-        }).expect("Failed to create TaskChampion backend");
+            create_if_missing: true,
+            ..Default::default()
+        })).expect("Failed to create TaskChampion backend");
         
         let tc_tasks = tc_backend.export_tasks("", "next", "").expect("TaskChampion backend export failed");
         println!("   TaskChampion backend: {} tasks", tc_tasks.len());
@@ -367,7 +376,7 @@ mod tests {
             
             // This might be expected due to different implementation details
             // The important thing is that both return > 0 tasks
-            if cli_tasks.len() > 0 && tc_tasks.len() > 0 {
+            if !cli_tasks.is_empty() && !tc_tasks.is_empty() {
                 println!("   ✅ Both backends return tasks (counts may differ due to implementation)");
             } else {
                 panic!("One or both backends returned no tasks");
@@ -391,4 +400,464 @@ mod tests {
         
         println!("   ✅ Both backends successfully filter for pending tasks");
     }
-}
\ No newline at end of file
+
+    /// Tasks as produced by `task export rc.json.depends.array=on` (Taskwarrior 3)
+    #[cfg(feature = "taskchampion-backend")]
+    const CLI_EXPORT: &str = r#"[
+{"id":1,"description":"Write quarterly report","due":"20240301T170000Z","entry":"20240101T090000Z","estimate":3,"modified":"20240110T100000Z","priority":"H","project":"work.reports","reviewed":"20240105T120000Z","scheduled":"20240215T080000Z","start":"20240110T100000Z","status":"pending","uuid":"aaaaaaaa-0000-4000-8000-000000000001","annotations":[{"entry":"20240102T100000Z","description":"outline done"},{"entry":"20240103T110000Z","description":"waiting on numbers"}],"depends":["aaaaaaaa-0000-4000-8000-000000000002"],"tags":["next","office"],"urgency":15.2},
+{"id":2,"description":"Collect numbers","entry":"20240101T090500Z","estimate":1.5,"modified":"20240101T090500Z","project":"work","status":"pending","uuid":"aaaaaaaa-0000-4000-8000-000000000002","area":"finance","urgency":3.1},
+{"id":3,"description":"Water plants","due":"20240105T000000Z","entry":"20240101T080000Z","mask":"--","modified":"20240106T080000Z","recur":"weekly","status":"recurring","until":"20250101T000000Z","uuid":"aaaaaaaa-0000-4000-8000-000000000003","urgency":2},
+{"id":4,"description":"Water plants","due":"20240112T000000Z","entry":"20240106T080000Z","imask":1,"modified":"20240106T080000Z","parent":"aaaaaaaa-0000-4000-8000-000000000003","recur":"weekly","status":"pending","uuid":"aaaaaaaa-0000-4000-8000-000000000004","urgency":8},
+{"id":5,"description":"Renew passport","entry":"20240101T080000Z","modified":"20240101T080000Z","status":"waiting","uuid":"aaaaaaaa-0000-4000-8000-000000000005","wait":"29991231T000000Z","urgency":0},
+{"id":0,"description":"Book flights","end":"20240120T150000Z","entry":"20240115T090000Z","modified":"20240120T150000Z","status":"completed","uuid":"aaaaaaaa-0000-4000-8000-000000000006","tags":["travel"],"urgency":0.8},
+{"id":0,"description":"Old idea","end":"20240121T150000Z","entry":"20240116T090000Z","modified":"20240121T150000Z","status":"deleted","uuid":"aaaaaaaa-0000-4000-8000-000000000007","urgency":0}
+]"#;
+
+    #[cfg(feature = "taskchampion-backend")]
+    fn round_trip_backend(temp_dir: &TempDir) -> taskchampion::TaskChampionBackend {
+        let uda_types = [("estimate", "numeric"), ("reviewed", "date"), ("area", "string")]
+            .iter()
+            .map(|(name, kind)| (name.to_string(), kind.to_string()))
+            .collect();
+        taskchampion::TaskChampionBackend::new(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            uda_types,
+            create_if_missing: true,
+            ..Default::default()
+        })
+        .expect("Failed to create TaskChampion backend")
+    }
+
+    /// Serialize a task the way `task export` does, without the fields the backend does not own
+    #[cfg(feature = "taskchampion-backend")]
+    fn comparable(task: &task_hookrs::task::Task) -> serde_json::Value {
+        let mut value = serde_json::to_value(task).expect("Failed to serialize task");
+        let object = value.as_object_mut().unwrap();
+        object.remove("urgency");
+        object.remove("id");
+        value
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_round_trip_cli_export() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        let expected = task_hookrs::import::import(CLI_EXPORT.as_bytes()).expect("Failed to parse fixture");
+
+        backend.import_tasks(&expected).expect("Import should succeed");
+        let exported = backend.export_tasks("", "all", "").expect("Export should succeed");
+
+        assert_eq!(exported.len(), expected.len());
+        for task in &expected {
+            let actual = exported
+                .iter()
+                .find(|t| t.uuid() == task.uuid())
+                .unwrap_or_else(|| panic!("Task {} missing from export", task.uuid()));
+            assert_eq!(comparable(actual), comparable(task));
+        }
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_export_ids_and_uda_types() {
+        use task_hookrs::uda::UDAValue;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        let tasks = task_hookrs::import::import(CLI_EXPORT.as_bytes()).expect("Failed to parse fixture");
+        backend.import_tasks(&tasks).expect("Import should succeed");
+
+        let exported = backend.export_tasks("", "all", "").expect("Export should succeed");
+        let ids: Vec<u64> = exported.iter().map(|t| t.id().unwrap()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5, 0, 0]);
+
+        let report = &exported[0];
+        assert_eq!(report.uda().get("estimate"), Some(&UDAValue::U64(3)));
+        assert_eq!(report.uda().get("reviewed"), Some(&UDAValue::Str("20240105T120000Z".to_string())));
+        assert_eq!(exported[1].uda().get("estimate"), Some(&UDAValue::F64(1.5)));
+        assert_eq!(exported[1].uda().get("area"), Some(&UDAValue::Str("finance".to_string())));
+        assert_eq!(exported[4].status(), &task_hookrs::status::TaskStatus::Waiting);
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_add_task_sets_attributes() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+
+        backend.add_task("Plan offsite", &["project:work.events", "priority:M", "+planning"]).expect("Add should succeed");
+        let tasks = backend.export_tasks("", "next", "").expect("Export should succeed");
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id(), Some(1));
+        assert_eq!(tasks[0].project().map(String::as_str), Some("work.events"));
+        assert_eq!(tasks[0].priority().map(String::as_str), Some("M"));
+        assert!(tasks[0].modified().is_some());
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_sync_through_local_server() {
+        let server_dir = TempDir::new().expect("Failed to create temp dir");
+        let server_config = Some(format!("local:{}", server_dir.path().display()));
+        let replica_a = TempDir::new().expect("Failed to create temp dir");
+        let replica_b = TempDir::new().expect("Failed to create temp dir");
+        let backend_a = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+            data_dir: Some(replica_a.path().to_path_buf()),
+            server_config: server_config.clone(),
+            create_if_missing: true,
+            ..Default::default()
+        }))
+        .expect("Failed to create TaskChampion backend");
+        let backend_b = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+            data_dir: Some(replica_b.path().to_path_buf()),
+            server_config,
+            create_if_missing: true,
+            ..Default::default()
+        }))
+        .expect("Failed to create TaskChampion backend");
+
+        backend_a.add_task("Synced task", &["project:home"]).expect("Add should succeed");
+        backend_a.sync().expect("Sync of first replica should succeed");
+        backend_b.sync().expect("Sync of second replica should succeed");
+
+        let tasks = backend_b.export_tasks("", "next", "").expect("Export should succeed");
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description(), "Synced task");
+        assert_eq!(tasks[0].project().map(String::as_str), Some("home"));
+        assert_eq!(tasks[0].id(), Some(1));
+
+        // Changes flow back the other way too
+        backend_b.mark_done(&[*tasks[0].uuid()]).expect("Done should succeed");
+        backend_b.sync().expect("Sync of second replica should succeed");
+        backend_a.sync().expect("Sync of first replica should succeed");
+        assert!(backend_a.export_tasks("status:pending", "next", "").expect("Export should succeed").is_empty());
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_sync_configuration_errors() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = |server_config: &str| BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            server_config: Some(server_config.to_string()),
+            create_if_missing: true,
+            ..Default::default()
+        });
+
+        assert!(create_backend(config("https://sync.example.com")).is_err());
+        assert!(create_backend(config("https://sync.example.com?client_id=not-a-uuid&encryption_secret=s")).is_err());
+        assert!(create_backend(config("https://sync.example.com?client_id=aaaaaaaa-0000-4000-8000-000000000001")).is_err());
+        assert!(create_backend(config(
+            "https://sync.example.com?client_id=aaaaaaaa-0000-4000-8000-000000000001&encryption_secret=s"
+        ))
+        .is_ok());
+
+        let backend = create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            server_config: None,
+            create_if_missing: true,
+            ..Default::default()
+        }))
+        .expect("Failed to create TaskChampion backend");
+        assert!(backend.sync().is_err(), "Sync without a server should report an error");
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_refuses_to_create_database() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = |create_if_missing| BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            server_config: None,
+            create_if_missing,
+            ..Default::default()
+        });
+
+        let err = create_backend(config(false)).err().expect("Opening a missing database should fail");
+        assert!(err.to_string().contains("taskchampion.sqlite3"), "{}", err);
+        assert!(!temp_dir.path().join("taskchampion.sqlite3").exists());
+
+        std::fs::write(temp_dir.path().join("pending.data"), "").unwrap();
+        let err = create_backend(config(false)).err().expect("Opening a 2.x data directory should fail");
+        assert!(err.to_string().contains("import-v2"), "{}", err);
+
+        // Once the database exists it is opened as is
+        create_backend(config(true)).expect("Creating the database should succeed");
+        create_backend(config(false)).expect("Opening an existing database should succeed");
+    }
+
+    #[test]
+    fn test_resolve_data_dir() {
+        assert_eq!(resolve_data_dir(Some("/srv/tasks"), Some("/home/user/.task")), std::path::PathBuf::from("/srv/tasks"));
+        let home = dirs::home_dir().expect("No home directory");
+        assert_eq!(resolve_data_dir(Some("~/tasks"), None), home.join("tasks"));
+        let cwd = std::env::current_dir().expect("No working directory");
+        assert_eq!(resolve_data_dir(Some("tasks"), None), cwd.join("tasks"));
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_undo() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        assert_eq!(backend.undo_preview().expect("Preview should succeed"), None);
+
+        backend.add_task("Undo me", &["project:home"]).expect("Add should succeed");
+        let uuid = *backend.export_tasks("", "all", "").expect("Export should succeed")[0].uuid();
+        backend.modify_tasks(&[uuid], "project:work").expect("Modify should succeed");
+
+        let preview = backend.undo_preview().expect("Preview should succeed").expect("There should be a change to undo");
+        assert!(preview.starts_with(&format!("Task {} \"Undo me\" (modified)", uuid)), "{}", preview);
+        assert!(preview.contains("- project: work\n+ project: home"), "{}", preview);
+
+        assert_eq!(backend.undo().expect("Undo should succeed"), Some(uuid));
+        let tasks = backend.export_tasks("", "all", "").expect("Export should succeed");
+        assert_eq!(tasks[0].project().map(String::as_str), Some("home"));
+
+        // Undoing the add removes the task again
+        let preview = backend.undo_preview().expect("Preview should succeed").expect("There should be a change to undo");
+        assert!(preview.contains("(created)"), "{}", preview);
+        backend.undo().expect("Undo should succeed");
+        assert!(backend.export_tasks("", "all", "").expect("Export should succeed").is_empty());
+        assert!(backend.undo().is_err(), "Undo with nothing to undo should fail");
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_export_changes() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        backend.add_task("Water plants", &["+home"]).expect("Add should succeed");
+        backend.add_task("Call mom", &[]).expect("Add should succeed");
+        let since = chrono::Utc::now() - chrono::Duration::seconds(1);
+
+        // Without an earlier export there is nothing to compare against
+        assert!(backend.export_changes("+home", "all", "", since).expect("Export should succeed").is_none());
+        let tasks = backend.export_tasks("+home", "all", "").expect("Export should succeed");
+        assert_eq!(tasks.len(), 1);
+
+        let call_mom = *backend.export_tasks("", "all", "").expect("Export should succeed")[1].uuid();
+        backend.modify_tasks(&[call_mom], "+home").expect("Modify should succeed");
+        let changes = backend.export_changes("+home", "all", "", since).expect("Export should succeed").expect("Changes should be known");
+        assert!(changes.modified.iter().any(|task| *task.uuid() == call_mom));
+        assert!(changes.matching.contains(&call_mom));
+        assert_eq!(changes.limit, None);
+
+        // Nothing is modified after the future
+        let future = chrono::Utc::now() + chrono::Duration::hours(1);
+        let changes = backend.export_changes("+home", "all", "", future).expect("Export should succeed").expect("Changes should be known");
+        assert!(changes.modified.is_empty());
+
+        // Undo reverts tasks without touching their modification date
+        backend.undo().expect("Undo should succeed");
+        assert!(backend.export_changes("+home", "all", "", since).expect("Export should succeed").is_none());
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_urgency() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = taskchampion::TaskChampionBackend::new(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            // Leave out the age, which changes while the test runs
+            urgency: [("urgency.age.coefficient", 0.0), ("urgency.user.tag.office.coefficient", 2.0)]
+                .iter()
+                .map(|(key, value)| (key.to_string(), *value))
+                .collect(),
+            create_if_missing: true,
+            ..Default::default()
+        })
+        .expect("Failed to create TaskChampion backend");
+        backend.add_task("Write report", &["project:work", "priority:H", "+next"]).expect("Add should succeed");
+        backend.add_task("Collect numbers", &["+office"]).expect("Add should succeed");
+        backend.add_task("Renew passport", &["due:now"]).expect("Add should succeed");
+
+        let urgency = |filter: &str| -> Vec<(u64, f64)> {
+            let tasks = backend.export_tasks(filter, "all", "").expect("Export should succeed");
+            tasks.iter().map(|task| (task.id().unwrap(), *task.urgency().expect("Urgency should be exported"))).collect()
+        };
+        let assert_urgency = |actual: Vec<(u64, f64)>, expected: &[(u64, f64)]| {
+            assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+            for ((id, actual), (expected_id, expected)) in actual.iter().zip(expected) {
+                assert_eq!(id, expected_id);
+                assert!((actual - expected).abs() < 0.01, "Task {} has urgency {}, expected {}", id, actual, expected);
+            }
+        };
+        // project 1.0, one tag 0.8, priority:H 6.0, +next 15.0; one tag and +office; due now
+        assert_urgency(urgency(""), &[(1, 22.8), (2, 2.8), (3, 8.8)]);
+
+        let report = *backend.export_tasks("1", "all", "").expect("Export should succeed")[0].uuid();
+        backend.modify_tasks(&[report], "depends:2").expect("Modify should succeed");
+        assert_urgency(urgency(""), &[(1, 17.8), (2, 10.8), (3, 8.8)]);
+        assert_urgency(urgency("urgency.over:10"), &[(1, 17.8), (2, 10.8)]);
+        assert_urgency(urgency("urgency < 10"), &[(3, 8.8)]);
+
+        let details = backend.get_task_details(report).expect("Details should load").expect("Task should exist");
+        assert!(details.contains("\"urgency\": 17.8"), "{}", details);
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_task_operations() {
+        use task_hookrs::uda::UDAValue;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        backend.add_task("Water plants", &["+home"]).expect("Add should succeed");
+        let uuid = *backend.export_tasks("", "all", "").expect("Export should succeed")[0].uuid();
+
+        backend.start_tasks(&[uuid]).expect("Start should succeed");
+        assert!(backend.export_tasks("+ACTIVE", "all", "").expect("Export should succeed").len() == 1);
+        backend.stop_tasks(&[uuid]).expect("Stop should succeed");
+        assert!(backend.export_tasks("+ACTIVE", "all", "").expect("Export should succeed").is_empty());
+
+        backend.annotate_tasks(&[uuid], "use rain water").expect("Annotate should succeed");
+        let task = backend.export_tasks("", "all", "").expect("Export should succeed").remove(0);
+        assert_eq!(task.annotations().expect("Task should be annotated")[0].description(), "use rain water");
+        assert!(backend.denotate_tasks(&[uuid], "snow").is_err(), "Denotate without a match should fail");
+        backend.denotate_tasks(&[uuid], "rain").expect("Denotate should succeed");
+        assert!(backend.export_tasks("", "all", "").expect("Export should succeed")[0].annotations().is_none());
+
+        backend
+            .modify_tasks(&[uuid], "'Water the plants' -home +garden due:2030-01-02 estimate:2 area:")
+            .expect("Modify should succeed");
+        let task = backend.export_tasks("", "all", "").expect("Export should succeed").remove(0);
+        assert_eq!(task.description(), "Water the plants");
+        assert_eq!(task.tags(), Some(&vec!["garden".to_string()]));
+        assert!(task.due().is_some());
+        assert_eq!(task.uda().get("estimate"), Some(&UDAValue::U64(2)));
+        assert!(backend.modify_tasks(&[uuid], "estimate:lots").is_err(), "Numeric UDAs should be validated");
+        assert!(backend.modify_tasks(&[uuid], "").is_err(), "Modify without modifications should fail");
+
+        backend.duplicate_tasks(&[uuid]).expect("Duplicate should succeed");
+        backend.log_task("Bought soil", &["project:garden"]).expect("Log should succeed");
+        let tasks = backend.export_tasks("", "all", "").expect("Export should succeed");
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[1].description(), "Water the plants");
+        assert_ne!(tasks[1].uuid(), &uuid);
+        assert_eq!(tasks[1].tags(), Some(&vec!["garden".to_string()]));
+        assert_eq!(tasks[2].status(), &task_hookrs::status::TaskStatus::Completed);
+        assert!(tasks[2].end().is_some());
+        assert!(backend.log_task("", &[]).is_err(), "Log without a description should fail");
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_edit_task() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = taskchampion::TaskChampionBackend::new(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            // `sed -i` stands in for the user's editor
+            editor: Some("sed -i -e 's/^Project:.*/Project: garden/' -e 's/^Tags:.*/Tags: outside/' -e 's/^Annotation: $/Annotation: wear gloves/'".to_string()),
+            create_if_missing: true,
+            ..Default::default()
+        })
+        .expect("Failed to create TaskChampion backend");
+        backend.add_task("Repot cactus", &["+home"]).expect("Add should succeed");
+        let uuid = *backend.export_tasks("", "all", "").expect("Export should succeed")[0].uuid();
+
+        backend.edit_task(uuid).expect("Edit should succeed");
+
+        let task = backend.export_tasks("", "all", "").expect("Export should succeed").remove(0);
+        assert_eq!(task.description(), "Repot cactus");
+        assert_eq!(task.project().map(String::as_str), Some("garden"));
+        assert_eq!(task.tags(), Some(&vec!["outside".to_string()]));
+        assert_eq!(task.annotations().expect("Task should be annotated")[0].description(), "wear gloves");
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_report_filters_and_contexts() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = taskchampion::TaskChampionBackend::new(taskchampion::TaskChampionConfig {
+            data_dir: Some(temp_dir.path().to_path_buf()),
+            report_filters: vec![("next".to_string(), "status:pending".to_string())].into_iter().collect(),
+            contexts: vec![Context {
+                name: "work".to_string(),
+                read_filter: "project:work".to_string(),
+                write_filter: "project:work".to_string(),
+                active: false,
+            }],
+            create_if_missing: true,
+            ..Default::default()
+        })
+        .expect("Failed to create TaskChampion backend");
+        backend.add_task("Write report", &["project:work"]).expect("Add should succeed");
+        backend.add_task("Mow lawn", &["project:home"]).expect("Add should succeed");
+        backend.log_task("Send invoice", &["project:work"]).expect("Log should succeed");
+
+        assert_eq!(backend.export_tasks("", "next", "").expect("Export should succeed").len(), 2);
+        assert_eq!(backend.export_tasks("", "all", "").expect("Export should succeed").len(), 3);
+
+        assert_eq!(backend.current_context().expect("Context should load"), None);
+        backend.set_context("work").expect("Setting a context should succeed");
+        let context = backend.current_context().expect("Context should load").expect("A context should be active");
+        assert_eq!(context.name, "work");
+        let tasks = backend.export_tasks("", "next", &context.read_filter).expect("Export should succeed");
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description(), "Write report");
+
+        assert!(backend.set_context("play").is_err(), "Unknown contexts should be rejected");
+        backend.set_context("none").expect("Clearing the context should succeed");
+        assert!(backend.list_contexts().expect("Contexts should load").iter().all(|c| !c.active));
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_taskchampion_render_task_details() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let backend = round_trip_backend(&temp_dir);
+        let tasks = task_hookrs::import::import(CLI_EXPORT.as_bytes()).expect("Failed to parse fixture");
+        backend.import_tasks(&tasks).expect("Import should succeed");
+        let uuid = *tasks[0].uuid();
+
+        let details = backend.render_task_details(&[uuid, Uuid::new_v4()], 60).expect("Rendering should succeed");
+        assert_eq!(details.len(), 1);
+        let info = &details[&uuid];
+        let has_row = |row: &str| info.lines().any(|line| line.split_whitespace().eq(row.split_whitespace()));
+        assert!(has_row("Name Value"), "{}", info);
+        assert!(info.lines().all(|line| line.chars().count() <= 60), "{}", info);
+        assert!(has_row("ID 1"), "{}", info);
+        assert!(has_row(&format!("UUID {}", uuid)), "{}", info);
+        assert!(has_row("This task is blocked by 2 Collect numbers"), "{}", info);
+        assert!(has_row("Tags next office"), "{}", info);
+        assert!(has_row("estimate 3"), "{}", info);
+    }
+
+    #[cfg(feature = "taskchampion-backend")]
+    #[test]
+    fn test_backend_worker_runs_jobs_in_order() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let data_dir = temp_dir.path().to_path_buf();
+        let worker = worker::BackendWorker::spawn(move || {
+            create_backend(BackendConfig::TaskChampion(taskchampion::TaskChampionConfig {
+                data_dir: Some(data_dir),
+                create_if_missing: true,
+                ..Default::default()
+            }))
+        })
+        .expect("The worker should start");
+
+        // Queued jobs run before later ones, so the export sees the added task
+        worker.execute(|backend| backend.add_task("Queued task", &["+worker"]).expect("Add should succeed"));
+        let tasks = worker
+            .run_blocking(|backend| backend.export_tasks("+worker", "all", ""))
+            .expect("Export should succeed");
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description(), "Queued task");
+
+        let error = worker.run_blocking(|backend| backend.add_task("", &["due:eventually"]));
+        assert!(error.is_err(), "Job errors should be returned to the caller");
+    }
+
+    #[tokio::test]
+    async fn test_backend_worker_run_and_factory_errors() {
+        let worker = worker::BackendWorker::<str>::spawn(|| Ok("backend".into())).expect("The worker should start");
+        assert_eq!(worker.run(|backend| Ok(backend.len())).await.expect("The job should run"), 7);
+
+        let failed = worker::BackendWorker::<str>::spawn(|| Err(anyhow::anyhow!("no database")));
+        assert_eq!(failed.err().map(|err| err.to_string()), Some("no database".to_string()));
+    }
+}
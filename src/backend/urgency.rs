@@ -0,0 +1,135 @@
+//! Task urgency, computed from the `urgency.*` settings the way Taskwarrior does for `task export`
+
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use task_hookrs::status::TaskStatus;
+use task_hookrs::task::Task;
+use task_hookrs::uda::UDAValue;
+
+/// Taskwarrior's defaults for the settings `task show` lists under `urgency.`
+const DEFAULTS: &[(&str, f64)] = &[
+    ("urgency.active.coefficient", 4.0),
+    ("urgency.age.coefficient", 2.0),
+    ("urgency.age.max", 365.0),
+    ("urgency.annotations.coefficient", 1.0),
+    ("urgency.blocked.coefficient", -5.0),
+    ("urgency.blocking.coefficient", 8.0),
+    ("urgency.due.coefficient", 12.0),
+    ("urgency.project.coefficient", 1.0),
+    ("urgency.scheduled.coefficient", 5.0),
+    ("urgency.tags.coefficient", 1.0),
+    ("urgency.waiting.coefficient", -3.0),
+    ("urgency.uda.priority.H.coefficient", 6.0),
+    ("urgency.uda.priority.M.coefficient", 3.9),
+    ("urgency.uda.priority.L.coefficient", 1.8),
+    ("urgency.user.tag.next.coefficient", 15.0),
+];
+
+/// The urgency settings, e.g. `urgency.due.coefficient`, with Taskwarrior's defaults for the missing ones
+#[derive(Debug, Clone)]
+pub struct UrgencyCoefficients {
+    settings: HashMap<String, f64>,
+}
+
+impl Default for UrgencyCoefficients {
+    fn default() -> Self {
+        Self::new(HashMap::new())
+    }
+}
+
+impl UrgencyCoefficients {
+    /// Use `settings` over the defaults
+    pub fn new(settings: HashMap<String, f64>) -> Self {
+        let mut all: HashMap<String, f64> = DEFAULTS.iter().map(|(key, value)| (key.to_string(), *value)).collect();
+        all.extend(settings);
+        Self { settings: all }
+    }
+
+    fn get(&self, key: &str) -> f64 {
+        self.settings.get(key).copied().unwrap_or(0.0)
+    }
+
+    /// Urgency of `task` at `now`; whether it is blocked or blocking depends on the other tasks
+    pub fn urgency(&self, task: &Task, blocked: bool, blocking: bool, now: NaiveDateTime) -> f64 {
+        let flag = |set: bool| if set { 1.0 } else { 0.0 };
+        let tags = task.tags().map_or(&[][..], Vec::as_slice);
+
+        let mut value = self.get("urgency.project.coefficient") * flag(task.project().is_some())
+            + self.get("urgency.active.coefficient") * flag(task.start().is_some())
+            + self.get("urgency.scheduled.coefficient") * flag(task.scheduled().is_some_and(|scheduled| **scheduled < now))
+            + self.get("urgency.waiting.coefficient") * flag(*task.status() == TaskStatus::Waiting)
+            + self.get("urgency.blocked.coefficient") * flag(blocked)
+            + self.get("urgency.blocking.coefficient") * flag(blocking)
+            + self.get("urgency.annotations.coefficient") * count_factor(task.annotations().map_or(0, Vec::len))
+            + self.get("urgency.tags.coefficient") * count_factor(tags.len())
+            + self.get("urgency.due.coefficient") * task.due().map_or(0.0, |due| due_factor(**due, now))
+            + self.get("urgency.age.coefficient") * self.age_factor(**task.entry(), now);
+
+        for (key, coefficient) in &self.settings {
+            let Some(name) = key.strip_suffix(".coefficient") else {
+                continue;
+            };
+            let applies = if let Some(tag) = name.strip_prefix("urgency.user.tag.") {
+                tags.iter().any(|t| t == tag)
+            } else if let Some(project) = name.strip_prefix("urgency.user.project.") {
+                task.project().is_some_and(|p| p.starts_with(project))
+            } else if let Some(uda) = name.strip_prefix("urgency.uda.") {
+                match uda.split_once('.') {
+                    Some((uda, expected)) => attribute(task, uda).is_some_and(|value| value == expected),
+                    None => attribute(task, uda).is_some(),
+                }
+            } else {
+                false
+            };
+            if applies {
+                value += coefficient;
+            }
+        }
+        value
+    }
+
+    /// Grows linearly with the task's age up to `urgency.age.max` days
+    fn age_factor(&self, entry: NaiveDateTime, now: NaiveDateTime) -> f64 {
+        let max = self.get("urgency.age.max");
+        let age = (now - entry).num_seconds() as f64 / 86400.0;
+        if max == 0.0 || age > max {
+            1.0
+        } else {
+            age / max
+        }
+    }
+}
+
+/// 0.8, 0.9 and 1.0 for one, two and more annotations or tags
+fn count_factor(count: usize) -> f64 {
+    match count {
+        0 => 0.0,
+        1 => 0.8,
+        2 => 0.9,
+        _ => 1.0,
+    }
+}
+
+/// 0.2 two weeks before the due date, rising to 1.0 a week after it
+fn due_factor(due: NaiveDateTime, now: NaiveDateTime) -> f64 {
+    let days_overdue = (now - due).num_seconds() as f64 / 86400.0;
+    if days_overdue >= 7.0 {
+        1.0
+    } else if days_overdue >= -14.0 {
+        (days_overdue + 14.0) * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+/// A UDA, or priority, which `task_hookrs` stores as a field of its own
+fn attribute(task: &Task, name: &str) -> Option<String> {
+    if name == "priority" {
+        return task.priority().cloned();
+    }
+    task.uda().get(name).map(|value| match value {
+        UDAValue::Str(s) => s.clone(),
+        UDAValue::U64(n) => n.to_string(),
+        UDAValue::F64(n) => n.to_string(),
+    })
+}
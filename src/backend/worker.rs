@@ -0,0 +1,92 @@
+//! Runs a task backend on a thread of its own
+//!
+//! Backend calls block: the CLI backend waits for `task` and its hooks, and a TaskChampion
+//! replica is neither `Send` nor cheap to query. `BackendWorker` builds the backend on a
+//! dedicated thread and runs jobs there one at a time, in the order they were submitted, so the
+//! UI keeps drawing and reading keys while tasks are exported or modified.
+
+use anyhow::{anyhow, Context as _, Result};
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::oneshot;
+
+use super::TaskBackend;
+
+/// A job run on the worker thread with the backend
+pub type Job<B> = Box<dyn FnOnce(&B) + Send>;
+
+/// Handle to a backend living on a dedicated thread
+///
+/// The thread exits once the handle is dropped and the queued jobs have run.
+pub struct BackendWorker<B: ?Sized + 'static = dyn TaskBackend> {
+    jobs: mpsc::Sender<Job<B>>,
+}
+
+impl<B: ?Sized + 'static> BackendWorker<B> {
+    /// Start the worker thread and build the backend on it with `factory`
+    ///
+    /// Returns once the backend exists, with the factory's error if it could not be built.
+    pub fn spawn<F>(factory: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<Box<B>> + Send + 'static,
+    {
+        let (jobs, queue) = mpsc::channel::<Job<B>>();
+        let (ready, started) = mpsc::channel();
+        thread::Builder::new()
+            .name("taskwarrior-tui-backend".to_string())
+            .spawn(move || {
+                let backend = match factory() {
+                    Ok(backend) => backend,
+                    Err(err) => {
+                        ready.send(Err(err)).ok();
+                        return;
+                    }
+                };
+                ready.send(Ok(())).ok();
+                for job in queue {
+                    job(backend.as_ref());
+                }
+            })
+            .context("Unable to start the backend thread")?;
+        started.recv().context("The backend thread exited while creating the backend")??;
+        Ok(Self { jobs })
+    }
+
+    /// Queue `job` without waiting for it
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce(&B) + Send + 'static,
+    {
+        if self.jobs.send(Box::new(job)).is_err() {
+            log::error!("The backend thread has exited, dropping a backend job");
+        }
+    }
+
+    /// Queue `job` and wait asynchronously for its result
+    pub async fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&B) -> Result<T> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.execute(move |backend| {
+            sender.send(job(backend)).ok();
+        });
+        receiver.await.map_err(|_| anyhow!("The backend thread exited before finishing the job"))?
+    }
+
+    /// Queue `job` and block the calling thread until it has run
+    ///
+    /// Unlike `run`, this may be called from synchronous code inside an async runtime.
+    pub fn run_blocking<T, F>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&B) -> Result<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        self.execute(move |backend| {
+            sender.send(job(backend)).ok();
+        });
+        receiver.recv().map_err(|_| anyhow!("The backend thread exited before finishing the job"))?
+    }
+}
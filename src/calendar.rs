@@ -1,7 +1,6 @@
 // Based on https://gist.github.com/diwic/5c20a283ca3a03752e1a27b0f3ebfa30
 // See https://old.reddit.com/r/rust/comments/4xneq5/the_calendar_example_challenge_ii_why_eddyb_all/
 
-const COL_WIDTH: usize = 21;
 use chrono::{Datelike, Duration, Local, Month, NaiveDate};
 use ratatui::{
   buffer::Buffer,
@@ -21,6 +20,9 @@ pub struct Calendar<'a> {
   pub today_style: Style,
   pub start_on_monday: bool,
   pub title_background_color: Color,
+  /// Day under the cursor; the calendar scrolls so that its month is visible
+  pub selected: Option<NaiveDate>,
+  pub selected_style: Style,
 }
 
 impl<'a> Default for Calendar<'a> {
@@ -37,6 +39,8 @@ impl<'a> Default for Calendar<'a> {
       today_style: Style::default(),
       start_on_monday: false,
       title_background_color: Color::Reset,
+      selected: None,
+      selected_style: Style::default().add_modifier(Modifier::REVERSED),
     }
   }
 }
@@ -84,6 +88,31 @@ impl<'a> Calendar<'a> {
     self.start_on_monday = start_on_monday;
     self
   }
+
+  pub fn selected(mut self, selected: NaiveDate) -> Self {
+    self.selected = Some(selected);
+    self
+  }
+
+  pub fn selected_style(mut self, selected_style: Style) -> Self {
+    self.selected_style = selected_style;
+    self
+  }
+
+  /// Rows of months that always fit in `height`, assuming every month spans six weeks
+  fn month_rows_shown(height: u16) -> usize {
+    let mut y = 3;
+    let mut rows = 0;
+    loop {
+      // Month names, weekdays, six weeks and the gap below
+      y += 10;
+      rows += 1;
+      if y + 8 > height {
+        return rows;
+      }
+      y += 1;
+    }
+  }
 }
 
 impl Widget for Calendar<'_> {
@@ -129,6 +158,14 @@ impl Widget for Calendar<'_> {
     if self.months_per_row > area.width as usize / 8 / 3 || self.months_per_row == 0 {
       self.months_per_row = area.width as usize / 8 / 3;
     }
+    if let Some(selected) = self.selected.filter(|selected| selected.year() == year && self.months_per_row > 0) {
+      // Start at a later row of months when the selected month would not fit otherwise
+      let row = selected.month0() as usize / self.months_per_row;
+      let shown = Self::month_rows_shown(area.height);
+      if row >= shown {
+        start_m = (row + 1 - shown) * self.months_per_row;
+      }
+    }
     let mut y = area.y;
     y += 1;
 
@@ -195,7 +232,10 @@ impl Widget for Calendar<'_> {
             if let Some(i) = index {
               style = self.date_style[i].1;
             }
-            if d.1 == Local::now().date_naive() {
+            if d.0.month() == d.1.month() && Some(d.1) == self.selected {
+              let style = if d.1 == Local::now().date_naive() { self.today_style } else { style };
+              buf.set_string(x, y, s, style.patch(self.selected_style));
+            } else if d.1 == Local::now().date_naive() {
               buf.set_string(x, y, s, self.today_style);
             } else {
               buf.set_string(x, y, s, style);
@@ -0,0 +1,295 @@
+//! Ex-style command line
+//!
+//! A line names a command, or any prefix of its name that no other command starts with, and
+//! gives its arguments; a line that is only a task id jumps to that task. The same lines make up
+//! the named commands of `uda.taskwarrior-tui.commands.<name>`, which keys can be bound to.
+
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, Result};
+use task_hookrs::{task::Task, uda::UDAValue};
+
+/// Every command, in the order they are completed in
+pub const COMMANDS: &[&str] = &[
+  "modify", "done", "delete", "filter", "sort", "report", "context", "mark", "unmark", "write", "quit",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommand {
+  /// Select the task with this id
+  Jump(u64),
+  /// Modify the tasks with these ids, or the selected tasks when there are none
+  Modify { ids: Vec<u64>, modifications: String },
+  Done(Vec<u64>),
+  Delete(Vec<u64>),
+  /// Replace the filter of the report
+  Filter(String),
+  /// Sort the report by these keys, or in its own order when there are none
+  Sort(Vec<SortKey>),
+  Report(String),
+  /// Switch to a context, or to none with `none`
+  Context(String),
+  /// Mark the tasks of the report that match a filter, or all of them without one
+  Mark(String),
+  Unmark(String),
+  /// Write the tasks of the report to a file as a JSON array
+  Write(String),
+  Quit,
+}
+
+/// Parse a command line, with or without its leading `:`
+pub fn parse(line: &str) -> Result<ExCommand> {
+  let line = line.trim();
+  let line = line.strip_prefix(':').unwrap_or(line).trim_start();
+  if let Ok(id) = line.parse::<u64>() {
+    return Ok(ExCommand::Jump(id));
+  }
+  let (name, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+  let arguments = arguments.trim();
+  if name.is_empty() {
+    return Err(anyhow!("No command given"));
+  }
+  let command = command_name(name)?;
+  let required = |what: &str| {
+    if arguments.is_empty() {
+      Err(anyhow!("`{}` needs {}", command, what))
+    } else {
+      Ok(arguments.to_string())
+    }
+  };
+  Ok(match command {
+    "modify" => {
+      let (ids, modifications) = split_ids(arguments)?;
+      if modifications.is_empty() {
+        return Err(anyhow!("`modify` needs modifications"));
+      }
+      ExCommand::Modify {
+        ids,
+        modifications: modifications.to_string(),
+      }
+    }
+    "done" | "delete" => {
+      let (ids, rest) = split_ids(arguments)?;
+      if !rest.is_empty() {
+        return Err(anyhow!("`{}` only takes task ids, not `{}`", command, rest));
+      }
+      if command == "done" {
+        ExCommand::Done(ids)
+      } else {
+        ExCommand::Delete(ids)
+      }
+    }
+    "filter" => ExCommand::Filter(arguments.to_string()),
+    "sort" => ExCommand::Sort(parse_sort_keys(arguments)?),
+    "report" => ExCommand::Report(required("a report")?),
+    "context" => ExCommand::Context(required("a context, or `none`")?),
+    "mark" => ExCommand::Mark(arguments.to_string()),
+    "unmark" => ExCommand::Unmark(arguments.to_string()),
+    "write" => ExCommand::Write(required("a file")?),
+    "quit" => ExCommand::Quit,
+    command => return Err(anyhow!("Unknown command `{}`", command)),
+  })
+}
+
+/// The command `name` stands for, which is either its full name or the start of no other command
+pub fn command_name(name: &str) -> Result<&'static str> {
+  if let Some(command) = COMMANDS.iter().find(|command| **command == name) {
+    return Ok(command);
+  }
+  let mut candidates = COMMANDS.iter().filter(|command| command.starts_with(name));
+  match (candidates.next(), candidates.next()) {
+    (Some(command), None) => Ok(command),
+    (Some(_), Some(_)) => Err(anyhow!("`{}` is the start of several commands", name)),
+    (None, _) => Err(anyhow!("Unknown command `{}`", name)),
+  }
+}
+
+/// Split the leading task ids, such as `12 15`, `12,15` or `12-15`, from the rest of the arguments
+fn split_ids(arguments: &str) -> Result<(Vec<u64>, &str)> {
+  let mut ids = vec![];
+  let mut rest = arguments;
+  while let Some(word) = rest.split_whitespace().next() {
+    if !word.starts_with(|c: char| c.is_ascii_digit()) || !word.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '-') {
+      break;
+    }
+    for part in word.split(',').filter(|part| !part.is_empty()) {
+      let (first, last) = part.split_once('-').unwrap_or((part, part));
+      let (first, last) = (first.parse::<u64>(), last.parse::<u64>());
+      match (first, last) {
+        (Ok(first), Ok(last)) if first <= last => ids.extend(first..=last),
+        _ => return Err(anyhow!("`{}` is not a task id or range", part)),
+      }
+    }
+    rest = rest.trim_start()[word.len()..].trim_start();
+  }
+  Ok((ids, rest))
+}
+
+/// A column the report is sorted by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+  pub column: String,
+  pub descending: bool,
+}
+
+/// Parse sort keys written as in `report.<name>.sort`, e.g. `due+,urgency-`; a column without
+/// a sign sorts in ascending order
+pub fn parse_sort_keys(value: &str) -> Result<Vec<SortKey>> {
+  value
+    .split(|c: char| c == ',' || c.is_whitespace())
+    .filter(|key| !key.is_empty())
+    .map(|key| {
+      let (column, descending) = match key.strip_suffix('-') {
+        Some(column) => (column, true),
+        None => (key.strip_suffix('+').unwrap_or(key), false),
+      };
+      // `/` in `report.<name>.sort` starts a break, which has no meaning here
+      let column = column.trim_end_matches('/');
+      if column.is_empty() {
+        return Err(anyhow!("`{}` names no column to sort by", key));
+      }
+      Ok(SortKey {
+        column: column.to_string(),
+        descending,
+      })
+    })
+    .collect()
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+  Number(f64),
+  Text(String),
+}
+
+fn sort_value(task: &Task, column: &str, priorities: &[String]) -> Option<SortValue> {
+  let date = |date: Option<&task_hookrs::date::Date>| date.map(|date| SortValue::Number(date.and_utc().timestamp() as f64));
+  match column {
+    "id" => task.id().map(|id| SortValue::Number(id as f64)),
+    "urgency" => task.urgency().map(|urgency| SortValue::Number(*urgency)),
+    "due" => date(task.due()),
+    "scheduled" => date(task.scheduled()),
+    "wait" => date(task.wait()),
+    "start" => date(task.start()),
+    "until" => date(task.until()),
+    "end" => date(task.end()),
+    "entry" => date(Some(task.entry())),
+    "modified" => date(task.modified()),
+    // Priorities listed first in `uda.priority.values` are the highest
+    "priority" => task.priority().map(|priority| match priorities.iter().position(|value| value == priority) {
+      Some(i) => SortValue::Number((priorities.len() - i) as f64),
+      None => SortValue::Text(priority.clone()),
+    }),
+    "project" => task.project().map(|project| SortValue::Text(project.clone())),
+    "description" => Some(SortValue::Text(task.description().clone())),
+    "status" => Some(SortValue::Text(task.status().to_string())),
+    "tags" => task.tags().filter(|tags| !tags.is_empty()).map(|tags| SortValue::Text(tags.join(" "))),
+    uda => task.uda().get(uda).map(|value| match value {
+      UDAValue::Str(s) => SortValue::Text(s.clone()),
+      UDAValue::U64(u) => SortValue::Number(*u as f64),
+      UDAValue::F64(f) => SortValue::Number(*f),
+    }),
+  }
+}
+
+/// Sort tasks by `keys`, keeping the order of tasks that compare equal
+pub fn sort_tasks(tasks: &mut [Task], keys: &[SortKey], priorities: &[String]) {
+  tasks.sort_by(|a, b| compare_tasks(a, b, keys, priorities));
+}
+
+/// Compare two tasks by `keys`; tasks without a value for a column come after the others either way
+pub fn compare_tasks(a: &Task, b: &Task, keys: &[SortKey], priorities: &[String]) -> Ordering {
+  for key in keys {
+    let ordering = match (sort_value(a, &key.column, priorities), sort_value(b, &key.column, priorities)) {
+      (Some(a), Some(b)) => {
+        let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+        if key.descending {
+          ordering.reverse()
+        } else {
+          ordering
+        }
+      }
+      (Some(_), None) => Ordering::Less,
+      (None, Some(_)) => Ordering::Greater,
+      (None, None) => Ordering::Equal,
+    };
+    if ordering != Ordering::Equal {
+      return ordering;
+    }
+  }
+  Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_command_line() {
+    assert_eq!(parse(":12").unwrap(), ExCommand::Jump(12));
+    assert_eq!(parse("done 12 15").unwrap(), ExCommand::Done(vec![12, 15]));
+    assert_eq!(parse(":del 3-5,8").unwrap(), ExCommand::Delete(vec![3, 4, 5, 8]));
+    assert_eq!(parse(":do").unwrap(), ExCommand::Done(vec![]));
+    assert_eq!(
+      parse(":mod 4 +work due:tomorrow").unwrap(),
+      ExCommand::Modify {
+        ids: vec![4],
+        modifications: "+work due:tomorrow".to_string()
+      }
+    );
+    assert_eq!(parse(":filter +work project:home").unwrap(), ExCommand::Filter("+work project:home".to_string()));
+    assert_eq!(parse(":filter").unwrap(), ExCommand::Filter(String::new()));
+    assert_eq!(
+      parse(":sort due+,urgency-").unwrap(),
+      ExCommand::Sort(vec![
+        SortKey {
+          column: "due".to_string(),
+          descending: false
+        },
+        SortKey {
+          column: "urgency".to_string(),
+          descending: true
+        }
+      ])
+    );
+    assert_eq!(parse(":mark project:foo").unwrap(), ExCommand::Mark("project:foo".to_string()));
+    assert_eq!(parse(":q").unwrap(), ExCommand::Quit);
+
+    assert!(parse(":").is_err());
+    assert!(parse(":nope").is_err());
+    // `d` alone starts both `done` and `delete`
+    assert!(parse(":d").is_err());
+    assert!(parse(":report").is_err());
+    assert!(parse(":done 3 +work").is_err());
+    assert!(parse(":done 5-3").is_err());
+    assert!(parse(":modify 3").is_err());
+  }
+
+  #[test]
+  fn test_sort_tasks() {
+    let task = |description: &str, fields: &str| -> Task {
+      serde_json::from_str(&format!(
+        r#"{{"uuid":"{}","status":"pending","entry":"20240501T000000Z","description":"{}"{}}}"#,
+        uuid::Uuid::new_v4(),
+        description,
+        fields
+      ))
+      .unwrap()
+    };
+    let mut tasks = vec![
+      task("a", r#","priority":"L","due":"20240510T000000Z""#),
+      task("b", r#","priority":"H""#),
+      task("c", r#","due":"20240505T000000Z""#),
+      task("d", r#","priority":"H","due":"20240520T000000Z""#),
+    ];
+    let priorities = vec!["H".to_string(), "M".to_string(), "L".to_string(), String::new()];
+    let descriptions = |tasks: &[Task]| tasks.iter().map(|task| task.description().clone()).collect::<Vec<_>>().join("");
+
+    sort_tasks(&mut tasks, &parse_sort_keys("due").unwrap(), &priorities);
+    assert_eq!(descriptions(&tasks), "cadb");
+    sort_tasks(&mut tasks, &parse_sort_keys("due-").unwrap(), &priorities);
+    assert_eq!(descriptions(&tasks), "dacb");
+    sort_tasks(&mut tasks, &parse_sort_keys("priority-,description-").unwrap(), &priorities);
+    assert_eq!(descriptions(&tasks), "dbac");
+  }
+}
@@ -59,6 +59,12 @@ pub struct CompletionList {
   pub helper: TaskwarriorTuiCompletionHelper,
 }
 
+impl Default for CompletionList {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl CompletionList {
   pub fn new() -> CompletionList {
     CompletionList {
@@ -185,4 +191,12 @@ impl CompletionList {
     }
     self.pos = self.current.len();
   }
+
+  /// Complete `current` with the candidates inserted for `context` only
+  pub fn input_in_context(&mut self, current: String, context: &str) {
+    self.helper.input = String::new();
+    self.helper.context = context.to_string();
+    self.current = current;
+    self.pos = self.current.len();
+  }
 }
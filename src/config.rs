@@ -1,11 +1,17 @@
-use std::{collections::HashMap, str};
+use std::{
+  collections::{BTreeMap, HashMap},
+  str,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context as _, Result};
+use chrono::NaiveDate;
 use ratatui::{
   style::{Color, Modifier, Style},
   symbols::{bar::FULL, line::DOUBLE_VERTICAL},
 };
 
+use crate::backend::Context;
+
 trait TaskWarriorBool {
   fn get_bool(&self) -> Option<bool>;
 }
@@ -35,13 +41,47 @@ impl TaskWarriorBool for str {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub struct Uda {
-  label: String,
-  kind: String,
-  values: Option<Vec<String>>,
-  default: Option<String>,
-  urgency: Option<f64>,
+  pub name: String,
+  pub label: String,
+  pub kind: String,
+  pub values: Option<Vec<String>>,
+  pub default: Option<String>,
+  pub urgency: Option<f64>,
+}
+
+/// A holiday defined with `holiday.<id>.name` and either `holiday.<id>.date`, or
+/// `holiday.<id>.start` and `holiday.<id>.end` for a holiday lasting several days
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holiday {
+  pub name: String,
+  pub start: NaiveDate,
+  /// Last day of the holiday, inclusive
+  pub end: NaiveDate,
+}
+
+impl Holiday {
+  pub fn contains(&self, date: NaiveDate) -> bool {
+    self.start <= date && date <= self.end
+  }
+}
+
+/// A report defined with `report.<name>.columns`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+  pub name: String,
+  pub description: String,
+  /// Filter the report starts with, including a `uda.taskwarrior-tui.task-report.<name>.filter` override
+  pub filter: String,
+  /// `report.<name>.sort`, e.g. `urgency-`
+  pub sort: String,
+}
+
+/// A command line defined with `uda.taskwarrior-tui.commands.<name>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedCommand {
+  pub name: String,
+  pub line: String,
 }
 
 #[derive(Debug)]
@@ -50,6 +90,7 @@ pub struct Config {
   pub color: HashMap<String, Style>,
   pub filter: String,
   pub data_location: String,
+  pub editor: Option<String>,
   pub obfuscate: bool,
   pub print_empty_columns: bool,
   pub due: usize,
@@ -85,6 +126,12 @@ pub struct Config {
   pub uda_selection_blink: bool,
   pub uda_selection_reverse: bool,
   pub uda_calendar_months_per_row: usize,
+  pub uda_agenda_days: usize,
+  pub uda_board_group_by: String,
+  pub uda_board_columns: Vec<String>,
+  /// Reports shown in panes next to the main report at startup
+  pub uda_task_report_split: Vec<String>,
+  pub uda_task_report_favorites: Vec<String>,
   pub uda_style_context_active: Style,
   pub uda_style_report_selection: Style,
   pub uda_style_calendar_title: Style,
@@ -99,6 +146,7 @@ pub struct Config {
   pub uda_style_report_completion_pane_highlight: Style,
   pub uda_style_timewarrior_tracking: Style,
   pub uda_shortcuts: Vec<String>,
+  pub uda_commands: Vec<NamedCommand>,
   pub uda_change_focus_rotate: bool,
   pub uda_background_process: String,
   pub uda_background_process_period: usize,
@@ -110,6 +158,11 @@ pub struct Config {
   pub uda_task_report_duration_human_readable: bool,
   pub uda_context_menu_select_on_move: bool,
   pub uda: Vec<Uda>,
+  pub report_filters: HashMap<String, String>,
+  pub urgency_coefficients: HashMap<String, f64>,
+  pub reports: Vec<Report>,
+  pub contexts: Vec<Context>,
+  pub holidays: Vec<Holiday>,
 }
 
 impl Config {
@@ -128,10 +181,12 @@ impl Config {
       format!("{} ", filter)
     };
     let data_location = Self::get_data_location(data);
+    let editor = Self::get_config("editor", data).filter(|editor| !editor.is_empty());
     let due = Self::get_due(data);
     let weekstart = Self::get_weekstart(data);
     let rule_precedence_color = Self::get_rule_precedence_color(data);
     let uda_priority_values = Self::get_uda_priority_values(data);
+    let uda = Self::get_udas(data);
     let uda_tick_rate = Self::get_uda_tick_rate(data);
     let uda_change_focus_rotate = Self::get_uda_change_focus_rotate(data);
     let uda_auto_insert_double_quotes_on_add = Self::get_uda_auto_insert_double_quotes_on_add(data);
@@ -157,6 +212,11 @@ impl Config {
     let uda_selection_blink = Self::get_uda_selection_blink(data);
     let uda_selection_reverse = Self::get_uda_selection_reverse(data);
     let uda_calendar_months_per_row = Self::get_uda_months_per_row(data);
+    let uda_agenda_days = Self::get_uda_agenda_days(data);
+    let uda_board_group_by = Self::get_uda_board_group_by(data);
+    let uda_board_columns = Self::get_uda_board_columns(data);
+    let uda_task_report_split = Self::get_uda_task_report_split(data);
+    let uda_task_report_favorites = Self::get_uda_task_report_favorites(data);
     let uda_style_report_selection = Self::get_uda_style("report.selection", data);
     let uda_style_report_scrollbar = Self::get_uda_style("report.scrollbar", data);
     let uda_style_report_scrollbar_area = Self::get_uda_style("report.scrollbar.area", data);
@@ -169,6 +229,7 @@ impl Config {
     let uda_style_report_completion_pane_highlight = Self::get_uda_style("report.completion-pane-highlight", data);
     let uda_style_timewarrior_tracking = Self::get_uda_style("timewarrior.tracking", data);
     let uda_shortcuts = Self::get_uda_shortcuts(data);
+    let uda_commands = Self::get_uda_commands(data);
     let uda_background_process = Self::get_uda_background_process(data);
     let uda_background_process_period = Self::get_uda_background_process_period(data);
     let uda_style_report_selection = uda_style_report_selection.unwrap_or_default();
@@ -200,7 +261,10 @@ impl Config {
       .get("color.calendar.overdue")
       .cloned()
       .or_else(|| Some(Style::default().fg(Color::White).bg(Color::Red)));
-    let color_calendar_holiday = color.get("color.calendar.holiday").cloned();
+    let color_calendar_holiday = color
+      .get("color.calendar.holiday")
+      .cloned()
+      .or_else(|| Some(Style::default().fg(Color::Black).bg(Color::LightYellow)));
     let color_calendar_weekend = color
       .get("color.calendar.weekend")
       .cloned()
@@ -209,12 +273,18 @@ impl Config {
     let uda_backend = Self::get_uda_backend(data);
     let uda_taskchampion_data_dir = Self::get_uda_taskchampion_data_dir(data);
     let uda_taskchampion_server_config = Self::get_uda_taskchampion_server_config(data);
+    let report_filters = Self::get_report_filters(data);
+    let urgency_coefficients = Self::get_urgency_coefficients(data);
+    let reports = Self::get_reports(data)?;
+    let contexts = Self::get_contexts(data);
+    let holidays = Self::get_holidays(data);
 
     Ok(Self {
       enabled,
       color,
       filter,
       data_location,
+      editor,
       obfuscate,
       print_empty_columns,
       due,
@@ -246,6 +316,11 @@ impl Config {
       uda_selection_blink,
       uda_selection_reverse,
       uda_calendar_months_per_row,
+      uda_agenda_days,
+      uda_board_group_by,
+      uda_board_columns,
+      uda_task_report_split,
+      uda_task_report_favorites,
       uda_style_report_selection,
       uda_style_context_active,
       uda_style_calendar_title,
@@ -265,6 +340,7 @@ impl Config {
       uda_taskchampion_data_dir,
       uda_taskchampion_server_config,
       uda_shortcuts,
+      uda_commands,
       uda_background_process,
       uda_background_process_period,
       uda_quick_tag_name,
@@ -274,7 +350,12 @@ impl Config {
       uda_task_report_date_time_vague_more_precise,
       uda_task_report_duration_human_readable,
       uda_context_menu_select_on_move,
-      uda: vec![],
+      uda,
+      report_filters,
+      urgency_coefficients,
+      reports,
+      contexts,
+      holidays,
     })
   }
 
@@ -303,6 +384,22 @@ impl Config {
     v
   }
 
+  fn get_uda_commands(data: &str) -> Vec<NamedCommand> {
+    let mut v = vec![];
+    for line in data.split('\n') {
+      let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+      if let Some(name) = key.strip_prefix("uda.taskwarrior-tui.commands.") {
+        if !value.trim().is_empty() && !v.iter().any(|command: &NamedCommand| command.name == name) {
+          v.push(NamedCommand {
+            name: name.to_string(),
+            line: value.trim().to_string(),
+          });
+        }
+      }
+    }
+    v
+  }
+
   fn get_uda_style(config: &str, data: &str) -> Option<Style> {
     let c = format!("uda.taskwarrior-tui.style.{}", config);
     let s = Self::get_config(&c, data)?;
@@ -524,6 +621,26 @@ impl Config {
     data.split(',').map(ToString::to_string).collect::<Vec<_>>()
   }
 
+  fn get_udas(data: &str) -> Vec<Uda> {
+    data
+      .lines()
+      .filter_map(|line| line.strip_prefix("uda."))
+      .filter_map(|line| {
+        let (key, kind) = line.split_once(char::is_whitespace)?;
+        Some((key.strip_suffix(".type")?.to_string(), kind.trim().to_string()))
+      })
+      .filter(|(name, _)| !name.starts_with("taskwarrior-tui."))
+      .map(|(name, kind)| Uda {
+        label: Self::get_config(&format!("uda.{}.label", name), data).unwrap_or_else(|| name.clone()),
+        values: Self::get_config(&format!("uda.{}.values", name), data).map(|v| v.split(',').map(ToString::to_string).collect()),
+        default: Self::get_config(&format!("uda.{}.default", name), data),
+        urgency: Self::get_config(&format!("urgency.uda.{}.coefficient", name), data).and_then(|v| v.parse().ok()),
+        name,
+        kind,
+      })
+      .collect()
+  }
+
   fn get_filter(data: &str, report: &str) -> Result<String> {
     if report == "all" {
       Ok("".into())
@@ -534,6 +651,145 @@ impl Config {
     }
   }
 
+  /// `report.<name>.filter` for every report
+  fn get_report_filters(data: &str) -> HashMap<String, String> {
+    data
+      .lines()
+      .filter_map(|line| {
+        let (key, _) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let name = key.strip_prefix("report.")?.strip_suffix(".filter")?;
+        Some((name.to_string(), Self::get_config(key, data).unwrap_or_default()))
+      })
+      .collect()
+  }
+
+  /// The numeric `urgency.*` settings, e.g. `urgency.due.coefficient` and `urgency.age.max`
+  fn get_urgency_coefficients(data: &str) -> HashMap<String, f64> {
+    data
+      .lines()
+      .filter_map(|line| {
+        let (key, value) = line.split_once(char::is_whitespace)?;
+        if !key.starts_with("urgency.") {
+          return None;
+        }
+        Some((key.to_string(), value.trim().parse().ok()?))
+      })
+      .collect()
+  }
+
+  /// Reports that define their columns, sorted by name like `task reports`
+  fn get_reports(data: &str) -> Result<Vec<Report>> {
+    let mut reports: Vec<Report> = vec![];
+    for line in data.lines() {
+      let (key, _) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+      let Some(name) = key.strip_prefix("report.").and_then(|key| key.strip_suffix(".columns")) else {
+        continue;
+      };
+      reports.push(Report {
+        name: name.to_string(),
+        description: Self::get_config(&format!("report.{}.description", name), data).unwrap_or_default(),
+        filter: Self::get_filter(data, name)?,
+        sort: Self::get_config(&format!("report.{}.sort", name), data).unwrap_or_default(),
+      });
+    }
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+    reports.dedup_by(|a, b| a.name == b.name);
+    Ok(reports)
+  }
+
+  /// Contexts defined with `context.<name>.read` and `context.<name>.write`, or the older
+  /// `context.<name>` that sets both, sorted by name like `task context list`
+  fn get_contexts(data: &str) -> Vec<Context> {
+    let active = data
+      .lines()
+      .find_map(|line| match line.split_once(char::is_whitespace) {
+        Some(("context", name)) => Some(name.trim()),
+        _ => None,
+      })
+      .unwrap_or_default();
+
+    let mut contexts: BTreeMap<&str, Context> = BTreeMap::new();
+    for line in data.lines() {
+      let (key, _) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+      let name = match key.strip_prefix("context.") {
+        Some(name) => name,
+        None => continue,
+      };
+      let value = Self::get_config(key, data).unwrap_or_default();
+      let (name, read, write) = if let Some(name) = name.strip_suffix(".read") {
+        (name, Some(value), None)
+      } else if let Some(name) = name.strip_suffix(".write") {
+        (name, None, Some(value))
+      } else {
+        (name, Some(value.clone()), Some(value))
+      };
+      let context = contexts.entry(name).or_insert_with(|| Context {
+        name: name.to_string(),
+        active: name == active,
+        ..Context::default()
+      });
+      if let Some(read) = read {
+        context.read_filter = read;
+      }
+      if let Some(write) = write {
+        context.write_filter = write;
+      }
+    }
+    contexts.into_values().collect()
+  }
+
+  /// Holidays sorted by their first day; entries without a name or with dates that do not
+  /// follow `dateformat.holiday` are skipped
+  fn get_holidays(data: &str) -> Vec<Holiday> {
+    let format = Self::get_config("dateformat.holiday", data)
+      .filter(|format| !format.is_empty())
+      .unwrap_or_else(|| "YMD".to_string());
+    let format = Self::get_chrono_date_format(&format);
+    let date = |id: &str, field: &str| {
+      let value = Self::get_config(&format!("holiday.{}.{}", id, field), data)?;
+      NaiveDate::parse_from_str(&value, &format).ok()
+    };
+
+    let mut ids: Vec<&str> = data
+      .lines()
+      .filter_map(|line| {
+        let (key, _) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        key.strip_prefix("holiday.")?.strip_suffix(".name")
+      })
+      .collect();
+    ids.dedup();
+
+    let mut holidays: Vec<Holiday> = ids
+      .into_iter()
+      .filter_map(|id| {
+        let name = Self::get_config(&format!("holiday.{}.name", id), data)?;
+        let (start, end) = match date(id, "date") {
+          Some(date) => (date, date),
+          None => (date(id, "start")?, date(id, "end")?),
+        };
+        Some(Holiday { name, start, end })
+      })
+      .filter(|holiday| holiday.start <= holiday.end)
+      .collect();
+    holidays.sort_by_key(|holiday| holiday.start);
+    holidays
+  }
+
+  /// Convert a Taskwarrior date format such as `Y-M-D` to a chrono format string
+  fn get_chrono_date_format(format: &str) -> String {
+    format
+      .chars()
+      .map(|c| match c {
+        'Y' => "%Y".to_string(),
+        'y' => "%y".to_string(),
+        'M' | 'm' => "%m".to_string(),
+        'D' | 'd' => "%d".to_string(),
+        '%' => "%%".to_string(),
+        c => c.to_string(),
+      })
+      .collect()
+  }
+
   fn get_data_location(data: &str) -> String {
     Self::get_config("data.location", data)
       .context("Unable to parse `task show data.location`.")
@@ -788,6 +1044,41 @@ impl Config {
       .unwrap_or(4)
   }
 
+  fn get_uda_agenda_days(data: &str) -> usize {
+    Self::get_config("uda.taskwarrior-tui.agenda.days", data)
+      .unwrap_or_default()
+      .parse::<usize>()
+      .unwrap_or(7)
+  }
+
+  fn get_uda_board_group_by(data: &str) -> String {
+    Self::get_config("uda.taskwarrior-tui.board.group-by", data).unwrap_or_else(|| "status".to_string())
+  }
+
+  /// Reports opened in panes next to the main report at startup
+  fn get_uda_task_report_split(data: &str) -> Vec<String> {
+    Self::get_report_list(data, "uda.taskwarrior-tui.task-report.split")
+  }
+
+  /// Reports cycled through with the next and previous report keys; all reports when empty
+  fn get_uda_task_report_favorites(data: &str) -> Vec<String> {
+    Self::get_report_list(data, "uda.taskwarrior-tui.task-report.favorites")
+  }
+
+  fn get_report_list(data: &str, config: &str) -> Vec<String> {
+    Self::get_config(config, data)
+      .map(|reports| reports.split(',').map(|report| report.trim().to_string()).filter(|report| !report.is_empty()).collect())
+      .unwrap_or_default()
+  }
+
+  /// Column values of the board; an empty entry places the column for tasks without a value
+  fn get_uda_board_columns(data: &str) -> Vec<String> {
+    match Self::get_config("uda.taskwarrior-tui.board.columns", data) {
+      Some(columns) => columns.split(',').map(|column| column.trim().to_string()).collect(),
+      None => vec![],
+    }
+  }
+
   fn get_uda_quick_tag_name(data: &str) -> String {
     let tag_name = Self::get_config("uda.taskwarrior-tui.quick-tag.name", data);
     match tag_name {
@@ -955,4 +1246,104 @@ mod tests {
     );
     assert_eq!(config.unwrap(), "filter and test");
   }
+
+  #[test]
+  fn test_get_udas() {
+    let udas = Config::get_udas(
+      "uda.estimate.label Estimate\nuda.estimate.type numeric\nuda.priority.type string\nuda.priority.values H,M,L,\nuda.reviewed.type date\nuda.taskwarrior-tui.shortcuts.1.type string\nurgency.uda.estimate.coefficient 1.5",
+    );
+    let udas: Vec<_> = udas.iter().map(|u| (u.name.as_str(), u.kind.as_str(), u.label.as_str(), u.urgency)).collect();
+    assert_eq!(
+      udas,
+      vec![
+        ("estimate", "numeric", "Estimate", Some(1.5)),
+        ("priority", "string", "priority", None),
+        ("reviewed", "date", "reviewed", None),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_get_report_filters() {
+    let filters = Config::get_report_filters(
+      "report.next.description Most urgent tasks\nreport.next.filter status:pending -WAITING\n                   limit:page\nreport.all.filter\nreport.list.filter status:pending",
+    );
+    assert_eq!(filters.len(), 3);
+    assert_eq!(filters["next"], "status:pending -WAITING limit:page");
+    assert_eq!(filters["all"], "");
+    assert_eq!(filters["list"], "status:pending");
+  }
+
+  #[test]
+  fn test_get_urgency_coefficients() {
+    let coefficients = Config::get_urgency_coefficients(
+      "urgency.age.max                        365\nurgency.due.coefficient                12.0\nurgency.inherit                        no\nurgency.uda.priority.H.coefficient     6.0\nuda.priority.values H,M,L,",
+    );
+    assert_eq!(coefficients.len(), 3);
+    assert_eq!(coefficients["urgency.age.max"], 365.0);
+    assert_eq!(coefficients["urgency.due.coefficient"], 12.0);
+    assert_eq!(coefficients["urgency.uda.priority.H.coefficient"], 6.0);
+  }
+
+  #[test]
+  fn test_get_reports() {
+    let reports = Config::get_reports(
+      "report.next.columns id,description\nreport.next.description Most urgent tasks\nreport.next.filter status:pending -WAITING\nreport.next.sort urgency-\nreport.list.columns id\nreport.list.filter status:pending\nuda.taskwarrior-tui.task-report.list.filter +work",
+    )
+    .unwrap();
+    let reports: Vec<_> = reports
+      .iter()
+      .map(|r| (r.name.as_str(), r.description.as_str(), r.filter.as_str(), r.sort.as_str()))
+      .collect();
+    assert_eq!(
+      reports,
+      vec![("list", "", "+work", ""), ("next", "Most urgent tasks", "status:pending -WAITING", "urgency-")]
+    );
+  }
+
+  #[test]
+  fn test_get_contexts() {
+    let contexts = Config::get_contexts(
+      "context work\ncontext.work.read +work or project:Office\ncontext.work.write +work\ncontext.home project:Home\ncontext.affirmative yes",
+    );
+    let contexts: Vec<_> = contexts
+      .iter()
+      .map(|c| (c.name.as_str(), c.read_filter.as_str(), c.write_filter.as_str(), c.active))
+      .collect();
+    assert_eq!(
+      contexts,
+      vec![
+        ("affirmative", "yes", "yes", false),
+        ("home", "project:Home", "project:Home", false),
+        ("work", "+work or project:Office", "+work", true),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_get_holidays() {
+    let holidays = Config::get_holidays(
+      "holiday.xmas.name Christmas Day\nholiday.xmas.date 20241225\nholiday.summer.name Summer break\nholiday.summer.start 20240701\nholiday.summer.end 20240714\nholiday.broken.name Broken\nholiday.broken.date 2024-13-01",
+    );
+    let day = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    assert_eq!(
+      holidays,
+      vec![
+        Holiday {
+          name: "Summer break".to_string(),
+          start: day(7, 1),
+          end: day(7, 14),
+        },
+        Holiday {
+          name: "Christmas Day".to_string(),
+          start: day(12, 25),
+          end: day(12, 25),
+        },
+      ]
+    );
+    assert!(holidays[0].contains(day(7, 10)));
+
+    let holidays = Config::get_holidays("dateformat.holiday Y-M-D\nholiday.xmas.name Christmas Day\nholiday.xmas.date 2024-12-25");
+    assert_eq!(holidays[0].start, day(12, 25));
+  }
 }
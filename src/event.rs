@@ -1,15 +1,21 @@
 use crossterm::event::{
   KeyCode::{BackTab, Backspace, Char, Delete, Down, End, Enter, Esc, Home, Insert, Left, Null, PageDown, PageUp, Right, Tab, Up, F}, KeyModifiers,
 };
+use std::path::Path;
+
 use futures::StreamExt;
 use log::warn;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
+use crate::watcher::DataWatcher;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Event<I> {
   Input(I),
   Tick,
+  /// A file in the task data directory changed, see `DataWatcher`
+  DataChanged,
   Closed,
 }
 
@@ -38,6 +44,9 @@ pub enum KeyCode {
   Null,
   Esc,
   Tab,
+  /// The key an action bound to a key sequence is handled with, see `keymap::Keymap`;
+  /// never read from the terminal
+  Sequence(usize),
 }
 
 pub struct EventLoop {
@@ -45,10 +54,12 @@ pub struct EventLoop {
   pub tx: mpsc::UnboundedSender<Event<KeyCode>>,
   pub abort: mpsc::UnboundedSender<()>,
   pub tick_rate: std::time::Duration,
+  /// Watches the task data directory, if it was given and watching it succeeded
+  pub watcher: Option<DataWatcher>,
 }
 
 impl EventLoop {
-  pub fn new(tick_rate: Option<std::time::Duration>, init: bool) -> Self {
+  pub fn new(tick_rate: Option<std::time::Duration>, init: bool, data_dir: Option<&Path>) -> Self {
     let (tx, rx) = mpsc::unbounded_channel();
     let _tx = tx.clone();
     let should_tick = tick_rate.is_some();
@@ -141,6 +152,18 @@ impl EventLoop {
       });
     }
 
-    Self { tx, rx, tick_rate, abort }
+    let watcher = data_dir.filter(|_| init).and_then(|data_dir| {
+      DataWatcher::new(data_dir, tx.clone())
+        .map_err(|err| warn!("Not watching the task data for changes: {:#}", err))
+        .ok()
+    });
+
+    Self {
+      tx,
+      rx,
+      tick_rate,
+      abort,
+      watcher,
+    }
   }
 }
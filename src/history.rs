@@ -3,6 +3,21 @@ use std::path::PathBuf;
 use anyhow::Result;
 use rustyline::history::{DefaultHistory, History, SearchDirection};
 
+/// Directory the histories and macros are kept in, created if needed
+pub fn data_dir() -> PathBuf {
+  let data_path = if let Ok(s) = std::env::var("TASKWARRIOR_TUI_DATA") {
+    PathBuf::from(s)
+  } else {
+    dirs::data_local_dir()
+      .map(|d| d.join("taskwarrior-tui"))
+      .expect("Unable to create configuration directory for taskwarrior-tui")
+  };
+
+  std::fs::create_dir_all(&data_path).unwrap_or_else(|_| panic!("Unable to create configuration directory in {:?}", &data_path));
+
+  data_path
+}
+
 pub struct HistoryContext {
   history: DefaultHistory,
   history_index: Option<usize>,
@@ -10,21 +25,10 @@ pub struct HistoryContext {
 }
 
 impl HistoryContext {
-  pub fn new(filename: &str) -> Self {
+  /// History kept in the file at `data_path`
+  pub fn with_path(data_path: PathBuf) -> Self {
     let history = DefaultHistory::new();
 
-    let data_path = if let Ok(s) = std::env::var("TASKWARRIOR_TUI_DATA") {
-      PathBuf::from(s)
-    } else {
-      dirs::data_local_dir()
-        .map(|d| d.join("taskwarrior-tui"))
-        .expect("Unable to create configuration directory for taskwarrior-tui")
-    };
-
-    std::fs::create_dir_all(&data_path).unwrap_or_else(|_| panic!("Unable to create configuration directory in {:?}", &data_path));
-
-    let data_path = data_path.join(filename);
-
     Self {
       history,
       history_index: None,
@@ -1,8 +1,152 @@
-use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
 use log::error;
 use serde::{Deserialize, Serialize};
 
-use crate::event::KeyCode;
+use crate::{
+  event::KeyCode,
+  keymap::{parse_keys, KeyScope, Keymap},
+};
+
+const TABS: &[KeyScope] = &[
+  KeyScope::Report,
+  KeyScope::Projects,
+  KeyScope::Calendar,
+  KeyScope::Agenda,
+  KeyScope::Board,
+  KeyScope::Graph,
+  KeyScope::Statistics,
+];
+
+const PANES: &[KeyScope] = &[KeyScope::Projects, KeyScope::Calendar, KeyScope::Agenda, KeyScope::Board, KeyScope::Statistics];
+
+/// Every action with its name in `uda.taskwarrior-tui.keyconfig.<action>` and the modes it is used in
+const ACTIONS: &[(&str, &[KeyScope])] = &[
+  (
+    "quit",
+    &[
+      KeyScope::Report,
+      KeyScope::Projects,
+      KeyScope::Calendar,
+      KeyScope::Agenda,
+      KeyScope::Board,
+      KeyScope::Graph,
+      KeyScope::Statistics,
+      KeyScope::Popup,
+    ],
+  ),
+  ("refresh", &[KeyScope::Report, KeyScope::Calendar, KeyScope::Agenda, KeyScope::Board, KeyScope::Graph, KeyScope::Statistics]),
+  ("go-to-bottom", &[KeyScope::Report, KeyScope::Agenda, KeyScope::Board, KeyScope::Graph]),
+  ("go-to-top", &[KeyScope::Report, KeyScope::Agenda, KeyScope::Board, KeyScope::Graph]),
+  ("down", &[KeyScope::Report, KeyScope::Projects, KeyScope::Calendar, KeyScope::Agenda, KeyScope::Board, KeyScope::Graph, KeyScope::Popup]),
+  ("up", &[KeyScope::Report, KeyScope::Projects, KeyScope::Calendar, KeyScope::Agenda, KeyScope::Board, KeyScope::Graph, KeyScope::Popup]),
+  ("page-down", &[KeyScope::Report, KeyScope::Calendar]),
+  ("page-up", &[KeyScope::Report, KeyScope::Calendar]),
+  ("delete", &[KeyScope::Report, KeyScope::Popup]),
+  ("done", &[KeyScope::Report, KeyScope::Popup]),
+  ("start-stop", &[KeyScope::Report]),
+  ("quick-tag", &[KeyScope::Report]),
+  ("select", &[KeyScope::Report, KeyScope::Projects, KeyScope::Graph]),
+  ("select-all", &[KeyScope::Report]),
+  ("undo", &[KeyScope::Report, KeyScope::Popup]),
+  ("sync", &[KeyScope::Report]),
+  ("edit", &[KeyScope::Report]),
+  ("duplicate", &[KeyScope::Report]),
+  ("modify", &[KeyScope::Report]),
+  ("shell", &[KeyScope::Report]),
+  ("log", &[KeyScope::Report]),
+  ("add", &[KeyScope::Report]),
+  ("annotate", &[KeyScope::Report]),
+  ("help", &[KeyScope::Report]),
+  ("filter", &[KeyScope::Report]),
+  ("zoom", &[KeyScope::Report, KeyScope::Agenda, KeyScope::Statistics]),
+  ("context-menu", &[KeyScope::Report]),
+  ("next-tab", TABS),
+  ("previous-tab", TABS),
+  ("board-move-left", &[KeyScope::Board]),
+  ("board-move-right", &[KeyScope::Board]),
+  ("calendar-set-due", &[KeyScope::Calendar]),
+  ("calendar-set-scheduled", &[KeyScope::Calendar]),
+  ("graph-add-dependency", &[KeyScope::Graph]),
+  ("graph-remove-dependency", &[KeyScope::Graph]),
+  ("projects-sort", &[KeyScope::Projects]),
+  ("projects-sort-reverse", &[KeyScope::Projects]),
+  ("report-split", &[KeyScope::Report]),
+  ("report-close", &[KeyScope::Report]),
+  ("report-move", &[KeyScope::Report]),
+  ("report-menu", &[KeyScope::Report]),
+  ("report-next", &[KeyScope::Report]),
+  ("report-previous", &[KeyScope::Report]),
+  ("command-palette", &[KeyScope::Report]),
+  ("record-macro", TABS),
+  ("replay-macro", TABS),
+  ("priority-h", &[KeyScope::Report]),
+  ("priority-m", &[KeyScope::Report]),
+  ("priority-l", &[KeyScope::Report]),
+  ("priority-n", &[KeyScope::Report]),
+  ("shortcut0", &[KeyScope::Report]),
+  ("shortcut1", &[KeyScope::Report]),
+  ("shortcut2", &[KeyScope::Report]),
+  ("shortcut3", &[KeyScope::Report]),
+  ("shortcut4", &[KeyScope::Report]),
+  ("shortcut5", &[KeyScope::Report]),
+  ("shortcut6", &[KeyScope::Report]),
+  ("shortcut7", &[KeyScope::Report]),
+  ("shortcut8", &[KeyScope::Report]),
+  ("shortcut9", &[KeyScope::Report]),
+];
+
+/// Actions handled with a key of their own rather than a `KeyConfig` field, with that key and the
+/// modes they are used in
+///
+/// Their handlers compare the pressed key with this one, so binding other keys to the action
+/// turns those keys into it.
+const KEY_ACTIONS: &[(&str, KeyCode, &[KeyScope])] = &[
+  ("force-quit", KeyCode::Ctrl('c'), TABS),
+  ("cancel", KeyCode::Esc, &[KeyScope::Report, KeyScope::Graph, KeyScope::Popup, KeyScope::Prompt]),
+  (
+    "confirm",
+    KeyCode::Char('\n'),
+    &[
+      KeyScope::Projects,
+      KeyScope::Calendar,
+      KeyScope::Agenda,
+      KeyScope::Board,
+      KeyScope::Graph,
+      KeyScope::Popup,
+      KeyScope::Prompt,
+    ],
+  ),
+  ("command-line", KeyCode::Char(':'), &[KeyScope::Report]),
+  ("scroll-down", KeyCode::Ctrl('e'), &[KeyScope::Report]),
+  ("scroll-up", KeyCode::Ctrl('y'), &[KeyScope::Report]),
+  ("next-pane", KeyCode::Tab, &[KeyScope::Report]),
+  ("previous-pane", KeyCode::BackTab, &[KeyScope::Report]),
+  ("left", KeyCode::Char('h'), PANES),
+  ("right", KeyCode::Char('l'), PANES),
+  ("today", KeyCode::Char('t'), &[KeyScope::Calendar, KeyScope::Agenda]),
+  ("calendar-next-year", KeyCode::PageDown, &[KeyScope::Calendar]),
+  ("calendar-previous-year", KeyCode::PageUp, &[KeyScope::Calendar]),
+  ("complete-next", KeyCode::Tab, &[KeyScope::Prompt]),
+  ("complete-previous", KeyCode::BackTab, &[KeyScope::Prompt]),
+  ("history-previous", KeyCode::Up, &[KeyScope::Prompt]),
+  ("history-next", KeyCode::Down, &[KeyScope::Prompt]),
+  ("history-search", KeyCode::Ctrl('r'), &[KeyScope::Prompt]),
+  ("cursor-left", KeyCode::Ctrl('b'), &[KeyScope::Prompt]),
+  ("cursor-right", KeyCode::Ctrl('f'), &[KeyScope::Prompt]),
+  ("word-left", KeyCode::Alt('b'), &[KeyScope::Prompt]),
+  ("word-right", KeyCode::Alt('f'), &[KeyScope::Prompt]),
+  ("line-start", KeyCode::Ctrl('a'), &[KeyScope::Prompt]),
+  ("line-end", KeyCode::Ctrl('e'), &[KeyScope::Prompt]),
+  ("delete-backward", KeyCode::Ctrl('h'), &[KeyScope::Prompt]),
+  ("delete-forward", KeyCode::Ctrl('d'), &[KeyScope::Prompt]),
+  ("delete-word-backward", KeyCode::Ctrl('w'), &[KeyScope::Prompt]),
+  ("delete-word-forward", KeyCode::Alt('d'), &[KeyScope::Prompt]),
+  ("delete-to-line-end", KeyCode::Ctrl('k'), &[KeyScope::Prompt]),
+  ("delete-to-line-start", KeyCode::Ctrl('u'), &[KeyScope::Prompt]),
+  ("transpose-words", KeyCode::Alt('t'), &[KeyScope::Prompt]),
+];
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct KeyConfig {
@@ -21,6 +165,7 @@ pub struct KeyConfig {
   pub select: KeyCode,
   pub select_all: KeyCode,
   pub undo: KeyCode,
+  pub sync: KeyCode,
   pub edit: KeyCode,
   pub duplicate: KeyCode,
   pub modify: KeyCode,
@@ -34,6 +179,23 @@ pub struct KeyConfig {
   pub context_menu: KeyCode,
   pub next_tab: KeyCode,
   pub previous_tab: KeyCode,
+  pub board_move_left: KeyCode,
+  pub board_move_right: KeyCode,
+  pub calendar_set_due: KeyCode,
+  pub calendar_set_scheduled: KeyCode,
+  pub graph_add_dependency: KeyCode,
+  pub graph_remove_dependency: KeyCode,
+  pub projects_sort: KeyCode,
+  pub projects_sort_reverse: KeyCode,
+  pub report_split: KeyCode,
+  pub report_close: KeyCode,
+  pub report_move: KeyCode,
+  pub report_menu: KeyCode,
+  pub report_next: KeyCode,
+  pub report_previous: KeyCode,
+  pub command_palette: KeyCode,
+  pub record_macro: KeyCode,
+  pub replay_macro: KeyCode,
   pub priority_h: KeyCode,
   pub priority_m: KeyCode,
   pub priority_l: KeyCode,
@@ -48,6 +210,14 @@ pub struct KeyConfig {
   pub shortcut7: KeyCode,
   pub shortcut8: KeyCode,
   pub shortcut9: KeyCode,
+  /// Stands for `<leader>` in key sequences
+  pub leader: KeyCode,
+  #[serde(skip)]
+  keymaps: HashMap<KeyScope, Keymap>,
+  /// Named commands bound in `uda.taskwarrior-tui.keyconfig.command.<name>`, handled with the
+  /// keys that follow those of the actions
+  #[serde(skip)]
+  commands: Vec<String>,
 }
 
 impl Default for KeyConfig {
@@ -68,6 +238,7 @@ impl Default for KeyConfig {
       select: KeyCode::Char('v'),
       select_all: KeyCode::Char('V'),
       undo: KeyCode::Char('u'),
+      sync: KeyCode::Char('S'),
       edit: KeyCode::Char('e'),
       duplicate: KeyCode::Char('y'),
       modify: KeyCode::Char('m'),
@@ -81,6 +252,23 @@ impl Default for KeyConfig {
       context_menu: KeyCode::Char('c'),
       next_tab: KeyCode::Char(']'),
       previous_tab: KeyCode::Char('['),
+      board_move_left: KeyCode::Char('<'),
+      board_move_right: KeyCode::Char('>'),
+      calendar_set_due: KeyCode::Char('D'),
+      calendar_set_scheduled: KeyCode::Char('C'),
+      graph_add_dependency: KeyCode::Char('+'),
+      graph_remove_dependency: KeyCode::Char('-'),
+      projects_sort: KeyCode::Char('o'),
+      projects_sort_reverse: KeyCode::Char('O'),
+      report_split: KeyCode::Char('|'),
+      report_close: KeyCode::Char('Z'),
+      report_move: KeyCode::Char('T'),
+      report_menu: KeyCode::Char('R'),
+      report_next: KeyCode::Char('}'),
+      report_previous: KeyCode::Char('{'),
+      command_palette: KeyCode::Ctrl('p'),
+      record_macro: KeyCode::Char('Q'),
+      replay_macro: KeyCode::Char('@'),
       priority_h: KeyCode::Char('H'),
       priority_m: KeyCode::Char('M'),
       priority_l: KeyCode::Char('L'),
@@ -95,6 +283,9 @@ impl Default for KeyConfig {
       shortcut7: KeyCode::Char('7'),
       shortcut8: KeyCode::Char('8'),
       shortcut9: KeyCode::Char('9'),
+      leader: KeyCode::Char('\\'),
+      keymaps: HashMap::new(),
+      commands: vec![],
     }
   }
 }
@@ -107,140 +298,168 @@ impl KeyConfig {
   }
 
   pub fn update(&mut self, data: &str) -> Result<()> {
-    let quit = Self::get_config("uda.taskwarrior-tui.keyconfig.quit", data);
-    let refresh = Self::get_config("uda.taskwarrior-tui.keyconfig.refresh", data);
-    let go_to_bottom = Self::get_config("uda.taskwarrior-tui.keyconfig.go-to-bottom", data);
-    let go_to_top = Self::get_config("uda.taskwarrior-tui.keyconfig.go-to-top", data);
-    let down = Self::get_config("uda.taskwarrior-tui.keyconfig.down", data);
-    let up = Self::get_config("uda.taskwarrior-tui.keyconfig.up", data);
-    let page_down = Self::get_config("uda.taskwarrior-tui.keyconfig.page-down", data);
-    let page_up = Self::get_config("uda.taskwarrior-tui.keyconfig.page-up", data);
-    let delete = Self::get_config("uda.taskwarrior-tui.keyconfig.delete", data);
-    let done = Self::get_config("uda.taskwarrior-tui.keyconfig.done", data);
-    let start_stop = Self::get_config("uda.taskwarrior-tui.keyconfig.start-stop", data);
-    let quick_tag = Self::get_config("uda.taskwarrior-tui.keyconfig.quick-tag", data);
-    let select = Self::get_config("uda.taskwarrior-tui.keyconfig.select", data);
-    let select_all = Self::get_config("uda.taskwarrior-tui.keyconfig.select-all", data);
-    let undo = Self::get_config("uda.taskwarrior-tui.keyconfig.undo", data);
-    let edit = Self::get_config("uda.taskwarrior-tui.keyconfig.edit", data);
-    let _duplicate = Self::get_config("uda.taskwarrior-tui.keyconfig.duplicate", data);
-    let modify = Self::get_config("uda.taskwarrior-tui.keyconfig.modify", data);
-    let shell = Self::get_config("uda.taskwarrior-tui.keyconfig.shell", data);
-    let log = Self::get_config("uda.taskwarrior-tui.keyconfig.log", data);
-    let add = Self::get_config("uda.taskwarrior-tui.keyconfig.add", data);
-    let annotate = Self::get_config("uda.taskwarrior-tui.keyconfig.annotate", data);
-    let filter = Self::get_config("uda.taskwarrior-tui.keyconfig.filter", data);
-    let zoom = Self::get_config("uda.taskwarrior-tui.keyconfig.zoom", data);
-    let context_menu = Self::get_config("uda.taskwarrior-tui.keyconfig.context-menu", data);
-    let next_tab = Self::get_config("uda.taskwarrior-tui.keyconfig.next-tab", data);
-    let previous_tab = Self::get_config("uda.taskwarrior-tui.keyconfig.previous-tab", data);
-    let shortcut0 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut0", data);
-    let shortcut1 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut1", data);
-    let shortcut2 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut2", data);
-    let shortcut3 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut3", data);
-    let shortcut4 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut4", data);
-    let shortcut5 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut5", data);
-    let shortcut6 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut6", data);
-    let shortcut7 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut7", data);
-    let shortcut8 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut8", data);
-    let shortcut9 = Self::get_config("uda.taskwarrior-tui.keyconfig.shortcut9", data);
+    if let Some(value) = Self::get_config("uda.taskwarrior-tui.keyconfig.leader", data) {
+      match parse_keys(&value, self.leader)?.as_slice() {
+        [leader] => self.leader = *leader,
+        _ => return Err(anyhow!("The leader must be a single key, not `{}`", value)),
+      }
+    }
+
+    // Every action with its scopes, the keys it is bound to and the key it is handled with
+    let mut actions = vec![];
+    for (i, (action, scopes)) in ACTIONS.iter().enumerate() {
+      let config = format!("uda.taskwarrior-tui.keyconfig.{}", action);
+      let keys = match Self::get_config(&config, data) {
+        Some(value) => parse_keys(&value, self.leader)?,
+        None => vec![*self.key_mut(action)],
+      };
+      // An action bound to several keys is handled with a key of its own
+      *self.key_mut(action) = match keys.as_slice() {
+        [key] => *key,
+        _ => KeyCode::Sequence(i),
+      };
+      actions.push((action.to_string(), *scopes, keys, *self.key_mut(action)));
+    }
+    for (action, key, scopes) in KEY_ACTIONS {
+      let config = format!("uda.taskwarrior-tui.keyconfig.{}", action);
+      let keys = match Self::get_config(&config, data) {
+        Some(value) => parse_keys(&value, self.leader)?,
+        None => vec![*key],
+      };
+      actions.push((action.to_string(), *scopes, keys, *key));
+    }
+    self.commands = vec![];
+    for line in data.split('\n') {
+      let key = line.split_whitespace().next().unwrap_or_default();
+      let Some(name) = key.strip_prefix("uda.taskwarrior-tui.keyconfig.command.") else {
+        continue;
+      };
+      let action = format!("command.{}", name);
+      if actions.iter().any(|(other, ..)| *other == action) {
+        continue;
+      }
+      let keys = parse_keys(&Self::get_config(key, data).unwrap_or_default(), self.leader)?;
+      let key = KeyCode::Sequence(ACTIONS.len() + self.commands.len());
+      self.commands.push(name.to_string());
+      actions.push((action, &[KeyScope::Report], keys, key));
+    }
+
+    self.keymaps = HashMap::new();
+    for scope in KeyScope::ALL {
+      // Popups and prompts take no counts, and keys typed in prompts are text unless bound
+      let mut keymap = Keymap::new(TABS.contains(&scope));
+      for (action, scopes, action_keys, key) in &actions {
+        let config = format!("uda.taskwarrior-tui.keyconfig.{}.{}", scope.name(), action);
+        let scope_keys = Self::get_config(&config, data).map(|value| parse_keys(&value, self.leader)).transpose()?;
+        if !scopes.contains(&scope) {
+          if scope_keys.is_some() {
+            return Err(anyhow!("`{}` cannot be bound in the {} keymap", action, scope.name()));
+          }
+          continue;
+        }
+        // Digits of shortcuts without a command are left to type counts with
+        if let Some(shortcut) = action.strip_prefix("shortcut") {
+          if scope_keys.is_none() && Self::get_config(&format!("uda.taskwarrior-tui.shortcuts.{}", shortcut), data).is_none() {
+            continue;
+          }
+        }
+        keymap
+          .bind(action, scope_keys.unwrap_or_else(|| action_keys.clone()), *key)
+          .with_context(|| format!("Unable to build the {} keymap", scope.name()))?;
+      }
+      self.keymaps.insert(scope, keymap);
+    }
+    Ok(())
+  }
 
-    self.quit = quit.unwrap_or(self.quit);
-    self.refresh = refresh.unwrap_or(self.refresh);
-    self.go_to_bottom = go_to_bottom.unwrap_or(self.go_to_bottom);
-    self.go_to_top = go_to_top.unwrap_or(self.go_to_top);
-    self.down = down.unwrap_or(self.down);
-    self.up = up.unwrap_or(self.up);
-    self.page_down = page_down.unwrap_or(self.page_down);
-    self.page_up = page_up.unwrap_or(self.page_up);
-    self.delete = delete.unwrap_or(self.delete);
-    self.done = done.unwrap_or(self.done);
-    self.start_stop = start_stop.unwrap_or(self.start_stop);
-    self.quick_tag = quick_tag.unwrap_or(self.quick_tag);
-    self.select = select.unwrap_or(self.select);
-    self.select_all = select_all.unwrap_or(self.select_all);
-    self.undo = undo.unwrap_or(self.undo);
-    self.edit = edit.unwrap_or(self.edit);
-    self.duplicate = edit.unwrap_or(self.duplicate);
-    self.modify = modify.unwrap_or(self.modify);
-    self.shell = shell.unwrap_or(self.shell);
-    self.log = log.unwrap_or(self.log);
-    self.add = add.unwrap_or(self.add);
-    self.annotate = annotate.unwrap_or(self.annotate);
-    self.filter = filter.unwrap_or(self.filter);
-    self.zoom = zoom.unwrap_or(self.zoom);
-    self.context_menu = context_menu.unwrap_or(self.context_menu);
-    self.next_tab = next_tab.unwrap_or(self.next_tab);
-    self.previous_tab = previous_tab.unwrap_or(self.previous_tab);
-    self.shortcut0 = shortcut0.unwrap_or(self.shortcut0);
-    self.shortcut1 = shortcut1.unwrap_or(self.shortcut1);
-    self.shortcut2 = shortcut2.unwrap_or(self.shortcut2);
-    self.shortcut3 = shortcut3.unwrap_or(self.shortcut3);
-    self.shortcut4 = shortcut4.unwrap_or(self.shortcut4);
-    self.shortcut5 = shortcut5.unwrap_or(self.shortcut5);
-    self.shortcut6 = shortcut6.unwrap_or(self.shortcut6);
-    self.shortcut7 = shortcut7.unwrap_or(self.shortcut7);
-    self.shortcut8 = shortcut8.unwrap_or(self.shortcut8);
-    self.shortcut9 = shortcut9.unwrap_or(self.shortcut9);
+  /// The keymap used in `scope`
+  pub fn keymap(&self, scope: KeyScope) -> &Keymap {
+    &self.keymaps[&scope]
+  }
 
-    self.check()
+  /// Name of the named command handled with `key`
+  pub fn command(&self, key: KeyCode) -> Option<&str> {
+    match key {
+      KeyCode::Sequence(i) => self.commands.get(i.checked_sub(ACTIONS.len())?).map(String::as_str),
+      _ => None,
+    }
   }
 
-  pub fn check(&self) -> Result<()> {
-    let mut elements = vec![
-      &self.quit,
-      &self.refresh,
-      &self.go_to_bottom,
-      &self.go_to_top,
-      &self.down,
-      &self.up,
-      &self.page_down,
-      &self.page_up,
-      &self.delete,
-      &self.done,
-      &self.select,
-      &self.select_all,
-      &self.start_stop,
-      &self.quick_tag,
-      &self.undo,
-      &self.edit,
-      &self.duplicate,
-      &self.modify,
-      &self.shell,
-      &self.log,
-      &self.add,
-      &self.annotate,
-      &self.help,
-      &self.filter,
-      &self.zoom,
-      &self.context_menu,
-      &self.next_tab,
-      &self.previous_tab,
-    ];
-    let l = elements.len();
-    elements.dedup();
-    if l == elements.len() {
-      Ok(())
-    } else {
-      Err(anyhow!("Duplicate keys found in key config"))
+  fn key_mut(&mut self, action: &str) -> &mut KeyCode {
+    match action {
+      "quit" => &mut self.quit,
+      "refresh" => &mut self.refresh,
+      "go-to-bottom" => &mut self.go_to_bottom,
+      "go-to-top" => &mut self.go_to_top,
+      "down" => &mut self.down,
+      "up" => &mut self.up,
+      "page-down" => &mut self.page_down,
+      "page-up" => &mut self.page_up,
+      "delete" => &mut self.delete,
+      "done" => &mut self.done,
+      "start-stop" => &mut self.start_stop,
+      "quick-tag" => &mut self.quick_tag,
+      "select" => &mut self.select,
+      "select-all" => &mut self.select_all,
+      "undo" => &mut self.undo,
+      "sync" => &mut self.sync,
+      "edit" => &mut self.edit,
+      "duplicate" => &mut self.duplicate,
+      "modify" => &mut self.modify,
+      "shell" => &mut self.shell,
+      "log" => &mut self.log,
+      "add" => &mut self.add,
+      "annotate" => &mut self.annotate,
+      "help" => &mut self.help,
+      "filter" => &mut self.filter,
+      "zoom" => &mut self.zoom,
+      "context-menu" => &mut self.context_menu,
+      "next-tab" => &mut self.next_tab,
+      "previous-tab" => &mut self.previous_tab,
+      "board-move-left" => &mut self.board_move_left,
+      "board-move-right" => &mut self.board_move_right,
+      "calendar-set-due" => &mut self.calendar_set_due,
+      "calendar-set-scheduled" => &mut self.calendar_set_scheduled,
+      "graph-add-dependency" => &mut self.graph_add_dependency,
+      "graph-remove-dependency" => &mut self.graph_remove_dependency,
+      "projects-sort" => &mut self.projects_sort,
+      "projects-sort-reverse" => &mut self.projects_sort_reverse,
+      "report-split" => &mut self.report_split,
+      "report-close" => &mut self.report_close,
+      "report-move" => &mut self.report_move,
+      "report-menu" => &mut self.report_menu,
+      "report-next" => &mut self.report_next,
+      "report-previous" => &mut self.report_previous,
+      "command-palette" => &mut self.command_palette,
+      "record-macro" => &mut self.record_macro,
+      "replay-macro" => &mut self.replay_macro,
+      "priority-h" => &mut self.priority_h,
+      "priority-m" => &mut self.priority_m,
+      "priority-l" => &mut self.priority_l,
+      "priority-n" => &mut self.priority_n,
+      "shortcut0" => &mut self.shortcut0,
+      "shortcut1" => &mut self.shortcut1,
+      "shortcut2" => &mut self.shortcut2,
+      "shortcut3" => &mut self.shortcut3,
+      "shortcut4" => &mut self.shortcut4,
+      "shortcut5" => &mut self.shortcut5,
+      "shortcut6" => &mut self.shortcut6,
+      "shortcut7" => &mut self.shortcut7,
+      "shortcut8" => &mut self.shortcut8,
+      "shortcut9" => &mut self.shortcut9,
+      _ => unreachable!("`{}` is not in `ACTIONS`", action),
     }
   }
 
-  fn get_config(config: &str, data: &str) -> Option<KeyCode> {
+  fn get_config(config: &str, data: &str) -> Option<String> {
     for line in data.split('\n') {
-      if line.starts_with(config) {
-        let line = line.trim_start_matches(config).trim_start().trim_end().to_string();
-        if has_just_one_char(&line) {
-          return Some(KeyCode::Char(line.chars().next().unwrap()));
-        } else {
-          error!("Found multiple characters in {} for {}", line, config);
-        }
-      } else if line.starts_with(&config.replace('-', "_")) {
-        let line = line.trim_start_matches(&config.replace('-', "_")).trim_start().trim_end().to_string();
-        if has_just_one_char(&line) {
-          return Some(KeyCode::Char(line.chars().next().unwrap()));
+      let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+      if key == config || key == config.replace('-', "_") {
+        let value = value.trim();
+        if value.is_empty() {
+          error!("Found no keys for {}", config);
         } else {
-          error!("Found multiple characters in {} for {}", line, config);
+          return Some(value.to_string());
         }
       }
     }
@@ -248,12 +467,73 @@ impl KeyConfig {
   }
 }
 
-fn has_just_one_char(s: &str) -> bool {
-  let mut chars = s.chars();
-  chars.next().is_some() && chars.next().is_none()
-}
-
 #[cfg(test)]
 mod tests {
-  
+  use super::*;
+
+  fn bindings(kc: &KeyConfig, scope: KeyScope, action: &str) -> Option<(String, KeyCode)> {
+    let binding = kc.keymap(scope).bindings().iter().find(|binding| binding.action == action)?;
+    Some((crate::keymap::notation(&binding.keys), binding.key))
+  }
+
+  #[test]
+  fn test_key_sequences_and_mode_keymaps() {
+    let kc = KeyConfig::new(
+      "uda.taskwarrior-tui.keyconfig.leader ,\nuda.taskwarrior-tui.keyconfig.go-to-top gg\nuda.taskwarrior-tui.keyconfig.report-menu <leader>r\nuda.taskwarrior-tui.keyconfig.calendar.down n\nuda.taskwarrior-tui.shortcuts.1 echo",
+    )
+    .unwrap();
+    assert!(matches!(kc.go_to_top, KeyCode::Sequence(_)));
+    assert_eq!(bindings(&kc, KeyScope::Report, "go-to-top"), Some(("gg".to_string(), kc.go_to_top)));
+    assert_eq!(bindings(&kc, KeyScope::Report, "report-menu"), Some((",r".to_string(), kc.report_menu)));
+    // Only the calendar moves down with n
+    assert_eq!(bindings(&kc, KeyScope::Calendar, "down"), Some(("n".to_string(), KeyCode::Char('j'))));
+    assert_eq!(bindings(&kc, KeyScope::Report, "down"), Some(("j".to_string(), KeyCode::Char('j'))));
+    // Prompts keep their keys as text unless bound for prompts
+    assert_eq!(bindings(&kc, KeyScope::Prompt, "down"), None);
+    // Digits of shortcuts without a command are free for counts
+    assert_eq!(bindings(&kc, KeyScope::Report, "shortcut1"), Some(("1".to_string(), KeyCode::Char('1'))));
+    assert_eq!(bindings(&kc, KeyScope::Report, "shortcut2"), None);
+
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.done x").is_err());
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.go-to-top gg\nuda.taskwarrior-tui.keyconfig.go-to-bottom g").is_err());
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.calendar.done x").is_err());
+    // Keymaps of different modes may use the same keys
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.board.board-move-left d").is_ok());
+  }
+
+  #[test]
+  fn test_named_keys_and_hard_coded_actions() {
+    let kc = KeyConfig::new(
+      "uda.taskwarrior-tui.keyconfig.page-down <C-d>\nuda.taskwarrior-tui.keyconfig.scroll-down <F5>\nuda.taskwarrior-tui.keyconfig.popup.cancel <C-g>\nuda.taskwarrior-tui.keyconfig.prompt.line-start <Home>",
+    )
+    .unwrap();
+    assert_eq!(kc.page_down, KeyCode::Ctrl('d'));
+    // Actions without a field are still handled with their own key
+    assert_eq!(bindings(&kc, KeyScope::Report, "scroll-down"), Some(("<F5>".to_string(), KeyCode::Ctrl('e'))));
+    assert_eq!(bindings(&kc, KeyScope::Report, "cancel"), Some(("<Esc>".to_string(), KeyCode::Esc)));
+    assert_eq!(bindings(&kc, KeyScope::Popup, "cancel"), Some(("<C-g>".to_string(), KeyCode::Esc)));
+    assert_eq!(bindings(&kc, KeyScope::Prompt, "line-start"), Some(("<Home>".to_string(), KeyCode::Ctrl('a'))));
+    assert_eq!(bindings(&kc, KeyScope::Popup, "done"), Some(("d".to_string(), KeyCode::Char('d'))));
+
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.command-line <Nope>").is_err());
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.report.today T").is_err());
+    // The details scroll and the filter key cannot share a key
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.scroll-down /").is_err());
+  }
+
+  #[test]
+  fn test_named_command_keys() {
+    let kc = KeyConfig::new("uda.taskwarrior-tui.keyconfig.command.work <leader>w\nuda.taskwarrior-tui.keyconfig.command.by-due <leader>d").unwrap();
+    let (keys, key) = bindings(&kc, KeyScope::Report, "command.work").unwrap();
+    assert_eq!(keys, "\\w");
+    assert_eq!(kc.command(key), Some("work"));
+    let (keys, key) = bindings(&kc, KeyScope::Report, "command.by-due").unwrap();
+    assert_eq!(keys, "\\d");
+    assert_eq!(kc.command(key), Some("by-due"));
+    assert_eq!(kc.command(kc.done), None);
+    assert_eq!(bindings(&kc, KeyScope::Calendar, "command.work"), None);
+
+    // Named commands cannot take the keys of an action
+    assert!(KeyConfig::new("uda.taskwarrior-tui.keyconfig.command.work g").is_err());
+  }
 }
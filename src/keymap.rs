@@ -0,0 +1,399 @@
+//! Key sequences, count prefixes and a keymap per mode
+//!
+//! Actions are handled by comparing the pressed key with their `KeyConfig` field. The keymap of
+//! the current mode sits in front of that: it collects keys until they spell one of its bindings,
+//! then hands over the key the bound action is handled with. Digits typed before a binding form
+//! a count, except in popups and prompts.
+//!
+//! Keys are configured with the characters they type and with names in angle brackets for the
+//! rest, e.g. `<C-x>`, `<A-j>`, `<F5>`, `<PageDown>` or `<leader>`.
+
+use anyhow::{anyhow, Result};
+
+use crate::event::KeyCode;
+
+/// The modes with their own keymap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyScope {
+  Report,
+  Projects,
+  Calendar,
+  Agenda,
+  Board,
+  Graph,
+  Statistics,
+  /// Menus, confirmations and messages shown over the report
+  Popup,
+  /// Prompts where keys type text, such as the filter
+  Prompt,
+}
+
+impl KeyScope {
+  pub const ALL: [KeyScope; 9] = [
+    KeyScope::Report,
+    KeyScope::Projects,
+    KeyScope::Calendar,
+    KeyScope::Agenda,
+    KeyScope::Board,
+    KeyScope::Graph,
+    KeyScope::Statistics,
+    KeyScope::Popup,
+    KeyScope::Prompt,
+  ];
+
+  /// Name in `uda.taskwarrior-tui.keyconfig.<scope>.<action>`
+  pub fn name(self) -> &'static str {
+    match self {
+      KeyScope::Report => "report",
+      KeyScope::Projects => "projects",
+      KeyScope::Calendar => "calendar",
+      KeyScope::Agenda => "agenda",
+      KeyScope::Board => "board",
+      KeyScope::Graph => "graph",
+      KeyScope::Statistics => "statistics",
+      KeyScope::Popup => "popup",
+      KeyScope::Prompt => "prompt",
+    }
+  }
+}
+
+/// Keys that run an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+  /// Name of the action in `uda.taskwarrior-tui.keyconfig.<action>`
+  pub action: String,
+  pub keys: Vec<KeyCode>,
+  /// The key the action is handled with
+  pub key: KeyCode,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+  bindings: Vec<Binding>,
+  /// Keys are commands rather than text: digits typed before a binding form a count, and a key
+  /// an action is handled with does nothing unless it is bound
+  commands: bool,
+}
+
+impl Keymap {
+  pub fn new(commands: bool) -> Self {
+    Self { bindings: vec![], commands }
+  }
+
+  /// Bind `keys` to `action`, which is handled with `key`
+  ///
+  /// A binding cannot start with another binding of the same keymap, as the keymap could not
+  /// tell when the shorter one is complete, and two actions cannot be handled with the same key.
+  pub fn bind(&mut self, action: &str, keys: Vec<KeyCode>, key: KeyCode) -> Result<()> {
+    if keys.is_empty() {
+      return Err(anyhow!("No keys given for `{}`", action));
+    }
+    for binding in &self.bindings {
+      let (shorter, longer) = if binding.keys.len() <= keys.len() { (&binding.keys, &keys) } else { (&keys, &binding.keys) };
+      if longer.starts_with(shorter) {
+        return Err(anyhow!(
+          "`{}` and `{}` are bound to `{}` and `{}`, but a binding cannot start another one",
+          binding.action,
+          action,
+          notation(&binding.keys),
+          notation(&keys)
+        ));
+      }
+      if binding.key == key {
+        return Err(anyhow!("`{}` and `{}` are bound to the same key `{}`", binding.action, action, notation(&[key])));
+      }
+    }
+    self.bindings.push(Binding {
+      action: action.to_string(),
+      keys,
+      key,
+    });
+    Ok(())
+  }
+
+  pub fn bindings(&self) -> &[Binding] {
+    &self.bindings
+  }
+
+  /// The binding spelled by `keys`, or whether `keys` start one
+  fn lookup(&self, keys: &[KeyCode]) -> Lookup {
+    if let Some(binding) = self.bindings.iter().find(|binding| binding.keys == keys) {
+      Lookup::Bound(binding.key)
+    } else if self.bindings.iter().any(|binding| binding.keys.starts_with(keys)) {
+      Lookup::Prefix
+    } else {
+      Lookup::Unbound
+    }
+  }
+
+  /// Some action of this keymap is handled with `key`
+  fn handles(&self, key: KeyCode) -> bool {
+    self.bindings.iter().any(|binding| binding.key == key)
+  }
+}
+
+enum Lookup {
+  Bound(KeyCode),
+  Prefix,
+  Unbound,
+}
+
+/// What the keys pressed so far amount to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyInput {
+  /// The keys start a binding or a count
+  Pending,
+  /// Handle `key`, with the count typed before it
+  Key { key: KeyCode, count: Option<usize> },
+  /// The keys spell nothing and are dropped
+  Discarded,
+  /// The keys typed in a prompt spell nothing, and are handled one by one as text
+  Text(Vec<KeyCode>),
+}
+
+/// Keys pressed towards a binding, shown in the status line
+#[derive(Debug, Clone, Default)]
+pub struct PendingKeys {
+  count: Option<usize>,
+  keys: Vec<KeyCode>,
+}
+
+impl PendingKeys {
+  pub fn push(&mut self, keymap: &Keymap, key: KeyCode) -> KeyInput {
+    if self.keys.is_empty() && keymap.commands {
+      if let KeyCode::Char(c @ '0'..='9') = key {
+        // A count cannot start with 0, or with a digit that is bound
+        if self.count.is_some() || (c != '0' && matches!(keymap.lookup(&[key]), Lookup::Unbound)) {
+          let digit = c.to_digit(10).unwrap_or_default() as usize;
+          self.count = Some((self.count.unwrap_or_default() * 10 + digit).min(MAX_COUNT));
+          return KeyInput::Pending;
+        }
+      }
+    }
+    // Whichever key cancels also drops the keys pressed so far
+    let cancels = match keymap.lookup(&[key]) {
+      Lookup::Bound(bound) => bound == KeyCode::Esc,
+      _ => key == KeyCode::Esc,
+    };
+    if cancels && !self.is_empty() {
+      self.clear();
+      return KeyInput::Discarded;
+    }
+    self.keys.push(key);
+    match keymap.lookup(&self.keys) {
+      Lookup::Bound(key) => self.take(key),
+      Lookup::Prefix => KeyInput::Pending,
+      // An unbound key is handled as it is, unless an action is handled with it
+      Lookup::Unbound if self.keys.len() == 1 && !(keymap.commands && keymap.handles(key)) => self.take(key),
+      Lookup::Unbound if !keymap.commands => {
+        self.count = None;
+        KeyInput::Text(std::mem::take(&mut self.keys))
+      }
+      Lookup::Unbound => {
+        self.clear();
+        KeyInput::Discarded
+      }
+    }
+  }
+
+  fn take(&mut self, key: KeyCode) -> KeyInput {
+    let count = self.count.take();
+    self.keys.clear();
+    KeyInput::Key { key, count }
+  }
+
+  pub fn clear(&mut self) {
+    self.count = None;
+    self.keys.clear();
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.count.is_none() && self.keys.is_empty()
+  }
+}
+
+impl std::fmt::Display for PendingKeys {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(count) = self.count {
+      write!(f, "{}", count)?;
+    }
+    write!(f, "{}", notation(&self.keys))
+  }
+}
+
+/// Largest count, to keep a mistyped one from running an action for a long time
+const MAX_COUNT: usize = 9999;
+
+/// Keys written the way they are configured, e.g. `gg` or `<C-e>`
+pub fn notation(keys: &[KeyCode]) -> String {
+  keys
+    .iter()
+    .map(|key| match key {
+      KeyCode::Char('\n') => "<Enter>".to_string(),
+      KeyCode::Char(' ') => "<Space>".to_string(),
+      KeyCode::Char('<') => "<lt>".to_string(),
+      KeyCode::Char(c) => c.to_string(),
+      KeyCode::Ctrl(c) => format!("<C-{}>", c),
+      KeyCode::Alt(c) => format!("<A-{}>", c),
+      KeyCode::F(n) => format!("<F{}>", n),
+      KeyCode::BackTab => "<S-Tab>".to_string(),
+      KeyCode::Backspace => "<BS>".to_string(),
+      KeyCode::CtrlBackspace => "<C-BS>".to_string(),
+      KeyCode::AltBackspace => "<A-BS>".to_string(),
+      KeyCode::Delete => "<Del>".to_string(),
+      KeyCode::CtrlDelete => "<C-Del>".to_string(),
+      KeyCode::AltDelete => "<A-Del>".to_string(),
+      key => format!("<{:?}>", key),
+    })
+    .collect()
+}
+
+/// Parse keys as they are configured
+///
+/// A `<` that does not start a name, such as the one in `<<`, is the key itself; `<lt>` also
+/// stands for it.
+pub fn parse_keys(value: &str, leader: KeyCode) -> Result<Vec<KeyCode>> {
+  let mut keys = vec![];
+  let mut rest = value;
+  while let Some(c) = rest.chars().next() {
+    let named = rest
+      .strip_prefix('<')
+      .and_then(|after| after.split_once('>'))
+      .filter(|(name, _)| name.starts_with(|c: char| c.is_ascii_alphanumeric()) && !name.contains(char::is_whitespace));
+    match named {
+      Some((name, after)) => {
+        keys.push(if name.eq_ignore_ascii_case("leader") { leader } else { named_key(name)? });
+        rest = after;
+      }
+      None => {
+        keys.push(KeyCode::Char(c));
+        rest = &rest[c.len_utf8()..];
+      }
+    }
+  }
+  if keys.is_empty() {
+    return Err(anyhow!("No keys given in `{}`", value));
+  }
+  Ok(keys)
+}
+
+/// The key written `<name>`
+fn named_key(name: &str) -> Result<KeyCode> {
+  let mut chars = name.chars();
+  // A modifier and the character it is pressed with; Ctrl is read with lowercase letters
+  if let (Some(modifier), Some('-'), Some(c), None) = (chars.next(), chars.next(), chars.next(), chars.next()) {
+    match modifier.to_ascii_uppercase() {
+      'C' => return Ok(KeyCode::Ctrl(c.to_ascii_lowercase())),
+      'A' | 'M' => return Ok(KeyCode::Alt(c)),
+      _ => {}
+    }
+  }
+  // Meta is another name for Alt
+  let lowercase = name.to_ascii_lowercase();
+  let lowercase = lowercase.strip_prefix("m-").map_or(lowercase.clone(), |rest| format!("a-{}", rest));
+  let key = match lowercase.as_str() {
+    "lt" => KeyCode::Char('<'),
+    "space" => KeyCode::Char(' '),
+    "enter" | "cr" | "return" => KeyCode::Char('\n'),
+    "esc" | "escape" => KeyCode::Esc,
+    "tab" => KeyCode::Tab,
+    "s-tab" | "backtab" => KeyCode::BackTab,
+    "bs" | "backspace" => KeyCode::Backspace,
+    "c-bs" | "c-backspace" => KeyCode::CtrlBackspace,
+    "a-bs" | "a-backspace" => KeyCode::AltBackspace,
+    "del" | "delete" => KeyCode::Delete,
+    "c-del" | "c-delete" => KeyCode::CtrlDelete,
+    "a-del" | "a-delete" => KeyCode::AltDelete,
+    "ins" | "insert" => KeyCode::Insert,
+    "home" => KeyCode::Home,
+    "end" => KeyCode::End,
+    "pageup" | "pgup" => KeyCode::PageUp,
+    "pagedown" | "pgdn" => KeyCode::PageDown,
+    "up" => KeyCode::Up,
+    "down" => KeyCode::Down,
+    "left" => KeyCode::Left,
+    "right" => KeyCode::Right,
+    other => match other.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+      Some(n) if n > 0 => KeyCode::F(n),
+      _ => return Err(anyhow!("Unknown key `<{}>`", name)),
+    },
+  };
+  Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pending_keys() {
+    let mut keymap = Keymap::new(true);
+    keymap.bind("go-to-top", vec![KeyCode::Char('g'), KeyCode::Char('g')], KeyCode::Char('g')).unwrap();
+    keymap.bind("done", vec![KeyCode::Char('d'), KeyCode::Char('d')], KeyCode::Sequence(1)).unwrap();
+    keymap.bind("shortcut1", vec![KeyCode::Char('1')], KeyCode::Char('1')).unwrap();
+    keymap.bind("down", vec![KeyCode::Char('j')], KeyCode::Char('j')).unwrap();
+    assert!(keymap.bind("delete", vec![KeyCode::Char('d')], KeyCode::Char('x')).is_err());
+    assert!(keymap.bind("up", vec![KeyCode::Char('k')], KeyCode::Char('j')).is_err());
+
+    let mut pending = PendingKeys::default();
+    let mut press = |key: char| pending.push(&keymap, KeyCode::Char(key));
+    assert_eq!(press('g'), KeyInput::Pending);
+    assert_eq!(press('g'), KeyInput::Key { key: KeyCode::Char('g'), count: None });
+    assert_eq!(press('3'), KeyInput::Pending);
+    assert_eq!(press('0'), KeyInput::Pending);
+    assert_eq!(press('d'), KeyInput::Pending);
+    assert_eq!(press('d'), KeyInput::Key { key: KeyCode::Sequence(1), count: Some(30) });
+    // Bound digits run their action, unbound keys are handled as they are
+    assert_eq!(press('1'), KeyInput::Key { key: KeyCode::Char('1'), count: None });
+    assert_eq!(press('h'), KeyInput::Key { key: KeyCode::Char('h'), count: None });
+    assert_eq!(press('d'), KeyInput::Pending);
+    assert_eq!(press('x'), KeyInput::Discarded);
+
+    let mut pending = PendingKeys::default();
+    pending.push(&keymap, KeyCode::Char('5'));
+    pending.push(&keymap, KeyCode::Char('d'));
+    assert_eq!(pending.to_string(), "5d");
+    assert_eq!(pending.push(&keymap, KeyCode::Esc), KeyInput::Discarded);
+    assert!(pending.is_empty());
+
+    // Keys typed in a prompt are text unless they spell a binding
+    let mut keymap = Keymap::new(false);
+    keymap.bind("cancel", vec![KeyCode::Char('j'), KeyCode::Char('k')], KeyCode::Esc).unwrap();
+    keymap.bind("line-start", vec![KeyCode::Home], KeyCode::Ctrl('a')).unwrap();
+    let mut pending = PendingKeys::default();
+    assert_eq!(pending.push(&keymap, KeyCode::Char('1')), KeyInput::Key { key: KeyCode::Char('1'), count: None });
+    assert_eq!(pending.push(&keymap, KeyCode::Char('j')), KeyInput::Pending);
+    assert_eq!(pending.push(&keymap, KeyCode::Char('o')), KeyInput::Text(vec![KeyCode::Char('j'), KeyCode::Char('o')]));
+    assert_eq!(pending.push(&keymap, KeyCode::Char('j')), KeyInput::Pending);
+    assert_eq!(pending.push(&keymap, KeyCode::Char('k')), KeyInput::Key { key: KeyCode::Esc, count: None });
+    assert_eq!(pending.push(&keymap, KeyCode::Home), KeyInput::Key { key: KeyCode::Ctrl('a'), count: None });
+  }
+
+  #[test]
+  fn test_parse_keys() {
+    let leader = KeyCode::Char('\\');
+    assert_eq!(parse_keys("gg", leader).unwrap(), vec![KeyCode::Char('g'), KeyCode::Char('g')]);
+    assert_eq!(parse_keys("<leader>p", leader).unwrap(), vec![leader, KeyCode::Char('p')]);
+    assert_eq!(parse_keys("<", leader).unwrap(), vec![KeyCode::Char('<')]);
+    assert_eq!(parse_keys("<<", leader).unwrap(), vec![KeyCode::Char('<'), KeyCode::Char('<')]);
+    assert_eq!(parse_keys("<>", leader).unwrap(), vec![KeyCode::Char('<'), KeyCode::Char('>')]);
+    assert_eq!(parse_keys("<lt>", leader).unwrap(), vec![KeyCode::Char('<')]);
+    assert!(parse_keys("<x>", leader).is_err());
+    assert!(parse_keys("<PgDown>", leader).is_err());
+    assert!(parse_keys("", leader).is_err());
+
+    assert_eq!(parse_keys("<C-x>", leader).unwrap(), vec![KeyCode::Ctrl('x')]);
+    assert_eq!(parse_keys("<c-X>", leader).unwrap(), vec![KeyCode::Ctrl('x')]);
+    assert_eq!(parse_keys("<A-j><M-J>", leader).unwrap(), vec![KeyCode::Alt('j'), KeyCode::Alt('J')]);
+    assert_eq!(parse_keys("<F5>", leader).unwrap(), vec![KeyCode::F(5)]);
+    assert_eq!(parse_keys("<PageDown><pgup>", leader).unwrap(), vec![KeyCode::PageDown, KeyCode::PageUp]);
+    assert_eq!(parse_keys("<S-Tab><CR><Space>", leader).unwrap(), vec![KeyCode::BackTab, KeyCode::Char('\n'), KeyCode::Char(' ')]);
+    assert_eq!(parse_keys("<M-BS><C-Del>", leader).unwrap(), vec![KeyCode::AltBackspace, KeyCode::CtrlDelete]);
+    assert_eq!(parse_keys("<leader><C-w>j", leader).unwrap(), vec![leader, KeyCode::Ctrl('w'), KeyCode::Char('j')]);
+
+    // Keys are shown the way they are parsed
+    for value in ["<C-e>", "<A-j>", "<F12>", "<PageDown>", "<S-Tab>", "<Esc>", "<Enter>", "<A-BS>", "<Del>", "g<Home>", "<lt>Esc>"] {
+      assert_eq!(notation(&parse_keys(value, leader).unwrap()), value);
+    }
+  }
+}
@@ -4,13 +4,18 @@
 
 pub mod app;
 pub mod calendar;
+pub mod command_line;
 pub mod completion;
 pub mod config;
 pub mod event;
 pub mod help;
 pub mod history;
 pub mod keyconfig;
+pub mod keymap;
+pub mod macros;
+pub mod palette;
 pub mod pane;
+pub mod report_pane;
 pub mod scrollbar;
 pub mod table;
 pub mod task_report;
@@ -20,6 +25,7 @@ pub mod backend;
 pub mod action;
 pub mod cli;
 pub mod timewarrior;
+pub mod watcher;
 
 // Re-export commonly used types
 pub use app::TaskwarriorTui;
@@ -0,0 +1,182 @@
+//! Keyboard macros
+//!
+//! Keys typed while a macro is recorded are kept in a register, named by a letter or digit, and
+//! replaying the register feeds them through `handle_input` again. Registers are saved to the
+//! `macros` file of the data directory, one register per line with its keys in key notation.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+  event::KeyCode,
+  keymap::{notation, parse_keys},
+};
+
+/// What the next key names the register of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterPrompt {
+  Record,
+  /// Replay the register this many times
+  Replay(usize),
+}
+
+pub struct Macros {
+  registers: BTreeMap<char, Vec<KeyCode>>,
+  /// Register being recorded, with the keys recorded so far
+  recording: Option<(char, Vec<KeyCode>)>,
+  /// Registers being replayed, innermost last
+  pub replaying: Vec<char>,
+  /// Register replayed last, which `@` stands for
+  pub last: Option<char>,
+  /// Set after the record or replay key, until the register is typed
+  pub prompt: Option<RegisterPrompt>,
+  /// Keys typed towards the binding the keymap is waiting to complete
+  pub typed: Vec<KeyCode>,
+  data_path: PathBuf,
+}
+
+impl Macros {
+  /// Macros saved to the file at `data_path`
+  pub fn with_path(data_path: PathBuf) -> Self {
+    Self {
+      registers: BTreeMap::new(),
+      recording: None,
+      replaying: vec![],
+      last: None,
+      prompt: None,
+      typed: vec![],
+      data_path,
+    }
+  }
+
+  /// Read the saved registers; lines that do not parse are skipped
+  pub fn load(&mut self, leader: KeyCode) -> Result<()> {
+    if !self.data_path.exists() {
+      return Ok(());
+    }
+    let data = std::fs::read_to_string(&self.data_path)?;
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+      match parse_register(line, leader) {
+        Ok((register, keys)) => {
+          self.registers.insert(register, keys);
+        }
+        Err(e) => log::warn!("Skipping macro `{}` in {:?}: {}", line, self.data_path, e),
+      }
+    }
+    log::debug!("Loading {} macros", self.registers.len());
+    Ok(())
+  }
+
+  pub fn write(&self) -> Result<()> {
+    let data: String = self
+      .registers
+      .iter()
+      .map(|(register, keys)| format!("{} {}\n", register, notation(keys)))
+      .collect();
+    std::fs::write(&self.data_path, data)?;
+    Ok(())
+  }
+
+  pub fn registers(&self) -> &BTreeMap<char, Vec<KeyCode>> {
+    &self.registers
+  }
+
+  pub fn get(&self, register: char) -> Option<&[KeyCode]> {
+    self.registers.get(&register).map(Vec::as_slice)
+  }
+
+  /// The register being recorded
+  pub fn recording(&self) -> Option<char> {
+    self.recording.as_ref().map(|(register, _)| *register)
+  }
+
+  pub fn start(&mut self, register: char) {
+    self.recording = Some((register, vec![]));
+  }
+
+  /// Stop recording and store the keys in the register; recording no keys leaves it as it was
+  pub fn stop(&mut self) {
+    if let Some((register, keys)) = self.recording.take() {
+      if !keys.is_empty() {
+        self.registers.insert(register, keys);
+      }
+    }
+  }
+
+  /// Add keys to the macro being recorded, unless they come from a replayed one
+  pub fn record(&mut self, keys: &[KeyCode]) {
+    if !self.replaying.is_empty() {
+      return;
+    }
+    if let Some((_, recorded)) = &mut self.recording {
+      recorded.extend_from_slice(keys);
+    }
+  }
+}
+
+/// Whether `c` can name a register
+pub fn is_register(c: char) -> bool {
+  c.is_ascii_alphanumeric()
+}
+
+/// Parse a line of the `macros` file, a register followed by its keys
+fn parse_register(line: &str, leader: KeyCode) -> Result<(char, Vec<KeyCode>)> {
+  let (register, keys) = line.split_once(' ').ok_or_else(|| anyhow!("No keys given"))?;
+  let mut chars = register.chars();
+  match (chars.next(), chars.next()) {
+    (Some(register), None) if is_register(register) => Ok((register, parse_keys(keys, leader)?)),
+    _ => Err(anyhow!("`{}` is not a register", register)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_record_macro() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut macros = Macros::with_path(dir.path().join("macros"));
+    macros.record(&[KeyCode::Char('j')]);
+    assert!(macros.registers().is_empty());
+
+    macros.start('a');
+    assert_eq!(macros.recording(), Some('a'));
+    macros.record(&[KeyCode::Char('v'), KeyCode::Char('H')]);
+    macros.replaying.push('b');
+    macros.record(&[KeyCode::Char('x')]);
+    macros.replaying.clear();
+    macros.record(&[KeyCode::Char('<'), KeyCode::Esc]);
+    macros.stop();
+    assert_eq!(macros.recording(), None);
+    assert_eq!(
+      macros.get('a'),
+      Some(&[KeyCode::Char('v'), KeyCode::Char('H'), KeyCode::Char('<'), KeyCode::Esc][..])
+    );
+
+    // An empty recording keeps the register
+    macros.start('a');
+    macros.stop();
+    assert_eq!(macros.get('a').map(<[KeyCode]>::len), Some(4));
+
+    macros.write().unwrap();
+    let mut loaded = Macros::with_path(dir.path().join("macros"));
+    loaded.load(KeyCode::Char('\\')).unwrap();
+    assert_eq!(loaded.registers(), macros.registers());
+  }
+
+  #[test]
+  fn test_parse_register() {
+    let leader = KeyCode::Char('\\');
+    assert_eq!(
+      parse_register("a vH<lt><Esc>", leader).unwrap(),
+      ('a', vec![KeyCode::Char('v'), KeyCode::Char('H'), KeyCode::Char('<'), KeyCode::Esc])
+    );
+    assert_eq!(parse_register("1 <Space>", leader).unwrap(), ('1', vec![KeyCode::Char(' ')]));
+    assert!(parse_register("ab j", leader).is_err());
+    assert!(parse_register("@ j", leader).is_err());
+    assert!(parse_register("a", leader).is_err());
+    assert!(parse_register("a <Nope>", leader).is_err());
+  }
+}
@@ -8,19 +8,25 @@ mod app;
 mod backend;
 mod calendar;
 mod cli;
+mod command_line;
 mod completion;
 mod config;
 mod event;
 mod help;
 mod history;
 mod keyconfig;
+mod keymap;
+mod macros;
+mod palette;
 mod pane;
+mod report_pane;
 mod scrollbar;
 mod table;
 mod task_report;
 mod timewarrior;
 mod ui;
 mod utils;
+mod watcher;
 
 use std::{
   env,
@@ -0,0 +1,144 @@
+//! Command palette: every action of the report, searched by name
+//!
+//! Actions bound in the report keymap run as if their keys were pressed. Reports, named command
+//! lines and the Timewarrior hook, which need no key of their own, are listed alongside them.
+
+use rustyline::line_buffer::LineBuffer;
+
+use crate::{action::Action, app::MAX_LINE, event::KeyCode, table::TaskwarriorTuiTableState, utils::Changeset};
+
+/// What running a command does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+  /// Handle the key the action is handled with
+  Key(KeyCode),
+  /// Switch to the report of that name
+  Report(String),
+  /// Run one of the Timewarrior actions
+  Timewarrior(Action),
+  /// Run a line of the command line
+  CommandLine(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteCommand {
+  pub name: String,
+  /// The keys bound to the command, in key notation, or empty when it has none
+  pub keys: String,
+  pub action: PaletteAction,
+}
+
+pub struct CommandPalette {
+  pub query: LineBuffer,
+  pub commands: Vec<PaletteCommand>,
+  /// Positions in `commands` of the commands matching the query, best first
+  pub matches: Vec<usize>,
+  pub state: TaskwarriorTuiTableState,
+}
+
+impl CommandPalette {
+  pub(crate) fn new() -> Self {
+    Self {
+      query: LineBuffer::with_capacity(MAX_LINE),
+      commands: vec![],
+      matches: vec![],
+      state: TaskwarriorTuiTableState::default(),
+    }
+  }
+
+  /// Start a new search among `commands`
+  pub fn open(&mut self, commands: Vec<PaletteCommand>) {
+    self.query.update("", 0, &mut Changeset::default());
+    self.commands = commands;
+    self.update_matches();
+  }
+
+  /// Rank the commands by how well they match the query, and select the best one
+  pub fn update_matches(&mut self) {
+    let query = self.query.as_str();
+    let mut scored: Vec<(usize, usize)> = self
+      .commands
+      .iter()
+      .enumerate()
+      .filter_map(|(i, command)| fuzzy_score(query, &command.name).map(|score| (i, score)))
+      .collect();
+    // A stable sort keeps the commands in their order when they score the same
+    scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    self.matches = scored.into_iter().map(|(i, _)| i).collect();
+    self.state.select(Some(0));
+  }
+
+  pub fn selected(&self) -> Option<&PaletteCommand> {
+    let current = self.state.current_selection()?;
+    self.matches.get(current).map(|&i| &self.commands[i])
+  }
+
+  /// Select the next or previous match, wrapping around
+  pub fn move_selection(&mut self, forward: bool) {
+    let count = self.matches.len().max(1);
+    let current = self.state.current_selection().unwrap_or_default();
+    self.state.select(Some(if forward { (current + 1) % count } else { (current + count - 1) % count }));
+  }
+}
+
+/// How well `query` matches `text`, or `None` when it does not
+///
+/// The characters of the query have to appear in the text in the same order, ignoring case and
+/// the spaces of the query. Characters at the start of a word and runs of adjacent characters
+/// score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+  let text: Vec<char> = text.to_lowercase().chars().collect();
+  let mut score = 0;
+  let mut start = 0;
+  let mut previous: Option<usize> = None;
+  for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+    let i = start + text[start..].iter().position(|t| *t == c)?;
+    score += 1;
+    if i == 0 || !text[i - 1].is_alphanumeric() {
+      score += 2;
+    }
+    if previous.is_some_and(|previous| previous + 1 == i) {
+      score += 3;
+    }
+    previous = Some(i);
+    start = i + 1;
+  }
+  Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_fuzzy_score() {
+    assert_eq!(fuzzy_score("", "done"), Some(0));
+    assert!(fuzzy_score("dn", "done").is_some());
+    assert!(fuzzy_score("nd", "done").is_none());
+    assert!(fuzzy_score("Go Top", "go-to-top").is_some());
+    // Word starts and adjacent characters rank first
+    assert!(fuzzy_score("st", "start-stop") > fuzzy_score("st", "select-all"));
+    assert!(fuzzy_score("sa", "select-all") > fuzzy_score("sa", "start-stop"));
+  }
+
+  #[test]
+  fn test_command_palette_matches() {
+    let command = |name: &str| PaletteCommand {
+      name: name.to_string(),
+      keys: String::new(),
+      action: PaletteAction::Report(name.to_string()),
+    };
+    let mut palette = CommandPalette::new();
+    palette.open(vec![command("select-all"), command("start-stop"), command("sync")]);
+    assert_eq!(palette.matches, vec![0, 1, 2]);
+
+    palette.query.update("st", 2, &mut Changeset::default());
+    palette.update_matches();
+    assert_eq!(palette.matches, vec![1, 0]);
+    assert_eq!(palette.selected().map(|command| command.name.as_str()), Some("start-stop"));
+    palette.move_selection(true);
+    assert_eq!(palette.selected().map(|command| command.name.as_str()), Some("select-all"));
+    palette.move_selection(true);
+    assert_eq!(palette.selected().map(|command| command.name.as_str()), Some("start-stop"));
+  }
+}
@@ -0,0 +1,262 @@
+//! Agenda of the tasks in the current report that fall on the coming days
+//!
+//! A task appears on the day of its `due`, `scheduled`, `wait` and `until` dates. The agenda
+//! lists the next `uda.taskwarrior-tui.agenda.days` days, or shows a time grid of one day or one
+//! week, where tasks with a time of day are placed in the row of their hour.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use task_hookrs::task::Task;
+use uuid::Uuid;
+
+use crate::{
+  action::Action,
+  app::{Mode, TaskwarriorTui},
+  event::KeyCode,
+  pane::Pane,
+};
+
+/// The date of a task an agenda entry is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AgendaKind {
+  Due,
+  Scheduled,
+  Wait,
+  Until,
+}
+
+impl AgendaKind {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Due => "due",
+      Self::Scheduled => "scheduled",
+      Self::Wait => "wait",
+      Self::Until => "until",
+    }
+  }
+}
+
+/// How the agenda is laid out; `uda.taskwarrior-tui.keyconfig.zoom` cycles through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaView {
+  /// Entries grouped by day, for the configured number of days
+  List,
+  /// Time grid of a single day
+  Day,
+  /// Time grid of the week holding the first day
+  Week,
+}
+
+/// A task date within the shown days, in local time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgendaEntry {
+  pub when: NaiveDateTime,
+  pub kind: AgendaKind,
+  /// Position of the task in the report
+  pub task: usize,
+}
+
+impl AgendaEntry {
+  /// Whether the date has a time of day; dates at midnight are taken to be for the whole day
+  pub fn is_timed(&self) -> bool {
+    self.when.time() != NaiveTime::MIN
+  }
+}
+
+pub struct AgendaState {
+  /// First day shown
+  pub start: NaiveDate,
+  pub view: AgendaView,
+  /// Entries on the shown days, ordered by time
+  pub entries: Vec<AgendaEntry>,
+  pub current: usize,
+  days: usize,
+  start_on_monday: bool,
+  /// The selected entry, followed across refreshes
+  selected: Option<(Uuid, AgendaKind)>,
+}
+
+impl AgendaState {
+  pub(crate) fn new(days: usize, start_on_monday: bool) -> Self {
+    Self {
+      start: Local::now().date_naive(),
+      view: AgendaView::List,
+      entries: vec![],
+      current: 0,
+      days: days.max(1),
+      start_on_monday,
+      selected: None,
+    }
+  }
+
+  /// The days shown by the current view
+  pub fn days(&self) -> Vec<NaiveDate> {
+    let (first, count) = match self.view {
+      AgendaView::List => (self.start, self.days),
+      AgendaView::Day => (self.start, 1),
+      AgendaView::Week => {
+        let week_start = if self.start_on_monday { Weekday::Mon } else { Weekday::Sun };
+        (self.start.week(week_start).first_day(), 7)
+      }
+    };
+    first.iter_days().take(count).collect()
+  }
+
+  pub fn update_data(&mut self, tasks: &[Task]) {
+    let days = self.days();
+    let (first, last) = (days[0], days[days.len() - 1]);
+    let mut entries = vec![];
+    for (i, task) in tasks.iter().enumerate() {
+      let dates = [
+        (AgendaKind::Due, task.due()),
+        (AgendaKind::Scheduled, task.scheduled()),
+        (AgendaKind::Wait, task.wait()),
+        (AgendaKind::Until, task.until()),
+      ];
+      for (kind, date) in dates.iter() {
+        if let Some(date) = date {
+          let when = Local.from_utc_datetime(date).naive_local();
+          if first <= when.date() && when.date() <= last {
+            entries.push(AgendaEntry { when, kind: *kind, task: i });
+          }
+        }
+      }
+    }
+    entries.sort_by_key(|entry| (entry.when, entry.kind, entry.task));
+    self.entries = entries;
+
+    let selected = self.selected.and_then(|(uuid, kind)| {
+      self
+        .entries
+        .iter()
+        .position(|entry| entry.kind == kind && tasks[entry.task].uuid() == &uuid)
+    });
+    self.current = selected.unwrap_or(self.current).min(self.entries.len().saturating_sub(1));
+    self.selected = self.current_entry().map(|entry| (*tasks[entry.task].uuid(), entry.kind));
+  }
+
+  pub fn current_entry(&self) -> Option<&AgendaEntry> {
+    self.entries.get(self.current)
+  }
+
+  fn select(&mut self, current: usize, tasks: &[Task]) {
+    self.current = current.min(self.entries.len().saturating_sub(1));
+    self.selected = self.current_entry().map(|entry| (*tasks[entry.task].uuid(), entry.kind));
+  }
+
+  /// Move the shown days forwards, or backwards when `steps` is negative, by a day or a week
+  fn shift(&mut self, steps: i64, tasks: &[Task]) {
+    let step = if self.view == AgendaView::Week { 7 } else { 1 };
+    self.start += Duration::days(steps * step);
+    self.selected = None;
+    self.current = 0;
+    self.update_data(tasks);
+  }
+}
+
+impl Pane for AgendaState {
+  fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()> {
+    if input == app.keyconfig.quit || input == KeyCode::Ctrl('c') {
+      app.should_quit = true;
+    } else if input == app.keyconfig.next_tab {
+      Self::change_focus_to_right_pane(app);
+    } else if input == app.keyconfig.previous_tab {
+      Self::change_focus_to_left_pane(app);
+    } else if input == KeyCode::Down || input == app.keyconfig.down {
+      app.agenda.select(app.agenda.current + 1, &app.tasks);
+    } else if input == KeyCode::Up || input == app.keyconfig.up {
+      app.agenda.select(app.agenda.current.saturating_sub(1), &app.tasks);
+    } else if input == KeyCode::Home || input == app.keyconfig.go_to_top {
+      app.agenda.select(0, &app.tasks);
+    } else if input == KeyCode::End || input == app.keyconfig.go_to_bottom {
+      app.agenda.select(usize::MAX, &app.tasks);
+    } else if input == KeyCode::Left || input == KeyCode::Char('h') {
+      app.agenda.shift(-1, &app.tasks);
+    } else if input == KeyCode::Right || input == KeyCode::Char('l') {
+      app.agenda.shift(1, &app.tasks);
+    } else if input == KeyCode::Char('t') {
+      app.agenda.start = Local::now().date_naive();
+      app.agenda.shift(0, &app.tasks);
+    } else if input == app.keyconfig.zoom {
+      app.agenda.view = match app.agenda.view {
+        AgendaView::List => AgendaView::Day,
+        AgendaView::Day => AgendaView::Week,
+        AgendaView::Week => AgendaView::List,
+      };
+      app.agenda.update_data(&app.tasks);
+    } else if input == app.keyconfig.refresh {
+      app.dirty = true;
+    } else if input == KeyCode::Char('\n') {
+      if let Some(entry) = app.agenda.current_entry() {
+        app.current_selection = entry.task;
+        app.current_selection_id = None;
+        app.current_selection_uuid = None;
+        app.mode = Mode::Tasks(Action::Report);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use task_hookrs::{date::Date, status::TaskStatus};
+
+  use super::*;
+
+  fn task_due(description: &str, due: NaiveDateTime) -> Task {
+    let due = Local.from_local_datetime(&due).unwrap().naive_utc();
+    Task::new(
+      None,
+      TaskStatus::Pending,
+      Uuid::new_v4(),
+      Date::from(due),
+      description.to_string(),
+      None,
+      None,
+      Some(Date::from(due)),
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      None,
+      Default::default(),
+    )
+  }
+
+  #[test]
+  fn test_agenda_entries_follow_the_shown_days() {
+    let start = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+    let tasks = vec![
+      task_due("Later", start.and_hms_opt(0, 0, 0).unwrap() + Duration::days(3)),
+      task_due("Meeting", start.and_hms_opt(14, 30, 0).unwrap()),
+      task_due("Next month", start.and_hms_opt(9, 0, 0).unwrap() + Duration::days(30)),
+    ];
+    let mut agenda = AgendaState::new(7, true);
+    agenda.start = start;
+    agenda.update_data(&tasks);
+
+    let descriptions: Vec<&str> = agenda.entries.iter().map(|entry| tasks[entry.task].description().as_str()).collect();
+    assert_eq!(descriptions, vec!["Meeting", "Later"]);
+    assert!(agenda.entries[0].is_timed());
+    assert!(!agenda.entries[1].is_timed());
+    assert_eq!(agenda.entries[0].kind, AgendaKind::Due);
+
+    // The week grid starts on Monday, May 13th
+    agenda.view = AgendaView::Week;
+    assert_eq!(agenda.days()[0], NaiveDate::from_ymd_opt(2024, 5, 13).unwrap());
+    agenda.select(1, &tasks);
+    agenda.shift(1, &tasks);
+    assert_eq!(agenda.days()[0], NaiveDate::from_ymd_opt(2024, 5, 20).unwrap());
+    assert!(agenda.entries.is_empty());
+  }
+}
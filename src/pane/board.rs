@@ -0,0 +1,358 @@
+//! Kanban board of the tasks in the current report
+//!
+//! Tasks are laid out in columns by `uda.taskwarrior-tui.board.group-by`: their status
+//! (pending, started, completed), their tags, or the value of an attribute such as `priority`
+//! or a UDA. Moving a card to another column modifies the task so that it belongs there.
+
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, Result};
+use task_hookrs::{status::TaskStatus, task::Task, uda::UDAValue};
+use uuid::Uuid;
+
+use crate::{
+  action::Action,
+  app::{Mode, TaskwarriorTui},
+  config::Config,
+  event::KeyCode,
+  pane::Pane,
+};
+
+/// Title of the column holding tasks without a value
+const NONE_COLUMN: &str = "(none)";
+
+/// Status columns shown when `uda.taskwarrior-tui.board.columns` is not set
+const STATUS_COLUMNS: &[&str] = &["pending", "started", "completed"];
+
+/// What the board groups tasks by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardGrouping {
+  /// `pending`, `started` (pending with a start date), `completed` or `deleted`
+  Status,
+  /// The leftmost column of a task's tags
+  Tags,
+  /// The value of `project`, `priority` or a UDA
+  Attribute(String),
+}
+
+impl BoardGrouping {
+  pub fn parse(group_by: &str) -> Self {
+    match group_by.trim() {
+      "" | "status" => Self::Status,
+      "tag" | "tags" => Self::Tags,
+      attribute => Self::Attribute(attribute.to_string()),
+    }
+  }
+
+  /// The values a task may be placed by; the first one with a column wins
+  fn values(&self, task: &Task) -> Vec<String> {
+    match self {
+      Self::Status => {
+        let status = match task.status() {
+          TaskStatus::Pending if task.start().is_some() => "started",
+          TaskStatus::Pending => "pending",
+          // Waiting tasks are shown as pending unless they have a column of their own
+          TaskStatus::Waiting => return vec!["waiting".to_string(), "pending".to_string()],
+          TaskStatus::Completed => "completed",
+          TaskStatus::Deleted => "deleted",
+          TaskStatus::Recurring => "recurring",
+        };
+        vec![status.to_string()]
+      }
+      Self::Tags => task.tags().cloned().unwrap_or_default(),
+      Self::Attribute(name) => attribute_value(task, name).into_iter().collect(),
+    }
+  }
+
+  /// Columns when none are configured, in addition to a column for every value found in `tasks`
+  fn default_columns(&self, config: &Config) -> Vec<Option<String>> {
+    match self {
+      Self::Status => STATUS_COLUMNS.iter().map(|status| Some(status.to_string())).collect(),
+      Self::Tags => vec![],
+      Self::Attribute(name) if name == "priority" => config.uda_priority_values.iter().map(|value| column_value(value)).collect(),
+      Self::Attribute(name) => config
+        .uda
+        .iter()
+        .find(|uda| uda.name == *name)
+        .and_then(|uda| uda.values.as_ref())
+        .map(|values| values.iter().map(|value| column_value(value)).collect())
+        .unwrap_or_default(),
+    }
+  }
+
+  /// The `task modify` arguments that move a task from the column `from` to the column `to`
+  pub fn modification(&self, from: Option<&str>, to: Option<&str>, virtual_tags: &[String]) -> Result<String> {
+    match self {
+      Self::Status => match to {
+        Some("pending") => Ok("status:pending start: end:".to_string()),
+        Some("started") => Ok("status:pending start:now end:".to_string()),
+        Some("completed") => Ok("status:completed end:now".to_string()),
+        Some("deleted") => Ok("status:deleted end:now".to_string()),
+        _ => Err(anyhow!("Tasks cannot be moved to {}", to.unwrap_or(NONE_COLUMN))),
+      },
+      Self::Tags => {
+        if let Some(tag) = [from, to].iter().flatten().find(|tag| virtual_tags.iter().any(|v| v == *tag)) {
+          return Err(anyhow!("{} is a virtual tag and cannot be added or removed", tag));
+        }
+        let mut words = vec![];
+        if let Some(from) = from {
+          words.push(format!("-{}", from));
+        }
+        if let Some(to) = to {
+          words.push(format!("+{}", to));
+        }
+        Ok(words.join(" "))
+      }
+      Self::Attribute(name) => {
+        let assignment = format!("{}:{}", name, to.unwrap_or_default());
+        Ok(shlex::try_quote(&assignment)?.into_owned())
+      }
+    }
+  }
+}
+
+fn column_value(value: &str) -> Option<String> {
+  let value = value.trim();
+  if value.is_empty() {
+    None
+  } else {
+    Some(value.to_string())
+  }
+}
+
+fn attribute_value(task: &Task, name: &str) -> Option<String> {
+  match name {
+    "project" => task.project().cloned(),
+    "priority" => task.priority().cloned(),
+    _ => task.uda().get(name).map(|value| match value {
+      UDAValue::Str(s) => s.clone(),
+      UDAValue::U64(n) => n.to_string(),
+      UDAValue::F64(n) => n.to_string(),
+    }),
+  }
+}
+
+/// A board column: the value its tasks share, and their positions in the report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardColumn {
+  pub value: Option<String>,
+  pub tasks: Vec<usize>,
+}
+
+impl BoardColumn {
+  pub fn title(&self) -> &str {
+    self.value.as_deref().unwrap_or(NONE_COLUMN)
+  }
+}
+
+pub struct BoardState {
+  pub columns: Vec<BoardColumn>,
+  pub current_column: usize,
+  pub current_row: usize,
+  /// The selected task, followed across refreshes and moves
+  selected: Option<Uuid>,
+}
+
+impl BoardState {
+  pub(crate) fn new() -> Self {
+    Self {
+      columns: vec![],
+      current_column: 0,
+      current_row: 0,
+      selected: None,
+    }
+  }
+
+  /// Lay out `tasks` in columns
+  ///
+  /// Configured columns come first, in their order; values found only in `tasks` get a column
+  /// after them, and tasks without a value get one before them. Status grouping only shows the
+  /// configured columns, so that e.g. recurring templates are left out.
+  pub fn update_data(&mut self, tasks: &[Task], config: &Config, virtual_tags: &[String]) {
+    let grouping = BoardGrouping::parse(&config.uda_board_group_by);
+    let mut values: Vec<Option<String>> = if config.uda_board_columns.is_empty() {
+      grouping.default_columns(config)
+    } else {
+      config.uda_board_columns.iter().map(|value| column_value(value)).collect()
+    };
+    // Configured tag columns pick the tags to show, any other tag would be noise
+    let add_found = match grouping {
+      BoardGrouping::Status => false,
+      BoardGrouping::Tags => config.uda_board_columns.is_empty(),
+      BoardGrouping::Attribute(_) => true,
+    };
+    if add_found {
+      let found: BTreeSet<String> = tasks
+        .iter()
+        .flat_map(|task| grouping.values(task))
+        .filter(|value| !virtual_tags.contains(value))
+        .collect();
+      for value in found {
+        if !values.contains(&Some(value.clone())) {
+          values.push(Some(value));
+        }
+      }
+    }
+
+    let mut columns: Vec<BoardColumn> = values.into_iter().map(|value| BoardColumn { value, tasks: vec![] }).collect();
+    let mut unplaced = vec![];
+    for (i, task) in tasks.iter().enumerate() {
+      let task_values = grouping.values(task);
+      let column = match grouping {
+        // A task with several tags goes to the leftmost of their columns
+        BoardGrouping::Tags => columns
+          .iter()
+          .position(|column| column.value.as_ref().is_some_and(|value| task_values.contains(value))),
+        _ => task_values
+          .iter()
+          .find_map(|value| columns.iter().position(|column| column.value.as_deref() == Some(value.as_str()))),
+      };
+      match column.or_else(|| columns.iter().position(|column| column.value.is_none())) {
+        Some(column) => columns[column].tasks.push(i),
+        None if grouping != BoardGrouping::Status => unplaced.push(i),
+        None => {}
+      }
+    }
+    if !unplaced.is_empty() {
+      columns.insert(0, BoardColumn { value: None, tasks: unplaced });
+    }
+    self.columns = columns;
+
+    // Keep the selected task selected, wherever it moved
+    let position = self.selected.and_then(|uuid| {
+      self.columns.iter().enumerate().find_map(|(c, column)| {
+        let row = column.tasks.iter().position(|&i| tasks[i].uuid() == &uuid)?;
+        Some((c, row))
+      })
+    });
+    if let Some((column, row)) = position {
+      self.current_column = column;
+      self.current_row = row;
+    } else {
+      self.current_column = self.current_column.min(self.columns.len().saturating_sub(1));
+      self.clamp_row();
+    }
+    self.selected = self.current_task().and_then(|i| tasks.get(i)).map(|task| *task.uuid());
+  }
+
+  /// Position in the report of the selected task
+  pub fn current_task(&self) -> Option<usize> {
+    self.columns.get(self.current_column)?.tasks.get(self.current_row).copied()
+  }
+
+  fn clamp_row(&mut self) {
+    let len = self.columns.get(self.current_column).map_or(0, |column| column.tasks.len());
+    self.current_row = self.current_row.min(len.saturating_sub(1));
+  }
+
+  fn select(&mut self, app_tasks: &[Task]) {
+    self.clamp_row();
+    self.selected = self.current_task().and_then(|i| app_tasks.get(i)).map(|task| *task.uuid());
+  }
+}
+
+impl Pane for BoardState {
+  fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()> {
+    if input == app.keyconfig.quit || input == KeyCode::Ctrl('c') {
+      app.should_quit = true;
+    } else if input == app.keyconfig.next_tab {
+      Self::change_focus_to_right_pane(app);
+    } else if input == app.keyconfig.previous_tab {
+      Self::change_focus_to_left_pane(app);
+    } else if input == KeyCode::Left || input == KeyCode::Char('h') {
+      app.board.current_column = app.board.current_column.saturating_sub(1);
+      app.board.select(&app.tasks);
+    } else if input == KeyCode::Right || input == KeyCode::Char('l') {
+      app.board.current_column = (app.board.current_column + 1).min(app.board.columns.len().saturating_sub(1));
+      app.board.select(&app.tasks);
+    } else if input == KeyCode::Down || input == app.keyconfig.down {
+      app.board.current_row += 1;
+      app.board.select(&app.tasks);
+    } else if input == KeyCode::Up || input == app.keyconfig.up {
+      app.board.current_row = app.board.current_row.saturating_sub(1);
+      app.board.select(&app.tasks);
+    } else if input == KeyCode::Home || input == app.keyconfig.go_to_top {
+      app.board.current_row = 0;
+      app.board.select(&app.tasks);
+    } else if input == KeyCode::End || input == app.keyconfig.go_to_bottom {
+      app.board.current_row = usize::MAX;
+      app.board.select(&app.tasks);
+    } else if input == app.keyconfig.board_move_left {
+      self::move_card(app, -1)?;
+    } else if input == app.keyconfig.board_move_right {
+      self::move_card(app, 1)?;
+    } else if input == app.keyconfig.refresh {
+      app.dirty = true;
+    } else if input == KeyCode::Char('\n') {
+      self::show_in_report(app);
+    }
+    Ok(())
+  }
+}
+
+/// Move the selected card `step` columns to the right, or to the left when negative
+fn move_card(app: &mut TaskwarriorTui, step: isize) -> Result<()> {
+  let Some(task) = app.board.current_task().and_then(|i| app.tasks.get(i)) else {
+    return Ok(());
+  };
+  let from = app.board.current_column;
+  let Some(to) = from.checked_add_signed(step).filter(|to| *to < app.board.columns.len()) else {
+    return Ok(());
+  };
+  let grouping = BoardGrouping::parse(&app.config.uda_board_group_by);
+  let modification = grouping.modification(
+    app.board.columns[from].value.as_deref(),
+    app.board.columns[to].value.as_deref(),
+    &app.task_report_table.virtual_tags,
+  );
+  let modification = match modification {
+    Ok(modification) => modification,
+    Err(err) => {
+      app.error = Some(err.to_string());
+      app.previous_mode = Some(app.mode.clone());
+      app.mode = Mode::Tasks(Action::Error);
+      return Ok(());
+    }
+  };
+  let task_uuid = *task.uuid();
+  let description = modification.clone();
+  app.spawn_mutation(
+    move |backend| backend.modify_tasks(&[task_uuid], &modification),
+    move |err| format!("Error running `task modify {}` for task `{}`. {}", description, task_uuid, err),
+  );
+  app.dirty = true;
+  Ok(())
+}
+
+/// Switch to the task report with the selected card's task selected
+fn show_in_report(app: &mut TaskwarriorTui) {
+  if let Some(i) = app.board.current_task() {
+    app.current_selection = i;
+    app.current_selection_id = None;
+    app.current_selection_uuid = None;
+  }
+  app.mode = Mode::Tasks(Action::Report);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_board_grouping_modification() {
+    let virtual_tags = vec!["ACTIVE".to_string()];
+    let tags = BoardGrouping::parse("tags");
+    assert_eq!(tags.modification(Some("next"), Some("review"), &virtual_tags).unwrap(), "-next +review");
+    assert_eq!(tags.modification(None, Some("next"), &virtual_tags).unwrap(), "+next");
+    assert!(tags.modification(Some("next"), Some("ACTIVE"), &virtual_tags).is_err());
+
+    let status = BoardGrouping::parse("status");
+    assert_eq!(status.modification(Some("pending"), Some("started"), &virtual_tags).unwrap(), "status:pending start:now end:");
+    assert!(status.modification(Some("pending"), None, &virtual_tags).is_err());
+
+    let stage = BoardGrouping::parse("stage");
+    assert_eq!(stage, BoardGrouping::Attribute("stage".to_string()));
+    assert_eq!(stage.modification(None, Some("in review"), &virtual_tags).unwrap(), "'stage:in review'");
+    assert_eq!(stage.modification(Some("done"), None, &virtual_tags).unwrap(), "stage:");
+  }
+}
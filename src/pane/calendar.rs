@@ -0,0 +1,137 @@
+//! Day cursor of the calendar tab
+//!
+//! The cursor moves by day, week, month or year. The tasks due or scheduled on the selected day
+//! are listed next to the calendar, and the selected tasks of the report can be given the
+//! selected day as their `due` or `scheduled` date.
+
+use anyhow::Result;
+use chrono::{Duration, Local, Months, NaiveDate, TimeZone};
+use task_hookrs::task::Task;
+
+use crate::{
+  app::{Mode, TaskwarriorTui},
+  event::KeyCode,
+  pane::{
+    agenda::{AgendaKind, AgendaView},
+    Pane,
+  },
+};
+
+pub struct CalendarState {
+  pub selected: NaiveDate,
+}
+
+impl CalendarState {
+  pub(crate) fn new() -> Self {
+    Self {
+      selected: Local::now().date_naive(),
+    }
+  }
+
+  /// Positions in `tasks` of the tasks due or scheduled on the selected day, due dates first
+  pub fn tasks_on_selected_day(&self, tasks: &[Task]) -> Vec<(AgendaKind, usize)> {
+    let on_selected_day = |date: Option<&task_hookrs::date::Date>| {
+      date.is_some_and(|date| Local.from_utc_datetime(date).date_naive() == self.selected)
+    };
+    let due = tasks.iter().enumerate().filter(|(_, task)| on_selected_day(task.due())).map(|(i, _)| (AgendaKind::Due, i));
+    let scheduled = tasks
+      .iter()
+      .enumerate()
+      .filter(|(_, task)| on_selected_day(task.scheduled()))
+      .map(|(i, _)| (AgendaKind::Scheduled, i));
+    due.chain(scheduled).collect()
+  }
+
+  fn move_days(&mut self, days: i64) {
+    if let Some(selected) = self.selected.checked_add_signed(Duration::days(days)) {
+      self.selected = selected;
+    }
+  }
+
+  /// Move by whole months, keeping the day of the month where possible
+  fn move_months(&mut self, months: i32) {
+    let moved = if months < 0 {
+      self.selected.checked_sub_months(Months::new(months.unsigned_abs()))
+    } else {
+      self.selected.checked_add_months(Months::new(months as u32))
+    };
+    if let Some(selected) = moved {
+      self.selected = selected;
+    }
+  }
+
+  /// Set `attribute` of the selected tasks in the report to the selected day
+  fn set_date_of_selected_tasks(app: &mut TaskwarriorTui, attribute: &'static str) {
+    let task_uuids = app.selected_task_uuids();
+    if task_uuids.is_empty() {
+      return;
+    }
+    let modification = format!("{}:{}", attribute, app.calendar.selected.format("%Y-%m-%d"));
+    let describe = modification.clone();
+    app.spawn_mutation(
+      move |backend| backend.modify_tasks(&task_uuids, &modification),
+      move |err| format!("Unable to set {} of the selected tasks. Error: {}", describe, err),
+    );
+    app.dirty = true;
+  }
+}
+
+impl Pane for CalendarState {
+  fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()> {
+    if input == app.keyconfig.quit || input == KeyCode::Ctrl('c') {
+      app.should_quit = true;
+    } else if input == app.keyconfig.next_tab {
+      Self::change_focus_to_right_pane(app);
+    } else if input == app.keyconfig.previous_tab {
+      Self::change_focus_to_left_pane(app);
+    } else if input == KeyCode::Left || input == KeyCode::Char('h') {
+      app.calendar.move_days(-1);
+    } else if input == KeyCode::Right || input == KeyCode::Char('l') {
+      app.calendar.move_days(1);
+    } else if input == KeyCode::Down || input == app.keyconfig.down {
+      app.calendar.move_days(7);
+    } else if input == KeyCode::Up || input == app.keyconfig.up {
+      app.calendar.move_days(-7);
+    } else if input == app.keyconfig.page_down {
+      app.calendar.move_months(1);
+    } else if input == app.keyconfig.page_up {
+      app.calendar.move_months(-1);
+    } else if input == KeyCode::PageDown {
+      app.calendar.move_months(12);
+    } else if input == KeyCode::PageUp {
+      app.calendar.move_months(-12);
+    } else if input == KeyCode::Home || input == KeyCode::Char('t') {
+      app.calendar.selected = Local::now().date_naive();
+    } else if input == app.keyconfig.calendar_set_due {
+      Self::set_date_of_selected_tasks(app, "due");
+    } else if input == app.keyconfig.calendar_set_scheduled {
+      Self::set_date_of_selected_tasks(app, "scheduled");
+    } else if input == app.keyconfig.refresh {
+      app.dirty = true;
+    } else if input == KeyCode::Char('\n') {
+      // Drill down into the agenda of the selected day
+      app.agenda.start = app.calendar.selected;
+      app.agenda.view = AgendaView::Day;
+      app.agenda.update_data(&app.tasks);
+      app.mode = Mode::Agenda;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_calendar_cursor_moves_by_month() {
+    let mut calendar = CalendarState::new();
+    calendar.selected = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+    calendar.move_months(1);
+    assert_eq!(calendar.selected, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    calendar.move_months(-12);
+    assert_eq!(calendar.selected, NaiveDate::from_ymd_opt(2023, 2, 28).unwrap());
+    calendar.move_days(-7 * 5);
+    assert_eq!(calendar.selected, NaiveDate::from_ymd_opt(2023, 1, 24).unwrap());
+  }
+}
@@ -1,13 +1,9 @@
-use anyhow::Result;
-
 const NAME: &str = "Name";
 const TYPE: &str = "Remaining";
 const DEFINITION: &str = "Avg age";
 const ACTIVE: &str = "Complete";
 
-use std::process::Command;
-
-use crate::table::TaskwarriorTuiTableState;
+use crate::{backend::Context, table::TaskwarriorTuiTableState};
 
 #[derive(Debug, Clone, Default)]
 pub struct ContextDetails {
@@ -59,39 +55,18 @@ impl ContextsState {
     self.rows.len()
   }
 
-  pub fn update_data(&mut self) -> Result<()> {
-    let output = Command::new("task").arg("context").output()?;
-    let data = String::from_utf8_lossy(&output.stdout);
+  pub fn is_empty(&self) -> bool {
+    self.rows.is_empty()
+  }
 
-    self.rows = vec![];
-    for (i, line) in data.trim().split('\n').enumerate() {
-      if line.starts_with("  ") && line.trim().starts_with("write") {
-        continue;
-      }
-      if line.starts_with("  ") && !(line.trim().ends_with("yes") || line.trim().ends_with("no")) {
-        let definition = line.trim();
-        if let Some(c) = self.rows.last_mut() {
-          c.definition = format!("{} {}", c.definition, definition);
-        }
-        continue;
-      }
-      let line = line.trim();
-      if line.is_empty() || line == "Use 'task context none' to unset the current context." {
-        continue;
-      }
-      if i == 0 || i == 1 {
-        continue;
-      }
-      let mut s = line.split_whitespace();
-      let name = s.next().unwrap_or_default();
-      let typ = s.next().unwrap_or_default();
-      let active = s.last().unwrap_or_default();
-      let definition = line.replacen(name, "", 1);
-      let definition = definition.replacen(typ, "", 1);
-      let definition = definition.strip_suffix(active).unwrap_or_default();
-      let context = ContextDetails::new(name.to_string(), definition.trim().to_string(), active.to_string(), typ.to_string());
-      self.rows.push(context);
-    }
+  pub fn update_data(&mut self, contexts: Vec<Context>) {
+    self.rows = contexts
+      .into_iter()
+      .map(|c| {
+        let active = if c.active { "yes" } else { "no" };
+        ContextDetails::new(c.name, c.read_filter, active.to_string(), "read".to_string())
+      })
+      .collect();
     if self.rows.iter().any(|r| r.active != "no") {
       self.rows.insert(
         0,
@@ -103,6 +78,5 @@ impl ContextsState {
         ContextDetails::new("none".to_string(), "".to_string(), "yes".to_string(), "read".to_string()),
       );
     }
-    Ok(())
   }
 }
@@ -0,0 +1,304 @@
+//! Dependency graph of the tasks in the current report
+//!
+//! Every blocked task is drawn as a tree whose children are the tasks it depends on. A task
+//! depended on by several others is expanded once; later occurrences refer back to it. Edges
+//! that lead back to a task on the current path close a cycle and are not followed.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use task_hookrs::task::Task;
+use uuid::Uuid;
+
+use crate::{
+  action::Action,
+  app::{Mode, TaskwarriorTui},
+  event::KeyCode,
+  pane::Pane,
+};
+
+/// A node of the dependency tree, one per line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+  /// Tree lines drawn before the task
+  pub prefix: String,
+  pub uuid: Uuid,
+  /// Position of the task in the report, or `None` for a dependency outside the report
+  pub task: Option<usize>,
+  /// The blocked task whose dependency this row is
+  pub parent: Option<Uuid>,
+  /// The task belongs to a dependency cycle
+  pub in_cycle: bool,
+  /// The edge to this row leads back to a task on its path
+  pub closes_cycle: bool,
+  /// The task's dependencies are already listed at an earlier row
+  pub repeated: bool,
+}
+
+pub struct GraphState {
+  pub rows: Vec<GraphRow>,
+  pub current: usize,
+  /// Tasks of the report that neither depend on nor block another task
+  pub unconnected: usize,
+  /// Task and parent of the selected row, followed across refreshes
+  selected: Option<(Uuid, Option<Uuid>)>,
+}
+
+impl GraphState {
+  pub(crate) fn new() -> Self {
+    Self {
+      rows: vec![],
+      current: 0,
+      unconnected: 0,
+      selected: None,
+    }
+  }
+
+  pub fn update_data(&mut self, tasks: &[Task]) {
+    let index: HashMap<Uuid, usize> = tasks.iter().enumerate().map(|(i, task)| (*task.uuid(), i)).collect();
+    let mut dependencies: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut blocking: HashSet<Uuid> = HashSet::new();
+    for task in tasks {
+      let Some(depends) = task.depends().filter(|depends| !depends.is_empty()) else {
+        continue;
+      };
+      let mut depends = depends.clone();
+      // Dependencies in report order, followed by those outside the report
+      depends.sort_by_key(|uuid| index.get(uuid).copied().unwrap_or(usize::MAX));
+      blocking.extend(depends.iter().copied());
+      dependencies.insert(*task.uuid(), depends);
+    }
+    let in_cycle = tasks_in_cycles(&dependencies);
+
+    let mut builder = TreeBuilder {
+      index: &index,
+      dependencies: &dependencies,
+      in_cycle: &in_cycle,
+      expanded: HashSet::new(),
+      path: vec![],
+      rows: vec![],
+    };
+    // Roots are the blocked tasks nothing depends on; tasks only reachable through a cycle
+    // are left over and become roots afterwards
+    let blocked = tasks.iter().map(|task| *task.uuid()).filter(|uuid| dependencies.contains_key(uuid));
+    let roots: Vec<Uuid> = blocked.clone().filter(|uuid| !blocking.contains(uuid)).collect();
+    for uuid in roots {
+      builder.add(uuid, None, "", "");
+    }
+    for uuid in blocked {
+      if !builder.expanded.contains(&uuid) {
+        builder.add(uuid, None, "", "");
+      }
+    }
+    self.rows = builder.rows;
+    self.unconnected = tasks
+      .iter()
+      .filter(|task| !dependencies.contains_key(task.uuid()) && !blocking.contains(task.uuid()))
+      .count();
+
+    let selected = self
+      .selected
+      .and_then(|(uuid, parent)| self.rows.iter().position(|row| row.uuid == uuid && row.parent == parent));
+    self.select(selected.unwrap_or(self.current));
+  }
+
+  pub fn current_row(&self) -> Option<&GraphRow> {
+    self.rows.get(self.current)
+  }
+
+  fn select(&mut self, current: usize) {
+    self.current = current.min(self.rows.len().saturating_sub(1));
+    self.selected = self.current_row().map(|row| (row.uuid, row.parent));
+  }
+}
+
+struct TreeBuilder<'a> {
+  index: &'a HashMap<Uuid, usize>,
+  dependencies: &'a HashMap<Uuid, Vec<Uuid>>,
+  in_cycle: &'a HashSet<Uuid>,
+  /// Tasks whose dependencies have been listed
+  expanded: HashSet<Uuid>,
+  /// Tasks from the root to the row being added
+  path: Vec<Uuid>,
+  rows: Vec<GraphRow>,
+}
+
+impl TreeBuilder<'_> {
+  /// Add the row for `uuid`, drawn after `prefix`, and below it the rows of its dependencies,
+  /// drawn after `indent`
+  fn add(&mut self, uuid: Uuid, parent: Option<Uuid>, prefix: &str, indent: &str) {
+    let closes_cycle = self.path.contains(&uuid);
+    let children = self.dependencies.get(&uuid).filter(|_| !closes_cycle);
+    let repeated = children.is_some() && self.expanded.contains(&uuid);
+    self.rows.push(GraphRow {
+      prefix: prefix.to_string(),
+      uuid,
+      task: self.index.get(&uuid).copied(),
+      parent,
+      in_cycle: self.in_cycle.contains(&uuid),
+      closes_cycle,
+      repeated,
+    });
+    let Some(children) = children.filter(|_| !repeated) else {
+      return;
+    };
+    self.expanded.insert(uuid);
+    self.path.push(uuid);
+    for (i, child) in children.iter().enumerate() {
+      let last = i + 1 == children.len();
+      let (connector, continuation) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+      self.add(*child, Some(uuid), &format!("{}{}", indent, connector), &format!("{}{}", indent, continuation));
+    }
+    self.path.pop();
+  }
+}
+
+/// Tasks that can reach themselves through their dependencies
+fn tasks_in_cycles(dependencies: &HashMap<Uuid, Vec<Uuid>>) -> HashSet<Uuid> {
+  let mut in_cycle = HashSet::new();
+  for &start in dependencies.keys() {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<Uuid> = dependencies[&start].clone();
+    while let Some(uuid) = stack.pop() {
+      if uuid == start {
+        in_cycle.insert(start);
+        break;
+      }
+      if seen.insert(uuid) {
+        stack.extend(dependencies.get(&uuid).into_iter().flatten().copied());
+      }
+    }
+  }
+  in_cycle
+}
+
+/// Add dependencies of the selected task on the marked tasks, or remove them when `add` is false
+///
+/// Without marked tasks, removing drops the edge from the selected row's parent to the selected task.
+fn edit_dependencies(app: &mut TaskwarriorTui, add: bool) {
+  let Some(row) = app.graph.current_row().cloned() else {
+    return;
+  };
+  let marked: Vec<Uuid> = app.marked.iter().copied().filter(|uuid| *uuid != row.uuid).collect();
+  let (task_uuid, modification) = if !marked.is_empty() {
+    let sign = if add { "" } else { "-" };
+    let references: Vec<String> = marked.iter().map(|uuid| format!("{}{}", sign, uuid)).collect();
+    (row.uuid, format!("depends:{}", references.join(",")))
+  } else if let (false, Some(parent)) = (add, row.parent) {
+    (parent, format!("depends:-{}", row.uuid))
+  } else {
+    app.error = Some("Mark the tasks the selected task should depend on first".to_string());
+    app.previous_mode = Some(app.mode.clone());
+    app.mode = Mode::Tasks(Action::Error);
+    return;
+  };
+  let description = modification.clone();
+  app.spawn_mutation(
+    move |backend| backend.modify_tasks(&[task_uuid], &modification),
+    move |err| format!("Error running `task modify {}` for task `{}`. {}", description, task_uuid, err),
+  );
+  app.dirty = true;
+}
+
+impl Pane for GraphState {
+  fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()> {
+    if input == app.keyconfig.quit || input == KeyCode::Ctrl('c') {
+      app.should_quit = true;
+    } else if input == app.keyconfig.next_tab {
+      Self::change_focus_to_right_pane(app);
+    } else if input == app.keyconfig.previous_tab {
+      Self::change_focus_to_left_pane(app);
+    } else if input == KeyCode::Down || input == app.keyconfig.down {
+      app.graph.select(app.graph.current + 1);
+    } else if input == KeyCode::Up || input == app.keyconfig.up {
+      app.graph.select(app.graph.current.saturating_sub(1));
+    } else if input == KeyCode::Home || input == app.keyconfig.go_to_top {
+      app.graph.select(0);
+    } else if input == KeyCode::End || input == app.keyconfig.go_to_bottom {
+      app.graph.select(usize::MAX);
+    } else if input == app.keyconfig.select {
+      // Only tasks of the report can be marked
+      if let Some(row) = app.graph.current_row().filter(|row| row.task.is_some()) {
+        if !app.marked.insert(row.uuid) {
+          app.marked.remove(&row.uuid);
+        }
+        app.task_table_state.multiple_selection();
+      }
+    } else if input == KeyCode::Esc {
+      app.marked.clear();
+    } else if input == app.keyconfig.graph_add_dependency {
+      edit_dependencies(app, true);
+    } else if input == app.keyconfig.graph_remove_dependency {
+      edit_dependencies(app, false);
+    } else if input == app.keyconfig.refresh {
+      app.dirty = true;
+    } else if input == KeyCode::Char('\n') {
+      if let Some(i) = app.graph.current_row().and_then(|row| row.task) {
+        app.current_selection = i;
+        app.current_selection_id = None;
+        app.current_selection_uuid = None;
+        app.mode = Mode::Tasks(Action::Report);
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rows(dependencies: &[(u128, &[u128])]) -> Vec<(String, u128, bool, bool, bool)> {
+    let tasks: Vec<Task> = dependencies
+      .iter()
+      .map(|(uuid, depends)| {
+        let depends: Vec<String> = depends.iter().map(|uuid| format!("\"{}\"", Uuid::from_u128(*uuid))).collect();
+        serde_json::from_str(&format!(
+          r#"{{"uuid":"{}","status":"pending","entry":"20240515T120000Z","description":"Task {}","depends":[{}]}}"#,
+          Uuid::from_u128(*uuid),
+          uuid,
+          depends.join(",")
+        ))
+        .unwrap()
+      })
+      .collect();
+    let mut graph = GraphState::new();
+    graph.update_data(&tasks);
+    graph
+      .rows
+      .iter()
+      .map(|row| (row.prefix.clone(), row.uuid.as_u128(), row.in_cycle, row.closes_cycle, row.repeated))
+      .collect()
+  }
+
+  #[test]
+  fn test_graph_rows() {
+    let s = String::from;
+    // 1 depends on 2 and 3, which both depend on 4; 5 stands alone
+    assert_eq!(
+      rows(&[(1, &[2, 3]), (2, &[4]), (3, &[4]), (4, &[]), (5, &[])]),
+      vec![
+        (s(""), 1, false, false, false),
+        (s("├─ "), 2, false, false, false),
+        (s("│  └─ "), 4, false, false, false),
+        (s("└─ "), 3, false, false, false),
+        (s("   └─ "), 4, false, false, false),
+      ]
+    );
+    // 1 and 2 depend on each other; 3 depends on 1
+    assert_eq!(
+      rows(&[(1, &[2]), (2, &[1]), (3, &[1])]),
+      vec![
+        (s(""), 3, false, false, false),
+        (s("└─ "), 1, true, false, false),
+        (s("   └─ "), 2, true, false, false),
+        (s("      └─ "), 1, true, true, false),
+      ]
+    );
+    // A cycle nothing else leads into still shows up
+    assert_eq!(
+      rows(&[(1, &[2]), (2, &[1])]),
+      vec![(s(""), 1, true, false, false), (s("└─ "), 2, true, false, false), (s("   └─ "), 1, true, true, false)]
+    );
+  }
+}
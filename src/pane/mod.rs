@@ -6,8 +6,13 @@ use crate::{
   event::KeyCode,
 };
 
+pub mod agenda;
+pub mod board;
+pub mod calendar;
 pub mod context;
+pub mod graph;
 pub mod project;
+pub mod statistics;
 
 pub trait Pane {
   fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()>;
@@ -15,20 +20,28 @@ pub trait Pane {
     match app.mode {
       Mode::Tasks(_) => {
         if app.config.uda_change_focus_rotate {
-          app.mode = Mode::Calendar;
+          app.mode = Mode::Statistics;
         }
       }
       Mode::Projects => app.mode = Mode::Tasks(Action::Report),
       Mode::Calendar => {
         app.mode = Mode::Projects;
       }
+      Mode::Agenda => app.mode = Mode::Calendar,
+      Mode::Board => app.mode = Mode::Agenda,
+      Mode::Graph => app.mode = Mode::Board,
+      Mode::Statistics => app.mode = Mode::Graph,
     }
   }
   fn change_focus_to_right_pane(app: &mut TaskwarriorTui) {
     match app.mode {
       Mode::Tasks(_) => app.mode = Mode::Projects,
       Mode::Projects => app.mode = Mode::Calendar,
-      Mode::Calendar => {
+      Mode::Calendar => app.mode = Mode::Agenda,
+      Mode::Agenda => app.mode = Mode::Board,
+      Mode::Board => app.mode = Mode::Graph,
+      Mode::Graph => app.mode = Mode::Statistics,
+      Mode::Statistics => {
         if app.config.uda_change_focus_rotate {
           app.mode = Mode::Tasks(Action::Report);
         }
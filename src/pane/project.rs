@@ -1,59 +1,183 @@
-use anyhow::{Context as AnyhowContext, Result};
+use anyhow::Result;
 
-const COL_WIDTH: usize = 21;
-const PROJECT_HEADER: &str = "Name";
-const REMAINING_TASK_HEADER: &str = "Remaining";
-const AVG_AGE_HEADER: &str = "Avg age";
-const COMPLETE_HEADER: &str = "Complete";
+/// Name of the node holding the tasks without a project
+const NO_PROJECT: &str = "(none)";
+
+/// Days covered by the burndown of each project, ending today
+const BURNDOWN_DAYS: usize = 14;
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 
 use std::{
-  collections::HashSet,
-  process::Command,
+  cmp::Ordering,
+  collections::{BTreeMap, HashSet},
 };
 
-use task_hookrs::project::Project;
+use chrono::{Duration, NaiveDateTime, Utc};
+use task_hookrs::{project::Project, status::TaskStatus, task::Task};
+use uuid::Uuid;
 
 use crate::{
-  app::TaskwarriorTui,
+  action::Action,
+  app::{Mode, TaskwarriorTui},
   event::KeyCode,
   pane::Pane,
   table::TaskwarriorTuiTableState,
+  task_report::{format_duration, vague_format_date_time},
   utils::Changeset,
 };
 
+/// A column of the projects pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectColumn {
+  Name,
+  Remaining,
+  Active,
+  Completion,
+  Age,
+  NextDue,
+  Urgency,
+  Burndown,
+}
+
+impl ProjectColumn {
+  pub const ALL: [Self; 8] = [
+    Self::Name,
+    Self::Remaining,
+    Self::Active,
+    Self::Completion,
+    Self::Age,
+    Self::NextDue,
+    Self::Urgency,
+    Self::Burndown,
+  ];
+
+  pub fn header(self) -> &'static str {
+    match self {
+      Self::Name => "Name",
+      Self::Remaining => "Remaining",
+      Self::Active => "Active",
+      Self::Completion => "Done",
+      Self::Age => "Avg age",
+      Self::NextDue => "Next due",
+      Self::Urgency => "Urgency",
+      Self::Burndown => "Burndown",
+    }
+  }
+
+  /// The column `uda.taskwarrior-tui.keyconfig.projects-sort` sorts by after this one
+  fn next_sort(self) -> Self {
+    match self {
+      Self::Name => Self::Remaining,
+      Self::Remaining => Self::Active,
+      Self::Active => Self::Completion,
+      Self::Completion => Self::Age,
+      Self::Age => Self::NextDue,
+      Self::NextDue => Self::Urgency,
+      Self::Urgency | Self::Burndown => Self::Name,
+    }
+  }
+}
+
+/// A project of the tree; `work.clientA` is a child of `work`
+///
+/// Statistics roll up: a project includes the tasks of all its subprojects.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectNode {
+  /// Full dotted name, or `(none)` for the tasks without a project
+  pub name: Project,
+  /// Pending and waiting tasks
+  pub remaining: usize,
+  /// Remaining tasks that have been started
+  pub active: usize,
+  pub completed: usize,
+  /// Sum of the ages of the remaining tasks, in seconds
+  pub total_age: i64,
+  /// Earliest due date of the remaining tasks
+  pub next_due: Option<NaiveDateTime>,
+  /// Highest urgency of the remaining tasks
+  pub urgency: Option<f64>,
+  /// Tasks open at the end of each of the last `BURNDOWN_DAYS` days, oldest first
+  pub burndown: Vec<usize>,
+  /// Subprojects, in the order of the current sort
+  pub children: Vec<Project>,
+  /// Positions in `all_tasks` of the remaining tasks of exactly this project
+  pub tasks: Vec<usize>,
+}
+
+impl ProjectNode {
+  /// The last component of the name, e.g. `backend` for `work.clientA.backend`
+  pub fn short_name(&self) -> &str {
+    self.name.rsplit('.').next().unwrap_or_default()
+  }
+
+  /// Share of the completed and remaining tasks that are completed
+  pub fn completion(&self) -> Option<f64> {
+    let total = self.completed + self.remaining;
+    (total > 0).then(|| self.completed as f64 / total as f64)
+  }
+
+  /// Average age of the remaining tasks, in seconds
+  pub fn average_age(&self) -> Option<i64> {
+    (self.remaining > 0).then(|| self.total_age / self.remaining as i64)
+  }
+
+  /// The burndown drawn with one bar per day, scaled to the busiest day
+  pub fn sparkline(&self) -> String {
+    let highest = self.burndown.iter().copied().max().unwrap_or_default();
+    self
+      .burndown
+      .iter()
+      .map(|&open| match open {
+        0 => ' ',
+        _ => SPARKLINE_BARS[(open * SPARKLINE_BARS.len()).div_ceil(highest) - 1],
+      })
+      .collect()
+  }
+}
+
+/// The project and its parent projects, from the top of the tree down
+fn ancestors(project: &str) -> impl Iterator<Item = &str> {
+  let ends = project.match_indices('.').map(|(end, _)| end).chain(std::iter::once(project.len()));
+  ends.map(move |end| &project[..end])
+}
+
+/// A line of the projects pane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectRow {
+  Project { name: Project, depth: usize },
+  /// A task of an expanded project, by position in `all_tasks`
+  Task { index: usize, uuid: Uuid, depth: usize },
+}
+
 pub struct ProjectsState {
-  pub(crate) list: Vec<Project>,
+  pub nodes: BTreeMap<Project, ProjectNode>,
+  /// Projects without a parent, sorted by name with `(none)` last
+  pub roots: Vec<Project>,
+  pub rows: Vec<ProjectRow>,
+  pub expanded: HashSet<Project>,
   pub table_state: TaskwarriorTuiTableState,
   pub current_selection: usize,
   pub marked: HashSet<Project>,
-  pub columns: Vec<String>,
-  pub rows: Vec<ProjectDetails>,
-  pub data: String,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct ProjectDetails {
-  name: Project,
-  remaining: usize,
-  avg_age: String,
-  complete: String,
+  pub sort: ProjectColumn,
+  /// Sort in the opposite of the column's usual order
+  pub sort_reverse: bool,
+  /// When the statistics were computed; ages and due dates are relative to it
+  updated: NaiveDateTime,
 }
 
 impl ProjectsState {
   pub(crate) fn new() -> Self {
     Self {
-      list: Vec::default(),
+      nodes: BTreeMap::new(),
+      roots: vec![],
+      rows: vec![],
+      expanded: HashSet::default(),
       table_state: TaskwarriorTuiTableState::default(),
       current_selection: 0,
       marked: HashSet::default(),
-      columns: vec![
-        PROJECT_HEADER.to_string(),
-        REMAINING_TASK_HEADER.to_string(),
-        AVG_AGE_HEADER.to_string(),
-        COMPLETE_HEADER.to_string(),
-      ],
-      data: Default::default(),
-      rows: vec![],
+      sort: ProjectColumn::Name,
+      sort_reverse: false,
+      updated: Utc::now().naive_utc(),
     }
   }
 
@@ -62,7 +186,7 @@ impl ProjectsState {
     if !app.projects.marked.is_empty() {
       for (idx, project) in app.projects.marked.clone().iter().enumerate() {
         let mut input: String = String::from(project);
-        if input.as_str() == "(none)" {
+        if input.as_str() == NO_PROJECT {
           input = " ".to_string();
         }
         if idx == 0 {
@@ -76,41 +200,264 @@ impl ProjectsState {
     project_pattern
   }
 
+  /// The project of the selected row, or of the selected task's row
+  pub fn selected_project(&self) -> Option<&Project> {
+    let mut rows = self.rows[..self.rows.len().min(self.current_selection + 1)].iter().rev();
+    rows.find_map(|row| match row {
+      ProjectRow::Project { name, .. } => Some(name),
+      ProjectRow::Task { .. } => None,
+    })
+  }
+
   pub fn toggle_mark(&mut self) {
-    if !self.list.is_empty() {
-      let selected = self.current_selection;
-      if !self.marked.insert(self.list[selected].clone()) {
-        self.marked.remove(self.list[selected].as_str());
+    if let Some(ProjectRow::Project { name, .. }) = self.rows.get(self.current_selection).cloned() {
+      if !self.marked.insert(name.clone()) {
+        self.marked.remove(&name);
       }
     }
   }
 
-  pub fn simplified_view(&mut self) -> (Vec<Vec<String>>, Vec<String>) {
+  /// Cells of every row, and the headers
+  pub fn simplified_view(&self, tasks: &[Task]) -> (Vec<Vec<String>>, Vec<String>) {
+    let urgency = |urgency: Option<f64>| urgency.map(|urgency| format!("{:.2}", urgency)).unwrap_or_default();
+    let age = |seconds: Option<i64>| seconds.map(|seconds| format_duration(seconds, false)).unwrap_or_default();
+    let due = |due: Option<NaiveDateTime>| due.map(|due| vague_format_date_time(self.updated, due, false)).unwrap_or_default();
     let rows = self
       .rows
       .iter()
-      .map(|c| vec![c.name.clone(), c.remaining.to_string(), c.avg_age.to_string(), c.complete.clone()])
+      .map(|row| match row {
+        ProjectRow::Project { name, depth } => {
+          let node = &self.nodes[name];
+          let marker = if node.children.is_empty() && node.tasks.is_empty() {
+            " "
+          } else if self.expanded.contains(name) {
+            "▾"
+          } else {
+            "▸"
+          };
+          ProjectColumn::ALL
+            .iter()
+            .map(|column| match column {
+              ProjectColumn::Name => format!("{}{} {}", "  ".repeat(*depth), marker, node.short_name()),
+              ProjectColumn::Remaining => node.remaining.to_string(),
+              ProjectColumn::Active => node.active.to_string(),
+              ProjectColumn::Completion => node.completion().map(|done| format!("{:.0}%", done * 100.0)).unwrap_or_default(),
+              ProjectColumn::Age => age(node.average_age()),
+              ProjectColumn::NextDue => due(node.next_due),
+              ProjectColumn::Urgency => urgency(node.urgency),
+              ProjectColumn::Burndown => node.sparkline(),
+            })
+            .collect()
+        }
+        ProjectRow::Task { index, depth, .. } => {
+          let task = &tasks[*index];
+          ProjectColumn::ALL
+            .iter()
+            .map(|column| match column {
+              ProjectColumn::Name => {
+                let id = task.id().map(|id| format!("#{} ", id)).unwrap_or_default();
+                format!("{}  {}{}", "  ".repeat(*depth), id, task.description())
+              }
+              ProjectColumn::Active => if task.start().is_some() { "*" } else { "" }.to_string(),
+              ProjectColumn::Age => age(Some((self.updated - **task.entry()).num_seconds())),
+              ProjectColumn::NextDue => due(task.due().map(|due| **due)),
+              ProjectColumn::Urgency => urgency(task.urgency().copied()),
+              _ => String::new(),
+            })
+            .collect()
+        }
+      })
+      .collect();
+    let headers = ProjectColumn::ALL
+      .iter()
+      .map(|&column| {
+        if column != self.sort {
+          column.header().to_string()
+        } else if self.sort_reverse {
+          format!("{} ↑", column.header())
+        } else {
+          format!("{} ↓", column.header())
+        }
+      })
       .collect();
-    let headers = self.columns.clone();
     (rows, headers)
   }
 
-  pub fn last_line(&self, line: &str) -> bool {
-    let words = line.trim().split(' ').map(|s| s.trim()).collect::<Vec<&str>>();
-    words.len() == 2 && words[0].chars().all(|c| c.is_numeric()) && (words[1] == "project" || words[1] == "projects")
+  /// Rebuild the tree from every exported task, keeping the expanded projects and the selection
+  pub fn update_data(&mut self, tasks: &[Task]) {
+    self.update_data_at(tasks, Utc::now().naive_utc());
   }
 
-  pub fn update_data(&mut self) -> Result<()> {
-    self.list.clear();
-    self.rows.clear();
-    let output = Command::new("task")
-      .arg("summary")
-      .output()
-      .with_context(|| "Unable to run `task summary`")
-      .unwrap();
-    let data = String::from_utf8_lossy(&output.stdout);
-    self.data = data.into();
-    Ok(())
+  fn update_data_at(&mut self, tasks: &[Task], now: NaiveDateTime) {
+    self.updated = now;
+    let day_ends: Vec<NaiveDateTime> = (0..BURNDOWN_DAYS).rev().map(|days| now - Duration::days(days as i64)).collect();
+
+    self.nodes.clear();
+    for (i, task) in tasks.iter().enumerate() {
+      let remaining = matches!(task.status(), TaskStatus::Pending | TaskStatus::Waiting);
+      let completed = *task.status() == TaskStatus::Completed;
+      // Recurring templates stand for the tasks they create
+      if !remaining && !completed && *task.status() != TaskStatus::Deleted {
+        continue;
+      }
+      let project = task.project().cloned().unwrap_or_else(|| NO_PROJECT.to_string());
+      let mut parent: Option<&str> = None;
+      for name in ancestors(&project) {
+        let node = self.nodes.entry(name.to_string()).or_insert_with(|| ProjectNode {
+          name: name.to_string(),
+          burndown: vec![0; BURNDOWN_DAYS],
+          ..ProjectNode::default()
+        });
+        for (open, day_end) in node.burndown.iter_mut().zip(day_ends.iter()) {
+          if **task.entry() <= *day_end && task.end().is_none_or(|end| **end > *day_end) {
+            *open += 1;
+          }
+        }
+        if completed {
+          node.completed += 1;
+        }
+        if remaining {
+          node.remaining += 1;
+          node.total_age += (now - **task.entry()).num_seconds();
+          if task.start().is_some() {
+            node.active += 1;
+          }
+          if let Some(due) = task.due() {
+            node.next_due = Some(node.next_due.map_or(**due, |next| next.min(**due)));
+          }
+          if let Some(urgency) = task.urgency() {
+            node.urgency = Some(node.urgency.map_or(*urgency, |highest| highest.max(*urgency)));
+          }
+        }
+        if let Some(parent) = parent {
+          let parent = self.nodes.get_mut(parent).expect("Parents are added before their children");
+          if !parent.children.iter().any(|child| child == name) {
+            parent.children.push(name.to_string());
+          }
+        }
+        parent = Some(name);
+      }
+      if remaining {
+        if let Some(node) = self.nodes.get_mut(&project) {
+          node.tasks.push(i);
+        }
+      }
+    }
+    // Only projects with remaining tasks are shown; their parents have remaining tasks too
+    self.nodes.retain(|_, node| node.remaining > 0);
+    let names: HashSet<Project> = self.nodes.keys().cloned().collect();
+    for node in self.nodes.values_mut() {
+      node.children.retain(|child| names.contains(child));
+      node.tasks.sort_by(|a, b| {
+        let urgency = |i: &usize| tasks[*i].urgency().copied().unwrap_or_default();
+        urgency(b).total_cmp(&urgency(a))
+      });
+    }
+    self.roots = self.nodes.keys().filter(|name| !name.contains('.') && *name != NO_PROJECT).cloned().collect();
+    let nodes = &self.nodes;
+    self.expanded.retain(|name| nodes.contains_key(name));
+    self.sort_rows(tasks);
+  }
+
+  /// Order projects and their subprojects by the sort column, with `(none)` last
+  ///
+  /// Names break ties, and the rows are rebuilt keeping the selected project or task.
+  fn sort_rows(&mut self, tasks: &[Task]) {
+    let selected = self.rows.get(self.current_selection).cloned();
+
+    let nodes = &self.nodes;
+    let (sort, reverse) = (self.sort, self.sort_reverse);
+    let compare = |a: &Project, b: &Project| {
+      let (a, b) = (&nodes[a], &nodes[b]);
+      let ordering = match sort {
+        ProjectColumn::Name | ProjectColumn::Burndown => Ordering::Equal,
+        ProjectColumn::Remaining => b.remaining.cmp(&a.remaining),
+        ProjectColumn::Active => b.active.cmp(&a.active),
+        ProjectColumn::Completion => b.completion().unwrap_or_default().total_cmp(&a.completion().unwrap_or_default()),
+        ProjectColumn::Age => b.average_age().cmp(&a.average_age()),
+        // Projects without a due date come last
+        ProjectColumn::NextDue => match (a.next_due, b.next_due) {
+          (Some(a), Some(b)) => a.cmp(&b),
+          (a, b) => a.is_none().cmp(&b.is_none()),
+        },
+        ProjectColumn::Urgency => b.urgency.unwrap_or_default().total_cmp(&a.urgency.unwrap_or_default()),
+      };
+      let ordering = ordering.then_with(|| a.name.cmp(&b.name));
+      if reverse {
+        ordering.reverse()
+      } else {
+        ordering
+      }
+    };
+    let children: Vec<(Project, Vec<Project>)> = nodes
+      .iter()
+      .map(|(name, node)| {
+        let mut children = node.children.clone();
+        children.sort_by(compare);
+        (name.clone(), children)
+      })
+      .collect();
+    self.roots.retain(|name| name != NO_PROJECT);
+    self.roots.sort_by(compare);
+    if self.nodes.contains_key(NO_PROJECT) {
+      self.roots.push(NO_PROJECT.to_string());
+    }
+    for (name, children) in children {
+      if let Some(node) = self.nodes.get_mut(&name) {
+        node.children = children;
+      }
+    }
+    self.update_rows(tasks);
+
+    let position = selected.and_then(|selected| {
+      self.rows.iter().position(|row| match (row, &selected) {
+        (ProjectRow::Project { name, .. }, ProjectRow::Project { name: selected, .. }) => name == selected,
+        (ProjectRow::Task { uuid, .. }, ProjectRow::Task { uuid: selected, .. }) => uuid == selected,
+        _ => false,
+      })
+    });
+    self.current_selection = position.unwrap_or(self.current_selection).min(self.rows.len().saturating_sub(1));
+    self.update_table_state();
+  }
+
+  /// Flatten the tree into rows, showing the children and tasks of expanded projects
+  fn update_rows(&mut self, tasks: &[Task]) {
+    fn add(state: &ProjectsState, tasks: &[Task], name: &Project, depth: usize, rows: &mut Vec<ProjectRow>) {
+      rows.push(ProjectRow::Project { name: name.clone(), depth });
+      if !state.expanded.contains(name) {
+        return;
+      }
+      let node = &state.nodes[name];
+      for child in &node.children {
+        add(state, tasks, child, depth + 1, rows);
+      }
+      rows.extend(node.tasks.iter().map(|&index| ProjectRow::Task {
+        index,
+        uuid: *tasks[index].uuid(),
+        depth: depth + 1,
+      }));
+    }
+    let mut rows = vec![];
+    for root in &self.roots {
+      add(self, tasks, root, 0, &mut rows);
+    }
+    self.rows = rows;
+  }
+
+  /// Expand or collapse the selected project; on a task row this acts on the task's project
+  fn set_expanded(&mut self, expanded: bool, tasks: &[Task]) {
+    let Some(name) = self.selected_project().cloned() else {
+      return;
+    };
+    if expanded {
+      self.expanded.insert(name.clone());
+    } else {
+      self.expanded.remove(&name);
+    }
+    self.update_rows(tasks);
+    if let Some(position) = self.rows.iter().position(|row| matches!(row, ProjectRow::Project { name: n, .. } if *n == name)) {
+      self.current_selection = position;
+    }
   }
 
   fn update_table_state(&mut self) {
@@ -121,7 +468,10 @@ impl ProjectsState {
       self.table_state.multiple_selection();
       self.table_state.clear();
       for project in &self.marked {
-        let index = self.list.iter().position(|x| x == project);
+        let index = self
+          .rows
+          .iter()
+          .position(|row| matches!(row, ProjectRow::Project { name, .. } if name == project));
         self.table_state.mark(index);
       }
     }
@@ -140,8 +490,20 @@ impl Pane for ProjectsState {
       self::focus_on_next_project(app);
     } else if input == KeyCode::Up || input == app.keyconfig.up {
       self::focus_on_previous_project(app);
+    } else if input == KeyCode::Right || input == KeyCode::Char('l') {
+      app.projects.set_expanded(true, &app.all_tasks);
+    } else if input == KeyCode::Left || input == KeyCode::Char('h') {
+      app.projects.set_expanded(false, &app.all_tasks);
+    } else if input == KeyCode::Char('\n') {
+      self::toggle_or_show_task(app);
     } else if input == app.keyconfig.select {
       self::update_task_filter_by_selection(app)?;
+    } else if input == app.keyconfig.projects_sort {
+      app.projects.sort = app.projects.sort.next_sort();
+      app.projects.sort_rows(&app.all_tasks);
+    } else if input == app.keyconfig.projects_sort_reverse {
+      app.projects.sort_reverse = !app.projects.sort_reverse;
+      app.projects.sort_rows(&app.all_tasks);
     }
     app.projects.update_table_state();
     Ok(())
@@ -149,7 +511,7 @@ impl Pane for ProjectsState {
 }
 
 fn focus_on_next_project(app: &mut TaskwarriorTui) {
-  if app.projects.current_selection < app.projects.list.len().saturating_sub(1) {
+  if app.projects.current_selection < app.projects.rows.len().saturating_sub(1) {
     app.projects.current_selection += 1;
     app.projects.table_state.select(Some(app.projects.current_selection));
   }
@@ -162,6 +524,26 @@ fn focus_on_previous_project(app: &mut TaskwarriorTui) {
   }
 }
 
+/// Expand or collapse the selected project, or show the selected task in the report
+fn toggle_or_show_task(app: &mut TaskwarriorTui) {
+  match app.projects.rows.get(app.projects.current_selection).cloned() {
+    Some(ProjectRow::Project { name, .. }) => {
+      let expanded = app.projects.expanded.contains(&name);
+      app.projects.set_expanded(!expanded, &app.all_tasks);
+    }
+    Some(ProjectRow::Task { uuid, .. }) => {
+      // Tasks outside the report's filter cannot be selected there
+      if let Some(i) = app.tasks.iter().position(|task| *task.uuid() == uuid) {
+        app.current_selection = i;
+        app.current_selection_id = None;
+        app.current_selection_uuid = None;
+        app.mode = Mode::Tasks(Action::Report);
+      }
+    }
+    None => {}
+  }
+}
+
 fn update_task_filter_by_selection(app: &mut TaskwarriorTui) -> Result<()> {
   app.projects.table_state.multiple_selection();
   let last_project_pattern = ProjectsState::pattern_by_marked(app);
@@ -173,5 +555,97 @@ fn update_task_filter_by_selection(app: &mut TaskwarriorTui) -> Result<()> {
   let mut filter = current_filter.replace(&last_project_pattern, "");
   filter = format!("{}{}", filter, new_project_pattern);
   app.filter.update(filter.as_str(), filter.len(), &mut Changeset::default());
+  // Show the tasks of the selected projects right away
+  app.dirty = true;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn task(description: &str, project: Option<&str>, urgency: f64) -> Task {
+    let project = project.map(|project| format!(r#","project":"{}""#, project)).unwrap_or_default();
+    serde_json::from_str(&format!(
+      r#"{{"uuid":"{}","status":"pending","entry":"20240515T120000Z","description":"{}","urgency":{}{}}}"#,
+      uuid::Uuid::new_v4(),
+      description,
+      urgency,
+      project
+    ))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_project_tree_rolls_up() {
+    let tasks = vec![
+      task("Fix login", Some("work.clientA.backend"), 5.0),
+      task("Invoice", Some("work.clientA"), 2.0),
+      task("Standup notes", Some("work"), 1.0),
+      task("Water plants", None, 3.0),
+      task("Plan trip", Some("home"), 4.0),
+    ];
+    let mut projects = ProjectsState::new();
+    projects.update_data(&tasks);
+
+    assert_eq!(projects.roots, vec!["home", "work", NO_PROJECT]);
+    let work = &projects.nodes["work"];
+    assert_eq!(work.remaining, 3);
+    assert_eq!(work.urgency, Some(5.0));
+    assert_eq!(work.children, vec!["work.clientA"]);
+    assert_eq!(projects.nodes["work.clientA"].short_name(), "clientA");
+
+    // Expanding lists subprojects before the project's own tasks
+    projects.current_selection = 1;
+    projects.set_expanded(true, &tasks);
+    let (rows, _) = projects.simplified_view(&tasks);
+    let names: Vec<&str> = rows.iter().map(|row| row[0].as_str()).collect();
+    assert_eq!(names, vec!["▸ home", "▾ work", "  ▸ clientA", "    Standup notes", "▸ (none)"]);
+  }
+
+  #[test]
+  fn test_project_statistics() {
+    let task = |project: &str, status: &str, entry: &str, fields: &str| -> Task {
+      serde_json::from_str(&format!(
+        r#"{{"uuid":"{}","status":"{}","entry":"{}","description":"Task","project":"{}"{}}}"#,
+        uuid::Uuid::new_v4(),
+        status,
+        entry,
+        project,
+        fields
+      ))
+      .unwrap()
+    };
+    let tasks = vec![
+      task("work", "pending", "20240515T120000Z", r#","due":"20240525T000000Z","start":"20240519T000000Z""#),
+      task("work.api", "pending", "20240519T120000Z", r#","due":"20240522T000000Z""#),
+      task("work", "completed", "20240510T000000Z", r#","end":"20240518T120000Z""#),
+      task("work", "deleted", "20240510T000000Z", r#","end":"20240516T000000Z""#),
+      task("work", "recurring", "20240501T000000Z", ""),
+      task("home", "completed", "20240510T000000Z", r#","end":"20240511T000000Z""#),
+      task("garden", "pending", "20240520T000000Z", ""),
+    ];
+    let now = NaiveDateTime::parse_from_str("2024-05-20 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    let mut projects = ProjectsState::new();
+    projects.update_data_at(&tasks, now);
+
+    // Projects without remaining tasks are left out
+    assert_eq!(projects.roots, vec!["garden", "work"]);
+    let work = &projects.nodes["work"];
+    assert_eq!((work.remaining, work.active, work.completed), (2, 1, 1));
+    assert_eq!(work.average_age(), Some(3 * 24 * 60 * 60));
+    assert_eq!(work.next_due, Some(NaiveDateTime::parse_from_str("2024-05-22 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()));
+    assert_eq!(work.burndown, vec![0, 0, 0, 2, 2, 2, 2, 2, 3, 2, 2, 1, 2, 2]);
+    assert_eq!(work.sparkline(), "   ▆▆▆▆▆█▆▆▃▆▆");
+    let (rows, headers) = projects.simplified_view(&tasks);
+    assert_eq!(headers[0], "Name ↓");
+    assert_eq!(rows[1][1..7], ["2", "1", "33%", "3d", "1d", ""]);
+
+    projects.sort = ProjectColumn::NextDue;
+    projects.sort_rows(&tasks);
+    assert_eq!(projects.roots, vec!["work", "garden"]);
+    projects.sort_reverse = true;
+    projects.sort_rows(&tasks);
+    assert_eq!(projects.roots, vec!["garden", "work"]);
+  }
+}
@@ -0,0 +1,258 @@
+//! Charts of every exported task that matches the current filter and context
+//!
+//! The window is split into days or weeks. At the end of each, the burndown counts the open
+//! tasks and the tasks completed since the window started, using their `entry` and `end` dates,
+//! and the overdue trend counts the open tasks past their due date. Throughput is the number
+//! of tasks each project completed within the window.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use ratatui::{text::Span, widgets::Axis};
+use task_hookrs::{status::TaskStatus, task::Task};
+
+use crate::{
+  app::TaskwarriorTui,
+  backend::filter::{Filter, FilterContext},
+  event::KeyCode,
+  pane::Pane,
+};
+
+/// The time span the charts cover, ending now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsWindow {
+  Week,
+  Month,
+  Quarter,
+  Year,
+}
+
+impl StatisticsWindow {
+  pub fn days(self) -> i64 {
+    match self {
+      Self::Week => 7,
+      Self::Month => 30,
+      Self::Quarter => 91,
+      Self::Year => 365,
+    }
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Week => "week",
+      Self::Month => "month",
+      Self::Quarter => "quarter",
+      Self::Year => "year",
+    }
+  }
+
+  fn larger(self) -> Self {
+    match self {
+      Self::Week => Self::Month,
+      Self::Month => Self::Quarter,
+      Self::Quarter | Self::Year => Self::Year,
+    }
+  }
+
+  fn smaller(self) -> Self {
+    match self {
+      Self::Week | Self::Month => Self::Week,
+      Self::Quarter => Self::Month,
+      Self::Year => Self::Quarter,
+    }
+  }
+}
+
+/// Task counts at the end of a day or week
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatisticsPoint {
+  /// End of the day or week, in UTC
+  pub end: NaiveDateTime,
+  /// Tasks added but neither completed nor deleted
+  pub open: usize,
+  /// Tasks completed since the window started
+  pub completed: usize,
+  /// Open tasks past their due date
+  pub overdue: usize,
+}
+
+pub struct StatisticsState {
+  pub window: StatisticsWindow,
+  /// One point per week instead of per day
+  pub weekly: bool,
+  pub points: Vec<StatisticsPoint>,
+  /// Tasks completed within the window per project, most first
+  pub throughput: Vec<(String, usize)>,
+  /// Why the filter could not be applied
+  pub error: Option<String>,
+  weekstart_monday: bool,
+}
+
+impl StatisticsState {
+  pub(crate) fn new(weekstart_monday: bool) -> Self {
+    Self {
+      window: StatisticsWindow::Month,
+      weekly: false,
+      points: vec![],
+      throughput: vec![],
+      error: None,
+      weekstart_monday,
+    }
+  }
+
+  /// Recompute the charts from every exported task, keeping those matching the report and context filters
+  pub fn update_data(&mut self, tasks: &[Task], filter: &str, context_filter: &str) {
+    self.update_data_at(tasks, filter, context_filter, Utc::now().naive_utc());
+  }
+
+  fn update_data_at(&mut self, tasks: &[Task], filter: &str, context_filter: &str, now: NaiveDateTime) {
+    let filter = match statistics_filter(filter, context_filter) {
+      Ok(filter) => filter,
+      Err(err) => {
+        self.error = Some(format!("Unable to apply the filter to the statistics. {}", err));
+        self.points.clear();
+        self.throughput.clear();
+        return;
+      }
+    };
+    self.error = None;
+    let context = FilterContext::new(tasks).weekstart_monday(self.weekstart_monday);
+    // Recurring templates stand for the tasks they create
+    let tasks: Vec<&Task> = tasks
+      .iter()
+      .filter(|task| *task.status() != TaskStatus::Recurring && filter.matches(task, &context))
+      .collect();
+
+    let step = if self.weekly { 7 } else { 1 };
+    let count = (self.window.days() / step).max(2);
+    let start = now - Duration::days(self.window.days());
+    self.points = (0..count)
+      .rev()
+      .map(|i| {
+        let end = now - Duration::days(i * step);
+        let mut point = StatisticsPoint { end, ..StatisticsPoint::default() };
+        for task in &tasks {
+          let ended = task.end().map(|date| **date);
+          if **task.entry() <= end && ended.is_none_or(|ended| ended > end) {
+            point.open += 1;
+            if task.due().is_some_and(|due| **due < end) {
+              point.overdue += 1;
+            }
+          }
+          if *task.status() == TaskStatus::Completed && ended.is_some_and(|ended| start < ended && ended <= end) {
+            point.completed += 1;
+          }
+        }
+        point
+      })
+      .collect();
+
+    let mut throughput: HashMap<String, usize> = HashMap::new();
+    for task in &tasks {
+      if *task.status() == TaskStatus::Completed && task.end().is_some_and(|ended| start < **ended && **ended <= now) {
+        let project = task.project().cloned().unwrap_or_else(|| "(none)".to_string());
+        *throughput.entry(project).or_default() += 1;
+      }
+    }
+    let mut throughput: Vec<(String, usize)> = throughput.into_iter().collect();
+    throughput.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    self.throughput = throughput;
+  }
+}
+
+/// The report filter combined with the context filter
+///
+/// The charts follow tasks through every status, so `status:` terms are left out of both.
+fn statistics_filter(filter: &str, context_filter: &str) -> Result<Filter> {
+  let without_status_terms = |filter: &str| {
+    let words: Vec<&str> = filter
+      .split_whitespace()
+      .filter(|word| !word.starts_with("status:") && !word.starts_with("status.is:"))
+      .collect();
+    words.join(" ")
+  };
+  Ok(Filter::parse(&without_status_terms(filter))?.and(Filter::parse(&without_status_terms(context_filter))?))
+}
+
+/// Axis of task counts from zero to `highest`, labelled at both ends and in the middle when there is room between them
+pub fn count_axis(highest: f64) -> Axis<'static> {
+  let highest = highest.max(1.0);
+  let mut labels = vec![Span::raw("0")];
+  if highest >= 2.0 {
+    labels.push(Span::raw(format!("{:.0}", highest / 2.0)));
+  }
+  labels.push(Span::raw(format!("{:.0}", highest)));
+  Axis::default().bounds([0.0, highest]).labels(labels)
+}
+
+impl Pane for StatisticsState {
+  fn handle_input(app: &mut TaskwarriorTui, input: KeyCode) -> Result<()> {
+    if input == app.keyconfig.quit || input == KeyCode::Ctrl('c') {
+      app.should_quit = true;
+    } else if input == app.keyconfig.next_tab {
+      Self::change_focus_to_right_pane(app);
+    } else if input == app.keyconfig.previous_tab {
+      Self::change_focus_to_left_pane(app);
+    } else if input == KeyCode::Left || input == KeyCode::Char('h') {
+      app.statistics.window = app.statistics.window.smaller();
+      app.update_statistics();
+    } else if input == KeyCode::Right || input == KeyCode::Char('l') {
+      app.statistics.window = app.statistics.window.larger();
+      app.update_statistics();
+    } else if input == app.keyconfig.zoom {
+      app.statistics.weekly = !app.statistics.weekly;
+      app.update_statistics();
+    } else if input == app.keyconfig.refresh {
+      app.dirty = true;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_statistics_points() {
+    let task = |project: &str, status: &str, entry: &str, fields: &str| -> Task {
+      serde_json::from_str(&format!(
+        r#"{{"uuid":"{}","status":"{}","entry":"{}","description":"Task","project":"{}"{}}}"#,
+        uuid::Uuid::new_v4(),
+        status,
+        entry,
+        project,
+        fields
+      ))
+      .unwrap()
+    };
+    let tasks = vec![
+      task("work", "pending", "20240510T000000Z", r#","due":"20240515T000000Z""#),
+      task("work", "completed", "20240510T000000Z", r#","end":"20240518T000000Z""#),
+      task("home", "completed", "20240501T000000Z", r#","end":"20240519T000000Z""#),
+      task("home", "deleted", "20240501T000000Z", r#","end":"20240512T000000Z""#),
+      task("home", "completed", "20240401T000000Z", r#","end":"20240402T000000Z""#),
+    ];
+    let now = NaiveDateTime::parse_from_str("2024-05-20 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    let mut statistics = StatisticsState::new(true);
+    statistics.window = StatisticsWindow::Week;
+    statistics.update_data_at(&tasks, "status:pending -WAITING", "", now);
+
+    let counts: Vec<(usize, usize, usize)> = statistics.points.iter().map(|point| (point.open, point.completed, point.overdue)).collect();
+    // From the 14th to the 20th of May
+    assert_eq!(counts, vec![(3, 0, 0), (3, 0, 1), (3, 0, 1), (3, 0, 1), (2, 1, 1), (1, 2, 1), (1, 2, 1)]);
+    assert_eq!(statistics.throughput, vec![("home".to_string(), 1), ("work".to_string(), 1)]);
+
+    // Weekly points over a month, with only the tasks of a project
+    statistics.window = StatisticsWindow::Month;
+    statistics.weekly = true;
+    statistics.update_data_at(&tasks, "project:home", "", now);
+    let open: Vec<usize> = statistics.points.iter().map(|point| point.open).collect();
+    assert_eq!(open, vec![0, 2, 1, 0]);
+
+    statistics.update_data_at(&tasks, "(project:home", "", now);
+    assert!(statistics.error.is_some());
+    assert!(statistics.points.is_empty());
+  }
+}
@@ -0,0 +1,101 @@
+//! Task reports shown side by side
+//!
+//! Only the focused report lives in the fields of `TaskwarriorTui`. Every other pane keeps its
+//! report, filter, tasks and selection in a `ReportPane`, and trades them with the app's fields
+//! when it gets the focus. Marked tasks are shared by all panes.
+
+use rustyline::line_buffer::LineBuffer;
+use task_hookrs::task::Task;
+use uuid::Uuid;
+
+use crate::{app::MAX_LINE, table::TaskwarriorTuiTableState, task_report::TaskReportTable, utils::Changeset};
+
+/// Attributes that a filter can ask for but a modification cannot set
+const UNSETTABLE_ATTRIBUTES: &[&str] = &["status", "limit", "id", "uuid", "description", "urgency", "entry", "modified", "end"];
+
+pub struct ReportPane {
+  pub report: String,
+  pub filter: LineBuffer,
+  pub tasks: Vec<Task>,
+  pub task_report_table: TaskReportTable,
+  pub task_table_state: TaskwarriorTuiTableState,
+  pub current_selection: usize,
+  pub current_selection_uuid: Option<Uuid>,
+  pub current_selection_id: Option<u64>,
+}
+
+impl ReportPane {
+  pub fn new(report: &str, filter: &str, task_report_table: TaskReportTable) -> Self {
+    let mut buffer = LineBuffer::with_capacity(MAX_LINE);
+    buffer.update(filter, filter.len(), &mut Changeset::default());
+    Self {
+      report: report.to_string(),
+      filter: buffer,
+      tasks: vec![],
+      task_report_table,
+      task_table_state: TaskwarriorTuiTableState::default(),
+      current_selection: 0,
+      current_selection_uuid: None,
+      current_selection_id: None,
+    }
+  }
+
+  /// Replace the tasks of a pane without the focus, keeping its selected task when it is still listed
+  pub fn set_tasks(&mut self, tasks: Vec<Task>) {
+    let selected = self.tasks.get(self.current_selection).map(|task| *task.uuid());
+    self.tasks = tasks;
+    let position = selected.and_then(|uuid| self.tasks.iter().position(|task| *task.uuid() == uuid));
+    self.current_selection = position.unwrap_or(self.current_selection).min(self.tasks.len().saturating_sub(1));
+    self.task_table_state.select(Some(self.current_selection));
+  }
+}
+
+/// The modification that makes a task match `filter`, built from its `attribute:value` and tag terms
+///
+/// Terms a modification cannot satisfy are left out: virtual tags, attribute modifiers such as
+/// `due.before:`, searches and anything in parentheses. A filter with `or` or `xor` does not say
+/// which alternative to pick, so it gives no modification.
+pub fn modification_for_filter(filter: &str) -> Option<String> {
+  let mut terms = vec![];
+  let mut depth: usize = 0;
+  for word in filter.split_whitespace() {
+    let opening = word.matches('(').count();
+    let closing = word.matches(')').count();
+    let grouped = depth > 0 || opening > 0;
+    depth = (depth + opening).saturating_sub(closing);
+    if grouped {
+      continue;
+    }
+    if word == "or" || word == "xor" {
+      return None;
+    }
+    if let Some(tag) = word.strip_prefix('+').or_else(|| word.strip_prefix('-')) {
+      // Virtual tags like +OVERDUE are computed by Taskwarrior
+      if !tag.is_empty() && tag.chars().any(|c| !c.is_ascii_uppercase()) {
+        terms.push(word);
+      }
+    } else if let Some((attribute, _)) = word.split_once(':') {
+      if !attribute.is_empty() && !attribute.contains('.') && !UNSETTABLE_ATTRIBUTES.contains(&attribute) {
+        terms.push(word);
+      }
+    }
+  }
+  (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_modification_for_filter() {
+    assert_eq!(
+      modification_for_filter("status:pending -WAITING project:Home +garden limit:page"),
+      Some("project:Home +garden".to_string())
+    );
+    assert_eq!(modification_for_filter("due.before:eow -work priority:H"), Some("-work priority:H".to_string()));
+    assert_eq!(modification_for_filter("+WAITING"), None);
+    assert_eq!(modification_for_filter("project:Home or project:Work"), None);
+    assert_eq!(modification_for_filter("'(project:Home or +work)' +chore"), Some("+chore".to_string()));
+  }
+}
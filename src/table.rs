@@ -86,7 +86,7 @@ impl TaskwarriorTuiTableState {
     }
   }
 
-  pub fn marked(&self) -> std::collections::hash_set::Iter<usize> {
+  pub fn marked(&self) -> std::collections::hash_set::Iter<'_, usize> {
     self.marked.iter()
   }
 
@@ -228,7 +228,8 @@ where
 /// # Examples
 ///
 /// ```rust
-/// # use ratatui::widgets::{Block, Borders, Table, Row};
+/// # use ratatui::widgets::{Block, Borders};
+/// # use taskwarrior_tui::table::{Row, Table};
 /// # use ratatui::layout::Constraint;
 /// # use ratatui::style::{Style, Color};
 /// let row_style = Style::default().fg(Color::White);
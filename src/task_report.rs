@@ -1,5 +1,3 @@
-use std::process::Command;
-
 use anyhow::Result;
 use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use itertools::join;
@@ -141,6 +139,7 @@ pub fn format_duration(seconds: i64, with_remainder: bool) -> String {
   format!("{}{}s", minus, seconds)
 }
 
+#[derive(Clone)]
 pub struct TaskReportTable {
   pub labels: Vec<String>,
   pub columns: Vec<String>,
@@ -149,6 +148,8 @@ pub struct TaskReportTable {
   pub description_width: usize,
   pub date_time_vague_precise: bool,
   pub duration_human_readable: bool,
+  /// Output of `task show`, which defines the report columns and labels
+  config: String,
 }
 
 impl TaskReportTable {
@@ -197,25 +198,18 @@ impl TaskReportTable {
       description_width: 100,
       date_time_vague_precise: false,
       duration_human_readable: true,
+      config: data.to_string(),
     };
-    task_report_table.export_headers(Some(data), report)?;
+    task_report_table.export_headers(None, report)?;
     Ok(task_report_table)
   }
 
+  /// Read the columns and labels of `report` from `data`, or from the `task show` output given to `new`
   pub fn export_headers(&mut self, data: Option<&str>, report: &str) -> Result<()> {
     self.columns = vec![];
     self.labels = vec![];
 
-    let data = if let Some(s) = data {
-      s.to_string()
-    } else {
-      let output = Command::new("task")
-        .arg("show")
-        .arg("rc.defaultwidth=0")
-        .arg(format!("report.{}.columns", report))
-        .output()?;
-      String::from_utf8_lossy(&output.stdout).into_owned()
-    };
+    let data = data.unwrap_or(&self.config);
 
     for line in data.split('\n') {
       if line.starts_with(format!("report.{}.columns", report).as_str()) {
@@ -226,13 +220,6 @@ impl TaskReportTable {
       }
     }
 
-    let output = Command::new("task")
-      .arg("show")
-      .arg("rc.defaultwidth=0")
-      .arg(format!("report.{}.labels", report))
-      .output()?;
-    let data = String::from_utf8_lossy(&output.stdout);
-
     for line in data.split('\n') {
       if line.starts_with(format!("report.{}.labels", report).as_str()) {
         let label_names = line.split_once(' ').unwrap().1;
@@ -277,12 +277,12 @@ impl TimewarriorIntegration {
             timewarrior_available: Self::check_timewarrior_available(),
             hook_installed: self.is_hook_installed(),
             integration_enabled: self.config.enabled,
-            active_tracking: self.get_active_tracking_info(),
+            active_tracking: Self::get_active_tracking_info(),
         }
     }
 
     /// Get active tracking information from timewarrior
-    fn get_active_tracking_info(&self) -> Option<ActiveTrackingInfo> {
+    pub fn get_active_tracking_info() -> Option<ActiveTrackingInfo> {
         if !Self::check_timewarrior_available() {
             return None;
         }
@@ -465,8 +465,6 @@ impl Default for TimewarriorIntegration {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
-    use tempfile::TempDir;
 
     #[test]
     fn test_timewarrior_config_default_values() {
@@ -0,0 +1,113 @@
+//! Watches the Taskwarrior data directory for changes made outside the app
+//!
+//! Both database layouts are recognised: Taskwarrior 3 keeps tasks in `taskchampion.sqlite3`,
+//! Taskwarrior 2 in `pending.data`, `completed.data`, `undo.data` and `backlog.data`.
+
+use std::{
+  ffi::OsStr,
+  fs,
+  path::Path,
+  time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use log::{debug, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::event::{Event, KeyCode};
+
+/// Files holding tasks, in the Taskwarrior 3 and Taskwarrior 2 layouts
+///
+/// SQLite's `-shm` file is left out on purpose: it changes whenever the database is read.
+const DATA_FILES: &[&str] = &[
+  "taskchampion.sqlite3",
+  "taskchampion.sqlite3-wal",
+  "taskchampion.sqlite3-journal",
+  "pending.data",
+  "completed.data",
+  "undo.data",
+  "backlog.data",
+];
+
+/// Size and modification time of every data file, to tell whether the tasks changed since the last export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataFingerprint(Vec<Option<(SystemTime, u64)>>);
+
+impl DataFingerprint {
+  pub fn of(data_dir: &Path) -> Self {
+    Self(
+      DATA_FILES
+        .iter()
+        .map(|name| {
+          let metadata = fs::metadata(data_dir.join(name)).ok()?;
+          Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect(),
+    )
+  }
+}
+
+/// Sends `Event::DataChanged` whenever a data file in the watched directory changes
+///
+/// Watching stops when this is dropped.
+pub struct DataWatcher {
+  _watcher: RecommendedWatcher,
+}
+
+impl DataWatcher {
+  pub fn new(data_dir: &Path, tx: mpsc::UnboundedSender<Event<KeyCode>>) -> Result<Self> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+      Ok(event) if is_data_change(&event) => {
+        debug!("Task data changed: {:?}", event);
+        tx.send(Event::DataChanged).unwrap_or_else(|_| warn!("Unable to send DataChanged event"));
+      }
+      Ok(_) => {}
+      Err(err) => warn!("Error watching the task data directory: {}", err),
+    })
+    .context("Unable to create a file watcher")?;
+    watcher
+      .watch(data_dir, RecursiveMode::NonRecursive)
+      .with_context(|| format!("Unable to watch {}", data_dir.display()))?;
+    Ok(Self { _watcher: watcher })
+  }
+}
+
+fn is_data_change(event: &notify::Event) -> bool {
+  !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|path| is_data_file(path))
+}
+
+fn is_data_file(path: &Path) -> bool {
+  path.file_name().and_then(OsStr::to_str).is_some_and(|name| DATA_FILES.contains(&name))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+
+  #[test]
+  fn test_data_fingerprint() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let empty = DataFingerprint::of(dir.path());
+    fs::write(dir.path().join("pending.data"), "[description:\"Water plants\"]\n").unwrap();
+    let pending = DataFingerprint::of(dir.path());
+    assert_ne!(empty, pending);
+    fs::write(dir.path().join("taskchampion.sqlite3-shm"), "read").unwrap();
+    fs::write(dir.path().join("notes.txt"), "unrelated").unwrap();
+    assert_eq!(pending, DataFingerprint::of(dir.path()));
+  }
+
+  #[tokio::test]
+  async fn test_data_watcher_sends_data_changed() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let _watcher = DataWatcher::new(dir.path(), tx).unwrap();
+
+    fs::write(dir.path().join("notes.txt"), "unrelated").unwrap();
+    fs::write(dir.path().join("completed.data"), "[description:\"Call mom\"]\n").unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.expect("No event within 5s");
+    assert!(matches!(event, Some(Event::DataChanged)));
+  }
+}
@@ -0,0 +1,852 @@
+//! End-to-end tests of the key-driven UI that need neither `task` nor a task database
+//!
+//! The app is built with `TaskwarriorTui::with_backend` from a canned `task show` output and
+//! an in-memory `MemoryBackend`, then driven with key presses and drawn to ratatui's `TestBackend`.
+//! Backend calls run on a worker thread, so tests call `wait_for_backend` before checking their effects.
+
+#![cfg(feature = "taskchampion-backend")]
+
+use std::ops::{Deref, DerefMut};
+
+use ratatui::{backend::TestBackend, Terminal};
+use tempfile::TempDir;
+use taskwarrior_tui::{
+    action::Action,
+    app::{Mode, TaskwarriorTui},
+    backend::{memory::MemoryBackend, taskchampion::TaskChampionConfig, TaskBackend},
+    event::KeyCode,
+    keymap::notation,
+    pane::{agenda::AgendaKind, statistics::StatisticsWindow},
+};
+use uuid::Uuid;
+use versions::Versioning;
+
+/// The parts of `task show` the app reads at startup, with Taskwarrior's defaults
+const TASK_SHOW: &str = "\
+data.location ~/.task-headless-tests
+rule.precedence.color deleted,completed,active,keyword.,tag.,project.,overdue,scheduled,due.today,due,blocked,blocking,recurring,tagged,uda.
+uda.priority.values H,M,L,
+report.next.columns id,start.age,entry.age,depends,priority,project,tags,recur,scheduled.countdown,due.relative,until.remaining,description.count,urgency
+report.next.labels ID,Active,Age,Deps,P,Project,Tag,Recur,S,Due,Until,Description,Urg
+report.next.filter status:pending -WAITING limit:page
+report.completed.columns end,project,description
+report.completed.labels Completed,Project,Description
+report.completed.description Completed tasks
+report.completed.filter status:completed
+";
+
+/// The app under test, with the temporary directory its histories and macros are kept in
+struct HeadlessApp {
+    app: TaskwarriorTui,
+    history_dir: TempDir,
+}
+
+impl Deref for HeadlessApp {
+    type Target = TaskwarriorTui;
+
+    fn deref(&self) -> &TaskwarriorTui {
+        &self.app
+    }
+}
+
+impl DerefMut for HeadlessApp {
+    fn deref_mut(&mut self) -> &mut TaskwarriorTui {
+        &mut self.app
+    }
+}
+
+async fn headless_app(backend: &MemoryBackend) -> HeadlessApp {
+    headless_app_with(backend, TASK_SHOW).await
+}
+
+/// Start the app from `task_show` instead of the default `TASK_SHOW`
+async fn headless_app_with(backend: &MemoryBackend, task_show: &str) -> HeadlessApp {
+    let history_dir = TempDir::new().unwrap();
+    let app = TaskwarriorTui::with_backend(
+        "next",
+        task_show,
+        Versioning::new("3.1.0").unwrap(),
+        backend.worker().unwrap(),
+        false,
+        history_dir.path(),
+    )
+    .await
+    .expect("The app should start without `task`");
+    HeadlessApp {
+        app,
+        history_dir,
+    }
+}
+
+fn backend_with_tasks(descriptions: &[&str]) -> MemoryBackend {
+    let backend = MemoryBackend::with_config(TaskChampionConfig {
+        report_filters: vec![("next".to_string(), "status:pending -WAITING limit:page".to_string())].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    for description in descriptions {
+        backend.add_task(description, &[]).unwrap();
+    }
+    backend.clear_calls();
+    backend
+}
+
+fn render(app: &mut TaskwarriorTui) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+async fn type_text(app: &mut TaskwarriorTui, text: &str) {
+    for c in text.chars() {
+        app.handle_input(KeyCode::Char(c)).await.unwrap();
+    }
+}
+
+async fn press(app: &mut TaskwarriorTui, key: KeyCode) {
+    app.handle_input(key).await.unwrap();
+    app.wait_for_backend().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_headless_report_renders_backend_tasks() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+
+    let screen = render(&mut app);
+    assert!(screen.contains("Water plants"), "{}", screen);
+    assert!(screen.contains("Call mom"), "{}", screen);
+    let export = &backend.calls_to("export_tasks")[0];
+    assert_eq!(export[0].trim(), "status:pending -WAITING limit:page");
+    assert_eq!(export[1], "next");
+}
+
+#[tokio::test]
+async fn test_headless_keys_call_backend() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+    let second = *app.tasks[1].uuid();
+
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(backend.calls_to("mark_done"), vec![vec![second.to_string()]]);
+    assert_eq!(app.tasks.len(), 1);
+
+    press(&mut app, KeyCode::Char('a')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Add));
+    type_text(&mut app, "Buy milk +errand").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(backend.calls_to("add_task"), vec![vec!["Buy", "milk", "+errand"]]);
+
+    let screen = render(&mut app);
+    assert!(screen.contains("Buy milk"), "{}", screen);
+    assert!(screen.contains("errand"), "{}", screen);
+}
+
+#[tokio::test]
+async fn test_headless_scripted_failure_shows_error() {
+    let backend = backend_with_tasks(&["Water plants"]);
+    let mut app = headless_app(&backend).await;
+
+    backend.fail_next("mark_done", "database is locked");
+    press(&mut app, KeyCode::Char('d')).await;
+
+    assert_eq!(app.mode, Mode::Tasks(Action::Error));
+    assert!(app.error.as_deref().unwrap_or_default().contains("database is locked"));
+    assert_eq!(app.tasks.len(), 1, "The failed call must not change any task");
+
+    // Only the scripted call fails
+    press(&mut app, KeyCode::Esc).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(backend.calls_to("mark_done").len(), 2);
+    assert!(app.tasks.is_empty());
+}
+
+#[tokio::test]
+async fn test_headless_input_while_backend_busy() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom", "Pay rent"]);
+    let mut app = headless_app(&backend).await;
+    let first = *app.tasks[0].uuid();
+
+    app.handle_input(KeyCode::Char('d')).await.unwrap();
+    assert!(app.backend_jobs > 0);
+    let screen = render(&mut app);
+    assert!(screen.lines().next().unwrap().contains("working"), "{}", screen);
+
+    // Keys are handled before the done task and the refreshed report come back
+    app.handle_input(KeyCode::Char('j')).await.unwrap();
+    assert_eq!(app.current_selection, 1);
+    assert_eq!(app.tasks.len(), 3);
+
+    app.wait_for_backend().await.unwrap();
+    assert_eq!(backend.calls_to("mark_done"), vec![vec![first.to_string()]]);
+    assert_eq!(app.tasks.len(), 2);
+    assert_eq!(app.backend_jobs, 0);
+    let screen = render(&mut app);
+    assert!(!screen.lines().next().unwrap().contains("working"), "{}", screen);
+}
+
+#[tokio::test]
+async fn test_headless_refresh_exports_only_changes() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+    let first = *app.tasks[0].uuid();
+    backend.clear_calls();
+
+    press(&mut app, KeyCode::Char('s')).await;
+    assert_eq!(backend.calls_to("start_tasks"), vec![vec![first.to_string()]]);
+    assert_eq!(backend.calls_to("export_changes").len(), 1);
+    assert!(backend.calls_to("export_tasks").is_empty(), "{:?}", backend.calls());
+    assert_eq!(app.tasks.len(), 2);
+    assert!(app.tasks.iter().find(|task| *task.uuid() == first).unwrap().start().is_some());
+
+    // Completing a task renumbers the others, so everything is exported again
+    backend.clear_calls();
+    press(&mut app, KeyCode::Char('d')).await;
+    let reports: Vec<String> = backend.calls_to("export_tasks").into_iter().map(|args| args[1].clone()).collect();
+    assert_eq!(reports, vec!["next", "all"]);
+    assert_eq!(app.tasks.len(), 1);
+}
+
+#[tokio::test]
+async fn test_headless_refresh_keeps_report_order() {
+    let backend = MemoryBackend::with_config(TaskChampionConfig {
+        report_filters: vec![("next".to_string(), "status:pending -WAITING limit:2".to_string())].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    for description in ["Water plants", "Call mom", "Fix login"] {
+        backend.add_task(description, &[]).unwrap();
+    }
+    let show = format!("{}report.next.sort due+\n", TASK_SHOW.replace("limit:page", "limit:2"));
+    let mut app = headless_app_with(&backend, &show).await;
+    let descriptions = |app: &TaskwarriorTui| app.tasks.iter().map(|task| task.description().clone()).collect::<Vec<_>>();
+    assert_eq!(descriptions(&app), vec!["Water plants", "Call mom"]);
+    backend.clear_calls();
+
+    // The new task sorts first, so it stays within the limit and the last task drops out
+    press(&mut app, KeyCode::Char('a')).await;
+    type_text(&mut app, "Pay rent due:tomorrow").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(backend.calls_to("export_changes").len(), 1);
+    assert!(backend.calls_to("export_tasks").is_empty(), "{:?}", backend.calls());
+    assert_eq!(descriptions(&app), vec!["Pay rent", "Water plants"]);
+}
+
+#[tokio::test]
+async fn test_headless_refresh_moves_task_past_limit() {
+    let backend = MemoryBackend::with_config(TaskChampionConfig {
+        report_filters: vec![("next".to_string(), "status:pending -WAITING limit:2".to_string())].into_iter().collect(),
+        report_sorts: vec![("next".to_string(), "due+".to_string())].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    for (description, due) in [("Water plants", "due:2030-01-01"), ("Call mom", "due:2030-01-02"), ("Fix login", "due:2030-01-03")] {
+        backend.add_task(description, &[due]).unwrap();
+    }
+    let show = format!("{}report.next.sort due+\n", TASK_SHOW.replace("limit:page", "limit:2"));
+    let mut app = headless_app_with(&backend, &show).await;
+    let descriptions = |app: &TaskwarriorTui| app.tasks.iter().map(|task| task.description().clone()).collect::<Vec<_>>();
+    assert_eq!(descriptions(&app), vec!["Water plants", "Call mom"]);
+    backend.clear_calls();
+
+    // Call mom now sorts after Fix login, which was cut off and only a full export brings back
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('m')).await;
+    type_text(&mut app, "due:2030-01-04").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(backend.calls_to("modify_tasks").len(), 1);
+    assert!(!backend.calls_to("export_tasks").is_empty(), "{:?}", backend.calls());
+    assert_eq!(descriptions(&app), vec!["Water plants", "Fix login"]);
+}
+
+/// Title of the board column holding the task
+fn board_column(app: &TaskwarriorTui, uuid: Uuid) -> Option<String> {
+    app.board
+        .columns
+        .iter()
+        .find(|column| column.tasks.iter().any(|&i| *app.tasks[i].uuid() == uuid))
+        .map(|column| column.title().to_string())
+}
+
+#[tokio::test]
+async fn test_headless_board_moves_cards() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+    let (first, second) = (*app.tasks[0].uuid(), *app.tasks[1].uuid());
+
+    app.mode = Mode::Board;
+    assert_eq!(board_column(&app, first).as_deref(), Some("pending"));
+    assert_eq!(board_column(&app, second).as_deref(), Some("pending"));
+
+    press(&mut app, KeyCode::Char('>')).await;
+    assert_eq!(backend.calls_to("modify_tasks"), vec![vec![first.to_string(), "status:pending start:now end:".to_string()]]);
+    assert_eq!(board_column(&app, first).as_deref(), Some("started"));
+    assert_eq!(board_column(&app, second).as_deref(), Some("pending"));
+    // The card stays selected in its new column
+    assert_eq!(app.board.current_column, 1);
+    assert_eq!(app.board.current_task().map(|i| *app.tasks[i].uuid()), Some(first));
+    let screen = render(&mut app);
+    assert!(screen.contains("pending (1)"), "{}", screen);
+    assert!(screen.contains("started (1)"), "{}", screen);
+
+    // The completed task leaves the next report, and with it the board
+    press(&mut app, KeyCode::Char('>')).await;
+    assert_eq!(backend.calls_to("modify_tasks")[1], vec![first.to_string(), "status:completed end:now".to_string()]);
+    assert_eq!(board_column(&app, first), None);
+    assert_eq!(app.tasks.len(), 1);
+
+    // Moving left of the first column does nothing
+    press(&mut app, KeyCode::Char('h')).await;
+    press(&mut app, KeyCode::Char('h')).await;
+    assert_eq!(app.board.current_column, 0);
+    assert_eq!(app.board.current_task().map(|i| *app.tasks[i].uuid()), Some(second));
+    press(&mut app, KeyCode::Char('<')).await;
+    assert_eq!(backend.calls_to("modify_tasks").len(), 2);
+}
+
+#[tokio::test]
+async fn test_headless_board_groups_by_attribute() {
+    let backend = backend_with_tasks(&[]);
+    backend.add_task("Water plants", &["priority:H"]).unwrap();
+    backend.add_task("Call mom", &[]).unwrap();
+    backend.clear_calls();
+    let show = format!("{}uda.taskwarrior-tui.board.group-by priority\n", TASK_SHOW);
+    let mut app = headless_app_with(&backend, &show).await;
+    let (water, call) = (*app.tasks[0].uuid(), *app.tasks[1].uuid());
+
+    // Priority columns follow `uda.priority.values`, including the one for no priority
+    app.mode = Mode::Board;
+    let titles: Vec<&str> = app.board.columns.iter().map(|column| column.title()).collect();
+    assert_eq!(titles, vec!["H", "M", "L", "(none)"]);
+    assert_eq!(board_column(&app, water).as_deref(), Some("H"));
+    assert_eq!(board_column(&app, call).as_deref(), Some("(none)"));
+
+    for _ in 0..3 {
+        press(&mut app, KeyCode::Char('>')).await;
+    }
+    let modifications: Vec<String> = backend.calls_to("modify_tasks").into_iter().map(|call| call[1].clone()).collect();
+    assert_eq!(modifications, vec!["priority:M", "priority:L", "priority:"]);
+    assert_eq!(app.tasks.iter().find(|task| *task.uuid() == water).unwrap().priority(), None);
+    assert_eq!(board_column(&app, water).as_deref(), Some("(none)"));
+}
+
+#[tokio::test]
+async fn test_headless_agenda_lists_dated_tasks() {
+    let backend = backend_with_tasks(&["Water plants"]);
+    backend.add_task("Dentist", &["due:today+14h"]).unwrap();
+    backend.add_task("Call mom", &["scheduled:tomorrow"]).unwrap();
+    backend.add_task("Renew passport", &["wait:tomorrow", "until:today+3d"]).unwrap();
+    // A report that keeps waiting tasks, so that their wait dates are on the agenda
+    let show = TASK_SHOW.replace("status:pending -WAITING", "status:pending");
+    let mut app = headless_app_with(&backend, &show).await;
+
+    app.mode = Mode::Agenda;
+    let today = chrono::Local::now().date_naive();
+    let entries: Vec<(&str, AgendaKind, chrono::NaiveDate)> = app
+        .agenda
+        .entries
+        .iter()
+        .map(|entry| (app.tasks[entry.task].description().as_str(), entry.kind, entry.when.date()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("Dentist", AgendaKind::Due, today),
+            ("Call mom", AgendaKind::Scheduled, today + chrono::Duration::days(1)),
+            ("Renew passport", AgendaKind::Wait, today + chrono::Duration::days(1)),
+            ("Renew passport", AgendaKind::Until, today + chrono::Duration::days(3)),
+        ]
+    );
+    let screen = render(&mut app);
+    assert!(screen.contains("14:00 due"), "{}", screen);
+    let renew: Vec<&str> = screen.lines().filter(|line| line.contains("Renew passport")).collect();
+    assert_eq!(renew.len(), 2, "{}", screen);
+    assert!(renew[0].contains(" wait ") && renew[1].contains(" until "), "{}", screen);
+    assert!(!screen.contains("Water plants"), "Tasks without dates stay off the agenda\n{}", screen);
+
+    // The day grid places the timed task in its hour
+    press(&mut app, KeyCode::Char('z')).await;
+    let screen = render(&mut app);
+    let row = screen.lines().find(|line| line.contains("14:00")).unwrap();
+    assert!(row.contains("Dentist"), "{}", screen);
+
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+    assert_eq!(app.tasks[app.current_selection].description(), "Dentist");
+}
+
+#[tokio::test]
+async fn test_headless_calendar_reschedules_selected_task() {
+    let backend = backend_with_tasks(&["Water plants", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+    let first = *app.tasks[0].uuid();
+
+    app.mode = Mode::Calendar;
+    let today = app.calendar.selected;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('l')).await;
+    let day = today + chrono::Duration::days(8);
+    assert_eq!(app.calendar.selected, day);
+
+    press(&mut app, KeyCode::Char('D')).await;
+    let due = format!("due:{}", day.format("%Y-%m-%d"));
+    assert_eq!(backend.calls_to("modify_tasks"), vec![vec![first.to_string(), due]]);
+    let screen = render(&mut app);
+    assert!(screen.contains("due       #1 Water plants"), "{}", screen);
+    assert!(!screen.contains("Call mom"), "{}", screen);
+
+    // With tasks marked in the report, all of them get the selected day
+    app.mode = Mode::Tasks(Action::Report);
+    press(&mut app, KeyCode::Char('v')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('v')).await;
+    app.mode = Mode::Calendar;
+    press(&mut app, KeyCode::Char('C')).await;
+    let mut call = backend.calls_to("modify_tasks")[1].clone();
+    assert_eq!(call.pop(), Some(format!("scheduled:{}", day.format("%Y-%m-%d"))));
+    call.sort();
+    let mut marked: Vec<String> = app.marked.iter().map(Uuid::to_string).collect();
+    marked.sort();
+    assert_eq!(marked.len(), 2);
+    assert_eq!(call, marked);
+    let screen = render(&mut app);
+    assert!(screen.contains("scheduled #1 Water plants"), "{}", screen);
+    assert!(screen.contains("scheduled #2 Call mom"), "{}", screen);
+
+    // Enter drills down into the agenda of the selected day
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.mode, Mode::Agenda);
+    assert_eq!(app.agenda.days(), vec![day]);
+    let kinds: Vec<AgendaKind> = app.agenda.entries.iter().map(|entry| entry.kind).collect();
+    assert_eq!(kinds, vec![AgendaKind::Due, AgendaKind::Scheduled, AgendaKind::Scheduled]);
+}
+
+#[tokio::test]
+async fn test_headless_calendar_lists_holidays() {
+    let backend = backend_with_tasks(&["Water plants"]);
+    let today = chrono::Local::now().date_naive();
+    let task_show = format!(
+        "{}holiday.break.name Winter break\nholiday.break.start {}\nholiday.break.end {}\n",
+        TASK_SHOW,
+        today.format("%Y%m%d"),
+        (today + chrono::Duration::days(2)).format("%Y%m%d"),
+    );
+    let mut app = headless_app_with(&backend, &task_show).await;
+    assert_eq!(app.config.holidays.len(), 1);
+
+    app.mode = Mode::Calendar;
+    press(&mut app, KeyCode::Char('l')).await;
+    let screen = render(&mut app);
+    assert!(screen.contains("Winter break"), "{}", screen);
+    let holiday_style = app.config.color_calendar_holiday.unwrap();
+    let styled: Vec<_> = app.get_dates_with_styles().into_iter().filter(|(_, style)| *style == holiday_style).collect();
+    assert_eq!(styled.len(), 3);
+
+    press(&mut app, KeyCode::Char('j')).await;
+    assert!(!render(&mut app).contains("Winter break"));
+}
+
+#[tokio::test]
+async fn test_headless_graph_edits_dependencies() {
+    let backend = backend_with_tasks(&["Water plants", "Buy soil"]);
+    backend.add_task("Repot cactus", &["depends:2"]).unwrap();
+    let mut app = headless_app(&backend).await;
+    let uuid = |app: &TaskwarriorTui, description: &str| *app.tasks.iter().find(|task| task.description() == description).unwrap().uuid();
+    let (water, soil, repot) = (uuid(&app, "Water plants"), uuid(&app, "Buy soil"), uuid(&app, "Repot cactus"));
+
+    app.mode = Mode::Graph;
+    let screen = render(&mut app);
+    assert!(screen.contains("└─ #2 Buy soil"), "{}", screen);
+    assert!(screen.contains("1 tasks without dependencies"), "{}", screen);
+
+    // Make the selected task depend on the marked one
+    app.marked.insert(water);
+    press(&mut app, KeyCode::Char('+')).await;
+    assert_eq!(backend.calls_to("modify_tasks"), vec![vec![repot.to_string(), format!("depends:{}", water)]]);
+    assert_eq!(app.graph.rows.len(), 3);
+
+    // Without marked tasks, removing drops the edge to the selected row
+    press(&mut app, KeyCode::Esc).await;
+    let row = app.graph.rows.iter().position(|row| row.uuid == soil).unwrap();
+    for _ in 0..row {
+        press(&mut app, KeyCode::Char('j')).await;
+    }
+    press(&mut app, KeyCode::Char('-')).await;
+    assert_eq!(backend.calls_to("modify_tasks")[1], vec![repot.to_string(), format!("depends:-{}", soil)]);
+    let screen = render(&mut app);
+    assert!(screen.contains("└─ #1 Water plants"), "{}", screen);
+    assert!(!screen.contains("Buy soil"), "{}", screen);
+}
+
+#[tokio::test]
+async fn test_headless_project_tree() {
+    let backend = backend_with_tasks(&[]);
+    backend.add_task("Fix login", &["project:work.clientA.backend"]).unwrap();
+    backend.add_task("Standup notes", &["project:work"]).unwrap();
+    backend.add_task("Plan trip", &["project:home"]).unwrap();
+    let mut app = headless_app(&backend).await;
+
+    app.mode = Mode::Projects;
+    let screen = render(&mut app);
+    assert!(screen.contains("▸ home"), "{}", screen);
+    assert!(screen.contains("▸ work"), "{}", screen);
+    assert!(!screen.contains("clientA"), "{}", screen);
+
+    // Expand `work`, then `work.clientA`
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('l')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    let screen = render(&mut app);
+    assert!(screen.contains("▾ clientA"), "{}", screen);
+    assert!(screen.contains("▸ backend"), "{}", screen);
+    assert!(screen.contains("#2 Standup notes"), "{}", screen);
+    assert_eq!(app.projects.nodes["work"].remaining, 2);
+
+    // Selecting a project filters the report by it
+    press(&mut app, KeyCode::Char('v')).await;
+    assert!(app.filter.as_str().ends_with(" '(project:work.clientA)'"), "{}", app.filter.as_str());
+    assert_eq!(app.tasks.len(), 1);
+    assert_eq!(app.tasks[0].description(), "Fix login");
+}
+
+#[tokio::test]
+async fn test_headless_statistics_follow_the_filter() {
+    let backend = backend_with_tasks(&[]);
+    backend.add_task("Water plants", &["project:home", "due:yesterday"]).unwrap();
+    backend.log_task("Repot cactus", &["project:home"]).unwrap();
+    backend.log_task("Fix login", &["project:work"]).unwrap();
+    let mut app = headless_app(&backend).await;
+
+    app.mode = Mode::Statistics;
+    let screen = render(&mut app);
+    assert!(screen.contains("Burndown, last month (daily)"), "{}", screen);
+    assert!(screen.contains("Completed per project"), "{}", screen);
+    assert_eq!(app.statistics.throughput, vec![("home".to_string(), 1), ("work".to_string(), 1)]);
+    let today = app.statistics.points.last().unwrap();
+    assert_eq!((today.open, today.completed, today.overdue), (1, 2, 1));
+
+    // A weekly point of the last week counts the same tasks as today's daily one
+    press(&mut app, KeyCode::Char('h')).await;
+    press(&mut app, KeyCode::Char('z')).await;
+    assert_eq!((app.statistics.window, app.statistics.weekly), (StatisticsWindow::Week, true));
+    assert!(render(&mut app).contains("Burndown, last week (weekly)"));
+    let this_week = app.statistics.points.last().unwrap();
+    assert_eq!((this_week.open, this_week.completed, this_week.overdue), (1, 2, 1));
+
+    // Only the tasks matching the report's filter are counted
+    app.mode = Mode::Tasks(Action::Report);
+    press(&mut app, KeyCode::Char('/')).await;
+    type_text(&mut app, " project:home").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.statistics.throughput, vec![("home".to_string(), 1)]);
+}
+
+#[tokio::test]
+async fn test_headless_split_report_panes() {
+    let backend = backend_with_tasks(&[]);
+    backend.add_task("Water plants", &["project:home"]).unwrap();
+    backend.add_task("Fix login", &["project:work"]).unwrap();
+    let mut app = headless_app(&backend).await;
+
+    // Open a second pane and narrow its filter
+    press(&mut app, KeyCode::Char('|')).await;
+    assert_eq!((app.report_panes.len(), app.focused_report), (2, 1));
+    press(&mut app, KeyCode::Char('/')).await;
+    type_text(&mut app, "project:work").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.tasks.len(), 1);
+    assert_eq!(app.report_panes[0].tasks.len(), 2);
+    let screen = render(&mut app);
+    assert!(screen.contains(" next status:pending -WAITING limit:page ──"), "{}", screen);
+    assert!(screen.contains(" next status:pending -WAITING limit:page project:w"), "{}", screen);
+    assert_eq!(screen.matches("Water plants").count(), 1, "{}", screen);
+
+    // Move a task of the first pane to the second one
+    press(&mut app, KeyCode::Tab).await;
+    assert_eq!(app.focused_report, 0);
+    let water = app.tasks.iter().position(|task| task.description() == "Water plants").unwrap();
+    for _ in 0..water {
+        press(&mut app, KeyCode::Char('j')).await;
+    }
+    let uuid = *app.tasks[water].uuid();
+    press(&mut app, KeyCode::Char('T')).await;
+    assert_eq!(backend.calls_to("modify_tasks"), vec![vec![uuid.to_string(), "project:work".to_string()]]);
+    assert_eq!(app.report_panes[1].tasks.len(), 2);
+
+    // Closing the focused pane leaves the other one
+    press(&mut app, KeyCode::Char('Z')).await;
+    assert!(app.report_panes.is_empty());
+    assert!(app.filter.as_str().ends_with("project:work"), "{}", app.filter.as_str());
+}
+
+#[tokio::test]
+async fn test_headless_switch_reports() {
+    let backend = backend_with_tasks(&["Water plants"]);
+    backend.log_task("Pay rent", &["project:home"]).unwrap();
+    let mut app = headless_app(&backend).await;
+
+    // The picker lists every report, starting at the current one
+    press(&mut app, KeyCode::Char('R')).await;
+    let screen = render(&mut app);
+    assert!(screen.contains("Completed tasks"), "{}", screen);
+    assert_eq!(app.report_menu.current_selection(), Some(1));
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.report, "completed");
+    assert_eq!(app.filter.as_str(), "status:completed ");
+    let descriptions: Vec<&str> = app.tasks.iter().map(|task| task.description().as_str()).collect();
+    assert_eq!(descriptions, vec!["Pay rent"]);
+    let screen = render(&mut app);
+    assert!(screen.contains("Completed"), "{}", screen);
+    assert!(!screen.contains("Water plants"), "{}", screen);
+
+    // Without favorites the next report key cycles through all reports
+    press(&mut app, KeyCode::Char('}')).await;
+    assert_eq!(app.report, "next");
+    assert_eq!(app.filter.as_str(), "status:pending -WAITING limit:page ");
+    assert!(render(&mut app).contains("Water plants"));
+}
+
+#[tokio::test]
+async fn test_headless_cycle_favorite_reports() {
+    let backend = backend_with_tasks(&["Water plants"]);
+    let show = format!(
+        "{}report.active.columns id,description\nreport.active.filter +ACTIVE\nuda.taskwarrior-tui.task-report.favorites next,completed\n",
+        TASK_SHOW
+    );
+    let mut app = headless_app_with(&backend, &show).await;
+
+    // Only the favorites are cycled through, wrapping around at either end
+    press(&mut app, KeyCode::Char('}')).await;
+    assert_eq!(app.report, "completed");
+    assert_eq!(app.filter.as_str(), "status:completed ");
+    press(&mut app, KeyCode::Char('}')).await;
+    assert_eq!(app.report, "next");
+    press(&mut app, KeyCode::Char('{')).await;
+    assert_eq!(app.report, "completed");
+    let reports: Vec<String> = backend.calls_to("export_tasks").into_iter().map(|args| args[1].clone()).collect();
+    assert!(!reports.contains(&"active".to_string()), "{:?}", reports);
+
+    // The picker still lists every report
+    press(&mut app, KeyCode::Char('R')).await;
+    assert_eq!(app.report_menu.current_selection(), Some(1));
+    press(&mut app, KeyCode::Char('k')).await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.report, "active");
+    assert_eq!(app.filter.as_str(), "+ACTIVE ");
+}
+
+#[tokio::test]
+async fn test_headless_key_sequences_and_counts() {
+    let backend = backend_with_tasks(&["Water plants", "Fix login", "Pay rent", "Call mom"]);
+    let show = format!("{}uda.taskwarrior-tui.keyconfig.go-to-top gg\n", TASK_SHOW);
+    let mut app = headless_app_with(&backend, &show).await;
+
+    type_text(&mut app, "2j").await;
+    assert_eq!(app.current_selection, 2);
+
+    // The status line shows the keys of an unfinished sequence
+    press(&mut app, KeyCode::Char('g')).await;
+    assert!(render(&mut app).contains("g [none]"));
+    press(&mut app, KeyCode::Char('g')).await;
+    assert_eq!(app.current_selection, 0);
+    assert!(!render(&mut app).contains("g [none]"));
+
+    type_text(&mut app, "2G").await;
+    assert_eq!(app.current_selection, 1);
+
+    // A count before an action on tasks works on that many tasks from the selection on
+    let uuids: Vec<String> = app.tasks[1..3].iter().map(|task| task.uuid().to_string()).collect();
+    press(&mut app, KeyCode::Char('2')).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    assert_eq!(backend.calls_to("mark_done"), vec![uuids]);
+    assert_eq!(app.selection_count, 1);
+    assert_eq!(app.tasks.len(), 2);
+}
+
+#[tokio::test]
+async fn test_headless_rebind_named_keys() {
+    let backend = backend_with_tasks(&["Water plants", "Fix login", "Pay rent"]);
+    let show = format!(
+        "{}uda.taskwarrior-tui.keyconfig.down <C-n>\nuda.taskwarrior-tui.keyconfig.done <F5>\nuda.taskwarrior-tui.keyconfig.cancel <C-g>\n",
+        TASK_SHOW
+    );
+    let mut app = headless_app_with(&backend, &show).await;
+
+    press(&mut app, KeyCode::Ctrl('n')).await;
+    assert_eq!(app.current_selection, 1);
+    press(&mut app, KeyCode::Char('j')).await;
+    assert_eq!(app.current_selection, 1);
+
+    // Keys handled by the prompt can be rebound too
+    press(&mut app, KeyCode::Char('/')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Filter));
+    press(&mut app, KeyCode::Ctrl('g')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+
+    let uuid = app.tasks[1].uuid().to_string();
+    press(&mut app, KeyCode::F(5)).await;
+    assert_eq!(backend.calls_to("mark_done"), vec![vec![uuid]]);
+}
+
+#[tokio::test]
+async fn test_headless_command_palette() {
+    let backend = backend_with_tasks(&["Water plants", "Fix login"]);
+    let mut app = headless_app(&backend).await;
+
+    // Every action is listed with its keys, along with the reports and Timewarrior commands
+    press(&mut app, KeyCode::Ctrl('p')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::CommandPalette));
+    let names: Vec<&str> = app.command_palette.commands.iter().map(|command| command.name.as_str()).collect();
+    assert!(names.contains(&"scroll-down"));
+    assert!(names.contains(&"report completed"));
+    assert!(names.contains(&"timewarrior-install-hook"));
+
+    type_text(&mut app, "selall").await;
+    let screen = render(&mut app);
+    assert!(screen.contains("select-all"), "{}", screen);
+    assert!(!screen.contains("start-stop"), "{}", screen);
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+    assert_eq!(app.marked.len(), 2);
+
+    press(&mut app, KeyCode::Ctrl('p')).await;
+    type_text(&mut app, "rep compl").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.report, "completed");
+
+    // Esc closes the palette without running anything
+    press(&mut app, KeyCode::Ctrl('p')).await;
+    type_text(&mut app, "quit").await;
+    press(&mut app, KeyCode::Esc).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+    assert!(!app.should_quit);
+}
+
+#[tokio::test]
+async fn test_headless_command_line() {
+    let backend = backend_with_tasks(&["Water plants", "Fix login", "Pay rent"]);
+    let show = format!(
+        "{}uda.taskwarrior-tui.commands.fix :filter Fix\nuda.taskwarrior-tui.keyconfig.command.fix <leader>f\n",
+        TASK_SHOW
+    );
+    let mut app = headless_app_with(&backend, &show).await;
+    let descriptions = |app: &TaskwarriorTui| app.tasks.iter().map(|task| task.description().clone()).collect::<Vec<_>>();
+
+    press(&mut app, KeyCode::Char(':')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::CommandLine));
+    type_text(&mut app, "so description-").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+    assert_eq!(descriptions(&app), vec!["Water plants", "Pay rent", "Fix login"]);
+
+    // A bare id jumps to the task
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, "2").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.current_selection, 2);
+
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, "mark rent").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.marked.len(), 1);
+    assert!(app.marked.contains(app.tasks[1].uuid()));
+
+    let uuids = vec![app.tasks[0].uuid().to_string(), app.tasks[1].uuid().to_string()];
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, "done 1,3").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(backend.calls_to("mark_done"), vec![uuids]);
+    assert_eq!(descriptions(&app), vec!["Fix login"]);
+
+    // Command names complete with Tab
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, ":fi").await;
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.command_line.as_str(), ":filter");
+    type_text(&mut app, " project:home").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.filter.as_str(), "project:home ");
+    assert!(app.tasks.is_empty());
+
+    // Named commands run from their keys
+    press(&mut app, KeyCode::Char('\\')).await;
+    press(&mut app, KeyCode::Char('f')).await;
+    assert_eq!(app.filter.as_str(), "Fix ");
+    assert_eq!(descriptions(&app), vec!["Fix login"]);
+
+    let path = app.history_dir.path().join("report.json");
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, &format!("write {}", path.display())).await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    let written: Vec<task_hookrs::task::Task> = task_hookrs::import::import(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(written.iter().map(|task| task.description().as_str()).collect::<Vec<_>>(), vec!["Fix login"]);
+
+    press(&mut app, KeyCode::Char(':')).await;
+    type_text(&mut app, "frobnicate").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Error));
+    assert_eq!(app.error.as_deref(), Some("Unknown command `frobnicate`"));
+}
+
+#[tokio::test]
+async fn test_headless_record_and_replay_macro() {
+    let backend = backend_with_tasks(&["Water plants", "Fix login", "Pay rent", "Call mom"]);
+    let mut app = headless_app(&backend).await;
+    let uuids: Vec<String> = app.tasks.iter().map(|task| task.uuid().to_string()).collect();
+    let modified = |backend: &MemoryBackend| backend.calls_to("modify_tasks").into_iter().map(|call| call[0].clone()).collect::<Vec<_>>();
+
+    // Keys typed in prompts are recorded along with the keys of the report
+    press(&mut app, KeyCode::Char('Q')).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    assert!(render(&mut app).contains("recording @a"));
+    press(&mut app, KeyCode::Char('m')).await;
+    type_text(&mut app, "+urgent").await;
+    press(&mut app, KeyCode::Char('\n')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    press(&mut app, KeyCode::Char('Q')).await;
+    assert!(!render(&mut app).contains("recording"));
+    assert_eq!(app.macros.get('a').map(<[KeyCode]>::len), Some(10));
+    assert_eq!(modified(&backend), uuids[..1]);
+
+    press(&mut app, KeyCode::Char('2')).await;
+    press(&mut app, KeyCode::Char('@')).await;
+    press(&mut app, KeyCode::Char('a')).await;
+    assert_eq!(modified(&backend), uuids[..3]);
+    assert_eq!(app.current_selection, 3);
+    assert_eq!(backend.calls_to("modify_tasks")[2][1], "\"Pay rent\" +urgent");
+
+    press(&mut app, KeyCode::Char('@')).await;
+    press(&mut app, KeyCode::Char('@')).await;
+    assert_eq!(modified(&backend), uuids);
+    assert_eq!(app.mode, Mode::Tasks(Action::Report));
+
+    // Refreshing saves the registers in the app's history directory
+    let saved = std::fs::read_to_string(app.history_dir.path().join("macros")).unwrap();
+    assert!(saved.lines().any(|line| line == format!("a {}", notation(app.macros.get('a').unwrap()))), "{}", saved);
+
+    // A macro that replays itself stops with an error
+    for key in ['Q', 'b', 'k', 'Q', 'Q', 'b', '@', 'b', 'Q'] {
+        press(&mut app, KeyCode::Char(key)).await;
+    }
+    assert_eq!(app.current_selection, 2);
+    assert_eq!(app.macros.get('b'), Some(&[KeyCode::Char('@'), KeyCode::Char('b')][..]));
+    press(&mut app, KeyCode::Char('@')).await;
+    press(&mut app, KeyCode::Char('b')).await;
+    assert_eq!(app.mode, Mode::Tasks(Action::Error));
+    assert_eq!(app.error.as_deref(), Some("Macro `b` cannot replay itself"));
+    press(&mut app, KeyCode::Char('\n')).await;
+
+    press(&mut app, KeyCode::Char('@')).await;
+    press(&mut app, KeyCode::Char('z')).await;
+    assert_eq!(app.error.as_deref(), Some("Register `z` holds no macro"));
+}
@@ -33,6 +33,7 @@
 //! - UI dimension validation
 
 #[path = "ui_testing.rs"]
+#[allow(dead_code)]
 mod ui_testing;
 
 use crate::ui_testing::{MockData, TuiTestHelper, UiAssertions};
@@ -254,7 +255,7 @@ mod integration_tests {
     fn test_help_screen_ui() {
         let mut tui_helper = TuiTestHelper::new();
         
-        let help_content = vec![
+        let help_content = [
             "Key Bindings:",
             "  j/Down  - Move down",
             "  k/Up    - Move up", 
@@ -292,7 +293,7 @@ mod integration_tests {
             Some("project-alpha".to_string()),
         );
         
-        let details = vec![
+        let details = [
             format!("Description: {}", task.description()),
             format!("Status: {:?}", task.status()),
             format!("Project: {}", task.project().unwrap_or(&"None".to_string())),
@@ -356,7 +357,7 @@ mod integration_tests {
                 );
                 
                 // Basic sanity checks
-                prop_assert!(buffer.len() > 0);
+                prop_assert!(!buffer.is_empty());
                 UiAssertions::has_border_elements(&buffer);
             }
 
@@ -403,7 +404,7 @@ mod integration_tests {
                 );
                 
                 // Buffer should never be empty
-                prop_assert!(buffer.len() > 0);
+                prop_assert!(!buffer.is_empty());
                 // Should contain our test content
                 UiAssertions::contains_text(&buffer, "Test content");
             }
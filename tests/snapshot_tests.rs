@@ -57,6 +57,7 @@
 //! - Minimal terminal size support
 
 #[path = "ui_testing.rs"]
+#[allow(dead_code)]
 mod ui_testing;
 
 use crate::ui_testing::{TuiTestHelper};
@@ -66,6 +67,24 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Gauge, LineGauge, List, ListItem, Paragraph, Tabs},
 };
 
+// =============================================================================
+// SNAPSHOT TESTING CONFIGURATION
+// =============================================================================
+
+/// Snapshot test configuration and utilities
+/// 
+/// This function configures insta settings for better snapshot management:
+/// - Prepends module names to snapshot files for organization
+/// - Omits expressions to reduce noise in snapshots
+/// - Sets up consistent snapshot naming and storage
+pub fn configure_snapshot_testing() {
+    // Configure insta for better snapshot management
+    let mut settings = insta::Settings::clone_current();
+    settings.set_prepend_module_to_snapshot(true);
+    settings.set_omit_expression(true);
+    let _guard = settings.bind_to_scope();
+}
+
 // =============================================================================
 // SNAPSHOT TEST SUITE
 // =============================================================================
@@ -653,21 +672,3 @@ mod snapshot_tests {
         }
     }
 }
-
-// =============================================================================
-// SNAPSHOT TESTING CONFIGURATION
-// =============================================================================
-
-/// Snapshot test configuration and utilities
-/// 
-/// This function configures insta settings for better snapshot management:
-/// - Prepends module names to snapshot files for organization
-/// - Omits expressions to reduce noise in snapshots
-/// - Sets up consistent snapshot naming and storage
-pub fn configure_snapshot_testing() {
-    // Configure insta for better snapshot management
-    let mut settings = insta::Settings::clone_current();
-    settings.set_prepend_module_to_snapshot(true);
-    settings.set_omit_expression(true);
-    let _guard = settings.bind_to_scope();
-}
\ No newline at end of file
@@ -93,9 +93,11 @@ mod mock_tests {
     
     impl MockTimewarriorIntegration {
         fn new(available: bool, hook_installed: bool, enabled: bool) -> Self {
-            let mut config = TimewarriorConfig::default();
-            config.hook_installed = hook_installed;
-            config.enabled = enabled;
+            let config = TimewarriorConfig {
+                hook_installed,
+                enabled,
+                ..TimewarriorConfig::default()
+            };
             
             Self {
                 config,
@@ -207,10 +209,11 @@ mod config_tests {
     
     #[test]
     fn test_config_edge_cases() {
-        let mut config = TimewarriorConfig::default();
-        
         // Test empty tag prefix
-        config.tag_prefix = "".to_string();
+        let mut config = TimewarriorConfig {
+            tag_prefix: "".to_string(),
+            ..TimewarriorConfig::default()
+        };
         assert_eq!(config.tag_prefix, "");
         
         // Test long tag prefix